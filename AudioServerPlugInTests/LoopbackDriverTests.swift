@@ -19,4 +19,17 @@ final class LoopbackDriverTests: XCTestCase {
             XCTAssertFalse(message.isEmpty)
         }
     }
+
+    func testStructuredLogDrain() {
+        device_kit_stop_driver()
+        _ = device_kit_start_driver()
+        var buffer = [CChar](repeating: 0, count: 256)
+        var found = false
+        while device_kit_pop_log_into(&buffer, buffer.count) > 0 {
+            if String(cString: buffer).hasSuffix("driver started") {
+                found = true
+            }
+        }
+        XCTAssertTrue(found)
+    }
 }
@@ -15,6 +15,15 @@ typedef struct LoopbackLevels {
     float outputs[8];
     uint32_t input_count;
     uint32_t output_count;
+    bool speaking[8];
+    float voice_probability[8];
+    float output_peaks[8];
+    float output_true_peaks[8];
+    bool output_clipped[8];
+    float master_rms;
+    float master_peak;
+    float master_true_peak;
+    bool master_clipped;
 } LoopbackLevels;
 
 typedef struct LoopbackRenderArgs {
@@ -23,6 +32,84 @@ typedef struct LoopbackRenderArgs {
     const AudioTimeStamp* timestamp;
 } LoopbackRenderArgs;
 
+typedef struct DuckingParams {
+    float threshold_db;
+    float depth_db;
+    float attack_ms;
+    float release_ms;
+    float hold_ms;
+} DuckingParams;
+
+typedef struct NormalizationParams {
+    float target_lufs;
+    float max_gain_db;
+    float max_cut_db;
+} NormalizationParams;
+
+#define LOOPBACK_ECHO_REFERENCE_MASTER UINT32_MAX
+
+typedef struct SpectrumConfig {
+    uint32_t bands;
+    float rate_hz;
+} SpectrumConfig;
+
+#define LOOPBACK_SPECTRUM_MASTER UINT32_MAX
+
+#define LOOPBACK_RECORD_FORMAT_F32 0
+#define LOOPBACK_RECORD_FORMAT_PCM24 1
+
+typedef struct LoopbackFileStatus {
+    double position_seconds;
+    double duration_seconds;
+    bool playing;
+    bool looping;
+    bool finished;
+    bool failed;
+} LoopbackFileStatus;
+
+#define LOOPBACK_GENERATOR_SINE 0
+#define LOOPBACK_GENERATOR_SWEEP 1
+#define LOOPBACK_GENERATOR_WHITE_NOISE 2
+#define LOOPBACK_GENERATOR_PINK_NOISE 3
+#define LOOPBACK_GENERATOR_IMPULSE 4
+#define LOOPBACK_GENERATOR_REFERENCE 5
+
+typedef struct LoopbackGenerator {
+    uint32_t kind;
+    float frequency_hz;
+    float end_frequency_hz;
+    float duration_seconds;
+    float level_dbfs;
+} LoopbackGenerator;
+
+#define LOOPBACK_CHANNEL_MODE_STEREO 0
+#define LOOPBACK_CHANNEL_MODE_MONO 1
+#define LOOPBACK_CHANNEL_MODE_LEFT_ONLY 2
+#define LOOPBACK_CHANNEL_MODE_RIGHT_ONLY 3
+
+#define LOOPBACK_EVENT_SOURCE_UNDERRUN 1
+#define LOOPBACK_EVENT_RING_OVERFLOW 2
+#define LOOPBACK_EVENT_CALLBACK_OVERRUN 3
+#define LOOPBACK_EVENT_NON_FINITE 4
+#define LOOPBACK_EVENT_FORMAT_ERROR 5
+#define LOOPBACK_EVENT_MASTER UINT32_MAX
+
+#define DEVICE_KIT_LOG_MAX_ARGS 4
+
+typedef struct {
+    uint64_t timestamp_ns;
+    uint32_t level;
+    uint32_t code;
+    double args[DEVICE_KIT_LOG_MAX_ARGS];
+} DeviceKitLogRecord;
+
+typedef struct {
+    uint64_t timestamp_ns;
+    uint32_t kind;
+    uint32_t source_id;
+    double value;
+} LoopbackEvent;
+
 LoopbackMixerHandle loopback_mixer_create(double sampleRate, uint32_t maxFrames);
 void loopback_mixer_destroy(LoopbackMixerHandle handle);
 OSStatus loopback_mixer_process(LoopbackMixerHandle handle, const LoopbackRenderArgs* args);
@@ -33,6 +120,35 @@ bool loopback_mixer_register_node_source(LoopbackMixerHandle handle, uint32_t so
 bool loopback_mixer_push_node_frames(LoopbackMixerHandle handle, uint32_t sourceIndex, const float* data, uint32_t frames, uint64_t timestamp_ns);
 bool loopback_mixer_set_node_gain(LoopbackMixerHandle handle, uint32_t sourceIndex, float gain);
 bool loopback_mixer_set_node_mute(LoopbackMixerHandle handle, uint32_t sourceIndex, bool mute);
+bool loopback_mixer_start_recording(LoopbackMixerHandle handle, const char* path, uint32_t format);
+bool loopback_mixer_stop_recording(LoopbackMixerHandle handle, uint64_t* outFrames);
+bool loopback_mixer_start_multitrack(LoopbackMixerHandle handle, const char* directory, uint32_t format);
+bool loopback_mixer_stop_multitrack(LoopbackMixerHandle handle, uint64_t* outFrames);
+bool loopback_mixer_load_file(LoopbackMixerHandle handle, uint32_t sourceIndex, const char* path);
+bool loopback_mixer_unload_file(LoopbackMixerHandle handle, uint32_t sourceIndex);
+bool loopback_mixer_set_file_playing(LoopbackMixerHandle handle, uint32_t sourceIndex, bool playing);
+bool loopback_mixer_seek_file(LoopbackMixerHandle handle, uint32_t sourceIndex, double seconds);
+bool loopback_mixer_set_file_looping(LoopbackMixerHandle handle, uint32_t sourceIndex, bool looping);
+bool loopback_mixer_get_file_status(LoopbackMixerHandle handle, uint32_t sourceIndex, LoopbackFileStatus* out);
+bool loopback_mixer_add_generator(LoopbackMixerHandle handle, uint32_t sourceIndex, LoopbackGenerator config);
+bool loopback_mixer_set_generator(LoopbackMixerHandle handle, uint32_t sourceIndex, LoopbackGenerator config);
+bool loopback_mixer_get_generator(LoopbackMixerHandle handle, uint32_t sourceIndex, LoopbackGenerator* out);
+bool loopback_mixer_remove_generator(LoopbackMixerHandle handle, uint32_t sourceIndex);
+bool loopback_mixer_set_channel_utility(LoopbackMixerHandle handle, uint32_t sourceIndex, bool invertLeft, bool invertRight, bool swapChannels, uint32_t mode);
+bool loopback_mixer_set_ducking(LoopbackMixerHandle handle, uint32_t keyIndex, const uint32_t* targets, uint32_t targetCount, DuckingParams params);
+bool loopback_mixer_clear_ducking(LoopbackMixerHandle handle, uint32_t keyIndex);
+bool loopback_mixer_set_automix_group(LoopbackMixerHandle handle, const uint32_t* members, uint32_t memberCount);
+bool loopback_mixer_set_automix_weight(LoopbackMixerHandle handle, uint32_t sourceIndex, float weight);
+bool loopback_mixer_enable_echo_cancellation(LoopbackMixerHandle handle, uint32_t sourceIndex, uint32_t referenceIndex, float filterLengthMs);
+bool loopback_mixer_disable_echo_cancellation(LoopbackMixerHandle handle, uint32_t sourceIndex);
+bool loopback_mixer_set_noise_suppression(LoopbackMixerHandle handle, uint32_t sourceIndex, bool enabled, float strength);
+bool loopback_mixer_set_loudness_metering(LoopbackMixerHandle handle, uint32_t sourceIndex, bool enabled);
+bool loopback_mixer_reset_loudness(LoopbackMixerHandle handle);
+bool loopback_mixer_reset_timing(LoopbackMixerHandle handle);
+uint32_t loopback_mixer_pop_events(LoopbackMixerHandle handle, LoopbackEvent* out, uint32_t capacity);
+bool loopback_mixer_set_loudness_normalization(LoopbackMixerHandle handle, bool enabled, NormalizationParams params);
+bool loopback_mixer_set_spectrum(LoopbackMixerHandle handle, bool enabled, SpectrumConfig config);
+uint32_t loopback_mixer_get_spectrum(LoopbackMixerHandle handle, uint32_t sourceIndex, float* levelsDb, float* frequenciesHz, uint32_t capacity);
 LoopbackMixerHandle loopback_mixer_global_handle(void);
 
 bool device_kit_get_levels(LoopbackLevels* levels_out);
@@ -47,6 +163,8 @@ uint32_t device_kit_source_count(void);
 bool device_kit_source_is_enabled(uint32_t sourceIndex);
 void device_kit_set_source_enabled(uint32_t sourceIndex, bool enabled);
 const char* device_kit_pop_log(void);
+size_t device_kit_pop_log_into(char* buffer, size_t capacity);
+bool device_kit_pop_log_record(DeviceKitLogRecord* out);
 
 #ifdef __cplusplus
 }
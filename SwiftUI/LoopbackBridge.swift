@@ -59,12 +59,30 @@ private struct CLoopbackLevels {
     var outputs: (Float, Float, Float, Float, Float, Float, Float, Float)
     var input_count: UInt32
     var output_count: UInt32
+    var speaking: (Bool, Bool, Bool, Bool, Bool, Bool, Bool, Bool)
+    var voice_probability: (Float, Float, Float, Float, Float, Float, Float, Float)
+    var output_peaks: (Float, Float, Float, Float, Float, Float, Float, Float)
+    var output_true_peaks: (Float, Float, Float, Float, Float, Float, Float, Float)
+    var output_clipped: (Bool, Bool, Bool, Bool, Bool, Bool, Bool, Bool)
+    var master_rms: Float
+    var master_peak: Float
+    var master_true_peak: Float
+    var master_clipped: Bool
 
     init() {
         inputs = (0, 0, 0, 0, 0, 0, 0, 0)
         outputs = (0, 0, 0, 0, 0, 0, 0, 0)
         input_count = 0
         output_count = 0
+        speaking = (false, false, false, false, false, false, false, false)
+        voice_probability = (0, 0, 0, 0, 0, 0, 0, 0)
+        output_peaks = (0, 0, 0, 0, 0, 0, 0, 0)
+        output_true_peaks = (0, 0, 0, 0, 0, 0, 0, 0)
+        output_clipped = (false, false, false, false, false, false, false, false)
+        master_rms = 0
+        master_peak = 0
+        master_true_peak = 0
+        master_clipped = false
     }
 }
 
@@ -111,6 +129,8 @@ final class LoopbackBridge {
             labels.append("Output \(idx + 1)")
             values.append(Double(value).clamped())
         }
+        labels.append(raw.master_clipped ? "Master (clip)" : "Master")
+        values.append(Double(raw.master_rms).clamped())
 
         return LevelSnapshot(labels: labels, values: values)
     }
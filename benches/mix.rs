@@ -0,0 +1,114 @@
+//! Render-path benchmarks: `Mixer::process` with N ring-fed sources at device block sizes.
+//!
+//! ```sh
+//! cargo bench --bench mix
+//! ```
+
+use std::f32::consts::TAU;
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const SOURCE_COUNTS: [usize; 4] = [1, 4, 16, 64];
+const BLOCK_SIZES: [usize; 4] = [32, 64, 128, 512];
+
+/// How each source is configured, covering the resampler and delay line paths.
+#[derive(Clone, Copy)]
+enum Setup {
+    /// Unity gain, no latency, no drift.
+    Plain,
+    /// Per-source latency compensation through the delay line.
+    Latency,
+    /// Clock feedback running the resampler off unity.
+    Drift,
+}
+
+struct Bench {
+    mixer: Mixer,
+    rings: Vec<Arc<SharedRingBuffer>>,
+    input: Vec<f32>,
+    output: Vec<f32>,
+}
+
+impl Bench {
+    fn new(sources: usize, block_frames: usize, setup: Setup) -> Self {
+        let mut mixer = Mixer::new(SAMPLE_RATE, block_frames);
+        let mut rings = Vec::with_capacity(sources);
+        for index in 0..sources {
+            let (handle, ring) = mixer.add_source(block_frames * 8);
+            mixer.set_gain(handle, 0.5).unwrap();
+            match setup {
+                Setup::Plain => {}
+                Setup::Latency => mixer.set_latency(handle, 48 + index as i32).unwrap(),
+                Setup::Drift => {
+                    mixer.submit_clock_feedback(handle, 0, 0).unwrap();
+                    mixer
+                        .submit_clock_feedback(handle, 1_000_000_000, 1_000_500_000)
+                        .unwrap();
+                }
+            }
+            rings.push(ring);
+        }
+        let input = (0..block_frames)
+            .flat_map(|frame| {
+                let value = (frame as f32 / block_frames as f32 * TAU).sin() * 0.25;
+                [value, -value]
+            })
+            .collect();
+        Self {
+            mixer,
+            rings,
+            input,
+            output: vec![0.0; block_frames * 2],
+        }
+    }
+
+    /// Refill every source with one block, then render one block.
+    fn render(&mut self) {
+        for ring in &self.rings {
+            // Keep the rings from filling up when the resampler consumes less than a block.
+            if ring.available_read() * 2 < ring.capacity_frames() {
+                ring.push(&self.input, Some(0));
+            }
+        }
+        let mut buffer = AudioBuffer {
+            data: self.output.as_mut_ptr(),
+            frames: (self.output.len() / 2) as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        black_box(self.mixer.process(&mut buffer).unwrap());
+    }
+}
+
+fn bench_setup(c: &mut Criterion, name: &str, setup: Setup) {
+    let mut group = c.benchmark_group(name);
+    for sources in SOURCE_COUNTS {
+        for block_frames in BLOCK_SIZES {
+            let mut bench = Bench::new(sources, block_frames, setup);
+            // Settle meters, delay lines and the interpolator before measuring.
+            for _ in 0..16 {
+                bench.render();
+            }
+            group.throughput(Throughput::Elements((sources * block_frames) as u64));
+            group.bench_function(
+                BenchmarkId::new(format!("{sources}_sources"), block_frames),
+                |b| b.iter(|| bench.render()),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn mix(c: &mut Criterion) {
+    bench_setup(c, "mix", Setup::Plain);
+    bench_setup(c, "mix_latency", Setup::Latency);
+    bench_setup(c, "mix_drift", Setup::Drift);
+}
+
+criterion_group!(benches, mix);
+criterion_main!(benches);
@@ -0,0 +1,165 @@
+//! Drives the DriverKit-facing `loopback_mixer_*` entry points with random node registrations,
+//! pushes, control calls and render quanta, including malformed buffer lists.
+#![no_main]
+
+use std::collections::HashSet;
+
+use arbitrary::Arbitrary;
+use coreaudio_sys::{AudioBuffer, AudioBufferList, AudioTimeStamp, kAudioTimeStampHostTimeValid};
+use device_kit::{
+    LoopbackRenderArgs, loopback_mixer_create, loopback_mixer_destroy, loopback_mixer_process,
+    loopback_mixer_push_node_frames, loopback_mixer_register_node_source,
+    loopback_mixer_set_node_gain, loopback_mixer_set_node_mute, loopback_mixer_submit_input,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Register {
+        index: u8,
+        capacity_frames: u16,
+    },
+    Push {
+        index: u8,
+        frames: u16,
+        level: f32,
+        timestamp_ns: u64,
+    },
+    SetGain {
+        index: u8,
+        gain: f32,
+    },
+    SetMute {
+        index: u8,
+        mute: bool,
+    },
+    SubmitInput {
+        frames: u16,
+        level: f32,
+    },
+    Process {
+        frames: u16,
+        channels: u8,
+        /// Bytes short of what `frames` needs.
+        short_bytes: u16,
+        no_buffers: bool,
+        null_data: bool,
+        /// Host time, or no timestamp at all.
+        host_time: Option<u64>,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    sample_rate: f64,
+    max_frames: u16,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let mixer = loopback_mixer_create(input.sample_rate, u32::from(input.max_frames % 4_096));
+    assert!(!mixer.is_null());
+    let mut registered = HashSet::new();
+    for op in input.ops {
+        match op {
+            Op::Register {
+                index,
+                capacity_frames,
+            } => {
+                let capacity = u32::from(capacity_frames % 8_192);
+                let index = u32::from(index % 8);
+                assert!(unsafe { loopback_mixer_register_node_source(mixer, index, capacity) });
+                registered.insert(index);
+            }
+            Op::Push {
+                index,
+                frames,
+                level,
+                timestamp_ns,
+            } => {
+                let index = u32::from(index % 8);
+                let frames = u32::from(frames % 8_192);
+                let data = vec![level; frames as usize * 2];
+                let pushed = unsafe {
+                    loopback_mixer_push_node_frames(
+                        mixer,
+                        index,
+                        data.as_ptr(),
+                        frames,
+                        timestamp_ns,
+                    )
+                };
+                assert_eq!(pushed, frames > 0 && registered.contains(&index));
+            }
+            Op::SetGain { index, gain } => {
+                let index = u32::from(index % 8);
+                let known = index == 0 || registered.contains(&index);
+                assert_eq!(
+                    unsafe { loopback_mixer_set_node_gain(mixer, index, gain) },
+                    known
+                );
+            }
+            Op::SetMute { index, mute } => {
+                let index = u32::from(index % 8);
+                let known = index == 0 || registered.contains(&index);
+                assert_eq!(
+                    unsafe { loopback_mixer_set_node_mute(mixer, index, mute) },
+                    known
+                );
+            }
+            Op::SubmitInput { frames, level } => {
+                let frames = u32::from(frames % 8_192);
+                let data = vec![level; frames as usize * 2];
+                unsafe { loopback_mixer_submit_input(mixer, data.as_ptr(), frames) };
+            }
+            Op::Process {
+                frames,
+                channels,
+                short_bytes,
+                no_buffers,
+                null_data,
+                host_time,
+            } => {
+                let frames = u32::from(frames % 8_192);
+                let channels = u32::from(channels % 4);
+                let mut output = vec![f32::NAN; frames as usize * channels as usize];
+                let capacity_bytes = (output.len() * size_of::<f32>()) as u32;
+                let byte_size = capacity_bytes.saturating_sub(u32::from(short_bytes));
+                let mut buffer_list = AudioBufferList {
+                    mNumberBuffers: u32::from(!no_buffers),
+                    mBuffers: [AudioBuffer {
+                        mNumberChannels: channels,
+                        mDataByteSize: byte_size,
+                        mData: if null_data {
+                            std::ptr::null_mut()
+                        } else {
+                            output.as_mut_ptr().cast()
+                        },
+                    }],
+                };
+                let mut timestamp: AudioTimeStamp = unsafe { std::mem::zeroed() };
+                if let Some(host_time) = host_time {
+                    timestamp.mHostTime = host_time;
+                    timestamp.mFlags = kAudioTimeStampHostTimeValid;
+                }
+                let args = LoopbackRenderArgs {
+                    buffer_list: &mut buffer_list,
+                    frame_count: frames,
+                    timestamp: if host_time.is_some() {
+                        &timestamp
+                    } else {
+                        std::ptr::null()
+                    },
+                };
+                let status = unsafe { loopback_mixer_process(mixer, &args) };
+                let valid = frames == 0
+                    || (!no_buffers && channels == 2 && !null_data && byte_size == capacity_bytes);
+                assert_eq!(status == 0, valid, "status {status}");
+                if valid && frames > 0 {
+                    assert!(output.iter().all(|sample| sample.is_finite()));
+                }
+            }
+        }
+    }
+    unsafe { loopback_mixer_destroy(mixer) };
+});
@@ -0,0 +1,179 @@
+//! Random interleavings of source control, ring writes and renders on a `Mixer`, driven
+//! through the C entry points where one exists and checked against a model of which sources
+//! exist and how full their rings are.
+#![no_main]
+
+use std::sync::Arc;
+
+use arbitrary::Arbitrary;
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{
+    AudioBuffer, SourceHandle, device_kit_mixer_free, device_kit_mixer_new,
+    device_kit_mixer_process, device_kit_source_write,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    AddSource {
+        capacity_frames: u16,
+    },
+    Remove {
+        slot: u8,
+    },
+    Write {
+        slot: u8,
+        frames: u16,
+        level: f32,
+        timestamp_ns: u64,
+    },
+    SetGain {
+        slot: u8,
+        gain: f32,
+    },
+    SetMute {
+        slot: u8,
+        mute: bool,
+    },
+    SetLatency {
+        slot: u8,
+        frames: i32,
+    },
+    ClockFeedback {
+        slot: u8,
+        device_ns: u64,
+        source_ns: u64,
+    },
+    Process {
+        frames: u16,
+        channels: u8,
+        timestamp_ns: u64,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    sample_rate: u32,
+    max_block_frames: u16,
+    ops: Vec<Op>,
+}
+
+/// A source the model knows about. Removed sources stay in the list so their stale handles
+/// keep being exercised.
+struct Slot {
+    handle: SourceHandle,
+    ring: Arc<SharedRingBuffer>,
+    live: bool,
+}
+
+fuzz_target!(|input: Input| {
+    // Meter and probe buffers scale with the rate, so stay within what devices actually run at.
+    let sample_rate = input.sample_rate % 384_001;
+    let max_block_frames = u32::from(input.max_block_frames % 4_096);
+    let mixer = device_kit_mixer_new(sample_rate, max_block_frames);
+    let mut slots: Vec<Slot> = Vec::new();
+    for op in input.ops {
+        let control = unsafe { &mut *mixer };
+        match op {
+            Op::AddSource { capacity_frames } => {
+                if slots.len() >= 16 {
+                    continue;
+                }
+                // Writes go through the C entry point; the ring is kept to check fill levels.
+                let (handle, ring) = control.add_source(usize::from(capacity_frames % 8_192));
+                slots.push(Slot {
+                    handle,
+                    ring,
+                    live: true,
+                });
+            }
+            Op::Remove { slot } => {
+                if let Some(slot) = pick(&mut slots, slot) {
+                    assert_eq!(control.remove_source(slot.handle).is_ok(), slot.live);
+                    slot.live = false;
+                }
+            }
+            Op::Write {
+                slot,
+                frames,
+                level,
+                timestamp_ns,
+            } => {
+                let Some(slot) = pick(&mut slots, slot) else {
+                    continue;
+                };
+                let frames = usize::from(frames % 8_192);
+                let data = vec![level; frames * 2];
+                let free = slot.ring.capacity_frames() - slot.ring.available_read();
+                let written = unsafe {
+                    device_kit_source_write(
+                        mixer,
+                        slot.handle,
+                        data.as_ptr(),
+                        frames as u32,
+                        timestamp_ns,
+                    )
+                };
+                let expected = if slot.live { frames.min(free) } else { 0 };
+                assert_eq!(written, expected);
+            }
+            Op::SetGain { slot, gain } => {
+                if let Some(slot) = pick(&mut slots, slot) {
+                    assert_eq!(control.set_gain(slot.handle, gain).is_ok(), slot.live);
+                }
+            }
+            Op::SetMute { slot, mute } => {
+                if let Some(slot) = pick(&mut slots, slot) {
+                    assert_eq!(control.set_mute(slot.handle, mute).is_ok(), slot.live);
+                }
+            }
+            Op::SetLatency { slot, frames } => {
+                if let Some(slot) = pick(&mut slots, slot) {
+                    let frames = frames % 96_000;
+                    assert_eq!(control.set_latency(slot.handle, frames).is_ok(), slot.live);
+                }
+            }
+            Op::ClockFeedback {
+                slot,
+                device_ns,
+                source_ns,
+            } => {
+                if let Some(slot) = pick(&mut slots, slot) {
+                    let result = control.submit_clock_feedback(slot.handle, device_ns, source_ns);
+                    assert_eq!(result.is_ok(), slot.live);
+                }
+            }
+            Op::Process {
+                frames,
+                channels,
+                timestamp_ns,
+            } => {
+                let frames = u32::from(frames % 8_192);
+                let channels = u32::from(channels % 4);
+                let mut output = vec![f32::NAN; frames as usize * channels as usize];
+                let mut buffer = AudioBuffer {
+                    data: output.as_mut_ptr(),
+                    frames,
+                    channels,
+                    timestamp_ns,
+                };
+                let rendered = unsafe { device_kit_mixer_process(mixer, &mut buffer) };
+                if channels == 2 {
+                    assert_eq!(rendered, frames as usize);
+                    assert!(output.iter().all(|sample| sample.is_finite()));
+                } else {
+                    assert_eq!(rendered, 0);
+                }
+                for slot in &slots {
+                    assert!(slot.ring.available_read() <= slot.ring.capacity_frames());
+                }
+            }
+        }
+    }
+    unsafe { device_kit_mixer_free(mixer) };
+});
+
+fn pick(slots: &mut [Slot], slot: u8) -> Option<&mut Slot> {
+    let len = slots.len();
+    (len > 0).then(|| &mut slots[usize::from(slot) % len])
+}
@@ -0,0 +1,66 @@
+//! Opens arbitrary mapped bytes as a ring, the way a peer process's buffer is attached, then
+//! exercises it. Header fields the ring cannot use must be rejected rather than trusted.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use device_kit::ring::SharedRingBuffer;
+use libfuzzer_sys::fuzz_target;
+use memmap2::MmapOptions;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Push { samples: u16, timestamp_ns: u64 },
+    Pop { samples: u16 },
+    Discard { frames: u16 },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    channels: u8,
+    contents: Vec<u8>,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(mut mmap) = MmapOptions::new()
+        .len(input.contents.len().max(1))
+        .map_anon()
+    else {
+        return;
+    };
+    mmap[..input.contents.len()].copy_from_slice(&input.contents);
+    let channels = usize::from(input.channels);
+    let Ok(ring) = SharedRingBuffer::from_mmap(mmap, channels) else {
+        return;
+    };
+
+    // Whatever the peer left in the indices, counts must stay within the ring.
+    let capacity = ring.capacity_frames();
+    let mut queued = ring.available_read();
+    assert!(queued <= capacity);
+    for op in input.ops {
+        match op {
+            Op::Push {
+                samples,
+                timestamp_ns,
+            } => {
+                let frames = vec![0.5; usize::from(samples)];
+                let written = ring.push(&frames, Some(timestamp_ns));
+                assert_eq!(written, (frames.len() / channels).min(capacity - queued));
+                queued += written;
+            }
+            Op::Pop { samples } => {
+                let mut out = vec![0.0; usize::from(samples)];
+                let read = ring.pop(&mut out);
+                assert_eq!(read, (out.len() / channels).min(queued));
+                queued -= read;
+            }
+            Op::Discard { frames } => {
+                let dropped = ring.discard(usize::from(frames));
+                assert_eq!(dropped, usize::from(frames).min(queued));
+                queued -= dropped;
+            }
+        }
+        assert_eq!(ring.available_read(), queued);
+    }
+});
@@ -0,0 +1,89 @@
+//! Push, pop and discard sequences against a `VecDeque` model of the ring.
+#![no_main]
+
+use std::collections::VecDeque;
+
+use arbitrary::Arbitrary;
+use device_kit::ring::SharedRingBuffer;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    /// Push `samples` samples, which need not be a whole number of frames.
+    Push {
+        samples: u16,
+        timestamp_ns: Option<u64>,
+    },
+    /// Pop into a buffer of `samples` samples.
+    Pop {
+        samples: u16,
+    },
+    Discard {
+        frames: u16,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    capacity_frames: u16,
+    channels: u8,
+    shared: bool,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let capacity = usize::from(input.capacity_frames % 4_096);
+    let channels = usize::from(input.channels % 9);
+    let ring = if input.shared {
+        match SharedRingBuffer::new_shared(capacity, channels) {
+            Ok(ring) => ring,
+            Err(_) => return,
+        }
+    } else {
+        SharedRingBuffer::new_local(capacity, channels)
+    };
+    assert_eq!(ring.capacity_frames(), capacity);
+
+    // Queued samples, each one a distinct counter value so misplaced copies show up.
+    let mut model = VecDeque::new();
+    let mut next = 0u32;
+    for op in input.ops {
+        match op {
+            Op::Push {
+                samples,
+                timestamp_ns,
+            } => {
+                let frames: Vec<f32> = (0..samples)
+                    .map(|offset| (next + u32::from(offset)) as f32)
+                    .collect();
+                let offered = frames.len().checked_div(channels).unwrap_or(0);
+                let free = capacity - model.len() / channels.max(1);
+                let expected = offered.min(free);
+                let written = ring.push(&frames, timestamp_ns);
+                assert_eq!(written, expected, "push");
+                model.extend(&frames[..written * channels]);
+                next += u32::from(samples);
+                if let (true, Some(timestamp_ns)) = (written > 0, timestamp_ns) {
+                    assert_eq!(ring.last_timestamp_ns(), timestamp_ns);
+                }
+            }
+            Op::Pop { samples } => {
+                let mut out = vec![f32::NAN; usize::from(samples)];
+                let requested = out.len().checked_div(channels).unwrap_or(0);
+                let expected = requested.min(model.len() / channels.max(1));
+                let read = ring.pop(&mut out);
+                assert_eq!(read, expected, "pop");
+                for sample in &out[..read * channels] {
+                    assert_eq!(Some(*sample), model.pop_front());
+                }
+                assert!(out[read * channels..].iter().all(|sample| sample.is_nan()));
+            }
+            Op::Discard { frames } => {
+                let expected = usize::from(frames).min(model.len() / channels.max(1));
+                assert_eq!(ring.discard(usize::from(frames)), expected, "discard");
+                model.drain(..expected * channels);
+            }
+        }
+        assert_eq!(ring.available_read(), model.len() / channels.max(1));
+    }
+});
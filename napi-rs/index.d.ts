@@ -3,8 +3,104 @@
 
 /* auto-generated by NAPI-RS */
 
+export interface RecordingSummary {
+  path: string
+  format: string
+  frames: number
+  droppedFrames: number
+  /** Host time of the first recorded block, in nanoseconds. */
+  startTimestampNs: number
+}
+export interface FileStatus {
+  path: string
+  sampleRate: number
+  channels: number
+  durationSeconds: number
+  positionSeconds: number
+  playing: boolean
+  looping: boolean
+  finished: boolean
+  error?: string
+}
+export interface ClipOptions {
+  gainDb?: number
+  /** Voices of the clip that may sound at once. Defaults to 1, which restarts the clip. */
+  polyphony?: number
+  /** Triggering the clip silences the other clips in this group. */
+  chokeGroup?: number
+}
+export interface ClipStatus {
+  id: number
+  path: string
+  durationSeconds: number
+  gainDb: number
+  polyphony: number
+  chokeGroup?: number
+  voices: number
+}
+export interface ChannelUtilityOptions {
+  invertLeft?: boolean
+  invertRight?: boolean
+  swapChannels?: boolean
+  /** One of "stereo", "mono", "left" or "right". */
+  mode?: string
+}
+export interface DuckingOptions {
+  thresholdDb?: number
+  depthDb?: number
+  attackMs?: number
+  releaseMs?: number
+  holdMs?: number
+}
+export interface VoiceActivityEvent {
+  sourceId: number
+  name: string
+  speaking: boolean
+  probability: number
+}
+export interface MixerEvent {
+  /** Host time at which the event was detected, in nanoseconds. */
+  timestampNs: number
+  kind: string
+  sourceId?: number
+  frames?: number
+  load?: number
+  channels?: number
+  message: string
+}
+export declare class VoiceActivityWatcher {
+  stop(): void
+}
+export declare class MixerEventWatcher {
+  stop(): void
+}
 export declare function registerSource(channel: number, capacityFrames?: number | undefined | null): boolean
 export declare function pushAudioFrame(channel: number, pcm: Float32Array, timestamp?: number | undefined | null): boolean
 export declare function setSourceGain(channel: number, gain: number): boolean
 export declare function setSourceMute(channel: number, mute: boolean): boolean
+/** Record the loopback output to a WAV file. `format` is "f32" (default) or "pcm24". */
+export declare function startRecording(path: string, format?: string | undefined | null): boolean
+export declare function stopRecording(): RecordingSummary
+/** Stream a WAV or FLAC file into the mix as `channel`. The file starts paused. */
+export declare function loadFile(channel: number, path: string): boolean
+export declare function unloadFile(channel: number): boolean
+export declare function playFile(channel: number): boolean
+export declare function pauseFile(channel: number): boolean
+export declare function seekFile(channel: number, seconds: number): boolean
+export declare function setFileLoop(channel: number, looping: boolean): boolean
+export declare function getFileStatus(channel: number): FileStatus | null
+/** Preload a WAV or FLAC file into the soundboard as clip `id`. */
+export declare function loadClip(id: number, path: string, options?: ClipOptions | undefined | null): boolean
+export declare function unloadClip(id: number): boolean
+export declare function triggerClip(id: number): boolean
+export declare function stopClip(id: number): boolean
+export declare function stopAllClips(): boolean
+/** Change the options of a loaded clip; omitted options keep their current value. */
+export declare function setClipOptions(id: number, options: ClipOptions): boolean
+export declare function getClips(): Array<ClipStatus>
+export declare function setSourceChannelUtility(channel: number, options?: ChannelUtilityOptions | undefined | null): boolean
+export declare function setSourceDucking(channel: number, targets: Array<number>, options?: DuckingOptions | undefined | null): boolean
+export declare function clearSourceDucking(channel: number): boolean
+export declare function onVoiceActivity(callback: (event: VoiceActivityEvent) => void, intervalMs?: number): VoiceActivityWatcher
+export declare function onMixerEvent(callback: (event: MixerEvent) => void, intervalMs?: number): MixerEventWatcher
 export declare function monotonicTimeNs(): number
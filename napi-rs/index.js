@@ -1,4 +1,5 @@
 /* eslint-disable @typescript-eslint/no-var-requires */
+const { EventEmitter } = require('events');
 const binding = require('./index.node');
 
 function registerSource(channel, capacityFrames = 4096) {
@@ -17,6 +18,104 @@ function setSourceMute(channel, mute) {
   return binding.set_source_mute(channel, mute);
 }
 
+function startRecording(path, format = 'f32') {
+  return binding.start_recording(path, format);
+}
+
+function stopRecording() {
+  return binding.stop_recording();
+}
+
+function loadFile(channel, path) {
+  return binding.load_file(channel, path);
+}
+
+function unloadFile(channel) {
+  return binding.unload_file(channel);
+}
+
+function playFile(channel) {
+  return binding.play_file(channel);
+}
+
+function pauseFile(channel) {
+  return binding.pause_file(channel);
+}
+
+function seekFile(channel, seconds) {
+  return binding.seek_file(channel, seconds);
+}
+
+function setFileLoop(channel, looping) {
+  return binding.set_file_loop(channel, looping);
+}
+
+function getFileStatus(channel) {
+  return binding.get_file_status(channel);
+}
+
+function loadClip(id, path, options = {}) {
+  return binding.load_clip(id, path, options);
+}
+
+function unloadClip(id) {
+  return binding.unload_clip(id);
+}
+
+function triggerClip(id) {
+  return binding.trigger_clip(id);
+}
+
+function stopClip(id) {
+  return binding.stop_clip(id);
+}
+
+function stopAllClips() {
+  return binding.stop_all_clips();
+}
+
+function setClipOptions(id, options) {
+  return binding.set_clip_options(id, options);
+}
+
+function getClips() {
+  return binding.get_clips();
+}
+
+function setSourceChannelUtility(channel, options = {}) {
+  return binding.set_source_channel_utility(channel, options);
+}
+
+function setSourceDucking(channel, targets, options = {}) {
+  return binding.set_source_ducking(channel, targets, options);
+}
+
+function clearSourceDucking(channel) {
+  return binding.clear_source_ducking(channel);
+}
+
+function onVoiceActivity(listener, intervalMs = 50) {
+  return binding.on_voice_activity(listener, intervalMs);
+}
+
+class MixerEventEmitter extends EventEmitter {
+  constructor(intervalMs = 100) {
+    super();
+    this.watcher = binding.on_mixer_event((event) => {
+      this.emit('event', event);
+      this.emit(event.kind, event);
+    }, intervalMs);
+  }
+
+  stop() {
+    this.watcher.stop();
+  }
+}
+
+function mixerEvents(intervalMs = 100) {
+  return new MixerEventEmitter(intervalMs);
+}
+
 function monotonicTimeNs() {
   return binding.monotonic_time_ns();
 }
@@ -26,5 +125,27 @@ module.exports = {
   pushAudioFrame,
   setSourceGain,
   setSourceMute,
+  setSourceChannelUtility,
+  startRecording,
+  stopRecording,
+  loadFile,
+  unloadFile,
+  playFile,
+  pauseFile,
+  seekFile,
+  setFileLoop,
+  getFileStatus,
+  loadClip,
+  unloadClip,
+  triggerClip,
+  stopClip,
+  stopAllClips,
+  setClipOptions,
+  getClips,
+  setSourceDucking,
+  clearSourceDucking,
+  onVoiceActivity,
+  MixerEventEmitter,
+  mixerEvents,
   monotonicTimeNs,
 };
@@ -1,9 +1,35 @@
 /* eslint-disable @typescript-eslint/no-var-requires */
+import { EventEmitter } from 'events';
+
 const binding = require('./index.node') as {
   register_source(channel: number, capacityFrames?: number): boolean;
   push_audio_frame(channel: number, pcm: Float32Array, timestamp?: number): boolean;
   set_source_gain(channel: number, gain: number): boolean;
   set_source_mute(channel: number, mute: boolean): boolean;
+  set_source_channel_utility(channel: number, options?: ChannelUtilityOptions): boolean;
+  start_recording(path: string, format?: RecordingFormat): boolean;
+  stop_recording(): RecordingSummary;
+  load_file(channel: number, path: string): boolean;
+  unload_file(channel: number): boolean;
+  play_file(channel: number): boolean;
+  pause_file(channel: number): boolean;
+  seek_file(channel: number, seconds: number): boolean;
+  set_file_loop(channel: number, looping: boolean): boolean;
+  get_file_status(channel: number): FileStatus | null;
+  load_clip(id: number, path: string, options?: ClipOptions): boolean;
+  unload_clip(id: number): boolean;
+  trigger_clip(id: number): boolean;
+  stop_clip(id: number): boolean;
+  stop_all_clips(): boolean;
+  set_clip_options(id: number, options: ClipOptions): boolean;
+  get_clips(): ClipStatus[];
+  set_source_ducking(channel: number, targets: number[], options?: DuckingOptions): boolean;
+  clear_source_ducking(channel: number): boolean;
+  on_voice_activity(
+    callback: (event: VoiceActivityEvent) => void,
+    intervalMs?: number,
+  ): VoiceActivityWatcher;
+  on_mixer_event(callback: (event: MixerEvent) => void, intervalMs?: number): MixerEventWatcher;
   monotonic_time_ns(): number;
 };
 
@@ -14,6 +40,140 @@ export interface PushAudioFrameOptions {
   timestampNs?: number;
 }
 
+export type RecordingFormat = 'f32' | 'pcm24';
+
+export interface RecordingSummary {
+  path: string;
+  format: RecordingFormat;
+  frames: number;
+  droppedFrames: number;
+  /** Host time of the first recorded block, in nanoseconds. */
+  startTimestampNs: number;
+}
+
+export interface FileStatus {
+  path: string;
+  /** Frame rate of the file; playback is resampled to the mixer rate. */
+  sampleRate: number;
+  channels: number;
+  /** Length of the file in seconds, or 0 when the file does not declare it. */
+  durationSeconds: number;
+  positionSeconds: number;
+  playing: boolean;
+  looping: boolean;
+  /** Playback ran to the end of the file and stopped. */
+  finished: boolean;
+  /** Decoding error that stopped playback. */
+  error?: string;
+}
+
+export interface ClipOptions {
+  /** Gain applied to the clip in decibels, at most +24. Defaults to 0. */
+  gainDb?: number;
+  /** Voices of the clip that may sound at once. Defaults to 1, which restarts the clip. */
+  polyphony?: number;
+  /** Triggering the clip silences the other clips in this group. */
+  chokeGroup?: number;
+}
+
+export interface ClipStatus {
+  id: number;
+  path: string;
+  durationSeconds: number;
+  gainDb: number;
+  polyphony: number;
+  chokeGroup?: number;
+  /** Voices currently sounding, including ones fading out. */
+  voices: number;
+}
+
+export interface ChannelUtilityOptions {
+  /** Invert the polarity of the left channel. */
+  invertLeft?: boolean;
+  /** Invert the polarity of the right channel. */
+  invertRight?: boolean;
+  /** Exchange left and right before inversion. */
+  swapChannels?: boolean;
+  /** Fold applied last. Defaults to 'stereo'. */
+  mode?: 'stereo' | 'mono' | 'left' | 'right';
+}
+
+export interface DuckingOptions {
+  /** Key level in dBFS above which ducking engages. Defaults to -40. */
+  thresholdDb?: number;
+  /** Attenuation applied to targets in dB. Defaults to 12. */
+  depthDb?: number;
+  /** Attack time in milliseconds. Defaults to 10. */
+  attackMs?: number;
+  /** Release time in milliseconds. Defaults to 400. */
+  releaseMs?: number;
+  /** Hold time in milliseconds after the key falls silent. Defaults to 200. */
+  holdMs?: number;
+}
+
+export interface VoiceActivityEvent {
+  sourceId: number;
+  name: string;
+  /** `true` when the source started speaking, `false` when it went quiet. */
+  speaking: boolean;
+  /** Smoothed voice probability (0-1) at the time of the transition. */
+  probability: number;
+}
+
+/** A running watcher. It does not keep the process alive on its own. */
+export interface VoiceActivityWatcher {
+  /** Stop polling and release the callback. */
+  stop(): void;
+}
+
+export type MixerEventKind =
+  | 'source_underrun'
+  | 'ring_overflow'
+  | 'callback_overrun'
+  | 'non_finite'
+  | 'format_error';
+
+export interface MixerEvent {
+  /** Host time at which the event was detected, in nanoseconds. */
+  timestampNs: number;
+  kind: MixerEventKind;
+  /** Source the event concerns; absent for master and callback-level events. */
+  sourceId?: number;
+  /** Silence inserted (underrun), frames lost (overflow) or buffer size (format error). */
+  frames?: number;
+  /** Callback time as a fraction of the block duration (overrun). */
+  load?: number;
+  /** Buffer channel count (format error). */
+  channels?: number;
+  /** Human-readable description. */
+  message: string;
+}
+
+interface MixerEventWatcher {
+  stop(): void;
+}
+
+/**
+ * Emits every dropout and glitch event as `'event'` and under its kind (for example
+ * `'source_underrun'`) until `stop()` is called or the emitter is garbage-collected. Events are
+ * taken from the shared log as they are read, so other consumers in the process miss them.
+ */
+export class MixerEventEmitter extends EventEmitter {
+  private readonly watcher: MixerEventWatcher;
+
+  constructor(intervalMs = 100) {
+    super();
+    this.watcher = binding.on_mixer_event((event) => {
+      this.emit('event', event);
+      this.emit(event.kind, event);
+    }, intervalMs);
+  }
+
+  stop(): void {
+    this.watcher.stop();
+  }
+}
+
 export function registerSource(channel: number, capacityFrames = 4096): boolean {
   return binding.register_source(channel, capacityFrames);
 }
@@ -30,6 +190,111 @@ export function setSourceMute(channel: number, mute: boolean): boolean {
   return binding.set_source_mute(channel, mute);
 }
 
+/** Record the loopback output to a WAV file (RF64 past 4 GiB). Throws if already recording. */
+export function startRecording(path: string, format: RecordingFormat = 'f32'): boolean {
+  return binding.start_recording(path, format);
+}
+
+/** Finish the running recording. Throws if none is running. */
+export function stopRecording(): RecordingSummary {
+  return binding.stop_recording();
+}
+
+/** Stream a WAV or FLAC file into the mix as `channel`. The file starts paused. */
+export function loadFile(channel: number, path: string): boolean {
+  return binding.load_file(channel, path);
+}
+
+export function unloadFile(channel: number): boolean {
+  return binding.unload_file(channel);
+}
+
+/** Start or resume playback; a finished file restarts from the beginning. */
+export function playFile(channel: number): boolean {
+  return binding.play_file(channel);
+}
+
+export function pauseFile(channel: number): boolean {
+  return binding.pause_file(channel);
+}
+
+export function seekFile(channel: number, seconds: number): boolean {
+  return binding.seek_file(channel, seconds);
+}
+
+export function setFileLoop(channel: number, looping: boolean): boolean {
+  return binding.set_file_loop(channel, looping);
+}
+
+export function getFileStatus(channel: number): FileStatus | null {
+  return binding.get_file_status(channel);
+}
+
+/** Preload a WAV or FLAC file (up to 60 s) into the soundboard as clip `id`. */
+export function loadClip(id: number, path: string, options: ClipOptions = {}): boolean {
+  return binding.load_clip(id, path, options);
+}
+
+export function unloadClip(id: number): boolean {
+  return binding.unload_clip(id);
+}
+
+/** Play a clip from the start on the next audio block. */
+export function triggerClip(id: number): boolean {
+  return binding.trigger_clip(id);
+}
+
+/** Fade out every voice of a clip. */
+export function stopClip(id: number): boolean {
+  return binding.stop_clip(id);
+}
+
+export function stopAllClips(): boolean {
+  return binding.stop_all_clips();
+}
+
+/** Change the options of a loaded clip; omitted options keep their current value. */
+export function setClipOptions(id: number, options: ClipOptions): boolean {
+  return binding.set_clip_options(id, options);
+}
+
+export function getClips(): ClipStatus[] {
+  return binding.get_clips();
+}
+
+/** Repair a badly produced source: swap, invert and fold its channels. */
+export function setSourceChannelUtility(
+  channel: number,
+  options: ChannelUtilityOptions = {},
+): boolean {
+  return binding.set_source_channel_utility(channel, options);
+}
+
+export function setSourceDucking(
+  channel: number,
+  targets: number[],
+  options: DuckingOptions = {},
+): boolean {
+  return binding.set_source_ducking(channel, targets, options);
+}
+
+export function clearSourceDucking(channel: number): boolean {
+  return binding.clear_source_ducking(channel);
+}
+
+/** Invoke `listener` whenever a source starts or stops speaking. */
+export function onVoiceActivity(
+  listener: (event: VoiceActivityEvent) => void,
+  intervalMs = 50,
+): VoiceActivityWatcher {
+  return binding.on_voice_activity(listener, intervalMs);
+}
+
+/** Start emitting mixer dropout and glitch events, polling every `intervalMs`. */
+export function mixerEvents(intervalMs = 100): MixerEventEmitter {
+  return new MixerEventEmitter(intervalMs);
+}
+
 export function monotonicTimeNs(): number {
   return binding.monotonic_time_ns();
 }
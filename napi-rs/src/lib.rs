@@ -1,10 +1,20 @@
 #![deny(clippy::all)]
 
-use napi::bindgen_prelude::{Error, Float32Array};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use napi::bindgen_prelude::{Error, Float32Array, ToNapiValue};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, JsFunction};
 use napi_derive::napi;
 
 const STEREO_CHANNELS: usize = 2;
 const DEFAULT_RING_CAPACITY: u32 = 4_096;
+const DEFAULT_VOICE_POLL_MS: u32 = 50;
+const DEFAULT_EVENT_POLL_MS: u32 = 100;
 
 fn ensure_capacity(capacity: Option<u32>) -> napi::Result<u32> {
     match capacity {
@@ -55,6 +65,475 @@ pub fn set_source_mute(channel: u32, mute: bool) -> napi::Result<bool> {
     Ok(device_kit::node_set_mute(channel, mute))
 }
 
+#[napi(object)]
+pub struct RecordingSummary {
+    pub path: String,
+    pub format: String,
+    pub frames: f64,
+    pub dropped_frames: f64,
+    /// Host time of the first recorded block, in nanoseconds.
+    pub start_timestamp_ns: f64,
+}
+
+/// Record the loopback output to a WAV file. `format` is "f32" (default) or "pcm24".
+#[napi]
+pub fn start_recording(path: String, format: Option<String>) -> napi::Result<bool> {
+    let format = match format.as_deref() {
+        None => device_kit::wav::SampleFormat::Float32,
+        Some(name) => device_kit::wav::SampleFormat::from_name(name).ok_or_else(|| {
+            Error::from_reason(format!(
+                "unknown recording format '{name}', expected f32 or pcm24"
+            ))
+        })?,
+    };
+    device_kit::control::api::start_recording(&path, format)
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    Ok(true)
+}
+
+#[napi]
+pub fn stop_recording() -> napi::Result<RecordingSummary> {
+    let status = device_kit::control::api::stop_recording()
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    Ok(RecordingSummary {
+        path: status.path.display().to_string(),
+        format: status.format.name().to_string(),
+        frames: status.frames as f64,
+        dropped_frames: status.dropped_frames as f64,
+        start_timestamp_ns: status.start_timestamp_ns as f64,
+    })
+}
+
+#[napi(object)]
+pub struct FileStatus {
+    pub path: String,
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub duration_seconds: f64,
+    pub position_seconds: f64,
+    pub playing: bool,
+    pub looping: bool,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+/// Stream a WAV or FLAC file into the mix as `channel`. The file starts paused.
+#[napi]
+pub fn load_file(channel: u32, path: String) -> napi::Result<bool> {
+    device_kit::control::api::load_file(channel, &path)
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    Ok(true)
+}
+
+#[napi]
+pub fn unload_file(channel: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::unload_file(channel))
+}
+
+#[napi]
+pub fn play_file(channel: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::play_file(channel))
+}
+
+#[napi]
+pub fn pause_file(channel: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::pause_file(channel))
+}
+
+#[napi]
+pub fn seek_file(channel: u32, seconds: f64) -> napi::Result<bool> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(Error::from_reason(format!(
+            "seek position must be a non-negative number of seconds, got {seconds}"
+        )));
+    }
+    Ok(device_kit::control::api::seek_file(channel, seconds))
+}
+
+#[napi]
+pub fn set_file_loop(channel: u32, looping: bool) -> napi::Result<bool> {
+    Ok(device_kit::control::api::set_file_loop(channel, looping))
+}
+
+#[napi]
+pub fn get_file_status(channel: u32) -> napi::Result<Option<FileStatus>> {
+    Ok(
+        device_kit::control::api::file_status(channel).map(|status| FileStatus {
+            path: status.path.display().to_string(),
+            sample_rate: status.file_sample_rate,
+            channels: status.file_channels as u32,
+            duration_seconds: status.duration_seconds,
+            position_seconds: status.position_seconds,
+            playing: status.playing,
+            looping: status.looping,
+            finished: status.finished,
+            error: status.error,
+        }),
+    )
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ClipOptions {
+    pub gain_db: Option<f64>,
+    /// Voices of the clip that may sound at once. Defaults to 1, which restarts the clip.
+    pub polyphony: Option<u32>,
+    /// Triggering the clip silences the other clips in this group.
+    pub choke_group: Option<u32>,
+}
+
+#[napi(object)]
+pub struct ClipStatus {
+    pub id: u32,
+    pub path: String,
+    pub duration_seconds: f64,
+    pub gain_db: f64,
+    pub polyphony: u32,
+    pub choke_group: Option<u32>,
+    pub voices: u32,
+}
+
+fn clip_config(
+    options: Option<ClipOptions>,
+    base: device_kit::soundboard::ClipConfig,
+) -> napi::Result<device_kit::soundboard::ClipConfig> {
+    let options = options.unwrap_or_default();
+    let config = device_kit::soundboard::ClipConfig {
+        gain_db: options.gain_db.map_or(base.gain_db, |gain| gain as f32),
+        polyphony: options.polyphony.unwrap_or(base.polyphony),
+        choke_group: options.choke_group.or(base.choke_group),
+    };
+    if !config.is_valid() {
+        return Err(Error::from_reason(format!(
+            "invalid clip options: gain must be at most 24 dB and polyphony between 1 and {}",
+            device_kit::soundboard::MAX_VOICES
+        )));
+    }
+    Ok(config)
+}
+
+/// Preload a WAV or FLAC file into the soundboard as clip `id`.
+#[napi]
+pub fn load_clip(id: u32, path: String, options: Option<ClipOptions>) -> napi::Result<bool> {
+    let config = clip_config(options, Default::default())?;
+    device_kit::control::api::load_clip(id, &path, config)
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    Ok(true)
+}
+
+#[napi]
+pub fn unload_clip(id: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::unload_clip(id))
+}
+
+#[napi]
+pub fn trigger_clip(id: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::trigger_clip(id))
+}
+
+#[napi]
+pub fn stop_clip(id: u32) -> napi::Result<bool> {
+    Ok(device_kit::control::api::stop_clip(id))
+}
+
+#[napi]
+pub fn stop_all_clips() -> napi::Result<bool> {
+    Ok(device_kit::control::api::stop_all_clips())
+}
+
+/// Change the options of a loaded clip; omitted options keep their current value.
+#[napi]
+pub fn set_clip_options(id: u32, options: ClipOptions) -> napi::Result<bool> {
+    let Some(current) = device_kit::control::api::clips()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|clip| clip.id == id)
+    else {
+        return Ok(false);
+    };
+    let config = clip_config(Some(options), current.config)?;
+    Ok(device_kit::control::api::set_clip_config(id, config))
+}
+
+#[napi]
+pub fn get_clips() -> napi::Result<Vec<ClipStatus>> {
+    Ok(device_kit::control::api::clips()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|clip| ClipStatus {
+            id: clip.id,
+            path: clip.path.display().to_string(),
+            duration_seconds: clip.duration_seconds,
+            gain_db: clip.config.gain_db as f64,
+            polyphony: clip.config.polyphony,
+            choke_group: clip.config.choke_group,
+            voices: clip.voices,
+        })
+        .collect())
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ChannelUtilityOptions {
+    pub invert_left: Option<bool>,
+    pub invert_right: Option<bool>,
+    pub swap_channels: Option<bool>,
+    /// One of "stereo", "mono", "left" or "right".
+    pub mode: Option<String>,
+}
+
+#[napi]
+pub fn set_source_channel_utility(
+    channel: u32,
+    options: Option<ChannelUtilityOptions>,
+) -> napi::Result<bool> {
+    let options = options.unwrap_or_default();
+    let mode = match options.mode.as_deref() {
+        None => device_kit::stereo::ChannelMode::default(),
+        Some(name) => device_kit::stereo::ChannelMode::from_name(name).ok_or_else(|| {
+            Error::from_reason(format!(
+                "unknown channel mode '{name}', expected stereo, mono, left or right"
+            ))
+        })?,
+    };
+    let utility = device_kit::stereo::ChannelUtility {
+        invert_left: options.invert_left.unwrap_or(false),
+        invert_right: options.invert_right.unwrap_or(false),
+        swap_channels: options.swap_channels.unwrap_or(false),
+        mode,
+    };
+    Ok(device_kit::node_set_channel_utility(channel, utility))
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct DuckingOptions {
+    pub threshold_db: Option<f64>,
+    pub depth_db: Option<f64>,
+    pub attack_ms: Option<f64>,
+    pub release_ms: Option<f64>,
+    pub hold_ms: Option<f64>,
+}
+
+#[napi]
+pub fn set_source_ducking(
+    channel: u32,
+    targets: Vec<u32>,
+    options: Option<DuckingOptions>,
+) -> napi::Result<bool> {
+    if targets.len() > device_kit::dynamics::MAX_DUCK_TARGETS {
+        return Err(Error::from_reason(format!(
+            "at most {} ducking targets are supported, got {}",
+            device_kit::dynamics::MAX_DUCK_TARGETS,
+            targets.len()
+        )));
+    }
+    let defaults = device_kit::dynamics::DuckingParams::default();
+    let options = options.unwrap_or_default();
+    let params = device_kit::dynamics::DuckingParams {
+        threshold_db: options
+            .threshold_db
+            .map_or(defaults.threshold_db, |v| v as f32),
+        depth_db: options.depth_db.map_or(defaults.depth_db, |v| v as f32),
+        attack_ms: options.attack_ms.map_or(defaults.attack_ms, |v| v as f32),
+        release_ms: options.release_ms.map_or(defaults.release_ms, |v| v as f32),
+        hold_ms: options.hold_ms.map_or(defaults.hold_ms, |v| v as f32),
+    };
+    Ok(device_kit::node_set_ducking(channel, &targets, params))
+}
+
+#[napi]
+pub fn clear_source_ducking(channel: u32) -> napi::Result<bool> {
+    Ok(device_kit::node_clear_ducking(channel))
+}
+
+#[napi(object)]
+pub struct VoiceActivityEvent {
+    pub source_id: u32,
+    pub name: String,
+    pub speaking: bool,
+    pub probability: f64,
+}
+
+#[napi]
+pub struct VoiceActivityWatcher {
+    poller: Poller,
+}
+
+#[napi]
+impl VoiceActivityWatcher {
+    #[napi]
+    pub fn stop(&mut self) {
+        self.poller.stop();
+    }
+}
+
+#[napi(ts_args_type = "callback: (event: VoiceActivityEvent) => void, intervalMs?: number")]
+pub fn on_voice_activity(
+    env: Env,
+    callback: JsFunction,
+    interval_ms: Option<u32>,
+) -> napi::Result<VoiceActivityWatcher> {
+    let interval =
+        Duration::from_millis(interval_ms.unwrap_or(DEFAULT_VOICE_POLL_MS).max(1) as u64);
+    let mut last: HashMap<u32, bool> = HashMap::new();
+    let poller = Poller::spawn(&env, callback, interval, move || {
+        let sources = device_kit::control::api::voice_activity().unwrap_or_default();
+        sources
+            .into_iter()
+            .filter(|source| {
+                let previous = last.insert(source.id, source.speaking);
+                previous.unwrap_or(false) != source.speaking
+            })
+            .map(|source| VoiceActivityEvent {
+                source_id: source.id,
+                name: source.name,
+                speaking: source.speaking,
+                probability: source.probability as f64,
+            })
+            .collect()
+    })?;
+    Ok(VoiceActivityWatcher { poller })
+}
+
+#[napi(object)]
+pub struct MixerEvent {
+    pub timestamp_ns: f64,
+    pub kind: String,
+    pub source_id: Option<u32>,
+    pub frames: Option<u32>,
+    pub load: Option<f64>,
+    pub channels: Option<u32>,
+    pub message: String,
+}
+
+impl From<device_kit::events::MixerEvent> for MixerEvent {
+    fn from(event: device_kit::events::MixerEvent) -> Self {
+        use device_kit::events::MixerEventKind;
+
+        let mut converted = MixerEvent {
+            timestamp_ns: event.timestamp_ns as f64,
+            kind: event.kind.name().to_string(),
+            source_id: None,
+            frames: None,
+            load: None,
+            channels: None,
+            message: event.kind.to_string(),
+        };
+        match event.kind {
+            MixerEventKind::SourceUnderrun {
+                source_id,
+                missing_frames,
+            } => {
+                converted.source_id = Some(source_id);
+                converted.frames = Some(missing_frames);
+            }
+            MixerEventKind::RingOverflow {
+                source_id,
+                dropped_frames,
+            } => {
+                converted.source_id = Some(source_id);
+                converted.frames = Some(dropped_frames);
+            }
+            MixerEventKind::CallbackOverrun { load } => converted.load = Some(load as f64),
+            MixerEventKind::NonFinite { source_id } => converted.source_id = source_id,
+            MixerEventKind::FormatError { channels, frames } => {
+                converted.channels = Some(channels);
+                converted.frames = Some(frames);
+            }
+        }
+        converted
+    }
+}
+
+#[napi]
+pub struct MixerEventWatcher {
+    poller: Poller,
+}
+
+#[napi]
+impl MixerEventWatcher {
+    #[napi]
+    pub fn stop(&mut self) {
+        self.poller.stop();
+    }
+}
+
+/// Poll the shared mixer event log and call `callback` with each event. Reading consumes the
+/// events, so any other consumer in the process no longer sees them while a watcher runs.
+#[napi(ts_args_type = "callback: (event: MixerEvent) => void, intervalMs?: number")]
+pub fn on_mixer_event(
+    env: Env,
+    callback: JsFunction,
+    interval_ms: Option<u32>,
+) -> napi::Result<MixerEventWatcher> {
+    let interval =
+        Duration::from_millis(interval_ms.unwrap_or(DEFAULT_EVENT_POLL_MS).max(1) as u64);
+    let poller = Poller::spawn(&env, callback, interval, || {
+        device_kit::control::api::drain_events()
+            .unwrap_or_default()
+            .into_iter()
+            .map(MixerEvent::from)
+            .collect()
+    })?;
+    Ok(MixerEventWatcher { poller })
+}
+
+/// Background thread behind the `on*` subscriptions, calling `callback` with whatever each
+/// poll returns. Its threadsafe function is unref'd so a running watcher never keeps Node
+/// alive, and it is aborted when the watcher stops or is garbage-collected.
+struct Poller {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    fn spawn<T, F>(
+        env: &Env,
+        callback: JsFunction,
+        interval: Duration,
+        mut poll: F,
+    ) -> napi::Result<Self>
+    where
+        T: ToNapiValue + Send + 'static,
+        F: FnMut() -> Vec<T> + Send + 'static,
+    {
+        let mut tsfn: ThreadsafeFunction<T, ErrorStrategy::Fatal> =
+            callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        tsfn.unref(env)?;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread = std::thread::spawn(move || {
+            while thread_running.load(Ordering::Acquire) {
+                for value in poll() {
+                    tsfn.call(value, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+                std::thread::park_timeout(interval);
+            }
+            let _ = tsfn.abort();
+        });
+        Ok(Self {
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Wake the thread, wait for it to exit and release the callback. Idempotent.
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 #[napi]
 pub fn monotonic_time_ns() -> napi::Result<f64> {
     Ok(device_kit::device_kit_monotonic_time_ns() as f64)
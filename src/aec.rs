@@ -0,0 +1,249 @@
+//! Acoustic echo cancellation using a partitioned-block frequency-domain adaptive filter.
+//!
+//! The canceller runs on a source (typically the microphone) and models the echo path from a
+//! far-end reference — another source or the previous master output — with an overlap-save
+//! NLMS filter split into `BLOCK_SIZE`-sample partitions. The estimated echo of the mid signal is
+//! subtracted from both channels. Processing works on fixed blocks, so the source is delayed by
+//! `BLOCK_SIZE` frames while cancellation is enabled.
+
+use crate::SourceHandle;
+use crate::fft::Fft;
+
+/// Partition length in frames; also the latency added to the processed source.
+pub const BLOCK_SIZE: usize = 128;
+
+/// Default echo tail covered by the adaptive filter, in milliseconds.
+pub const DEFAULT_FILTER_LENGTH_MS: f32 = 64.0;
+
+/// Reference index selecting the master output in the C and control APIs.
+pub const ECHO_REFERENCE_MASTER: u32 = u32::MAX;
+
+const FFT_SIZE: usize = BLOCK_SIZE * 2;
+const BINS: usize = BLOCK_SIZE + 1;
+const STEP_SIZE: f32 = 0.5;
+/// Reference blocks quieter than this (-70 dBFS mean square) freeze adaptation.
+const REFERENCE_SILENCE: f32 = 1e-7;
+const REGULARISATION: f32 = 1e-6;
+const ERLE_SMOOTHING: f32 = 0.9;
+
+/// Far-end signal the canceller removes from its source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EchoReference {
+    /// Pre-fader signal of another mixer source.
+    Source(SourceHandle),
+    /// The mixer's master output from the previous render block.
+    Master,
+}
+
+/// Frequency-domain adaptive echo canceller bound to one mixer source.
+pub(crate) struct EchoCanceller {
+    reference: EchoReference,
+    fft: Fft,
+    partitions: usize,
+    /// Filter spectra, `partitions * BINS` complex values.
+    weights_re: Vec<f32>,
+    weights_im: Vec<f32>,
+    /// Reference spectra history, newest partition first (circular by `newest`).
+    history_re: Vec<f32>,
+    history_im: Vec<f32>,
+    newest: usize,
+    /// Partition whose gradient is constrained this block (round-robin).
+    constrained: usize,
+    reference_window: Vec<f32>,
+    near_left: Vec<f32>,
+    near_right: Vec<f32>,
+    out_left: Vec<f32>,
+    out_right: Vec<f32>,
+    position: usize,
+    work_re: Vec<f32>,
+    work_im: Vec<f32>,
+    error_re: Vec<f32>,
+    error_im: Vec<f32>,
+    step_re: Vec<f32>,
+    step_im: Vec<f32>,
+    erle_db: f32,
+}
+
+impl EchoCanceller {
+    pub(crate) fn new(reference: EchoReference, sample_rate: u32, filter_length_ms: f32) -> Self {
+        let taps = (filter_length_ms.max(1.0) * 0.001 * sample_rate as f32) as usize;
+        let partitions = taps.div_ceil(BLOCK_SIZE).max(1);
+        Self {
+            reference,
+            fft: Fft::new(FFT_SIZE),
+            partitions,
+            weights_re: vec![0.0; partitions * BINS],
+            weights_im: vec![0.0; partitions * BINS],
+            history_re: vec![0.0; partitions * BINS],
+            history_im: vec![0.0; partitions * BINS],
+            newest: 0,
+            constrained: 0,
+            reference_window: vec![0.0; FFT_SIZE],
+            near_left: vec![0.0; BLOCK_SIZE],
+            near_right: vec![0.0; BLOCK_SIZE],
+            out_left: vec![0.0; BLOCK_SIZE],
+            out_right: vec![0.0; BLOCK_SIZE],
+            position: 0,
+            work_re: vec![0.0; FFT_SIZE],
+            work_im: vec![0.0; FFT_SIZE],
+            error_re: vec![0.0; FFT_SIZE],
+            error_im: vec![0.0; FFT_SIZE],
+            step_re: vec![0.0; BINS],
+            step_im: vec![0.0; BINS],
+            erle_db: 0.0,
+        }
+    }
+
+    pub(crate) fn reference(&self) -> EchoReference {
+        self.reference
+    }
+
+    /// Smoothed echo return loss enhancement of recent blocks, in dB.
+    pub(crate) fn erle_db(&self) -> f32 {
+        self.erle_db
+    }
+
+    /// Cancel echo of `reference` from the interleaved stereo `near` block in place. Both slices
+    /// cover the same frames; `near` is delayed by `BLOCK_SIZE` frames.
+    pub(crate) fn process(&mut self, near: &mut [f32], reference: &[f32]) {
+        for (frame, far) in near.chunks_exact_mut(2).zip(reference.chunks_exact(2)) {
+            let pos = self.position;
+            let (left, right) = (frame[0], frame[1]);
+            frame[0] = self.out_left[pos];
+            frame[1] = self.out_right[pos];
+            self.near_left[pos] = left;
+            self.near_right[pos] = right;
+            self.reference_window[BLOCK_SIZE + pos] = 0.5 * (far[0] + far[1]);
+            self.position += 1;
+            if self.position == BLOCK_SIZE {
+                self.position = 0;
+                self.process_block();
+            }
+        }
+    }
+
+    fn process_block(&mut self) {
+        // Newest reference spectrum from the last two blocks (overlap-save).
+        self.newest = (self.newest + self.partitions - 1) % self.partitions;
+        self.work_re.copy_from_slice(&self.reference_window);
+        self.work_im.fill(0.0);
+        self.fft.forward(&mut self.work_re, &mut self.work_im);
+        let base = self.newest * BINS;
+        self.history_re[base..base + BINS].copy_from_slice(&self.work_re[..BINS]);
+        self.history_im[base..base + BINS].copy_from_slice(&self.work_im[..BINS]);
+        let reference_power = self.reference_window[BLOCK_SIZE..]
+            .iter()
+            .map(|s| s * s)
+            .sum::<f32>()
+            / BLOCK_SIZE as f32;
+        self.reference_window.copy_within(BLOCK_SIZE.., 0);
+
+        // Echo estimate Y = sum_p W_p * X_{k-p}.
+        self.work_re.fill(0.0);
+        self.work_im.fill(0.0);
+        for p in 0..self.partitions {
+            let w = p * BINS;
+            let x = ((self.newest + p) % self.partitions) * BINS;
+            for k in 0..BINS {
+                let (wr, wi) = (self.weights_re[w + k], self.weights_im[w + k]);
+                let (xr, xi) = (self.history_re[x + k], self.history_im[x + k]);
+                self.work_re[k] += wr * xr - wi * xi;
+                self.work_im[k] += wr * xi + wi * xr;
+            }
+        }
+        mirror_spectrum(&mut self.work_re, &mut self.work_im);
+        self.fft.inverse(&mut self.work_re, &mut self.work_im);
+
+        // Error e = d - y on the mid signal; output removes the echo from both channels.
+        let mut near_energy = 0.0f32;
+        let mut error_energy = 0.0f32;
+        self.error_re[..BLOCK_SIZE].fill(0.0);
+        for n in 0..BLOCK_SIZE {
+            let echo = self.work_re[BLOCK_SIZE + n];
+            let mid = 0.5 * (self.near_left[n] + self.near_right[n]);
+            let error = mid - echo;
+            near_energy += mid * mid;
+            error_energy += error * error;
+            self.error_re[BLOCK_SIZE + n] = error;
+            self.out_left[n] = self.near_left[n] - echo;
+            self.out_right[n] = self.near_right[n] - echo;
+        }
+        if near_energy > 1e-9 && reference_power > REFERENCE_SILENCE {
+            let erle = 10.0 * (near_energy / (error_energy + 1e-12)).log10();
+            self.erle_db = ERLE_SMOOTHING * self.erle_db + (1.0 - ERLE_SMOOTHING) * erle;
+        }
+
+        if reference_power <= REFERENCE_SILENCE {
+            return;
+        }
+        self.adapt();
+    }
+
+    /// NLMS update of every partition from the current error block. As in the multidelay filter,
+    /// only one partition per block gets the gradient constraint, which keeps the cost close to
+    /// two FFTs per block while the filter still converges to a linear convolution.
+    fn adapt(&mut self) {
+        self.error_im.fill(0.0);
+        self.fft.forward(&mut self.error_re, &mut self.error_im);
+
+        // Per-bin step normalised by the reference energy across the whole filter length.
+        for k in 0..BINS {
+            let mut power = 0.0f32;
+            for p in 0..self.partitions {
+                let x = p * BINS + k;
+                power += self.history_re[x] * self.history_re[x]
+                    + self.history_im[x] * self.history_im[x];
+            }
+            let norm = STEP_SIZE / (power + REGULARISATION);
+            self.step_re[k] = self.error_re[k] * norm;
+            self.step_im[k] = self.error_im[k] * norm;
+        }
+
+        for p in 0..self.partitions {
+            let w = p * BINS;
+            let x = ((self.newest + p) % self.partitions) * BINS;
+            // Gradient conj(X) * E.
+            for k in 0..BINS {
+                let (xr, xi) = (self.history_re[x + k], self.history_im[x + k]);
+                let (er, ei) = (self.step_re[k], self.step_im[k]);
+                self.work_re[k] = xr * er + xi * ei;
+                self.work_im[k] = xr * ei - xi * er;
+            }
+            if p == self.constrained {
+                // Restrict this partition to its first BLOCK_SIZE taps so the accumulated
+                // circular wrap-around is removed.
+                self.work_re[..BINS]
+                    .iter_mut()
+                    .zip(&self.weights_re[w..w + BINS])
+                    .for_each(|(g, w)| *g += w);
+                self.work_im[..BINS]
+                    .iter_mut()
+                    .zip(&self.weights_im[w..w + BINS])
+                    .for_each(|(g, w)| *g += w);
+                mirror_spectrum(&mut self.work_re, &mut self.work_im);
+                self.fft.inverse(&mut self.work_re, &mut self.work_im);
+                self.work_re[BLOCK_SIZE..].fill(0.0);
+                self.work_im.fill(0.0);
+                self.fft.forward(&mut self.work_re, &mut self.work_im);
+                self.weights_re[w..w + BINS].copy_from_slice(&self.work_re[..BINS]);
+                self.weights_im[w..w + BINS].copy_from_slice(&self.work_im[..BINS]);
+                continue;
+            }
+            for k in 0..BINS {
+                self.weights_re[w + k] += self.work_re[k];
+                self.weights_im[w + k] += self.work_im[k];
+            }
+        }
+        self.constrained = (self.constrained + 1) % self.partitions;
+    }
+}
+
+/// Fill bins above Nyquist with the conjugate mirror so the inverse FFT is real.
+fn mirror_spectrum(re: &mut [f32], im: &mut [f32]) {
+    im[0] = 0.0;
+    im[BLOCK_SIZE] = 0.0;
+    for k in 1..BLOCK_SIZE {
+        re[FFT_SIZE - k] = re[k];
+        im[FFT_SIZE - k] = -im[k];
+    }
+}
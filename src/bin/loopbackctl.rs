@@ -1,7 +1,75 @@
 use std::env;
+use std::io;
 use std::process;
+use std::thread;
+use std::time::Duration;
 
 use device_kit::LoopbackLevels;
+use device_kit::events::MixerEvent;
+use device_kit::generator::{GeneratorConfig, Waveform};
+use device_kit::offline::RenderSession;
+use device_kit::player::PlayerStatus;
+use device_kit::timing::LOAD_BUCKET_WIDTH;
+use device_kit::wav::SampleFormat;
+
+/// Source id `loopbackctl play` loads files into unless `--source` says otherwise.
+const DEFAULT_PLAYER_SOURCE: u32 = 1_000;
+/// Source id `loopbackctl tone` adds its generator as unless `--source` says otherwise.
+const DEFAULT_GENERATOR_SOURCE: u32 = 1_001;
+
+fn to_db(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
+fn format_loudness(value: f32) -> String {
+    if value.is_finite() {
+        format!("{value:.1}")
+    } else {
+        "-inf".to_string()
+    }
+}
+
+/// One-line transport summary such as `intro.wav 12.3/45.0 s playing loop`.
+fn format_file_status(status: &PlayerStatus) -> String {
+    let state = if let Some(error) = &status.error {
+        format!("failed: {error}")
+    } else if status.finished {
+        "finished".to_string()
+    } else if status.playing {
+        "playing".to_string()
+    } else {
+        "paused".to_string()
+    };
+    format!(
+        "{} {:.1}/{:.1} s {state}{}",
+        status.path.display(),
+        status.position_seconds,
+        status.duration_seconds,
+        if status.looping { " loop" } else { "" },
+    )
+}
+
+/// Callback counts grouped into 10% load steps, up to the last non-empty step.
+fn format_histogram(histogram: &[u64]) -> String {
+    let per_step = (0.1 / LOAD_BUCKET_WIDTH).round() as usize;
+    let steps: Vec<u64> = histogram
+        .chunks(per_step)
+        .map(|chunk| chunk.iter().sum())
+        .collect();
+    let used = steps
+        .iter()
+        .rposition(|&count| count > 0)
+        .map_or(0, |last| last + 1);
+    if used == 0 {
+        return "-".to_string();
+    }
+    steps[..used]
+        .iter()
+        .enumerate()
+        .map(|(step, count)| format!("{}%:{count}", step * 10))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
 fn print_status() {
     match device_kit::control::api::get_status() {
@@ -10,29 +78,133 @@ fn print_status() {
             println!("Buffer Size : {} frames", status.buffer_frames);
             println!("Latency     : {:.2} ms", status.latency_ms);
             println!("CPU Usage   : {:.1}%", status.cpu_usage * 100.0);
+            println!(
+                "Callback    : min={:.1}% | avg={:.1}% | max={:.1}% | p99={:.1}% | overruns={}/{}",
+                status.timing.min_load * 100.0,
+                status.timing.avg_load * 100.0,
+                status.timing.max_load * 100.0,
+                status.timing.p99_load * 100.0,
+                status.timing.overruns,
+                status.timing.callbacks,
+            );
+            println!(
+                "Load Histo  : {}",
+                format_histogram(&status.timing.histogram)
+            );
+            println!(
+                "Events      : underruns={} | overflows={} | overruns={} | non-finite={} | format errors={} | dropped={}",
+                status.events.source_underruns,
+                status.events.ring_overflows,
+                status.events.callback_overruns,
+                status.events.non_finite,
+                status.events.format_errors,
+                status.events.dropped,
+            );
             println!("Buffer Fill : {:.1}%", status.buffer_fill * 100.0);
             println!("Drift       : {:.1} ppm", status.drift_ppm);
+            println!(
+                "Master      : rms={:.1} dBFS | peak={:.1} dBFS (hold {:.1}) | true peak={:.1} dBTP{}",
+                to_db(status.master.rms),
+                to_db(status.master.peak),
+                to_db(status.master.peak_hold),
+                to_db(status.master.true_peak),
+                if status.master.clipped { " | CLIP" } else { "" },
+            );
+            println!(
+                "Stereo      : correlation={:+.2} | width={:.2}{}",
+                status.stereo.correlation,
+                status.stereo.width,
+                if status.mono_warning {
+                    " | MONO WARNING"
+                } else {
+                    ""
+                },
+            );
+            println!(
+                "Loudness    : M {} | S {} | I {} LUFS | LRA {} LU",
+                format_loudness(status.loudness.momentary),
+                format_loudness(status.loudness.short_term),
+                format_loudness(status.loudness.integrated),
+                format_loudness(status.loudness.range),
+            );
+            match status.normalization {
+                Some(params) => println!(
+                    "Normalize   : {:+.1} dB toward {:.1} LUFS (max +{:.1} / -{:.1} dB)",
+                    status.normalization_gain_db,
+                    params.target_lufs,
+                    params.max_gain_db,
+                    params.max_cut_db,
+                ),
+                None => println!("Normalize   : off"),
+            }
+            match &status.recording {
+                Some(recording) => println!(
+                    "Recording   : {} ({}) | {:.1} s | dropped={} frames",
+                    recording.path.display(),
+                    recording.format.name(),
+                    recording.frames as f64 / status.sample_rate.max(1) as f64,
+                    recording.dropped_frames,
+                ),
+                None => println!("Recording   : off"),
+            }
+            if let Some(multitrack) = &status.multitrack {
+                println!(
+                    "Stems       : {} ({}) | {} sources | {:.1} s | dropped={} frames",
+                    multitrack.directory.display(),
+                    multitrack.format.name(),
+                    multitrack.stems.len(),
+                    multitrack.master.frames as f64 / status.sample_rate.max(1) as f64,
+                    multitrack
+                        .stems
+                        .iter()
+                        .map(|stem| stem.recording.dropped_frames)
+                        .fold(multitrack.master.dropped_frames, u64::max),
+                );
+            }
             println!("Sources:");
             for source in status.sources {
                 println!(
-                    "  [{}] {} | gain={:.1} dB | mute={} | rms={:.2} | latency={} frames | fill={:.1}% | drift={:.1} ppm",
+                    "  [{}] {} | gain={:.1} dB | mute={} | channels={} | rms={:.1} dBFS | peak={:.1} dBFS | tp={:.1} dBTP{} | corr={:+.2} | width={:.2}{} | latency={} frames | fill={:.1}% | drift={:.1} ppm | voice={} ({:.0}%) | aec={} | denoise={}",
                     source.id,
                     source.name,
                     source.gain_db,
                     if source.muted { "yes" } else { "no" },
-                    source.rms,
+                    source.channel_utility,
+                    to_db(source.rms),
+                    to_db(source.peak),
+                    to_db(source.true_peak),
+                    if source.clipped { " CLIP" } else { "" },
+                    source.correlation,
+                    source.stereo_width,
+                    if source.mono_warning { " MONO" } else { "" },
                     source.latency_frames,
                     source.buffer_fill * 100.0,
                     source.drift_ppm,
+                    if source.speaking {
+                        "speaking"
+                    } else {
+                        "silent"
+                    },
+                    source.voice_probability * 100.0,
+                    if source.echo_cancelling {
+                        format!("{:.1} dB ERLE", source.erle_db)
+                    } else {
+                        "off".to_string()
+                    },
+                    match source.noise_suppression {
+                        Some(strength) => format!("{:.0}%", strength * 100.0),
+                        None => "off".to_string(),
+                    },
                 );
+                if let Some(file) = &source.file {
+                    println!("      file {}", format_file_status(file));
+                }
+                if let Some(generator) = &source.generator {
+                    println!("      generator {generator}");
+                }
             }
 
-            let mut levels = LoopbackLevels {
-                inputs: [0.0; 8],
-                outputs: [0.0; 8],
-                input_count: 0,
-                output_count: 0,
-            };
+            let mut levels = LoopbackLevels::default();
             if unsafe { device_kit::device_kit_get_levels(&mut levels as *mut LoopbackLevels) } {
                 if levels.output_count > 0 {
                     println!("Output Levels:");
@@ -61,6 +233,261 @@ fn print_status() {
     }
 }
 
+fn print_event(event: &MixerEvent) {
+    println!(
+        "{:>16.6}  {:<16}  {}",
+        event.timestamp_ns as f64 * 1e-9,
+        event.kind.name(),
+        event.kind
+    );
+}
+
+/// Print unread events; with `follow`, keep polling until interrupted.
+fn print_events(follow: bool) {
+    let mut dropped = 0;
+    loop {
+        let Some(events) = device_kit::control::api::drain_events() else {
+            eprintln!("loopbackctl: no active mixer detected");
+            process::exit(1);
+        };
+        for event in &events {
+            print_event(event);
+        }
+        if !follow {
+            return;
+        }
+        if let Some(status) = device_kit::control::api::get_status() {
+            if status.events.dropped > dropped {
+                eprintln!(
+                    "loopbackctl: {} events lost, the log was full",
+                    status.events.dropped - dropped
+                );
+            }
+            dropped = status.events.dropped;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Wait `duration` seconds, or until Enter is pressed, while recording to `path`.
+fn wait_for_stop(path: &str, duration: Option<f64>) {
+    match duration {
+        Some(seconds) => {
+            eprintln!("loopbackctl: recording to {path} for {seconds} s");
+            thread::sleep(Duration::from_secs_f64(seconds));
+        }
+        None => {
+            eprintln!("loopbackctl: recording to {path}, press Enter to stop");
+            let _ = io::stdin().read_line(&mut String::new());
+        }
+    }
+}
+
+/// Record the master output to `path` for `duration` seconds, or until Enter is pressed.
+fn record(path: &str, format: SampleFormat, duration: Option<f64>) {
+    if let Err(err) = device_kit::control::api::start_recording(path, format) {
+        eprintln!("loopbackctl: cannot record to {path}: {err}");
+        process::exit(1);
+    }
+    wait_for_stop(path, duration);
+    match device_kit::control::api::stop_recording() {
+        Ok(status) => {
+            println!(
+                "Recorded {} frames to {} ({}), {} dropped",
+                status.frames,
+                status.path.display(),
+                status.format.name(),
+                status.dropped_frames,
+            );
+        }
+        Err(err) => {
+            eprintln!("loopbackctl: recording to {path} failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Record the master and a stem per source into `directory`, then list the tracks written.
+fn record_stems(directory: &str, format: SampleFormat, duration: Option<f64>) {
+    if let Err(err) = device_kit::control::api::start_multitrack(directory, format) {
+        eprintln!("loopbackctl: cannot record stems to {directory}: {err}");
+        process::exit(1);
+    }
+    wait_for_stop(directory, duration);
+    match device_kit::control::api::stop_multitrack() {
+        Ok(status) => {
+            println!(
+                "Recorded {} frames per track to {} ({})",
+                status.master.frames,
+                status.directory.display(),
+                status.format.name(),
+            );
+            println!(
+                "  master    -> {} ({} dropped)",
+                status.master.path.display(),
+                status.master.dropped_frames
+            );
+            for stem in &status.stems {
+                println!(
+                    "  [{}] {} -> {} ({} dropped)",
+                    stem.source_id,
+                    stem.name,
+                    stem.recording.path.display(),
+                    stem.recording.dropped_frames,
+                );
+            }
+            println!(
+                "Manifest: {}",
+                status
+                    .directory
+                    .join(device_kit::recorder::MANIFEST_FILE_NAME)
+                    .display()
+            );
+        }
+        Err(err) => {
+            eprintln!("loopbackctl: recording stems to {directory} failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Play `path` into the mix as source `source_id` until it ends or `q` is entered. Lines read
+/// from stdin control the transport: `p` pauses or resumes, `l` toggles looping and a number
+/// seeks to that many seconds.
+fn play(path: &str, source_id: u32, looping: bool, start: Option<f64>) {
+    use device_kit::control::api;
+
+    if let Err(err) = api::load_file(source_id, path) {
+        eprintln!("loopbackctl: cannot play {path}: {err}");
+        process::exit(1);
+    }
+    api::set_file_loop(source_id, looping);
+    if let Some(seconds) = start {
+        api::seek_file(source_id, seconds);
+    }
+    api::play_file(source_id);
+    eprintln!(
+        "loopbackctl: playing {path} as source {source_id}; p pause/resume, l loop, SECONDS seek, q stop"
+    );
+
+    let (commands, received) = crossbeam_channel::unbounded();
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if commands.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    loop {
+        if let Ok(line) = received.recv_timeout(Duration::from_millis(200)) {
+            let Some(status) = api::file_status(source_id) else {
+                break;
+            };
+            match line.trim() {
+                "q" => break,
+                "p" if status.playing => {
+                    api::pause_file(source_id);
+                }
+                "p" => {
+                    api::play_file(source_id);
+                }
+                "l" => {
+                    api::set_file_loop(source_id, !status.looping);
+                }
+                "" => {}
+                other => match other.parse::<f64>() {
+                    Ok(seconds) if seconds.is_finite() => {
+                        api::seek_file(source_id, seconds);
+                    }
+                    _ => eprintln!("loopbackctl: unknown command '{other}'"),
+                },
+            }
+        }
+        let Some(status) = api::file_status(source_id) else {
+            break;
+        };
+        println!("{}", format_file_status(&status));
+        if status.finished || status.error.is_some() {
+            break;
+        }
+    }
+    api::unload_file(source_id);
+}
+
+/// Render the session file at `session` offline into `output`, without touching the live mixer.
+fn render(session: &str, output: &str, format: SampleFormat) {
+    let result = RenderSession::load(session).and_then(|session| {
+        let (mut renderer, frames) = session.renderer()?;
+        renderer.render_to_wav(output, frames, format)?;
+        Ok((frames, session.sample_rate))
+    });
+    match result {
+        Ok((frames, sample_rate)) => println!(
+            "Rendered {:.3} s to {output}",
+            frames as f64 / sample_rate as f64
+        ),
+        Err(err) => {
+            eprintln!("loopbackctl: {session}: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Feed `config` into the mix as source `source_id` until `q` or an empty line is entered.
+fn tone(config: GeneratorConfig, source_id: u32) {
+    use device_kit::control::api;
+
+    if let Err(err) = api::add_generator(source_id, config) {
+        eprintln!("loopbackctl: cannot start {config}: {err}");
+        process::exit(1);
+    }
+    eprintln!("loopbackctl: playing {config} as source {source_id}; Enter or q stops");
+    for line in io::stdin().lines() {
+        match line.as_deref().map(str::trim) {
+            Ok("" | "q") | Err(_) => break,
+            Ok(other) => eprintln!("loopbackctl: unknown command '{other}'"),
+        }
+    }
+    api::remove_generator(source_id);
+}
+
+/// Print OpenMetrics telemetry once, or with `serve` keep an HTTP endpoint up until interrupted.
+#[cfg(feature = "metrics")]
+fn print_metrics(serve: Option<String>) {
+    let Some(addr) = serve else {
+        match device_kit::control::api::metrics_text() {
+            Some(text) => print!("{text}"),
+            None => {
+                eprintln!("loopbackctl: no active mixer detected");
+                process::exit(1);
+            }
+        }
+        return;
+    };
+    match device_kit::control::api::serve_metrics(addr.as_str()) {
+        Ok(server) => {
+            eprintln!(
+                "loopbackctl: serving metrics at http://{}/metrics",
+                server.local_addr()
+            );
+            loop {
+                thread::sleep(Duration::from_secs(60));
+            }
+        }
+        Err(err) => {
+            eprintln!("loopbackctl: cannot serve metrics on {addr}: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+fn print_metrics(_serve: Option<String>) {
+    eprintln!("loopbackctl: built without the `metrics` feature");
+    process::exit(1);
+}
+
 fn main() {
     let mut args = env::args().skip(1);
     if let Some(arg) = args.next() {
@@ -69,9 +496,227 @@ fn main() {
                 print_status();
                 return;
             }
+            "--reset-loudness" => {
+                if !device_kit::control::api::reset_loudness() {
+                    eprintln!("loopbackctl: no active mixer detected");
+                    process::exit(1);
+                }
+                return;
+            }
+            "--reset-timing" => {
+                if !device_kit::control::api::reset_timing() {
+                    eprintln!("loopbackctl: no active mixer detected");
+                    process::exit(1);
+                }
+                return;
+            }
+            "events" => {
+                let follow = match args.next().as_deref() {
+                    None => false,
+                    Some("--follow" | "-f") => true,
+                    Some(other) => {
+                        eprintln!("loopbackctl: unknown events argument '{other}'");
+                        process::exit(1);
+                    }
+                };
+                print_events(follow);
+                return;
+            }
+            "record" => {
+                let Some(path) = args.next() else {
+                    eprintln!("loopbackctl: record needs an output file");
+                    process::exit(1);
+                };
+                let mut format = SampleFormat::Float32;
+                let mut duration = None;
+                let mut stems = false;
+                while let Some(option) = args.next() {
+                    if option == "--stems" {
+                        stems = true;
+                        continue;
+                    }
+                    let value = args.next();
+                    match (option.as_str(), value.as_deref()) {
+                        ("--format", Some(name)) => {
+                            format = SampleFormat::from_name(name).unwrap_or_else(|| {
+                                eprintln!(
+                                    "loopbackctl: unknown format '{name}', expected f32 or pcm24"
+                                );
+                                process::exit(1);
+                            });
+                        }
+                        ("--duration", Some(seconds)) => match seconds.parse::<f64>() {
+                            Ok(seconds) if seconds.is_finite() && seconds > 0.0 => {
+                                duration = Some(seconds)
+                            }
+                            _ => {
+                                eprintln!("loopbackctl: invalid duration '{seconds}'");
+                                process::exit(1);
+                            }
+                        },
+                        _ => {
+                            eprintln!("loopbackctl: unknown record argument '{option}'");
+                            process::exit(1);
+                        }
+                    }
+                }
+                if stems {
+                    record_stems(&path, format, duration);
+                } else {
+                    record(&path, format, duration);
+                }
+                return;
+            }
+            "play" => {
+                let Some(path) = args.next() else {
+                    eprintln!("loopbackctl: play needs a WAV or FLAC file");
+                    process::exit(1);
+                };
+                let mut looping = false;
+                let mut start = None;
+                let mut source_id = DEFAULT_PLAYER_SOURCE;
+                while let Some(option) = args.next() {
+                    if option == "--loop" {
+                        looping = true;
+                        continue;
+                    }
+                    let value = args.next();
+                    match (option.as_str(), value.as_deref()) {
+                        ("--start", Some(seconds)) => match seconds.parse::<f64>() {
+                            Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+                                start = Some(seconds)
+                            }
+                            _ => {
+                                eprintln!("loopbackctl: invalid start time '{seconds}'");
+                                process::exit(1);
+                            }
+                        },
+                        ("--source", Some(id)) => match id.parse::<u32>() {
+                            Ok(id) if id != 0 => source_id = id,
+                            _ => {
+                                eprintln!("loopbackctl: invalid source id '{id}'");
+                                process::exit(1);
+                            }
+                        },
+                        _ => {
+                            eprintln!("loopbackctl: unknown play argument '{option}'");
+                            process::exit(1);
+                        }
+                    }
+                }
+                play(&path, source_id, looping, start);
+                return;
+            }
+            "tone" => {
+                let kind = args.next().unwrap_or_else(|| "reference".to_string());
+                let mut frequency = None;
+                let mut to = None;
+                let mut duration = None;
+                let mut level = None;
+                let mut source_id = DEFAULT_GENERATOR_SOURCE;
+                while let Some(option) = args.next() {
+                    let value = args.next();
+                    let Some(value) = value.as_deref() else {
+                        eprintln!("loopbackctl: {option} needs a value");
+                        process::exit(1);
+                    };
+                    if option == "--source" {
+                        match value.parse::<u32>() {
+                            Ok(id) if id != 0 => source_id = id,
+                            _ => {
+                                eprintln!("loopbackctl: invalid source id '{value}'");
+                                process::exit(1);
+                            }
+                        }
+                        continue;
+                    }
+                    let slot = match option.as_str() {
+                        "--freq" => &mut frequency,
+                        "--to" => &mut to,
+                        "--duration" | "--period" => &mut duration,
+                        "--level" => &mut level,
+                        _ => {
+                            eprintln!("loopbackctl: unknown tone argument '{option}'");
+                            process::exit(1);
+                        }
+                    };
+                    match value.parse::<f32>() {
+                        Ok(number) if number.is_finite() => *slot = Some(number),
+                        _ => {
+                            eprintln!("loopbackctl: invalid {option} value '{value}'");
+                            process::exit(1);
+                        }
+                    }
+                }
+                let Some(waveform) = Waveform::from_name(&kind, frequency, to, duration) else {
+                    eprintln!(
+                        "loopbackctl: unknown tone '{kind}', expected sine, sweep, white, pink, impulse or reference"
+                    );
+                    process::exit(1);
+                };
+                let mut config = GeneratorConfig::new(waveform);
+                if let Some(level) = level {
+                    config.level_dbfs = level;
+                }
+                if !config.is_valid() {
+                    eprintln!(
+                        "loopbackctl: invalid tone {config}; frequencies and durations must be positive and the level at most 0 dBFS"
+                    );
+                    process::exit(1);
+                }
+                tone(config, source_id);
+                return;
+            }
+            "render" => {
+                let Some(session) = args.next() else {
+                    eprintln!("loopbackctl: render needs a session file");
+                    process::exit(1);
+                };
+                let mut output = None;
+                let mut format = SampleFormat::Float32;
+                while let Some(option) = args.next() {
+                    let value = args.next();
+                    match (option.as_str(), value.as_deref()) {
+                        ("-o" | "--output", Some(path)) => output = Some(path.to_string()),
+                        ("--format", Some(name)) => {
+                            format = SampleFormat::from_name(name).unwrap_or_else(|| {
+                                eprintln!(
+                                    "loopbackctl: unknown format '{name}', expected f32 or pcm24"
+                                );
+                                process::exit(1);
+                            });
+                        }
+                        _ => {
+                            eprintln!("loopbackctl: unknown render argument '{option}'");
+                            process::exit(1);
+                        }
+                    }
+                }
+                let Some(output) = output else {
+                    eprintln!("loopbackctl: render needs an output file, given with -o");
+                    process::exit(1);
+                };
+                render(&session, &output, format);
+                return;
+            }
+            "metrics" => {
+                let serve = match args.next().as_deref() {
+                    None => None,
+                    Some("--serve") => Some(args.next().unwrap_or_else(|| {
+                        eprintln!("loopbackctl: --serve needs an address such as 127.0.0.1:9464");
+                        process::exit(1);
+                    })),
+                    Some(other) => {
+                        eprintln!("loopbackctl: unknown metrics argument '{other}'");
+                        process::exit(1);
+                    }
+                };
+                print_metrics(serve);
+                return;
+            }
             "--help" | "-h" => {
                 println!(
-                    "Usage: loopbackctl [--status]\n\nWithout arguments the interactive console launches."
+                    "Usage: loopbackctl [--status | --reset-loudness | --reset-timing | events [--follow] | record FILE|DIR [--stems] [--format f32|pcm24] [--duration SECONDS] | play FILE [--loop] [--start SECONDS] [--source ID] | tone [sine|sweep|white|pink|impulse|reference] [--freq HZ] [--to HZ] [--duration SECONDS] [--level DBFS] [--source ID] | render SESSION -o FILE [--format f32|pcm24] | metrics [--serve ADDR]]\n\nWithout arguments the interactive console launches."
                 );
                 return;
             }
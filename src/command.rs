@@ -0,0 +1,104 @@
+//! Lock-free handoff of render-side processors from the control thread.
+//!
+//! Processors the render thread owns are built on the control side and sent as a [`Command`].
+//! At the start of each block the render thread swaps every pending command's contents with
+//! what it currently holds and sends the command back, now carrying whatever it replaced. The
+//! control side frees that the next time it sends a command, so the render thread never
+//! allocates or frees and the control side never touches a processor while it is in use.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Source;
+use crate::aec::EchoCanceller;
+use crate::denoise::NoiseSuppressor;
+use crate::loudness::LoudnessMeter;
+use crate::queue::BoundedQueue;
+use crate::recorder::RecordTap;
+
+/// Commands that may be sent before the render thread has sent any back.
+pub(crate) const COMMAND_CAPACITY: usize = 128;
+
+/// Render-side state to swap in at the next block.
+pub(crate) enum Command {
+    /// Install, replace or (with `None`) remove a source's echo canceller.
+    EchoCanceller {
+        source_id: u32,
+        canceller: Option<Box<EchoCanceller>>,
+    },
+    /// Install or (with `None`) remove a source's noise suppressor.
+    NoiseSuppressor {
+        source_id: u32,
+        suppressor: Option<Box<NoiseSuppressor>>,
+    },
+    /// Install or (with `None`) remove a source's loudness meter.
+    LoudnessMeter {
+        source_id: u32,
+        meter: Option<Box<LoudnessMeter>>,
+    },
+    /// Detach a source from the mix. The render thread sends the source back in `source` so the
+    /// control side frees it, joining its decoder thread if it plays a file.
+    RemoveSource {
+        source_id: u32,
+        source: Option<Box<Source>>,
+    },
+    /// Start or (with `None`) stop feeding the master output to a recording.
+    Recording(Option<Arc<RecordTap>>),
+    /// Start or (with `None`) stop feeding the master and each listed source to a multitrack
+    /// session.
+    Multitrack {
+        master: Option<Arc<RecordTap>>,
+        stems: Box<[(u32, Option<Arc<RecordTap>>)]>,
+    },
+}
+
+/// Pair of queues carrying commands to the render thread and back.
+pub(crate) struct CommandQueue {
+    pending: BoundedQueue<Command>,
+    retired: BoundedQueue<Command>,
+    /// Commands sent and not yet reclaimed. Only the control side updates it.
+    outstanding: AtomicUsize,
+}
+
+impl CommandQueue {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: BoundedQueue::new(COMMAND_CAPACITY),
+            retired: BoundedQueue::new(COMMAND_CAPACITY),
+            outstanding: AtomicUsize::new(0),
+        }
+    }
+
+    /// Queue `command` for the render thread after freeing whatever it has sent back. Hands the
+    /// command back if [`COMMAND_CAPACITY`] commands are still outstanding.
+    pub(crate) fn send(&self, command: Command) -> Result<(), Command> {
+        self.reclaim();
+        if self.outstanding.load(Ordering::Relaxed) >= COMMAND_CAPACITY {
+            return Err(command);
+        }
+        self.pending.push(command)?;
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Free the state the render thread has replaced since the last call.
+    pub(crate) fn reclaim(&self) {
+        while let Some(command) = self.retired.pop() {
+            self.outstanding.fetch_sub(1, Ordering::Relaxed);
+            drop(command);
+        }
+    }
+
+    /// Take the oldest command the render thread has not applied yet.
+    pub(crate) fn receive(&self) -> Option<Command> {
+        self.pending.pop()
+    }
+
+    /// Send an applied command, now holding the state it replaced, back to the control side.
+    pub(crate) fn acknowledge(&self, command: Command) {
+        // `send` never lets more commands be outstanding than `retired` holds, so this cannot
+        // fail and drop the replaced state here on the render thread.
+        let returned = self.retired.push(command);
+        debug_assert!(returned.is_ok(), "retired command queue overflowed");
+    }
+}
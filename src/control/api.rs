@@ -1,10 +1,180 @@
-use crate::{MixerStatus, get_mixer_status, set_source_gain_db, set_source_mute};
+use crate::dynamics::DuckingParams;
+use crate::events::MixerEvent;
+use crate::generator::GeneratorConfig;
+use crate::loudness::NormalizationParams;
+use crate::player::{PlayerError, PlayerStatus};
+use crate::recorder::{MultitrackStatus, RecorderError, RecordingStatus};
+use crate::soundboard::{ClipConfig, ClipStatus, SoundboardError};
+use crate::spectrum::{Spectrum, SpectrumConfig};
+use crate::stereo::ChannelUtility;
+use crate::wav::SampleFormat;
+use crate::{
+    MixerError, MixerStatus, VoiceActivity, add_source_generator, clear_source_ducking,
+    disable_source_echo_cancellation, drain_mixer_events, enable_source_echo_cancellation,
+    get_mixer_spectrum, get_mixer_status, get_soundboard_clips, get_source_file_status,
+    get_source_generator, get_voice_activity, load_soundboard_clip, load_source_file,
+    remove_source_generator, reset_callback_timing, reset_loudness_measurement, seek_source_file,
+    set_automix_sources, set_master_normalization, set_mixer_spectrum, set_soundboard_clip_config,
+    set_source_automix_weight, set_source_channel_utility, set_source_ducking,
+    set_source_file_looping, set_source_file_playing, set_source_gain_db, set_source_generator,
+    set_source_loudness_metering, set_source_mute, set_source_noise_suppression,
+    start_master_recording, start_mixer_multitrack, stop_master_recording, stop_mixer_multitrack,
+    stop_soundboard, stop_soundboard_clip, trigger_soundboard_clip, unload_soundboard_clip,
+    unload_source_file,
+};
 
 /// Fetch the current mixer status snapshot if the mixer is active.
 pub fn get_status() -> Option<MixerStatus> {
     get_mixer_status()
 }
 
+/// Fetch the voice activity of every source if the mixer is active.
+pub fn voice_activity() -> Option<Vec<VoiceActivity>> {
+    get_voice_activity()
+}
+
+/// Take every unread dropout and glitch event, oldest first, if the mixer is active.
+pub fn drain_events() -> Option<Vec<MixerEvent>> {
+    drain_mixer_events()
+}
+
+/// Render the current mixer status as OpenMetrics text, if the mixer is active.
+#[cfg(feature = "metrics")]
+pub fn metrics_text() -> Option<String> {
+    get_mixer_status().map(|status| crate::metrics::render(&status))
+}
+
+/// Serve OpenMetrics telemetry at `http://<addr>/metrics` until the returned server is dropped.
+#[cfg(feature = "metrics")]
+pub fn serve_metrics(
+    addr: impl std::net::ToSocketAddrs,
+) -> std::io::Result<crate::metrics::MetricsServer> {
+    crate::metrics::MetricsServer::start(addr)
+}
+
+/// Record the loopback output to a WAV file at `path` until [`stop_recording`] is called.
+pub fn start_recording(
+    path: impl AsRef<std::path::Path>,
+    format: SampleFormat,
+) -> Result<(), RecorderError> {
+    start_master_recording(path.as_ref(), format)
+}
+
+/// Finish the running recording, returning how many frames were written and dropped.
+pub fn stop_recording() -> Result<RecordingStatus, RecorderError> {
+    stop_master_recording()
+}
+
+/// Record the loopback output and every source's post-fader signal to separate files in
+/// `directory` until [`stop_multitrack`] is called.
+pub fn start_multitrack(
+    directory: impl AsRef<std::path::Path>,
+    format: SampleFormat,
+) -> Result<(), RecorderError> {
+    start_mixer_multitrack(directory.as_ref(), format)
+}
+
+/// Finish the running multitrack session and write its manifest, returning every track's
+/// outcome.
+pub fn stop_multitrack() -> Result<MultitrackStatus, RecorderError> {
+    stop_mixer_multitrack()
+}
+
+/// Load a WAV or FLAC file as a new, paused source with the given id.
+pub fn load_file(source_id: u32, path: impl AsRef<std::path::Path>) -> Result<(), PlayerError> {
+    load_source_file(source_id, path.as_ref())
+}
+
+/// Stop and remove a source loaded with [`load_file`].
+pub fn unload_file(source_id: u32) -> bool {
+    unload_source_file(source_id)
+}
+
+/// Start or resume a file source; a finished file restarts from the beginning.
+pub fn play_file(source_id: u32) -> bool {
+    set_source_file_playing(source_id, true)
+}
+
+/// Pause a file source where it is.
+pub fn pause_file(source_id: u32) -> bool {
+    set_source_file_playing(source_id, false)
+}
+
+/// Move a file source to `seconds` into its file.
+pub fn seek_file(source_id: u32, seconds: f64) -> bool {
+    seek_source_file(source_id, seconds)
+}
+
+/// Make a file source loop, or stop at the end of its file.
+pub fn set_file_loop(source_id: u32, looping: bool) -> bool {
+    set_source_file_looping(source_id, looping)
+}
+
+/// Transport state of a file source.
+pub fn file_status(source_id: u32) -> Option<PlayerStatus> {
+    get_source_file_status(source_id)
+}
+
+/// Preload a WAV or FLAC file into the soundboard as clip `clip_id`, replacing any clip already
+/// loaded under that id.
+pub fn load_clip(
+    clip_id: u32,
+    path: impl AsRef<std::path::Path>,
+    config: ClipConfig,
+) -> Result<(), SoundboardError> {
+    load_soundboard_clip(clip_id, path.as_ref(), config)
+}
+
+/// Remove a clip from the soundboard.
+pub fn unload_clip(clip_id: u32) -> bool {
+    unload_soundboard_clip(clip_id)
+}
+
+/// Play a soundboard clip from the start.
+pub fn trigger_clip(clip_id: u32) -> bool {
+    trigger_soundboard_clip(clip_id)
+}
+
+/// Fade out every voice of a soundboard clip.
+pub fn stop_clip(clip_id: u32) -> bool {
+    stop_soundboard_clip(clip_id)
+}
+
+/// Fade out everything the soundboard is playing.
+pub fn stop_all_clips() -> bool {
+    stop_soundboard()
+}
+
+/// Change the gain, polyphony or choke group of a soundboard clip.
+pub fn set_clip_config(clip_id: u32, config: ClipConfig) -> bool {
+    set_soundboard_clip_config(clip_id, config)
+}
+
+/// Clips loaded into the soundboard, ordered by id.
+pub fn clips() -> Option<Vec<ClipStatus>> {
+    get_soundboard_clips()
+}
+
+/// Add a test-signal generator as a new source with the given id.
+pub fn add_generator(source_id: u32, config: GeneratorConfig) -> Result<(), MixerError> {
+    add_source_generator(source_id, config)
+}
+
+/// Change the signal of a generator source.
+pub fn set_generator(source_id: u32, config: GeneratorConfig) -> bool {
+    set_source_generator(source_id, config)
+}
+
+/// Settings of a generator source.
+pub fn generator(source_id: u32) -> Option<GeneratorConfig> {
+    get_source_generator(source_id)
+}
+
+/// Remove a source added with [`add_generator`].
+pub fn remove_generator(source_id: u32) -> bool {
+    remove_source_generator(source_id)
+}
+
 /// Adjust the gain (in decibels) for the specified source.
 pub fn set_gain(source_id: u32, gain_db: f32) -> bool {
     set_source_gain_db(source_id, gain_db)
@@ -14,3 +184,80 @@ pub fn set_gain(source_id: u32, gain_db: f32) -> bool {
 pub fn set_mute(source_id: u32, muted: bool) -> bool {
     set_source_mute(source_id, muted)
 }
+
+/// Invert, swap or fold the channels of `source_id` before any other processing.
+pub fn set_channel_utility(source_id: u32, utility: ChannelUtility) -> bool {
+    set_source_channel_utility(source_id, utility)
+}
+
+/// Make `source_id` duck each of `targets` while it is active.
+pub fn set_ducking(source_id: u32, targets: &[u32], params: DuckingParams) -> bool {
+    set_source_ducking(source_id, targets, params)
+}
+
+/// Remove any sidechain ducking driven by `source_id`.
+pub fn clear_ducking(source_id: u32) -> bool {
+    clear_source_ducking(source_id)
+}
+
+/// Put the listed sources into the gain-sharing automix group (empty disables automixing).
+pub fn set_automix_group(source_ids: &[u32]) -> bool {
+    set_automix_sources(source_ids)
+}
+
+/// Adjust how strongly a source competes for automix gain (1.0 is neutral).
+pub fn set_automix_weight(source_id: u32, weight: f32) -> bool {
+    set_source_automix_weight(source_id, weight)
+}
+
+/// Cancel echo of `reference_id` (or the master output when `None`) picked up by `source_id`.
+pub fn enable_echo_cancellation(
+    source_id: u32,
+    reference_id: Option<u32>,
+    filter_length_ms: f32,
+) -> bool {
+    enable_source_echo_cancellation(source_id, reference_id, filter_length_ms)
+}
+
+/// Stop cancelling echo on `source_id`.
+pub fn disable_echo_cancellation(source_id: u32) -> bool {
+    disable_source_echo_cancellation(source_id)
+}
+
+/// Enable noise suppression on `source_id` at `strength` (0-1), or disable it with `None`.
+pub fn set_noise_suppression(source_id: u32, strength: Option<f32>) -> bool {
+    set_source_noise_suppression(source_id, strength)
+}
+
+/// Restart integrated loudness and loudness range measurement on the master and all sources.
+pub fn reset_loudness() -> bool {
+    reset_loudness_measurement()
+}
+
+/// Restart callback timing statistics (min/avg/max/p99 load, histogram and overruns).
+pub fn reset_timing() -> bool {
+    reset_callback_timing()
+}
+
+/// Enable or disable loudness metering for `source_id`.
+pub fn set_loudness_metering(source_id: u32, enabled: bool) -> bool {
+    set_source_loudness_metering(source_id, enabled)
+}
+
+/// Normalize the loopback output towards `params.target_lufs`, or disable normalization with
+/// `None`.
+pub fn set_loudness_normalization(params: Option<NormalizationParams>) -> bool {
+    set_master_normalization(params)
+}
+
+/// Analyse the master and every source into log-frequency bands with `config`, or stop
+/// analysing with `None`.
+pub fn set_spectrum(config: Option<SpectrumConfig>) -> bool {
+    set_mixer_spectrum(config)
+}
+
+/// Latest band levels of `source_id`, or of the master output when `None`. Returns `None` when
+/// spectrum analysis is disabled.
+pub fn spectrum(source_id: Option<u32>) -> Option<Spectrum> {
+    get_mixer_spectrum(source_id)
+}
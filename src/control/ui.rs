@@ -14,16 +14,25 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap};
+use ratatui::widgets::{BarChart, Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap};
 
 use crate::control::api;
+use crate::generator::{GeneratorConfig, Waveform};
+use crate::spectrum::{Spectrum, SpectrumConfig};
+use crate::stereo::{ChannelMode, ChannelUtility};
 use crate::{MixerStatus, SourceStatus};
 
 const TICK_RATE: Duration = Duration::from_millis(100);
+/// Lowest level drawn in the spectrum panel, in dBFS.
+const SPECTRUM_RANGE_DB: f32 = 90.0;
+/// Source id the `t` key adds its test tone as.
+const TONE_SOURCE: u32 = 1_001;
 
 #[derive(Default)]
 struct AppState {
     status: Option<MixerStatus>,
+    spectrum: Option<Spectrum>,
+    show_spectrum: bool,
     selected: usize,
     mode: Mode,
     message: Option<String>,
@@ -51,8 +60,8 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     let (status_tx, status_rx) = unbounded();
     std::thread::spawn(move || {
         loop {
-            let status = api::get_status();
-            if status_tx.send(status).is_err() {
+            let update = (api::get_status(), api::spectrum(None));
+            if status_tx.send(update).is_err() {
                 break;
             }
             std::thread::sleep(TICK_RATE);
@@ -65,8 +74,9 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     loop {
         terminal.draw(|frame| draw(frame, &app, gain_editor.as_ref()))?;
 
-        if let Some(status) = try_recv_latest(&status_rx) {
+        if let Some((status, spectrum)) = try_recv_latest(&status_rx) {
             app.status = status;
+            app.spectrum = spectrum;
             app.last_update = Some(Instant::now());
             let source_len = app.status.as_ref().map(|s| s.sources.len()).unwrap_or(0);
             if source_len > 0 {
@@ -144,6 +154,66 @@ fn handle_key(
                     }
                 }
             }
+            KeyCode::Char('r') => {
+                let reset = api::reset_loudness();
+                if reset {
+                    app.message = Some("Loudness measurement reset".to_string());
+                }
+            }
+            KeyCode::Char(key @ ('[' | ']' | 's' | 'o')) => {
+                if let Some(src) = current_source(app) {
+                    let mut utility = src.channel_utility;
+                    match key {
+                        '[' => utility.invert_left = !utility.invert_left,
+                        ']' => utility.invert_right = !utility.invert_right,
+                        's' => utility.swap_channels = !utility.swap_channels,
+                        _ => utility.mode = next_channel_mode(utility.mode),
+                    }
+                    if api::set_channel_utility(src.id, utility) {
+                        app.message = Some(format!("Source {} channels: {}", src.name, utility));
+                    }
+                }
+            }
+            KeyCode::Char('f') => {
+                let show = !app.show_spectrum;
+                let config = show.then(SpectrumConfig::default);
+                if api::set_spectrum(config) {
+                    app.show_spectrum = show;
+                    app.message = Some(format!(
+                        "Spectrum analysis {}",
+                        if show { "enabled" } else { "disabled" }
+                    ));
+                }
+            }
+            KeyCode::Char('t') => {
+                let current = api::generator(TONE_SOURCE);
+                let next = next_tone(current.map(|config| config.waveform));
+                let changed = match (current, next) {
+                    (_, None) => api::remove_generator(TONE_SOURCE),
+                    (None, Some(config)) => api::add_generator(TONE_SOURCE, config).is_ok(),
+                    (Some(_), Some(config)) => api::set_generator(TONE_SOURCE, config),
+                };
+                if changed {
+                    app.message = Some(match next {
+                        Some(config) => format!("Test tone: {config}"),
+                        None => "Test tone off".to_string(),
+                    });
+                }
+            }
+            KeyCode::Char(key @ '0'..='9') => {
+                if key == '0' {
+                    if api::stop_all_clips() {
+                        app.message = Some("Soundboard stopped".to_string());
+                    }
+                } else {
+                    let clip = key.to_digit(10).unwrap_or_default();
+                    app.message = Some(if api::trigger_clip(clip) {
+                        format!("Triggered clip {clip}")
+                    } else {
+                        format!("Clip {clip} is not loaded")
+                    });
+                }
+            }
             KeyCode::Char('g') => {
                 if let Some(src) = current_source(app) {
                     gain_editor.replace(GainEditor {
@@ -187,23 +257,57 @@ fn handle_key(
     Ok(false)
 }
 
+fn next_channel_mode(mode: ChannelMode) -> ChannelMode {
+    match mode {
+        ChannelMode::Stereo => ChannelMode::Mono,
+        ChannelMode::Mono => ChannelMode::LeftOnly,
+        ChannelMode::LeftOnly => ChannelMode::RightOnly,
+        ChannelMode::RightOnly => ChannelMode::Stereo,
+    }
+}
+
+/// Test tone following `current` in the `t` key cycle, `None` meaning off.
+fn next_tone(current: Option<Waveform>) -> Option<GeneratorConfig> {
+    let waveform = match current {
+        None => Waveform::Reference,
+        Some(Waveform::Reference) => Waveform::Sine {
+            frequency_hz: 440.0,
+        },
+        Some(Waveform::Sine { .. }) => Waveform::Sweep {
+            start_hz: 20.0,
+            end_hz: 20_000.0,
+            duration_seconds: 10.0,
+        },
+        Some(Waveform::Sweep { .. }) => Waveform::WhiteNoise,
+        Some(Waveform::WhiteNoise) => Waveform::PinkNoise,
+        Some(Waveform::PinkNoise) => Waveform::Impulse { rate_hz: 1.0 },
+        Some(Waveform::Impulse { .. }) => return None,
+    };
+    Some(GeneratorConfig::new(waveform))
+}
+
 fn current_source(app: &AppState) -> Option<SourceStatus> {
     app.status.as_ref()?.sources.get(app.selected).cloned()
 }
 
 fn draw(frame: &mut ratatui::Frame<'_>, app: &AppState, gain_editor: Option<&GainEditor>) {
+    let spectrum_height = if app.show_spectrum { 12 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(4),
             Constraint::Min(8),
+            Constraint::Length(spectrum_height),
             Constraint::Length(3),
         ])
         .split(frame.size());
 
     draw_header(frame, chunks[0], app);
     draw_sources(frame, chunks[1], app);
-    draw_footer(frame, chunks[2], app);
+    if app.show_spectrum {
+        draw_spectrum(frame, chunks[2], app);
+    }
+    draw_footer(frame, chunks[3], app);
 
     if let Some(editor) = gain_editor {
         let area = Layout::default()
@@ -232,15 +336,35 @@ fn draw_header(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, app
 
     let content = if let Some(status) = &app.status {
         let stats = format!(
-            "Sample Rate: {} Hz    Buffer: {} frames    Latency: {:.2} ms    CPU: {:.1}%    Fill: {:.1}%    Drift: {:.1} ppm",
+            "Sample Rate: {} Hz    Buffer: {} frames    Latency: {:.2} ms    CPU: {:.1}%    Fill: {:.1}%    Drift: {:.1} ppm    Master: {:.1} dBFS / {:.1} dBTP",
             status.sample_rate,
             status.buffer_frames,
             status.latency_ms,
             status.cpu_usage * 100.0,
             status.buffer_fill * 100.0,
             status.drift_ppm,
+            level_db(status.master.rms),
+            level_db(status.master.true_peak),
+        );
+        let loudness = format!(
+            "Loudness  M: {}  S: {}  I: {} LUFS  LRA: {} LU    Callback  avg: {:.1}%  p99: {:.1}%  max: {:.1}%  overruns: {}",
+            format_loudness(status.loudness.momentary),
+            format_loudness(status.loudness.short_term),
+            format_loudness(status.loudness.integrated),
+            format_loudness(status.loudness.range),
+            status.timing.avg_load * 100.0,
+            status.timing.p99_load * 100.0,
+            status.timing.max_load * 100.0,
+            status.timing.overruns,
         );
-        Paragraph::new(stats)
+        let mut spans = vec![Span::raw(stats)];
+        if status.master.clipped {
+            spans.push(Span::styled(
+                "    CLIP",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        Paragraph::new(vec![Line::from(spans), Line::from(loudness)])
     } else {
         Paragraph::new(Line::from(vec![Span::styled(
             "No active mixer",
@@ -260,10 +384,13 @@ fn draw_sources(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, ap
             Cell::from("Name"),
             Cell::from("Gain (dB)"),
             Cell::from("Muted"),
-            Cell::from("RMS"),
+            Cell::from("Channels"),
+            Cell::from("RMS dB"),
+            Cell::from("Peak dBTP"),
             Cell::from("Latency (frames)"),
             Cell::from("Buffer %"),
             Cell::from("Drift ppm"),
+            Cell::from("Voice"),
         ])
         .style(
             Style::default()
@@ -273,15 +400,43 @@ fn draw_sources(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, ap
 
         let rows = status.sources.iter().enumerate().map(|(idx, src)| {
             let indicator = if idx == app.selected { ">" } else { "" };
+            let name_style = if src.speaking {
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let name = match &src.generator {
+                Some(generator) => format!("{} ~{}", src.name, generator.waveform.name()),
+                None => src.name.clone(),
+            };
             let mut row = Row::new(vec![
                 Cell::from(indicator.to_string()),
-                Cell::from(src.name.clone()),
+                Cell::from(name).style(name_style),
                 Cell::from(format!("{:.1}", src.gain_db)),
                 Cell::from(if src.muted { "Yes" } else { "No" }),
-                Cell::from(format!("{:.2}", src.rms)),
+                Cell::from(src.channel_utility.to_string()).style(
+                    if src.channel_utility == ChannelUtility::default() {
+                        Style::default()
+                    } else {
+                        Style::default().fg(Color::Magenta)
+                    },
+                ),
+                Cell::from(format!("{:.1}", level_db(src.rms))),
+                Cell::from(format!("{:.1}", level_db(src.true_peak))).style(if src.clipped {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                }),
                 Cell::from(format!("{}", src.latency_frames)),
                 Cell::from(format!("{:.1}", src.buffer_fill * 100.0)),
                 Cell::from(format!("{:.1}", src.drift_ppm)),
+                Cell::from(format!(
+                    "{} {:>3.0}%",
+                    if src.speaking { "●" } else { "○" },
+                    src.voice_probability * 100.0
+                )),
             ]);
             if idx == app.selected {
                 row = row.style(Style::default().fg(Color::Yellow));
@@ -296,10 +451,13 @@ fn draw_sources(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, ap
                 Constraint::Length(20),
                 Constraint::Length(12),
                 Constraint::Length(8),
+                Constraint::Length(14),
                 Constraint::Length(8),
+                Constraint::Length(10),
                 Constraint::Length(16),
                 Constraint::Length(12),
                 Constraint::Length(12),
+                Constraint::Length(8),
             ],
         )
         .header(header)
@@ -312,8 +470,72 @@ fn draw_sources(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, ap
     }
 }
 
+fn draw_spectrum(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, app: &AppState) {
+    let Some(spectrum) = app.spectrum.as_ref().filter(|s| !s.levels_db.is_empty()) else {
+        let block = Block::default()
+            .title("Master Spectrum")
+            .borders(Borders::ALL);
+        frame.render_widget(Paragraph::new("Waiting for analysis…").block(block), area);
+        return;
+    };
+    let first = spectrum.frequencies_hz.first().copied().unwrap_or_default();
+    let last = spectrum.frequencies_hz.last().copied().unwrap_or_default();
+    let block = Block::default()
+        .title(format!(
+            "Master Spectrum  {} – {}  (-{:.0} to 0 dBFS)",
+            format_frequency(first),
+            format_frequency(last),
+            SPECTRUM_RANGE_DB
+        ))
+        .borders(Borders::ALL);
+
+    let bars: Vec<(&str, u64)> = spectrum
+        .levels_db
+        .iter()
+        .map(|&db| {
+            (
+                "",
+                (db + SPECTRUM_RANGE_DB).clamp(0.0, SPECTRUM_RANGE_DB) as u64,
+            )
+        })
+        .collect();
+    let bands = bars.len() as u16;
+    let inner_width = area.width.saturating_sub(2);
+    let bar_width = (inner_width / bands).saturating_sub(1).max(1);
+
+    let chart = BarChart::default()
+        .block(block)
+        .data(&bars)
+        .max(SPECTRUM_RANGE_DB as u64)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Cyan).bg(Color::Cyan));
+    frame.render_widget(chart, area);
+}
+
+fn format_frequency(hz: f32) -> String {
+    if hz >= 1_000.0 {
+        format!("{:.1} kHz", hz / 1_000.0)
+    } else {
+        format!("{hz:.0} Hz")
+    }
+}
+
+fn format_loudness(value: f32) -> String {
+    if value.is_finite() {
+        format!("{value:.1}")
+    } else {
+        "-inf".to_string()
+    }
+}
+
+fn level_db(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
 fn draw_footer(frame: &mut ratatui::Frame<'_>, area: ratatui::prelude::Rect, app: &AppState) {
-    let info = "Up/Down: Select  •  g: Set gain  •  m: Toggle mute  •  q: Quit";
+    let info = "Up/Down: Select  •  g: Set gain  •  m: Toggle mute  •  [/]: Invert L/R  •  s: Swap  •  o: Channel mode  •  r: Reset loudness  •  f: Spectrum  •  t: Test tone  •  1-9: Trigger clip  •  0: Stop clips  •  q: Quit";
     let mut lines = vec![Line::from(info)];
     if let Some(message) = &app.message {
         lines.push(Line::from(Span::styled(
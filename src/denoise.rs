@@ -0,0 +1,174 @@
+//! STFT noise suppression for steady background noise such as fans and HVAC.
+//!
+//! Both channels are packed into one complex transform (left in the real part, right in the
+//! imaginary part) of square-root Hann windowed frames at 50% overlap. Each bin's noise power is
+//! tracked from the lower envelope of the smoothed spectrum and a decision-directed Wiener gain
+//! is applied symmetrically, so both channels receive the same real gain. `strength` sets how far
+//! the gain may drop: 0 leaves the signal untouched, 1 allows the full attenuation.
+
+use std::f32::consts::TAU;
+
+use crate::fft::Fft;
+
+/// Deepest attenuation applied to a noise-only bin at full strength.
+pub const MAX_ATTENUATION_DB: f32 = 25.0;
+
+const PERIODOGRAM_SMOOTHING: f32 = 0.8;
+/// Rate at which the noise estimate may climb when the spectrum stays above it.
+const NOISE_RISE_DB_PER_S: f32 = 3.0;
+/// Compensates the lower-envelope tracker sitting below the mean noise power.
+const NOISE_BIAS: f32 = 1.5;
+/// Frames averaged at start-up before minimum tracking takes over.
+const WARMUP_FRAMES: u32 = 8;
+const DECISION_DIRECTED: f32 = 0.96;
+const POWER_FLOOR: f32 = 1e-12;
+
+/// Analysis frame length used at `sample_rate`; the suppressor delays its source by this many
+/// frames.
+pub fn latency_frames(sample_rate: u32) -> usize {
+    (sample_rate.max(8_000) as usize / 50)
+        .next_power_of_two()
+        .clamp(256, 2048)
+}
+
+/// Per-source spectral noise suppressor.
+pub(crate) struct NoiseSuppressor {
+    fft: Fft,
+    window: Vec<f32>,
+    hop: usize,
+    input_left: Vec<f32>,
+    input_right: Vec<f32>,
+    output_left: Vec<f32>,
+    output_right: Vec<f32>,
+    overlap_left: Vec<f32>,
+    overlap_right: Vec<f32>,
+    position: usize,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    smoothed: Vec<f32>,
+    noise: Vec<f32>,
+    previous_clean: Vec<f32>,
+    noise_rise: f32,
+    frames_seen: u32,
+    strength: f32,
+}
+
+impl NoiseSuppressor {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        let size = latency_frames(sample_rate);
+        let hop = size / 2;
+        let bins = size / 2 + 1;
+        let window = (0..size)
+            .map(|n| (0.5 - 0.5 * (TAU * n as f32 / size as f32).cos()).sqrt())
+            .collect();
+        let hop_seconds = hop as f32 / sample_rate.max(8_000) as f32;
+        Self {
+            fft: Fft::new(size),
+            window,
+            hop,
+            input_left: vec![0.0; size],
+            input_right: vec![0.0; size],
+            output_left: vec![0.0; hop],
+            output_right: vec![0.0; hop],
+            overlap_left: vec![0.0; size],
+            overlap_right: vec![0.0; size],
+            position: 0,
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            smoothed: vec![0.0; bins],
+            noise: vec![0.0; bins],
+            previous_clean: vec![0.0; bins],
+            noise_rise: 10f32.powf(NOISE_RISE_DB_PER_S * hop_seconds / 10.0),
+            frames_seen: 0,
+            strength: 0.0,
+        }
+    }
+
+    /// Suppress noise in the interleaved stereo `block` in place at `strength` (0-1).
+    pub(crate) fn process(&mut self, block: &mut [f32], strength: f32) {
+        self.strength = strength.clamp(0.0, 1.0);
+        let offset = self.input_left.len() - self.hop;
+        for frame in block.chunks_exact_mut(2) {
+            let pos = self.position;
+            self.input_left[offset + pos] = frame[0];
+            self.input_right[offset + pos] = frame[1];
+            frame[0] = self.output_left[pos];
+            frame[1] = self.output_right[pos];
+            self.position += 1;
+            if self.position == self.hop {
+                self.position = 0;
+                self.process_frame();
+            }
+        }
+    }
+
+    fn process_frame(&mut self) {
+        let size = self.fft.size();
+        for n in 0..size {
+            self.re[n] = self.input_left[n] * self.window[n];
+            self.im[n] = self.input_right[n] * self.window[n];
+        }
+        self.input_left.copy_within(self.hop.., 0);
+        self.input_right.copy_within(self.hop.., 0);
+        self.fft.forward(&mut self.re, &mut self.im);
+
+        let floor = 10f32.powf(-MAX_ATTENUATION_DB * self.strength / 20.0);
+        let warming_up = self.frames_seen < WARMUP_FRAMES;
+        self.frames_seen = self.frames_seen.saturating_add(1);
+        for k in 0..=size / 2 {
+            let mirror = (size - k) % size;
+            // |L_k|^2 + |R_k|^2 recovered from the packed spectrum.
+            let power = 0.5
+                * (self.re[k] * self.re[k]
+                    + self.im[k] * self.im[k]
+                    + self.re[mirror] * self.re[mirror]
+                    + self.im[mirror] * self.im[mirror]);
+            self.track_noise(k, power, warming_up);
+
+            let noise = (self.noise[k] * NOISE_BIAS).max(POWER_FLOOR);
+            let posterior = power / noise;
+            let prior = DECISION_DIRECTED * self.previous_clean[k] / noise
+                + (1.0 - DECISION_DIRECTED) * (posterior - 1.0).max(0.0);
+            let gain = (prior / (1.0 + prior)).max(floor);
+            self.previous_clean[k] = gain * gain * power;
+
+            self.re[k] *= gain;
+            self.im[k] *= gain;
+            if mirror != k {
+                self.re[mirror] *= gain;
+                self.im[mirror] *= gain;
+            }
+        }
+
+        self.fft.inverse(&mut self.re, &mut self.im);
+        for n in 0..size {
+            self.overlap_left[n] += self.re[n] * self.window[n];
+            self.overlap_right[n] += self.im[n] * self.window[n];
+        }
+        self.output_left
+            .copy_from_slice(&self.overlap_left[..self.hop]);
+        self.output_right
+            .copy_from_slice(&self.overlap_right[..self.hop]);
+        self.overlap_left.copy_within(self.hop.., 0);
+        self.overlap_right.copy_within(self.hop.., 0);
+        self.overlap_left[size - self.hop..].fill(0.0);
+        self.overlap_right[size - self.hop..].fill(0.0);
+    }
+
+    /// Follow the lower envelope of the smoothed spectrum: drop immediately, rise slowly.
+    fn track_noise(&mut self, bin: usize, power: f32, warming_up: bool) {
+        if warming_up {
+            let weight = 1.0 / self.frames_seen as f32;
+            self.smoothed[bin] += (power - self.smoothed[bin]) * weight;
+            self.noise[bin] = self.smoothed[bin];
+            return;
+        }
+        self.smoothed[bin] =
+            PERIODOGRAM_SMOOTHING * self.smoothed[bin] + (1.0 - PERIODOGRAM_SMOOTHING) * power;
+        if self.smoothed[bin] < self.noise[bin] {
+            self.noise[bin] = self.smoothed[bin];
+        } else {
+            self.noise[bin] = (self.noise[bin] * self.noise_rise).min(self.smoothed[bin]);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Sidechain dynamics applied between mixer sources on the mix bus.
+//!
+//! A key source drives an envelope follower; whenever the key rises above its threshold the
+//! configured target sources are attenuated by `depth_db`. Parameters are stored in atomics so
+//! control threads can retune a key without blocking the render callback.
+//!
+//! The gain-sharing automixer follows Dugan's scheme: every member of the automix group receives
+//! a share of a constant total gain proportional to its weighted signal power, so the summed
+//! level of the group stays constant no matter how many microphones are open.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Maximum number of target sources a single sidechain key can attenuate.
+pub const MAX_DUCK_TARGETS: usize = 8;
+
+/// Detector release used to smooth the key envelope between waveform peaks.
+const DETECTOR_RELEASE_MS: f32 = 20.0;
+
+/// Averaging time of the automixer's per-channel power estimate.
+const AUTOMIX_AVERAGING_MS: f32 = 50.0;
+
+/// Power floor (-90 dBFS) so silent channels split the gain evenly instead of dividing by zero.
+const AUTOMIX_POWER_FLOOR: f32 = 1e-9;
+
+/// Smallest automix weight (-60 dB of share against a neutral channel). Weights are clamped to
+/// it so a group whose weights are all zero splits the gain evenly rather than falling back to
+/// unity on every channel.
+pub const AUTOMIX_MIN_WEIGHT: f32 = 1e-6;
+
+/// Largest automix weight (+60 dB of share against a neutral channel). Weights are clamped to it
+/// so the group's summed weighted power stays finite.
+pub const AUTOMIX_MAX_WEIGHT: f32 = 1e6;
+
+/// Parameters describing how a key source ducks its targets.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DuckingParams {
+    /// Key level in dBFS above which ducking engages.
+    pub threshold_db: f32,
+    /// Attenuation applied to targets while the key is active, in dB (positive values).
+    pub depth_db: f32,
+    /// Time for the attenuation to reach full depth, in milliseconds.
+    pub attack_ms: f32,
+    /// Time for targets to recover once the hold period expires, in milliseconds.
+    pub release_ms: f32,
+    /// Time the attenuation is held after the key drops below threshold, in milliseconds.
+    pub hold_ms: f32,
+}
+
+impl Default for DuckingParams {
+    fn default() -> Self {
+        Self {
+            threshold_db: -40.0,
+            depth_db: 12.0,
+            attack_ms: 10.0,
+            release_ms: 400.0,
+            hold_ms: 200.0,
+        }
+    }
+}
+
+/// Per-key sidechain state. Configuration lives in atomics; envelope state is owned by the
+/// render thread.
+pub(crate) struct SidechainDucker {
+    enabled: AtomicBool,
+    threshold_db: AtomicU32,
+    depth_db: AtomicU32,
+    attack_ms: AtomicU32,
+    release_ms: AtomicU32,
+    hold_ms: AtomicU32,
+    targets: [AtomicU32; MAX_DUCK_TARGETS],
+    /// Set by [`clear`](Self::clear); the render thread drops its envelope state on the next
+    /// block, even if the key has been re-armed in the meantime.
+    reset: AtomicBool,
+    envelope: f32,
+    hold_remaining: usize,
+    gain: f32,
+}
+
+impl SidechainDucker {
+    pub(crate) fn new() -> Self {
+        let defaults = DuckingParams::default();
+        Self {
+            enabled: AtomicBool::new(false),
+            threshold_db: AtomicU32::new(defaults.threshold_db.to_bits()),
+            depth_db: AtomicU32::new(defaults.depth_db.to_bits()),
+            attack_ms: AtomicU32::new(defaults.attack_ms.to_bits()),
+            release_ms: AtomicU32::new(defaults.release_ms.to_bits()),
+            hold_ms: AtomicU32::new(defaults.hold_ms.to_bits()),
+            targets: std::array::from_fn(|_| AtomicU32::new(0)),
+            reset: AtomicBool::new(false),
+            envelope: 0.0,
+            hold_remaining: 0,
+            gain: 1.0,
+        }
+    }
+
+    /// Install a new target set and parameters. Target ids of `0` are ignored.
+    pub(crate) fn configure(&self, targets: &[u32], params: DuckingParams) {
+        self.enabled.store(false, Ordering::Release);
+        for (slot, id) in self.targets.iter().enumerate() {
+            let value = targets.get(slot).copied().unwrap_or(0);
+            id.store(value, Ordering::Relaxed);
+        }
+        store_f32(&self.threshold_db, params.threshold_db);
+        store_f32(&self.depth_db, params.depth_db.max(0.0));
+        store_f32(&self.attack_ms, params.attack_ms.max(0.0));
+        store_f32(&self.release_ms, params.release_ms.max(0.0));
+        store_f32(&self.hold_ms, params.hold_ms.max(0.0));
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Disarm the key and return its detector to unity gain, so a later
+    /// [`configure`](Self::configure) starts from an unducked state.
+    pub(crate) fn clear(&self) {
+        self.enabled.store(false, Ordering::Release);
+        for id in &self.targets {
+            id.store(0, Ordering::Relaxed);
+        }
+        self.reset.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Copy the configured target ids; unused slots are `0`.
+    pub(crate) fn targets(&self) -> [u32; MAX_DUCK_TARGETS] {
+        std::array::from_fn(|slot| self.targets[slot].load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn params(&self) -> DuckingParams {
+        DuckingParams {
+            threshold_db: load_f32(&self.threshold_db),
+            depth_db: load_f32(&self.depth_db),
+            attack_ms: load_f32(&self.attack_ms),
+            release_ms: load_f32(&self.release_ms),
+            hold_ms: load_f32(&self.hold_ms),
+        }
+    }
+
+    /// Run the key signal through the detector and return the gain targets should reach by the
+    /// end of the block.
+    pub(crate) fn process_key(&mut self, key: &[f32], sample_rate: u32) -> f32 {
+        if self.reset.swap(false, Ordering::AcqRel) || !self.is_enabled() {
+            self.envelope = 0.0;
+            self.hold_remaining = 0;
+            self.gain = 1.0;
+        }
+        if !self.is_enabled() {
+            return 1.0;
+        }
+
+        let params = self.params();
+        let sr = sample_rate.max(1) as f32;
+        let threshold = db_to_linear(params.threshold_db);
+        let ducked_gain = db_to_linear(-params.depth_db);
+        let hold_samples = (params.hold_ms * 0.001 * sr) as usize;
+        let detector_coef = time_coefficient(DETECTOR_RELEASE_MS, sr);
+        let attack_coef = time_coefficient(params.attack_ms, sr);
+        let release_coef = time_coefficient(params.release_ms, sr);
+
+        for frame in key.chunks_exact(2) {
+            let level = frame[0].abs().max(frame[1].abs());
+            self.envelope = if level > self.envelope {
+                level
+            } else {
+                level + detector_coef * (self.envelope - level)
+            };
+
+            let target = if self.envelope >= threshold {
+                self.hold_remaining = hold_samples;
+                ducked_gain
+            } else if self.hold_remaining > 0 {
+                self.hold_remaining -= 1;
+                ducked_gain
+            } else {
+                1.0
+            };
+
+            let coef = if target < self.gain {
+                attack_coef
+            } else {
+                release_coef
+            };
+            self.gain = target + coef * (self.gain - target);
+        }
+
+        self.gain
+    }
+}
+
+/// Per-source membership, weight and gain state for the gain-sharing automixer.
+pub(crate) struct AutomixChannel {
+    member: AtomicBool,
+    weight: AtomicU32,
+    power: f32,
+    weighted_power: f32,
+    gain: f32,
+    gain_bits: AtomicU32,
+}
+
+impl AutomixChannel {
+    pub(crate) fn new() -> Self {
+        Self {
+            member: AtomicBool::new(false),
+            weight: AtomicU32::new(1.0f32.to_bits()),
+            power: 0.0,
+            weighted_power: 0.0,
+            gain: 1.0,
+            gain_bits: AtomicU32::new(1.0f32.to_bits()),
+        }
+    }
+
+    pub(crate) fn set_member(&self, member: bool) {
+        self.member.store(member, Ordering::Release);
+    }
+
+    pub(crate) fn is_member(&self) -> bool {
+        self.member.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_weight(&self, weight: f32) {
+        store_f32(
+            &self.weight,
+            weight.clamp(AUTOMIX_MIN_WEIGHT, AUTOMIX_MAX_WEIGHT),
+        );
+    }
+
+    pub(crate) fn weight(&self) -> f32 {
+        load_f32(&self.weight)
+    }
+
+    /// Update the averaged power from an interleaved block and return the weighted power this
+    /// channel contributes to the group total.
+    pub(crate) fn track(&mut self, block: &[f32], coefficient: f32) -> f32 {
+        let frames = block.len() / 2;
+        let mean_square = if frames == 0 {
+            0.0
+        } else {
+            block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32
+        };
+        self.power = mean_square + coefficient * (self.power - mean_square);
+        self.weighted_power = self.weight() * (self.power + AUTOMIX_POWER_FLOOR);
+        self.weighted_power
+    }
+
+    /// Assign this channel's share of the group gain given the summed weighted power.
+    pub(crate) fn share(&mut self, total_weighted_power: f32) {
+        self.gain = if total_weighted_power > 0.0 {
+            (self.weighted_power / total_weighted_power).sqrt()
+        } else {
+            1.0
+        };
+        self.gain_bits.store(self.gain.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Return to unity gain when the channel is not part of the group.
+    pub(crate) fn bypass(&mut self) {
+        self.power = 0.0;
+        self.weighted_power = 0.0;
+        self.gain = 1.0;
+        self.gain_bits.store(self.gain.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gain the render thread applies this block.
+    pub(crate) fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Most recently published gain, readable from control threads.
+    pub(crate) fn published_gain(&self) -> f32 {
+        f32::from_bits(self.gain_bits.load(Ordering::Relaxed))
+    }
+}
+
+/// Per-block smoothing coefficient for the automixer power average.
+pub(crate) fn automix_coefficient(frames: usize, sample_rate: u32) -> f32 {
+    let samples = AUTOMIX_AVERAGING_MS * 0.001 * sample_rate.max(1) as f32;
+    (-(frames as f32) / samples).exp()
+}
+
+/// One-pole smoothing coefficient reaching ~63% of a step after `time_ms`.
+fn time_coefficient(time_ms: f32, sample_rate: f32) -> f32 {
+    if time_ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time_ms * 0.001 * sample_rate)).exp()
+    }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn store_f32(cell: &AtomicU32, value: f32) {
+    cell.store(value.to_bits(), Ordering::Relaxed);
+}
+
+fn load_f32(cell: &AtomicU32) -> f32 {
+    f32::from_bits(cell.load(Ordering::Relaxed))
+}
@@ -0,0 +1,238 @@
+//! Timestamped log of dropouts and glitches.
+//!
+//! The render thread and the producer-side write paths record [`MixerEvent`]s into a
+//! preallocated lock-free queue; the control side drains it. Events carry the host time
+//! ([`monotonic_timestamp_ns`]) at which they were detected. Conditions that persist across
+//! callbacks (a starved source, a stream of NaNs, a wrong buffer format) are reported once when
+//! they start, not on every block. When the control side falls behind and the queue fills, new
+//! events are counted as dropped instead of blocking the writer. Per-kind totals are kept
+//! separately and survive draining.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::queue::BoundedQueue;
+use crate::ring::monotonic_timestamp_ns;
+
+/// Number of events the log holds before new ones are dropped.
+pub const EVENT_LOG_CAPACITY: usize = 1_024;
+
+/// [`LoopbackEvent::kind`] of a source underrun.
+pub const LOOPBACK_EVENT_SOURCE_UNDERRUN: u32 = 1;
+/// [`LoopbackEvent::kind`] of a ring overflow.
+pub const LOOPBACK_EVENT_RING_OVERFLOW: u32 = 2;
+/// [`LoopbackEvent::kind`] of a callback overrun.
+pub const LOOPBACK_EVENT_CALLBACK_OVERRUN: u32 = 3;
+/// [`LoopbackEvent::kind`] of non-finite samples.
+pub const LOOPBACK_EVENT_NON_FINITE: u32 = 4;
+/// [`LoopbackEvent::kind`] of a buffer format error.
+pub const LOOPBACK_EVENT_FORMAT_ERROR: u32 = 5;
+/// [`LoopbackEvent::source_id`] used for events that concern the master output.
+pub const LOOPBACK_EVENT_MASTER: u32 = u32::MAX;
+
+/// What happened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MixerEventKind {
+    /// A source's ring ran dry and the block was padded with `missing_frames` of silence.
+    SourceUnderrun {
+        /// Mixer source id.
+        source_id: u32,
+        /// Frames of silence inserted in the block that started the underrun.
+        missing_frames: u32,
+    },
+    /// A producer wrote into a full ring and `dropped_frames` frames were lost.
+    RingOverflow {
+        /// Mixer source id.
+        source_id: u32,
+        /// Frames that did not fit.
+        dropped_frames: u32,
+    },
+    /// A render callback took longer than its block duration.
+    CallbackOverrun {
+        /// Callback time as a fraction of the block duration.
+        load: f32,
+    },
+    /// NaN or infinite samples were found and replaced with silence.
+    NonFinite {
+        /// Mixer source id, or `None` for the master output.
+        source_id: Option<u32>,
+    },
+    /// The host handed the mixer a buffer it cannot render into.
+    FormatError {
+        /// Channel count of the buffer.
+        channels: u32,
+        /// Frame count of the buffer.
+        frames: u32,
+    },
+}
+
+impl MixerEventKind {
+    /// Stable numeric code, one of the `LOOPBACK_EVENT_*` constants.
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::SourceUnderrun { .. } => LOOPBACK_EVENT_SOURCE_UNDERRUN,
+            Self::RingOverflow { .. } => LOOPBACK_EVENT_RING_OVERFLOW,
+            Self::CallbackOverrun { .. } => LOOPBACK_EVENT_CALLBACK_OVERRUN,
+            Self::NonFinite { .. } => LOOPBACK_EVENT_NON_FINITE,
+            Self::FormatError { .. } => LOOPBACK_EVENT_FORMAT_ERROR,
+        }
+    }
+
+    /// Short machine-friendly name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SourceUnderrun { .. } => "source_underrun",
+            Self::RingOverflow { .. } => "ring_overflow",
+            Self::CallbackOverrun { .. } => "callback_overrun",
+            Self::NonFinite { .. } => "non_finite",
+            Self::FormatError { .. } => "format_error",
+        }
+    }
+}
+
+impl fmt::Display for MixerEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::SourceUnderrun {
+                source_id,
+                missing_frames,
+            } => write!(
+                f,
+                "source {source_id} underrun ({missing_frames} frames of silence)"
+            ),
+            Self::RingOverflow {
+                source_id,
+                dropped_frames,
+            } => write!(
+                f,
+                "source {source_id} ring overflow ({dropped_frames} frames dropped)"
+            ),
+            Self::CallbackOverrun { load } => {
+                write!(f, "callback overrun ({:.0}% of deadline)", load * 100.0)
+            }
+            Self::NonFinite {
+                source_id: Some(id),
+            } => {
+                write!(f, "non-finite samples from source {id}")
+            }
+            Self::NonFinite { source_id: None } => write!(f, "non-finite samples on master"),
+            Self::FormatError { channels, frames } => write!(
+                f,
+                "unsupported buffer format ({channels} channels, {frames} frames)"
+            ),
+        }
+    }
+}
+
+/// A recorded event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MixerEvent {
+    /// Host time at which the event was detected, in nanoseconds.
+    pub timestamp_ns: u64,
+    /// What happened.
+    pub kind: MixerEventKind,
+}
+
+/// Flat event record for the C ABI.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoopbackEvent {
+    /// Host time at which the event was detected, in nanoseconds.
+    pub timestamp_ns: u64,
+    /// One of the `LOOPBACK_EVENT_*` kinds.
+    pub kind: u32,
+    /// Mixer source id, or [`LOOPBACK_EVENT_MASTER`] when the event is not tied to a source.
+    pub source_id: u32,
+    /// Missing or dropped frames, callback load, or the buffer channel count, depending on kind.
+    pub value: f64,
+}
+
+impl From<MixerEvent> for LoopbackEvent {
+    fn from(event: MixerEvent) -> Self {
+        let (source_id, value) = match event.kind {
+            MixerEventKind::SourceUnderrun {
+                source_id,
+                missing_frames,
+            } => (source_id, missing_frames as f64),
+            MixerEventKind::RingOverflow {
+                source_id,
+                dropped_frames,
+            } => (source_id, dropped_frames as f64),
+            MixerEventKind::CallbackOverrun { load } => (LOOPBACK_EVENT_MASTER, load as f64),
+            MixerEventKind::NonFinite { source_id } => {
+                (source_id.unwrap_or(LOOPBACK_EVENT_MASTER), 0.0)
+            }
+            MixerEventKind::FormatError { channels, .. } => {
+                (LOOPBACK_EVENT_MASTER, channels as f64)
+            }
+        };
+        Self {
+            timestamp_ns: event.timestamp_ns,
+            kind: event.kind.code(),
+            source_id,
+            value,
+        }
+    }
+}
+
+/// Running totals per event kind since the mixer was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventCounts {
+    /// Source underruns.
+    pub source_underruns: u64,
+    /// Ring overflows.
+    pub ring_overflows: u64,
+    /// Callback overruns.
+    pub callback_overruns: u64,
+    /// Blocks in which non-finite samples started.
+    pub non_finite: u64,
+    /// Buffer format errors.
+    pub format_errors: u64,
+    /// Events lost because the log was full.
+    pub dropped: u64,
+}
+
+/// Lock-free event log shared by the render thread, producers and the control side.
+pub(crate) struct EventLog {
+    queue: BoundedQueue<MixerEvent>,
+    counts: [AtomicU64; 5],
+    dropped: AtomicU64,
+}
+
+impl EventLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            queue: BoundedQueue::new(EVENT_LOG_CAPACITY),
+            counts: std::array::from_fn(|_| AtomicU64::new(0)),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Record `kind` at the current host time.
+    pub(crate) fn record(&self, kind: MixerEventKind) {
+        self.counts[(kind.code() - 1) as usize].fetch_add(1, Ordering::Relaxed);
+        let event = MixerEvent {
+            timestamp_ns: monotonic_timestamp_ns(),
+            kind,
+        };
+        if self.queue.push(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn pop(&self) -> Option<MixerEvent> {
+        self.queue.pop()
+    }
+
+    pub(crate) fn counts(&self) -> EventCounts {
+        let count = |code: u32| self.counts[(code - 1) as usize].load(Ordering::Relaxed);
+        EventCounts {
+            source_underruns: count(LOOPBACK_EVENT_SOURCE_UNDERRUN),
+            ring_overflows: count(LOOPBACK_EVENT_RING_OVERFLOW),
+            callback_overruns: count(LOOPBACK_EVENT_CALLBACK_OVERRUN),
+            non_finite: count(LOOPBACK_EVENT_NON_FINITE),
+            format_errors: count(LOOPBACK_EVENT_FORMAT_ERROR),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
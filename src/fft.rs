@@ -0,0 +1,93 @@
+//! In-place radix-2 complex FFT shared by the analysis stages.
+//!
+//! Twiddles and the bit-reversal table are computed up front so `forward` can run on the render
+//! thread without allocating.
+
+use std::f32::consts::TAU;
+
+/// Precomputed radix-2 transform for a fixed power-of-two size.
+pub(crate) struct Fft {
+    size: usize,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+    bit_reverse: Vec<usize>,
+}
+
+impl Fft {
+    /// Build a transform for `size` points. `size` is rounded up to a power of two.
+    pub(crate) fn new(size: usize) -> Self {
+        let size = size.max(2).next_power_of_two();
+        let half = size / 2;
+        let cos = (0..half)
+            .map(|k| (TAU * k as f32 / size as f32).cos())
+            .collect();
+        let sin = (0..half)
+            .map(|k| (TAU * k as f32 / size as f32).sin())
+            .collect();
+        let bits = size.trailing_zeros();
+        let bit_reverse = (0..size)
+            .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+        Self {
+            size,
+            cos,
+            sin,
+            bit_reverse,
+        }
+    }
+
+    /// Number of points in the transform.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Forward transform of `re`/`im` in place. Both slices must hold `size` values.
+    pub(crate) fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        debug_assert!(re.len() >= self.size && im.len() >= self.size);
+        for i in 0..self.size {
+            let j = self.bit_reverse[i];
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= self.size {
+            let half = len / 2;
+            let stride = self.size / len;
+            for start in (0..self.size).step_by(len) {
+                for k in 0..half {
+                    let w_re = self.cos[k * stride];
+                    let w_im = -self.sin[k * stride];
+                    let a = start + k;
+                    let b = a + half;
+                    let t_re = re[b] * w_re - im[b] * w_im;
+                    let t_im = re[b] * w_im + im[b] * w_re;
+                    re[b] = re[a] - t_re;
+                    im[b] = im[a] - t_im;
+                    re[a] += t_re;
+                    im[a] += t_im;
+                }
+            }
+            len *= 2;
+        }
+    }
+
+    /// Inverse transform of `re`/`im` in place, including the `1/size` scale.
+    pub(crate) fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        // IFFT(x) = FFT(x with re/im swapped) with re/im swapped back.
+        self.forward(im, re);
+        let scale = 1.0 / self.size as f32;
+        for value in re[..self.size].iter_mut().chain(im[..self.size].iter_mut()) {
+            *value *= scale;
+        }
+    }
+}
+
+/// Periodic Hann window of `size` points.
+pub(crate) fn hann_window(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|n| 0.5 - 0.5 * (TAU * n as f32 / size as f32).cos())
+        .collect()
+}
@@ -0,0 +1,526 @@
+//! Minimal FLAC decoder for file playback.
+//!
+//! [`FlacReader`] parses the `STREAMINFO` block, skips the other metadata and decodes one frame
+//! at a time: constant, verbatim, fixed and LPC subframes with Rice-coded residuals, wasted bits,
+//! and the left/side, right/side and mid/side stereo modes. Frame CRCs and the MD5 signature are
+//! not checked. Without a seek table, [`FlacReader::seek`] rewinds to the first frame and decodes
+//! forward, so its cost grows with the target position.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+const STREAMINFO: u8 = 0;
+const STREAMINFO_BYTES: usize = 34;
+/// Largest residual quotient accepted before the stream is declared corrupt.
+const MAX_RICE_QUOTIENT: u64 = 1 << 32;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Big-endian bit reader over a byte stream.
+struct BitReader<R> {
+    inner: R,
+    /// Right-aligned unread bits.
+    bits: u64,
+    count: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.bits = 0;
+        self.count = 0;
+    }
+
+    /// Append one byte to the buffer, returning `false` at the end of the stream.
+    fn refill(&mut self) -> io::Result<bool> {
+        let mut byte = [0u8; 1];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(false),
+                Ok(_) => {
+                    self.bits = (self.bits << 8) | byte[0] as u64;
+                    self.count += 8;
+                    return Ok(true);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Read `n` (at most 40) bits as an unsigned value.
+    fn read_bits(&mut self, n: u32) -> io::Result<u64> {
+        debug_assert!(n <= 40);
+        if n == 0 {
+            return Ok(0);
+        }
+        while self.count < n {
+            if !self.refill()? {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+        self.count -= n;
+        let value = (self.bits >> self.count) & ((1u64 << n) - 1);
+        self.bits &= (1u64 << self.count) - 1;
+        Ok(value)
+    }
+
+    /// Read `n` bits as a two's complement value.
+    fn read_signed(&mut self, n: u32) -> io::Result<i64> {
+        if n == 0 {
+            return Ok(0);
+        }
+        let value = self.read_bits(n)?;
+        let shift = 64 - n;
+        Ok(((value << shift) as i64) >> shift)
+    }
+
+    /// Count zero bits up to the next one bit, consuming both.
+    fn read_unary(&mut self) -> io::Result<u64> {
+        let mut zeros = 0u64;
+        loop {
+            if self.count == 0 && !self.refill()? {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if self.bits == 0 {
+                zeros += self.count as u64;
+                self.count = 0;
+                if zeros > MAX_RICE_QUOTIENT {
+                    return Err(invalid("corrupt residual"));
+                }
+                continue;
+            }
+            let width = 64 - self.bits.leading_zeros();
+            zeros += (self.count - width) as u64;
+            self.count = width - 1;
+            self.bits &= (1u64 << self.count) - 1;
+            return Ok(zeros);
+        }
+    }
+
+    /// Drop the bits left in the current byte.
+    fn align(&mut self) {
+        self.count -= self.count % 8;
+        self.bits &= (1u64 << self.count) - 1;
+    }
+
+    /// Read the next whole byte, or `None` at the end of the stream. Only valid when aligned.
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.count == 0 && !self.refill()? {
+            return Ok(None);
+        }
+        Ok(Some(self.read_bits(8)? as u8))
+    }
+}
+
+/// Streaming decoder over the frames of a native FLAC file.
+pub struct FlacReader<R: Read> {
+    reader: BitReader<R>,
+    sample_rate: u32,
+    channels: u16,
+    bits_per_sample: u32,
+    total_frames: u64,
+    audio_offset: u64,
+    /// Decoded samples of the current frame, one run of `block_frames` per channel.
+    block: Vec<i64>,
+    block_frames: usize,
+    cursor: usize,
+    scale: f32,
+}
+
+impl<R: Read> FlacReader<R> {
+    /// Parse the stream header and metadata, positioning the reader at the first frame.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        inner.read_exact(&mut magic)?;
+        if &magic != b"fLaC" {
+            return Err(invalid("not a FLAC file"));
+        }
+        let mut offset = 4u64;
+        let mut info = None;
+        loop {
+            let mut header = [0u8; 4];
+            inner.read_exact(&mut header)?;
+            let last = header[0] & 0x80 != 0;
+            let kind = header[0] & 0x7F;
+            let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as u64;
+            let mut body = Vec::new();
+            (&mut inner).take(length).read_to_end(&mut body)?;
+            if (body.len() as u64) < length {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            offset += 4 + length;
+            if kind == STREAMINFO {
+                if body.len() < STREAMINFO_BYTES {
+                    return Err(invalid("truncated STREAMINFO block"));
+                }
+                info = Some(body);
+            }
+            if last {
+                break;
+            }
+        }
+        let info = info.ok_or_else(|| invalid("missing STREAMINFO block"))?;
+        let packed = u64::from_be_bytes(info[10..18].try_into().unwrap());
+        let sample_rate = (packed >> 44) as u32;
+        let channels = ((packed >> 41) & 0x7) as u16 + 1;
+        let bits_per_sample = ((packed >> 36) & 0x1F) as u32 + 1;
+        let total_frames = packed & 0xF_FFFF_FFFF;
+        if sample_rate == 0 || bits_per_sample < 4 {
+            return Err(invalid("invalid STREAMINFO block"));
+        }
+        Ok(Self {
+            reader: BitReader::new(inner),
+            sample_rate,
+            channels,
+            bits_per_sample,
+            total_frames,
+            audio_offset: offset,
+            block: Vec::new(),
+            block_frames: 0,
+            cursor: 0,
+            scale: 1.0 / (1u64 << (bits_per_sample - 1)) as f32,
+        })
+    }
+
+    /// Frames per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Interleaved channels per frame.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Bits per decoded sample.
+    pub fn bits_per_sample(&self) -> u32 {
+        self.bits_per_sample
+    }
+
+    /// Total frames declared by the stream header, or 0 when unknown.
+    pub fn frames(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// Decode up to `out.len()` interleaved samples, rounded down to whole frames, scaled to
+    /// ±1.0. Returns how many were written, 0 at the end of the stream.
+    pub fn read_samples(&mut self, out: &mut [f32]) -> io::Result<usize> {
+        let channels = self.channels as usize;
+        let wanted = out.len() / channels;
+        let mut written = 0;
+        while written < wanted {
+            if self.cursor == self.block_frames && !self.decode_frame()? {
+                break;
+            }
+            let frames = (wanted - written).min(self.block_frames - self.cursor);
+            for frame in 0..frames {
+                for channel in 0..channels {
+                    let sample = self.block[channel * self.block_frames + self.cursor + frame];
+                    out[(written + frame) * channels + channel] = sample as f32 * self.scale;
+                }
+            }
+            self.cursor += frames;
+            written += frames;
+        }
+        Ok(written * channels)
+    }
+
+    /// Decode the next frame into `block`, returning `false` at the end of the stream.
+    fn decode_frame(&mut self) -> io::Result<bool> {
+        if !self.find_sync()? {
+            return Ok(false);
+        }
+        let reader = &mut self.reader;
+        let size_code = reader.read_bits(4)?;
+        let rate_code = reader.read_bits(4)?;
+        let assignment = reader.read_bits(4)?;
+        let depth_code = reader.read_bits(3)?;
+        if reader.read_bits(1)? != 0 {
+            return Err(invalid("reserved frame header bit set"));
+        }
+        // The frame or sample number; only its length matters here.
+        let first = reader.read_bits(8)? as u8;
+        let extra = match first.leading_ones() {
+            0 => 0,
+            length @ 2..=7 => length - 1,
+            _ => return Err(invalid("invalid frame number")),
+        };
+        for _ in 0..extra {
+            if reader.read_bits(8)? & 0xC0 != 0x80 {
+                return Err(invalid("invalid frame number"));
+            }
+        }
+        let block_frames = match size_code {
+            1 => 192,
+            2..=5 => 576 << (size_code - 2),
+            6 => reader.read_bits(8)? as usize + 1,
+            7 => reader.read_bits(16)? as usize + 1,
+            8..=15 => 256 << (size_code - 8),
+            _ => return Err(invalid("reserved block size")),
+        };
+        match rate_code {
+            12 => {
+                reader.read_bits(8)?;
+            }
+            13 | 14 => {
+                reader.read_bits(16)?;
+            }
+            15 => return Err(invalid("invalid sample rate code")),
+            _ => {}
+        }
+        let bits = match depth_code {
+            0 => self.bits_per_sample,
+            1 => 8,
+            2 => 12,
+            4 => 16,
+            5 => 20,
+            6 => 24,
+            7 => 32,
+            _ => return Err(invalid("reserved sample size")),
+        };
+        if bits != self.bits_per_sample {
+            return Err(invalid("frame sample size differs from STREAMINFO"));
+        }
+        // Header CRC-8.
+        reader.read_bits(8)?;
+
+        let channels = match assignment {
+            0..=7 => assignment as u16 + 1,
+            8..=10 => 2,
+            _ => return Err(invalid("reserved channel assignment")),
+        };
+        if channels != self.channels {
+            return Err(invalid("frame channel count differs from STREAMINFO"));
+        }
+        let side_channel = match assignment {
+            8 | 10 => Some(1),
+            9 => Some(0),
+            _ => None,
+        };
+
+        self.block.resize(block_frames * channels as usize, 0);
+        for channel in 0..channels as usize {
+            let depth = bits + u32::from(side_channel == Some(channel));
+            let out = &mut self.block[channel * block_frames..(channel + 1) * block_frames];
+            decode_subframe(&mut self.reader, depth, out)?;
+        }
+        self.reader.align();
+        // Frame CRC-16.
+        self.reader.read_bits(16)?;
+
+        let (first, second) = self.block.split_at_mut(block_frames);
+        match assignment {
+            8 => {
+                for (left, side) in first.iter().zip(second.iter_mut()) {
+                    *side = left.wrapping_sub(*side);
+                }
+            }
+            9 => {
+                for (side, right) in first.iter_mut().zip(second.iter()) {
+                    *side = side.wrapping_add(*right);
+                }
+            }
+            10 => {
+                for (mid, side) in first.iter_mut().zip(second.iter_mut()) {
+                    let full = (mid.wrapping_shl(1)) | (*side & 1);
+                    let left = full.wrapping_add(*side) >> 1;
+                    let right = full.wrapping_sub(*side) >> 1;
+                    *mid = left;
+                    *side = right;
+                }
+            }
+            _ => {}
+        }
+        self.block_frames = block_frames;
+        self.cursor = 0;
+        Ok(true)
+    }
+
+    /// Skip to just past the next frame sync code, returning `false` at the end of the stream.
+    fn find_sync(&mut self) -> io::Result<bool> {
+        self.reader.align();
+        let mut previous = 0u8;
+        while let Some(byte) = self.reader.read_byte()? {
+            if previous == 0xFF && byte & 0xFE == 0xF8 {
+                return Ok(true);
+            }
+            previous = byte;
+        }
+        Ok(false)
+    }
+}
+
+impl<R: Read + Seek> FlacReader<R> {
+    /// Move to `frame` by decoding forward from the start, stopping at the end of the stream.
+    pub fn seek(&mut self, frame: u64) -> io::Result<()> {
+        self.reader.inner.seek(SeekFrom::Start(self.audio_offset))?;
+        self.reader.reset();
+        self.block_frames = 0;
+        self.cursor = 0;
+        let mut remaining = frame;
+        while remaining > 0 {
+            if !self.decode_frame()? {
+                break;
+            }
+            let skipped = remaining.min(self.block_frames as u64);
+            self.cursor = skipped as usize;
+            remaining -= skipped;
+        }
+        Ok(())
+    }
+}
+
+/// Decode one channel of a frame into `out`, whose length is the block size.
+fn decode_subframe<R: Read>(
+    reader: &mut BitReader<R>,
+    bits: u32,
+    out: &mut [i64],
+) -> io::Result<()> {
+    if reader.read_bits(1)? != 0 {
+        return Err(invalid("subframe padding bit set"));
+    }
+    let kind = reader.read_bits(6)?;
+    let wasted = if reader.read_bits(1)? == 1 {
+        reader.read_unary()? + 1
+    } else {
+        0
+    };
+    if wasted >= bits as u64 {
+        return Err(invalid("too many wasted bits"));
+    }
+    let bits = bits - wasted as u32;
+
+    match kind {
+        0 => {
+            let value = reader.read_signed(bits)?;
+            out.fill(value);
+        }
+        1 => {
+            for sample in out.iter_mut() {
+                *sample = reader.read_signed(bits)?;
+            }
+        }
+        8..=12 => {
+            let order = (kind - 8) as usize;
+            read_warm_up(reader, bits, order, out)?;
+            read_residual(reader, order, out)?;
+            for i in order..out.len() {
+                let prediction = match order {
+                    0 => 0,
+                    1 => out[i - 1],
+                    2 => out[i - 1].wrapping_mul(2).wrapping_sub(out[i - 2]),
+                    3 => out[i - 1]
+                        .wrapping_sub(out[i - 2])
+                        .wrapping_mul(3)
+                        .wrapping_add(out[i - 3]),
+                    _ => out[i - 1]
+                        .wrapping_add(out[i - 3])
+                        .wrapping_mul(4)
+                        .wrapping_sub(out[i - 2].wrapping_mul(6))
+                        .wrapping_sub(out[i - 4]),
+                };
+                out[i] = out[i].wrapping_add(prediction);
+            }
+        }
+        32..=63 => {
+            let order = (kind - 31) as usize;
+            read_warm_up(reader, bits, order, out)?;
+            let precision = reader.read_bits(4)? as u32 + 1;
+            if precision == 16 {
+                return Err(invalid("invalid LPC precision"));
+            }
+            let shift = reader.read_signed(5)?;
+            if shift < 0 {
+                return Err(invalid("negative LPC shift"));
+            }
+            let mut coefficients = [0i64; 32];
+            for coefficient in &mut coefficients[..order] {
+                *coefficient = reader.read_signed(precision)?;
+            }
+            read_residual(reader, order, out)?;
+            for i in order..out.len() {
+                let prediction = coefficients[..order]
+                    .iter()
+                    .enumerate()
+                    .fold(0i64, |sum, (j, c)| {
+                        sum.wrapping_add(c.wrapping_mul(out[i - 1 - j]))
+                    });
+                out[i] = out[i].wrapping_add(prediction >> shift);
+            }
+        }
+        _ => return Err(invalid("reserved subframe type")),
+    }
+
+    if wasted > 0 {
+        for sample in out.iter_mut() {
+            *sample = sample.wrapping_shl(wasted as u32);
+        }
+    }
+    Ok(())
+}
+
+fn read_warm_up<R: Read>(
+    reader: &mut BitReader<R>,
+    bits: u32,
+    order: usize,
+    out: &mut [i64],
+) -> io::Result<()> {
+    if order > out.len() {
+        return Err(invalid("predictor order exceeds block size"));
+    }
+    for sample in &mut out[..order] {
+        *sample = reader.read_signed(bits)?;
+    }
+    Ok(())
+}
+
+/// Read the Rice-coded residual of a predicted subframe into `out[order..]`.
+fn read_residual<R: Read>(
+    reader: &mut BitReader<R>,
+    order: usize,
+    out: &mut [i64],
+) -> io::Result<()> {
+    let (parameter_bits, escape) = match reader.read_bits(2)? {
+        0 => (4, 0xF),
+        1 => (5, 0x1F),
+        _ => return Err(invalid("reserved residual coding method")),
+    };
+    let partition_order = reader.read_bits(4)? as u32;
+    let partition_frames = out.len() >> partition_order;
+    if partition_frames << partition_order != out.len() || partition_frames < order {
+        return Err(invalid("invalid residual partition order"));
+    }
+    let mut index = order;
+    for partition in 0..1usize << partition_order {
+        let count = if partition == 0 {
+            partition_frames - order
+        } else {
+            partition_frames
+        };
+        let parameter = reader.read_bits(parameter_bits)? as u32;
+        let samples = &mut out[index..index + count];
+        if parameter == escape {
+            let raw_bits = reader.read_bits(5)? as u32;
+            for sample in samples {
+                *sample = reader.read_signed(raw_bits)?;
+            }
+        } else {
+            for sample in samples {
+                let quotient = reader.read_unary()?;
+                let value = (quotient << parameter) | reader.read_bits(parameter)?;
+                *sample = (value >> 1) as i64 ^ -((value & 1) as i64);
+            }
+        }
+        index += count;
+    }
+    Ok(())
+}
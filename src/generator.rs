@@ -0,0 +1,402 @@
+//! Built-in test-signal generators for line-checking the loopback path.
+//!
+//! A generator source synthesises its audio on the render thread instead of reading a ring, so
+//! it needs no producer and never underruns. Every [`Waveform`] is mono and written to both
+//! channels. Levels follow the AES17 convention: a sine at 0 dBFS peaks at full scale, noise is
+//! scaled to the RMS of a sine at the same level, and impulses peak at the level. Output is
+//! clamped to full scale.
+//!
+//! Noise comes from a fixed-seed xorshift generator, so two renders of the same configuration
+//! are identical. Pink noise is white noise through Paul Kellett's refined -3 dB/octave filter.
+//! Sweeps are logarithmic and restart from the start frequency once they reach the end.
+//!
+//! The configuration can be changed while the source plays: [`Generator::configure`] publishes
+//! it from the control side and the render thread picks it up at the start of its next block.
+
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::MIX_CHANNELS;
+
+/// Default generator level in dBFS, the SMPTE RP 155 alignment level.
+pub const DEFAULT_LEVEL_DBFS: f32 = -20.0;
+/// Frequency of the [`Waveform::Reference`] tone.
+pub const REFERENCE_FREQUENCY_HZ: f32 = 1_000.0;
+
+/// [`Waveform`] code of [`Waveform::Sine`] for the C ABI.
+pub const LOOPBACK_GENERATOR_SINE: u32 = 0;
+/// [`Waveform`] code of [`Waveform::Sweep`] for the C ABI.
+pub const LOOPBACK_GENERATOR_SWEEP: u32 = 1;
+/// [`Waveform`] code of [`Waveform::WhiteNoise`] for the C ABI.
+pub const LOOPBACK_GENERATOR_WHITE_NOISE: u32 = 2;
+/// [`Waveform`] code of [`Waveform::PinkNoise`] for the C ABI.
+pub const LOOPBACK_GENERATOR_PINK_NOISE: u32 = 3;
+/// [`Waveform`] code of [`Waveform::Impulse`] for the C ABI.
+pub const LOOPBACK_GENERATOR_IMPULSE: u32 = 4;
+/// [`Waveform`] code of [`Waveform::Reference`] for the C ABI.
+pub const LOOPBACK_GENERATOR_REFERENCE: u32 = 5;
+
+const NOISE_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+/// Scales uniform white noise in [-1, 1) to the RMS of a full-scale sine.
+const WHITE_SCALE: f32 = 1.224_744_9;
+/// Scales the pink filter output for uniform white input to the RMS of a full-scale sine.
+const PINK_SCALE: f32 = 0.405_2;
+
+/// Signal produced by a generator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// Continuous sine tone.
+    Sine {
+        /// Tone frequency in Hz.
+        frequency_hz: f32,
+    },
+    /// Logarithmic sine sweep that repeats every `duration_seconds`.
+    Sweep {
+        /// Frequency at the start of each sweep in Hz.
+        start_hz: f32,
+        /// Frequency at the end of each sweep in Hz.
+        end_hz: f32,
+        /// Length of one sweep in seconds.
+        duration_seconds: f32,
+    },
+    /// Uniform white noise.
+    WhiteNoise,
+    /// Pink noise, falling 3 dB per octave.
+    PinkNoise,
+    /// Single-sample impulses at a fixed rate, the first one at the start.
+    Impulse {
+        /// Impulses per second.
+        rate_hz: f32,
+    },
+    /// Continuous 1 kHz line-up tone.
+    Reference,
+}
+
+impl Waveform {
+    /// Stable numeric code, one of the `LOOPBACK_GENERATOR_*` constants.
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::Sine { .. } => LOOPBACK_GENERATOR_SINE,
+            Self::Sweep { .. } => LOOPBACK_GENERATOR_SWEEP,
+            Self::WhiteNoise => LOOPBACK_GENERATOR_WHITE_NOISE,
+            Self::PinkNoise => LOOPBACK_GENERATOR_PINK_NOISE,
+            Self::Impulse { .. } => LOOPBACK_GENERATOR_IMPULSE,
+            Self::Reference => LOOPBACK_GENERATOR_REFERENCE,
+        }
+    }
+
+    /// Waveform called `name` (see [`name`](Self::name)). `frequency_hz` stands for the tone
+    /// frequency, the sweep start or the impulse rate as the waveform needs; missing values
+    /// default to a 1 kHz sine, a 20 Hz to 20 kHz sweep over 10 s and one impulse per second.
+    pub fn from_name(
+        name: &str,
+        frequency_hz: Option<f32>,
+        end_hz: Option<f32>,
+        duration_seconds: Option<f32>,
+    ) -> Option<Self> {
+        Some(match name {
+            "sine" => Self::Sine {
+                frequency_hz: frequency_hz.unwrap_or(REFERENCE_FREQUENCY_HZ),
+            },
+            "sweep" => Self::Sweep {
+                start_hz: frequency_hz.unwrap_or(20.0),
+                end_hz: end_hz.unwrap_or(20_000.0),
+                duration_seconds: duration_seconds.unwrap_or(10.0),
+            },
+            "white" => Self::WhiteNoise,
+            "pink" => Self::PinkNoise,
+            "impulse" => Self::Impulse {
+                rate_hz: frequency_hz.unwrap_or(1.0),
+            },
+            "reference" => Self::Reference,
+            _ => return None,
+        })
+    }
+
+    /// Short machine-friendly name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sine { .. } => "sine",
+            Self::Sweep { .. } => "sweep",
+            Self::WhiteNoise => "white",
+            Self::PinkNoise => "pink",
+            Self::Impulse { .. } => "impulse",
+            Self::Reference => "reference",
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        match *self {
+            Self::Sine { frequency_hz } => positive(frequency_hz),
+            Self::Sweep {
+                start_hz,
+                end_hz,
+                duration_seconds,
+            } => positive(start_hz) && positive(end_hz) && positive(duration_seconds),
+            Self::Impulse { rate_hz } => positive(rate_hz),
+            Self::WhiteNoise | Self::PinkNoise | Self::Reference => true,
+        }
+    }
+}
+
+/// Generator settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratorConfig {
+    /// Signal to produce.
+    pub waveform: Waveform,
+    /// Output level in dBFS; see the module documentation for how each waveform is measured.
+    pub level_dbfs: f32,
+}
+
+impl GeneratorConfig {
+    /// `waveform` at [`DEFAULT_LEVEL_DBFS`].
+    pub fn new(waveform: Waveform) -> Self {
+        Self {
+            waveform,
+            level_dbfs: DEFAULT_LEVEL_DBFS,
+        }
+    }
+
+    /// The 1 kHz reference tone at the -20 dBFS alignment level.
+    pub fn reference() -> Self {
+        Self::new(Waveform::Reference)
+    }
+
+    /// Whether every frequency and duration is positive and the level is finite and at most
+    /// 0 dBFS.
+    pub fn is_valid(&self) -> bool {
+        self.waveform.is_valid() && self.level_dbfs.is_finite() && self.level_dbfs <= 0.0
+    }
+
+    /// Settings described by a C ABI struct, or `None` for an unknown kind or invalid values.
+    pub fn from_ffi(raw: &LoopbackGenerator) -> Option<Self> {
+        let waveform = match raw.kind {
+            LOOPBACK_GENERATOR_SINE => Waveform::Sine {
+                frequency_hz: raw.frequency_hz,
+            },
+            LOOPBACK_GENERATOR_SWEEP => Waveform::Sweep {
+                start_hz: raw.frequency_hz,
+                end_hz: raw.end_frequency_hz,
+                duration_seconds: raw.duration_seconds,
+            },
+            LOOPBACK_GENERATOR_WHITE_NOISE => Waveform::WhiteNoise,
+            LOOPBACK_GENERATOR_PINK_NOISE => Waveform::PinkNoise,
+            LOOPBACK_GENERATOR_IMPULSE => Waveform::Impulse {
+                rate_hz: raw.frequency_hz,
+            },
+            LOOPBACK_GENERATOR_REFERENCE => Waveform::Reference,
+            _ => return None,
+        };
+        let config = Self {
+            waveform,
+            level_dbfs: raw.level_dbfs,
+        };
+        config.is_valid().then_some(config)
+    }
+
+    /// Settings as a C ABI struct. Fields the waveform does not use are zero.
+    pub fn to_ffi(&self) -> LoopbackGenerator {
+        let mut raw = LoopbackGenerator {
+            kind: self.waveform.code(),
+            level_dbfs: self.level_dbfs,
+            ..LoopbackGenerator::default()
+        };
+        match self.waveform {
+            Waveform::Sine { frequency_hz } => raw.frequency_hz = frequency_hz,
+            Waveform::Sweep {
+                start_hz,
+                end_hz,
+                duration_seconds,
+            } => {
+                raw.frequency_hz = start_hz;
+                raw.end_frequency_hz = end_hz;
+                raw.duration_seconds = duration_seconds;
+            }
+            Waveform::Impulse { rate_hz } => raw.frequency_hz = rate_hz,
+            Waveform::Reference => raw.frequency_hz = REFERENCE_FREQUENCY_HZ,
+            Waveform::WhiteNoise | Waveform::PinkNoise => {}
+        }
+        raw
+    }
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self::reference()
+    }
+}
+
+impl fmt::Display for GeneratorConfig {
+    /// Compact description such as `sweep 20 Hz-20000 Hz/10 s -20 dBFS`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.waveform.name();
+        match self.waveform {
+            Waveform::Sine { frequency_hz } => write!(f, "{name} {frequency_hz} Hz")?,
+            Waveform::Sweep {
+                start_hz,
+                end_hz,
+                duration_seconds,
+            } => write!(f, "{name} {start_hz} Hz-{end_hz} Hz/{duration_seconds} s")?,
+            Waveform::Impulse { rate_hz } => write!(f, "{name} {rate_hz}/s")?,
+            Waveform::Reference => write!(f, "{name} {REFERENCE_FREQUENCY_HZ} Hz")?,
+            Waveform::WhiteNoise | Waveform::PinkNoise => f.write_str(name)?,
+        }
+        write!(f, " {} dBFS", self.level_dbfs)
+    }
+}
+
+/// Generator settings for the C ABI.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoopbackGenerator {
+    /// One of the `LOOPBACK_GENERATOR_*` codes.
+    pub kind: u32,
+    /// Sine frequency, sweep start frequency or impulse rate, in Hz.
+    pub frequency_hz: f32,
+    /// Sweep end frequency in Hz.
+    pub end_frequency_hz: f32,
+    /// Sweep length in seconds.
+    pub duration_seconds: f32,
+    /// Output level in dBFS.
+    pub level_dbfs: f32,
+}
+
+/// Settings published by the control side for the render thread.
+struct Control {
+    config: Mutex<GeneratorConfig>,
+    changed: AtomicBool,
+}
+
+/// Oscillator state of a generator source.
+pub(crate) struct Generator {
+    control: Arc<Control>,
+    config: GeneratorConfig,
+    sample_rate: f64,
+    amplitude: f32,
+    /// Oscillator phase in cycles.
+    phase: f64,
+    /// Frames rendered since the current sweep or impulse period started.
+    elapsed: u64,
+    rng: u64,
+    pink: [f32; 7],
+}
+
+impl Generator {
+    pub(crate) fn new(config: GeneratorConfig, sample_rate: u32) -> Self {
+        let mut generator = Self {
+            control: Arc::new(Control {
+                config: Mutex::new(config),
+                changed: AtomicBool::new(false),
+            }),
+            config,
+            sample_rate: sample_rate.max(1) as f64,
+            amplitude: 0.0,
+            phase: 0.0,
+            elapsed: 0,
+            rng: NOISE_SEED,
+            pink: [0.0; 7],
+        };
+        generator.apply(config);
+        generator
+    }
+
+    /// Publish new settings for the render thread.
+    pub(crate) fn configure(&self, config: GeneratorConfig) {
+        *self.control.config.lock() = config;
+        self.control.changed.store(true, Ordering::Release);
+    }
+
+    /// Latest published settings.
+    pub(crate) fn config(&self) -> GeneratorConfig {
+        *self.control.config.lock()
+    }
+
+    fn apply(&mut self, config: GeneratorConfig) {
+        self.config = config;
+        self.amplitude = 10f32.powf(config.level_dbfs / 20.0);
+        // Sweeps and impulse trains start a new period; tones keep their phase.
+        self.elapsed = 0;
+    }
+
+    /// Render interleaved stereo frames into `out`, replacing its contents.
+    pub(crate) fn render(&mut self, out: &mut [f32]) {
+        if self.control.changed.swap(false, Ordering::AcqRel) {
+            let published = self.control.config.try_lock().map(|config| *config);
+            match published {
+                Some(config) => self.apply(config),
+                // The control side is mid-update; pick it up next block.
+                None => self.control.changed.store(true, Ordering::Release),
+            }
+        }
+        for frame in out.chunks_exact_mut(MIX_CHANNELS) {
+            let sample = (self.next_sample() * self.amplitude).clamp(-1.0, 1.0);
+            frame.fill(sample);
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        match self.config.waveform {
+            Waveform::Sine { frequency_hz } => self.oscillate(frequency_hz as f64),
+            Waveform::Reference => self.oscillate(REFERENCE_FREQUENCY_HZ as f64),
+            Waveform::Sweep {
+                start_hz,
+                end_hz,
+                duration_seconds,
+            } => {
+                let length = (duration_seconds as f64 * self.sample_rate).max(1.0);
+                if self.elapsed as f64 >= length {
+                    self.elapsed = 0;
+                    self.phase = 0.0;
+                }
+                let progress = self.elapsed as f64 / length;
+                let frequency = start_hz as f64 * (end_hz as f64 / start_hz as f64).powf(progress);
+                self.elapsed += 1;
+                self.oscillate(frequency)
+            }
+            Waveform::WhiteNoise => self.white() * WHITE_SCALE,
+            Waveform::PinkNoise => {
+                let white = self.white();
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.055_517_9;
+                b[1] = 0.99332 * b[1] + white * 0.075_075_9;
+                b[2] = 0.96900 * b[2] + white * 0.153_852;
+                b[3] = 0.86650 * b[3] + white * 0.310_485_6;
+                b[4] = 0.55000 * b[4] + white * 0.532_952_2;
+                b[5] = -0.7616 * b[5] - white * 0.016_898;
+                let pink = b.iter().sum::<f32>() + white * 0.5362;
+                b[6] = white * 0.115_926;
+                pink * PINK_SCALE
+            }
+            Waveform::Impulse { rate_hz } => {
+                let period = (self.sample_rate / rate_hz as f64).round().max(1.0) as u64;
+                if self.elapsed >= period {
+                    self.elapsed = 0;
+                }
+                let sample = if self.elapsed == 0 { 1.0 } else { 0.0 };
+                self.elapsed += 1;
+                sample
+            }
+        }
+    }
+
+    /// Advance the oscillator at `frequency` and return the sine at the previous phase.
+    fn oscillate(&mut self, frequency: f64) -> f32 {
+        let sample = (self.phase * TAU).sin() as f32;
+        self.phase = (self.phase + frequency / self.sample_rate).fract();
+        sample
+    }
+
+    /// Next uniform sample in [-1, 1) from a xorshift64* sequence.
+    fn white(&mut self) -> f32 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let bits = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40;
+        bits as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
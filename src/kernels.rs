@@ -0,0 +1,246 @@
+//! Vectorised inner loops of the render path: fader gain, bus-gain ramp with accumulation into
+//! the mix, and fixed-phase linear interpolation.
+//!
+//! Each kernel has a scalar version that defines its result. The SIMD versions (AVX on x86_64
+//! when the CPU has it, NEON on aarch64) perform the same multiplies and adds in the same order,
+//! without fusing them, so every path produces bit-identical output and the choice never shows
+//! up in a render.
+
+/// Multiply every sample by `gain`.
+pub(crate) fn scale(samples: &mut [f32], gain: f32) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx") {
+        // SAFETY: the CPU supports AVX.
+        return unsafe { avx::scale(samples, gain) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: NEON is part of the aarch64 baseline.
+        return unsafe { neon::scale(samples, gain) };
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    scalar::scale(samples, gain);
+}
+
+/// Scale the interleaved stereo `block` by a gain ramping linearly from `start` (exclusive) in
+/// steps of `step` per frame, and add it into `output`. Frame `n` gets `start + step * (n + 1)`.
+pub(crate) fn ramp_mix(block: &mut [f32], output: &mut [f32], start: f32, step: f32) {
+    debug_assert_eq!(block.len(), output.len());
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx") {
+        // SAFETY: the CPU supports AVX.
+        return unsafe { avx::ramp_mix(block, output, start, step) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: NEON is part of the aarch64 baseline.
+        return unsafe { neon::ramp_mix(block, output, start, step) };
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    scalar::ramp_mix(block, output, start, step);
+}
+
+/// Interpolate between consecutive interleaved stereo frames of `input` at fraction `t`:
+/// output frame `n` lies between input frames `n` and `n + 1`. `input` must hold one frame more
+/// than `output`.
+pub(crate) fn lerp(output: &mut [f32], input: &[f32], t: f32) {
+    assert!(input.len() >= output.len() + STEREO);
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx") {
+        // SAFETY: the CPU supports AVX, and `input` covers every sample read.
+        return unsafe { avx::lerp(output, input, t) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: NEON is part of the aarch64 baseline, and `input` covers every sample read.
+        return unsafe { neon::lerp(output, input, t) };
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    scalar::lerp(output, input, t);
+}
+
+/// Samples per interleaved frame.
+const STEREO: usize = 2;
+
+pub(crate) mod scalar {
+    use super::STEREO;
+
+    pub(crate) fn scale(samples: &mut [f32], gain: f32) {
+        for sample in samples {
+            *sample *= gain;
+        }
+    }
+
+    pub(crate) fn ramp_mix(block: &mut [f32], output: &mut [f32], start: f32, step: f32) {
+        ramp_mix_from(block, output, start, step, 0);
+    }
+
+    /// [`ramp_mix`] for a block whose first frame is frame `first_frame` of the ramp, so the
+    /// SIMD versions can hand over their remainder.
+    pub(super) fn ramp_mix_from(
+        block: &mut [f32],
+        output: &mut [f32],
+        start: f32,
+        step: f32,
+        first_frame: usize,
+    ) {
+        let frames = block
+            .chunks_exact_mut(STEREO)
+            .zip(output.chunks_exact_mut(STEREO));
+        for (index, (frame, mixed)) in frames.enumerate() {
+            let bus = start + step * (first_frame + index + 1) as f32;
+            frame[0] *= bus;
+            frame[1] *= bus;
+            mixed[0] += frame[0];
+            mixed[1] += frame[1];
+        }
+    }
+
+    pub(crate) fn lerp(output: &mut [f32], input: &[f32], t: f32) {
+        for (index, sample) in output.iter_mut().enumerate() {
+            let a = input[index];
+            let b = input[index + STEREO];
+            *sample = a + (b - a) * t;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx {
+    use std::arch::x86_64::*;
+
+    use super::{STEREO, scalar};
+
+    /// Samples per 256-bit vector.
+    const LANES: usize = 8;
+
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn scale(samples: &mut [f32], gain: f32) {
+        let gain_v = _mm256_set1_ps(gain);
+        let mut chunks = samples.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` holds exactly `LANES` samples.
+            unsafe {
+                let v = _mm256_loadu_ps(chunk.as_ptr());
+                _mm256_storeu_ps(chunk.as_mut_ptr(), _mm256_mul_ps(v, gain_v));
+            }
+        }
+        scalar::scale(chunks.into_remainder(), gain);
+    }
+
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn ramp_mix(block: &mut [f32], output: &mut [f32], start: f32, step: f32) {
+        let start_v = _mm256_set1_ps(start);
+        let step_v = _mm256_set1_ps(step);
+        // Ramp positions of the four frames in a vector, each repeated for both channels.
+        let mut position = _mm256_setr_ps(1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0);
+        let advance = _mm256_set1_ps((LANES / STEREO) as f32);
+        let done = block.len() / LANES * (LANES / STEREO);
+        let mut blocks = block.chunks_exact_mut(LANES);
+        let mut outputs = output.chunks_exact_mut(LANES);
+        for (frame, mixed) in (&mut blocks).zip(&mut outputs) {
+            // SAFETY: both chunks hold exactly `LANES` samples.
+            unsafe {
+                let bus = _mm256_add_ps(start_v, _mm256_mul_ps(step_v, position));
+                let scaled = _mm256_mul_ps(_mm256_loadu_ps(frame.as_ptr()), bus);
+                _mm256_storeu_ps(frame.as_mut_ptr(), scaled);
+                let sum = _mm256_add_ps(_mm256_loadu_ps(mixed.as_ptr()), scaled);
+                _mm256_storeu_ps(mixed.as_mut_ptr(), sum);
+            }
+            position = _mm256_add_ps(position, advance);
+        }
+        scalar::ramp_mix_from(
+            blocks.into_remainder(),
+            outputs.into_remainder(),
+            start,
+            step,
+            done,
+        );
+    }
+
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn lerp(output: &mut [f32], input: &[f32], t: f32) {
+        let t_v = _mm256_set1_ps(t);
+        let vectors = output.len() / LANES;
+        for index in (0..vectors).map(|vector| vector * LANES) {
+            // SAFETY: the caller guarantees `input` holds `output.len() + STEREO` samples.
+            unsafe {
+                let a = _mm256_loadu_ps(input.as_ptr().add(index));
+                let b = _mm256_loadu_ps(input.as_ptr().add(index + STEREO));
+                let v = _mm256_add_ps(a, _mm256_mul_ps(_mm256_sub_ps(b, a), t_v));
+                _mm256_storeu_ps(output.as_mut_ptr().add(index), v);
+            }
+        }
+        let done = vectors * LANES;
+        scalar::lerp(&mut output[done..], &input[done..], t);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    use super::{STEREO, scalar};
+
+    /// Samples per 128-bit vector.
+    const LANES: usize = 4;
+
+    pub(super) unsafe fn scale(samples: &mut [f32], gain: f32) {
+        let mut chunks = samples.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` holds exactly `LANES` samples.
+            unsafe {
+                let v = vld1q_f32(chunk.as_ptr());
+                vst1q_f32(chunk.as_mut_ptr(), vmulq_n_f32(v, gain));
+            }
+        }
+        scalar::scale(chunks.into_remainder(), gain);
+    }
+
+    pub(super) unsafe fn ramp_mix(block: &mut [f32], output: &mut [f32], start: f32, step: f32) {
+        let start_v = vdupq_n_f32(start);
+        // Ramp positions of the two frames in a vector, each repeated for both channels.
+        let positions = [1.0, 1.0, 2.0, 2.0];
+        // SAFETY: `positions` holds `LANES` values.
+        let mut position = unsafe { vld1q_f32(positions.as_ptr()) };
+        let advance = vdupq_n_f32((LANES / STEREO) as f32);
+        let done = block.len() / LANES * (LANES / STEREO);
+        let mut blocks = block.chunks_exact_mut(LANES);
+        let mut outputs = output.chunks_exact_mut(LANES);
+        for (frame, mixed) in (&mut blocks).zip(&mut outputs) {
+            // SAFETY: both chunks hold exactly `LANES` samples.
+            unsafe {
+                let bus = vaddq_f32(start_v, vmulq_n_f32(position, step));
+                let scaled = vmulq_f32(vld1q_f32(frame.as_ptr()), bus);
+                vst1q_f32(frame.as_mut_ptr(), scaled);
+                vst1q_f32(
+                    mixed.as_mut_ptr(),
+                    vaddq_f32(vld1q_f32(mixed.as_ptr()), scaled),
+                );
+            }
+            position = vaddq_f32(position, advance);
+        }
+        scalar::ramp_mix_from(
+            blocks.into_remainder(),
+            outputs.into_remainder(),
+            start,
+            step,
+            done,
+        );
+    }
+
+    pub(super) unsafe fn lerp(output: &mut [f32], input: &[f32], t: f32) {
+        let vectors = output.len() / LANES;
+        for index in (0..vectors).map(|vector| vector * LANES) {
+            // SAFETY: the caller guarantees `input` holds `output.len() + STEREO` samples.
+            unsafe {
+                let a = vld1q_f32(input.as_ptr().add(index));
+                let b = vld1q_f32(input.as_ptr().add(index + STEREO));
+                let v = vaddq_f32(a, vmulq_n_f32(vsubq_f32(b, a), t));
+                vst1q_f32(output.as_mut_ptr().add(index), v);
+            }
+        }
+        let done = vectors * LANES;
+        scalar::lerp(&mut output[done..], &input[done..], t);
+    }
+}
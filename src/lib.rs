@@ -6,12 +6,17 @@
 //! The `Mixer` owns per-source [`SharedRingBuffer`](ring::SharedRingBuffer) instances that receive
 //! interleaved `f32` PCM frames from Swift or Node bridges. The mixer performs lock-free, allocation
 //! free processing in the audio callback, supporting per-source gain/mute, latency compensation,
-//! and fractional resampling driven by device clock feedback.
+//! fractional resampling driven by device clock feedback, sidechain ducking between sources, a
+//! gain-sharing automixer for microphone groups, per-source voice activity detection, acoustic
+//! echo cancellation against another source or the master output, spectral noise suppression,
+//! level and EBU R128 loudness metering, loudness normalization of the master output, render
+//! callback timing against the block deadline, and a timestamped log of dropouts and glitches.
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::ffi::{CString, c_void};
+use std::ffi::{CStr, CString, c_void};
 use std::os::raw::c_char;
+use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
@@ -22,45 +27,80 @@ use dasp_frame::Stereo;
 
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, RwLock};
-use tracing::debug;
 
 use coreaudio_sys::{
     AudioBufferList, AudioTimeStamp, OSStatus, kAudioHardwareUnspecifiedError,
     kAudioTimeStampHostTimeValid,
 };
 
+use crate::aec::{ECHO_REFERENCE_MASTER, EchoCanceller, EchoReference};
+use crate::command::{COMMAND_CAPACITY, Command, CommandQueue};
+use crate::denoise::NoiseSuppressor;
+use crate::dynamics::{
+    AutomixChannel, DuckingParams, MAX_DUCK_TARGETS, SidechainDucker, automix_coefficient,
+};
+use crate::events::{EventCounts, EventLog, LoopbackEvent, MixerEvent, MixerEventKind};
+use crate::generator::{Generator, GeneratorConfig, LoopbackGenerator};
 use crate::latency::{LatencyProbe, LatencyReport};
+use crate::log::{LogCode, LogLevel, LogRecord};
+use crate::loudness::{
+    LoudnessMeter, LoudnessNormalizer, LoudnessReading, LoudnessReadout, NormalizationParams,
+};
+use crate::meter::{Meter, MeterReading};
+use crate::player::{FilePlayer, LoopbackFileStatus, PlayerError, PlayerStatus};
+use crate::recorder::{
+    Multitrack, MultitrackStatus, RecordTap, RecorderError, Recording, RecordingStatus, StemSource,
+};
 use crate::ring::{SharedRingBuffer, host_time_to_ns, monotonic_timestamp_ns};
-
+use crate::soundboard::{ClipConfig, ClipStatus, SOUNDBOARD_SOURCE, Soundboard, SoundboardError};
+use crate::spectrum::{LOOPBACK_SPECTRUM_MASTER, Spectrum, SpectrumConfig, SpectrumTap};
+use crate::stereo::{ChannelMode, ChannelUtility, StereoMeter, StereoReading};
+use crate::timing::{CallbackTimer, CallbackTiming};
+use crate::vad::VoiceActivityDetector;
+use crate::wav::SampleFormat;
+
+/// Acoustic echo cancellation for microphone sources.
+pub mod aec;
+mod command;
 /// Developer-facing control and TUI support.
 pub mod control;
+pub mod denoise;
+pub mod dynamics;
+pub mod events;
+mod fft;
+pub mod flac;
+pub mod generator;
+mod kernels;
 pub mod latency;
+pub mod log;
+pub mod loudness;
+pub mod meter;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod offline;
+pub mod player;
+mod queue;
+pub mod recorder;
 pub mod ring;
+pub mod soundboard;
+pub mod spectrum;
+pub mod stereo;
+pub mod timing;
+mod vad;
+pub mod wav;
 
 #[cfg(test)]
 mod tests;
 
 const MIX_CHANNELS: usize = 2;
 
-static LOG_BUFFER: Lazy<Mutex<VecDeque<String>>> =
-    Lazy::new(|| Mutex::new(VecDeque::with_capacity(64)));
 static LOG_CACHE: Lazy<Mutex<Option<CString>>> = Lazy::new(|| Mutex::new(None));
-static TRACING_INIT: Once = Once::new();
+static LOG_INIT: Once = Once::new();
 static DRIVER_RUNNING: AtomicBool = AtomicBool::new(false);
 static ENGINE_RUNNING: AtomicBool = AtomicBool::new(false);
 
-fn init_tracing() {
-    TRACING_INIT.call_once(|| {
-        let _ = tracing_subscriber::fmt::try_init();
-    });
-}
-
-fn push_log(line: impl Into<String>) {
-    let mut guard = LOG_BUFFER.lock();
-    guard.push_back(line.into());
-    while guard.len() > 256 {
-        guard.pop_front();
-    }
+fn init_logging() {
+    LOG_INIT.call_once(log::init);
 }
 
 /// Interleaved floating-point audio buffer shared across the FFI boundary.
@@ -90,6 +130,11 @@ impl SourceHandle {
     fn new(id: u32) -> Self {
         Self { id }
     }
+
+    /// Numeric id used for the source in status snapshots and events.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
 }
 
 /// Error enumeration surfaced across the public API.
@@ -104,6 +149,33 @@ pub enum MixerError {
     /// Requested channel configuration is unsupported.
     #[error("unsupported channel count {0}, only stereo is supported")]
     UnsupportedChannels(u32),
+    /// Sidechain key was given more targets than it can track.
+    #[error("too many ducking targets: {0}, at most {MAX_DUCK_TARGETS} are supported")]
+    TooManyDuckTargets(usize),
+    /// Echo canceller was asked to use its own source as the far-end reference.
+    #[error("source {0} cannot be its own echo reference")]
+    SelfEchoReference(u32),
+    /// Transport control was sent to a source that does not play a file.
+    #[error("source {0} is not a file player")]
+    NotAFilePlayer(u32),
+    /// Generator settings were sent to a source that is not a generator.
+    #[error("source {0} is not a generator")]
+    NotAGenerator(u32),
+    /// Generator settings had a non-positive frequency or duration, or a level above 0 dBFS.
+    #[error("invalid generator settings: {0}")]
+    InvalidGenerator(GeneratorConfig),
+    /// The requested source index already belongs to another source.
+    #[error("source {0} is already in use")]
+    SourceInUse(u32),
+    /// Clip requests were sent to a source that is not a soundboard.
+    #[error("source {0} is not a soundboard")]
+    NotASoundboard(u32),
+    /// Automix weight was infinite or NaN.
+    #[error("invalid automix weight {0}, expected a finite number")]
+    InvalidAutomixWeight(f32),
+    /// The render thread has not picked up earlier processor changes yet.
+    #[error("{COMMAND_CAPACITY} processor changes are still waiting for the render thread")]
+    RenderBacklog,
 }
 
 /// Resampler state with drift tracking.
@@ -131,9 +203,17 @@ impl ResamplerState {
 }
 
 /// Clock feedback integrator maintains a smoothed drift estimate.
+///
+/// The source and device intervals between feedback reports are smoothed separately and the
+/// ratio taken of the results. Jitter in the report times cancels between consecutive intervals,
+/// whereas smoothing per-interval ratios would turn it into a bias toward running fast.
 struct ClockState {
     last_device_ts: Option<u64>,
     last_source_ts: Option<u64>,
+    /// Smoothed interval between reports on the source clock, in nanoseconds.
+    source_interval: f64,
+    /// Smoothed interval between reports on the device clock, in nanoseconds.
+    device_interval: f64,
     smoothed_ratio: f32,
 }
 
@@ -142,6 +222,8 @@ impl ClockState {
         Self {
             last_device_ts: None,
             last_source_ts: None,
+            source_interval: 0.0,
+            device_interval: 0.0,
             smoothed_ratio: 1.0,
         }
     }
@@ -153,17 +235,22 @@ impl ClockState {
             {
                 let device_delta = (device_ts - prev_device) as f64;
                 let source_delta = (source_ts - prev_source) as f64;
-                if device_delta > 0.0 && source_delta > 0.0 {
-                    let raw_ratio = (source_delta / device_delta) as f32;
-                    let clamped = raw_ratio.clamp(0.98, 1.02);
-                    // Critically damped first-order IIR smoother.
-                    const ALPHA: f32 = 0.05;
-                    self.smoothed_ratio =
-                        self.smoothed_ratio + ALPHA * (clamped - self.smoothed_ratio);
-                    self.last_device_ts = Some(device_ts);
-                    self.last_source_ts = Some(source_ts);
-                    return Some(self.smoothed_ratio);
+                // Limit the pull of implausible reports without biasing merely jittery ones.
+                let raw_ratio = (source_delta / device_delta).clamp(0.9, 1.1);
+                if self.device_interval == 0.0 {
+                    self.source_interval = device_delta;
+                    self.device_interval = device_delta;
                 }
+                // Critically damped first-order IIR smoother.
+                const ALPHA: f64 = 0.05;
+                self.source_interval += ALPHA * (device_delta * raw_ratio - self.source_interval);
+                self.device_interval += ALPHA * (device_delta - self.device_interval);
+                // The resampler corrects at most 2% either way.
+                let ratio = self.source_interval / self.device_interval;
+                self.smoothed_ratio = ratio.clamp(0.98, 1.02) as f32;
+                self.last_device_ts = Some(device_ts);
+                self.last_source_ts = Some(source_ts);
+                return Some(self.smoothed_ratio);
             }
             _ => {}
         }
@@ -218,18 +305,39 @@ impl DelayLine {
             return None;
         }
         let frame = self.buffer[self.read_idx];
-        self.read_idx = (self.read_idx + 1) % self.capacity;
+        self.read_idx = self.next_index(self.read_idx);
         self.len -= 1;
         Some(frame)
     }
 
+    fn next_index(&self, index: usize) -> usize {
+        if index + 1 == self.capacity {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    /// Whether frames would pass straight through: no delay is wanted and none is buffered.
+    fn is_bypassed(&self) -> bool {
+        self.target_delay == 0 && self.len == 0
+    }
+
+    /// Delay an interleaved stereo block in place.
+    fn process_block(&mut self, block: &mut [f32]) {
+        for frame in block.chunks_exact_mut(MIX_CHANNELS) {
+            let delayed = self.process_frame([frame[0], frame[1]]);
+            frame.copy_from_slice(&delayed);
+        }
+    }
+
     fn process_frame(&mut self, frame: Stereo<f32>) -> Stereo<f32> {
         self.buffer[self.write_idx] = frame;
-        self.write_idx = (self.write_idx + 1) % self.capacity;
+        self.write_idx = self.next_index(self.write_idx);
         if self.len < self.capacity {
             self.len += 1;
         } else {
-            self.read_idx = (self.read_idx + 1) % self.capacity;
+            self.read_idx = self.next_index(self.read_idx);
         }
 
         if self.len > self.target_delay {
@@ -246,6 +354,7 @@ struct Source {
     ring: Arc<SharedRingBuffer>,
     gain: std::sync::atomic::AtomicU32,
     mute: std::sync::atomic::AtomicBool,
+    utility_bits: std::sync::atomic::AtomicU32,
     latency_frames: std::sync::atomic::AtomicI64,
     current_latency_setting: i64,
     advance_deficit: usize,
@@ -253,17 +362,60 @@ struct Source {
     resampler: ResamplerState,
     clock: ClockState,
     scratch: Vec<f32>,
+    block: Vec<f32>,
+    block_frames: usize,
     prev_frame: Stereo<f32>,
+    /// Frame read past the end of the last block, when slowing down needed it to interpolate.
+    lookahead: Option<Stereo<f32>>,
+    ducker: SidechainDucker,
+    duck_target: f32,
+    duck_gain_bits: std::sync::atomic::AtomicU32,
+    automix: AutomixChannel,
+    bus_gain: f32,
+    vad: VoiceActivityDetector,
+    aec: Option<Box<EchoCanceller>>,
+    /// Whether an echo canceller has been sent to the render thread, for control-side status.
+    aec_enabled: std::sync::atomic::AtomicBool,
+    /// Whether another source's canceller uses this one as its far-end reference this block.
+    echo_reference: bool,
+    erle_bits: std::sync::atomic::AtomicU32,
+    denoise: Option<Box<NoiseSuppressor>>,
+    /// Whether a noise suppressor has been sent to the render thread, for control-side status.
+    denoise_enabled: std::sync::atomic::AtomicBool,
+    denoise_strength_bits: std::sync::atomic::AtomicU32,
+    meter: Meter,
+    stereo: StereoMeter,
+    loudness: Option<Box<LoudnessMeter>>,
+    /// Where every loudness meter the source is given publishes, so readings and reset
+    /// requests never touch the render-owned meter.
+    loudness_readout: Arc<LoudnessReadout>,
+    /// Whether a loudness meter has been sent to the render thread, for control-side readings.
+    loudness_enabled: std::sync::atomic::AtomicBool,
+    spectrum: SpectrumTap,
+    stem: Option<Arc<RecordTap>>,
+    player: Option<FilePlayer>,
+    generator: Option<Generator>,
+    soundboard: Option<Soundboard>,
+    starved: bool,
+    non_finite: bool,
+    /// Whether the source has been removed and is waiting for the render thread to detach it.
+    removed: std::sync::atomic::AtomicBool,
 }
 
 impl Source {
-    fn new(handle: SourceHandle, ring: Arc<SharedRingBuffer>, max_block_frames: usize) -> Self {
+    fn new(
+        handle: SourceHandle,
+        ring: Arc<SharedRingBuffer>,
+        sample_rate: u32,
+        max_block_frames: usize,
+    ) -> Self {
         let scratch_samples = max_block_frames * MIX_CHANNELS * 4;
         Self {
             handle,
             ring,
             gain: std::sync::atomic::AtomicU32::new(1.0f32.to_bits()),
             mute: std::sync::atomic::AtomicBool::new(false),
+            utility_bits: std::sync::atomic::AtomicU32::new(0),
             latency_frames: std::sync::atomic::AtomicI64::new(0),
             current_latency_setting: 0,
             advance_deficit: 0,
@@ -271,7 +423,36 @@ impl Source {
             resampler: ResamplerState::new(),
             clock: ClockState::new(),
             scratch: vec![0.0; scratch_samples],
+            block: vec![0.0; scratch_samples],
+            block_frames: 0,
             prev_frame: Stereo::EQUILIBRIUM,
+            lookahead: None,
+            ducker: SidechainDucker::new(),
+            duck_target: 1.0,
+            duck_gain_bits: std::sync::atomic::AtomicU32::new(1.0f32.to_bits()),
+            automix: AutomixChannel::new(),
+            bus_gain: 1.0,
+            vad: VoiceActivityDetector::new(sample_rate),
+            aec: None,
+            aec_enabled: std::sync::atomic::AtomicBool::new(false),
+            echo_reference: false,
+            erle_bits: std::sync::atomic::AtomicU32::new(0.0f32.to_bits()),
+            denoise: None,
+            denoise_enabled: std::sync::atomic::AtomicBool::new(false),
+            denoise_strength_bits: std::sync::atomic::AtomicU32::new(0.0f32.to_bits()),
+            meter: Meter::new(sample_rate),
+            stereo: StereoMeter::new(sample_rate),
+            loudness: None,
+            loudness_readout: Arc::new(LoudnessReadout::new()),
+            loudness_enabled: std::sync::atomic::AtomicBool::new(false),
+            spectrum: SpectrumTap::disabled(sample_rate),
+            stem: None,
+            player: None,
+            generator: None,
+            soundboard: None,
+            starved: true,
+            non_finite: false,
+            removed: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
@@ -288,10 +469,23 @@ impl Source {
         self.mute.store(mute, std::sync::atomic::Ordering::Relaxed);
     }
 
+    fn is_removed(&self) -> bool {
+        self.removed.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     fn is_muted(&self) -> bool {
         self.mute.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    fn set_channel_utility(&self, utility: ChannelUtility) {
+        self.utility_bits
+            .store(utility.to_bits(), std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn channel_utility(&self) -> ChannelUtility {
+        ChannelUtility::from_bits(self.utility_bits.load(std::sync::atomic::Ordering::Relaxed))
+    }
+
     fn set_latency(&self, frames: i64) {
         self.latency_frames
             .store(frames, std::sync::atomic::Ordering::Relaxed);
@@ -331,57 +525,105 @@ impl Source {
         self.ring.push(data, timestamp_ns)
     }
 
-    fn mix_into(&mut self, output: &mut [f32], frames: usize) {
-        if self.is_muted() {
-            return;
+    /// Render `frames` of pre-fader audio into the source's block buffer, returning how many
+    /// frames had to be padded with silence because the ring ran dry. A muted source renders
+    /// silence and leaves its ring untouched, with two exceptions that keep reading: echo
+    /// references, whose far end is usually muted in the mix but still feeds the canceller, and
+    /// file players, so muting one does not pause its transport.
+    fn render(&mut self, frames: usize) -> usize {
+        let frame_samples = MIX_CHANNELS;
+        let block_samples = frames * frame_samples;
+        if block_samples > self.block.len() {
+            // Real-time path must not reallocate; contribute silence instead.
+            self.block_frames = 0;
+            return 0;
+        }
+        self.block_frames = frames;
+        if let Some(generator) = self.generator.as_mut() {
+            generator.render(&mut self.block[..block_samples]);
+            return 0;
         }
+        if let Some(soundboard) = self.soundboard.as_mut() {
+            soundboard.render(&mut self.block[..block_samples]);
+            return 0;
+        }
+        self.block[..block_samples].fill(0.0);
+        if self.is_muted() && !self.echo_reference && self.player.is_none() {
+            return 0;
+        }
+        if self.player.as_ref().is_some_and(|player| !player.admit()) {
+            // Paused or finished file players keep their queued audio for later.
+            return 0;
+        }
+
         self.update_latency_state();
 
+        if self.advance_deficit > 0 && self.lookahead.take().is_some() {
+            self.advance_deficit -= 1;
+        }
         if self.advance_deficit > 0 {
             let dropped = self.ring.discard(self.advance_deficit);
             self.advance_deficit = self.advance_deficit.saturating_sub(dropped);
         }
 
         let ratio = self.resampler.ratio().clamp(0.95, 1.05);
-        let expected_input = ((frames as f32) * ratio).ceil() as usize + 2;
-        let frame_samples = MIX_CHANNELS;
+        // Input frames the interpolator steps over; anything more stays queued for the next block.
+        let consumed = (self.resampler.phase + frames as f32 * ratio).floor() as usize;
+        // Below unity the last output frame can fall short of the next input frame yet still
+        // interpolate toward it, so that frame is read ahead and held for the next block.
+        let interpolated =
+            (self.resampler.phase + (frames - 1) as f32 * ratio).floor() as usize + 1;
+        let wanted = consumed.max(interpolated);
+        let expected_input = wanted + 1;
         let scratch_needed = expected_input * frame_samples;
         if scratch_needed > self.scratch.len() {
             // Real-time path must not reallocate; clamp size.
-            return;
+            return 0;
         }
 
         // Seed first frame with previous value for smooth interpolation.
         let mut total_input_frames = 1usize;
         self.scratch[0] = self.prev_frame[0];
         self.scratch[1] = self.prev_frame[1];
+        if let Some(frame) = self.lookahead.take() {
+            self.scratch[frame_samples] = frame[0];
+            self.scratch[frame_samples + 1] = frame[1];
+            total_input_frames += 1;
+        }
 
-        let to_read_frames = expected_input.saturating_sub(1);
-        let read_samples = to_read_frames * frame_samples;
+        let to_read_frames = expected_input.saturating_sub(total_input_frames);
+        let start = total_input_frames * frame_samples;
         let read = self
             .ring
-            .pop(&mut self.scratch[frame_samples..frame_samples + read_samples]);
+            .pop(&mut self.scratch[start..start + to_read_frames * frame_samples]);
         total_input_frames += read;
 
-        let gain = self.gain();
-
         if total_input_frames < 2 {
-            for frame_index in 0..frames {
-                let delayed = self.delay_line.process_frame(Stereo::EQUILIBRIUM);
-                let base = frame_index * frame_samples;
-                output[base] += delayed[0] * gain;
-                output[base + 1] += delayed[1] * gain;
-            }
-            return;
+            self.delay_block(block_samples);
+            return self.underrun_frames(frames);
         }
 
         let mut produced_frames = 0usize;
+        let mut missing_frames = 0usize;
         let mut input_cursor = 0usize;
         let mut phase = self.resampler.phase;
         let last_available = total_input_frames.saturating_sub(1);
 
         while produced_frames < frames {
+            if ratio == 1.0 && input_cursor < last_available && (phase + 1.0) - 1.0 == phase {
+                // At unity each step moves exactly one input frame and leaves the phase alone,
+                // so the run up to the last available frame is a single fixed-fraction lerp.
+                let run = (frames - produced_frames).min(last_available - input_cursor);
+                let output =
+                    produced_frames * frame_samples..(produced_frames + run) * frame_samples;
+                let input = input_cursor * frame_samples..(input_cursor + run + 1) * frame_samples;
+                kernels::lerp(&mut self.block[output], &self.scratch[input], phase);
+                produced_frames += run;
+                input_cursor += run;
+                continue;
+            }
             let frame = if input_cursor >= last_available {
+                missing_frames += 1;
                 Stereo::EQUILIBRIUM
             } else {
                 let base_idx = input_cursor;
@@ -402,15 +644,164 @@ impl Source {
                 input_cursor = (input_cursor + advance).min(last_available);
             }
 
-            let delayed = self.delay_line.process_frame(frame);
             let base = produced_frames * frame_samples;
-            output[base] += delayed[0] * gain;
-            output[base + 1] += delayed[1] * gain;
+            self.block[base] = frame[0];
+            self.block[base + 1] = frame[1];
             produced_frames += 1;
         }
+        self.delay_block(block_samples);
 
         self.resampler.phase = phase;
-        self.prev_frame = read_interleaved(&self.scratch, last_available);
+        if input_cursor + 1 == last_available {
+            self.prev_frame = read_interleaved(&self.scratch, input_cursor);
+            self.lookahead = Some(read_interleaved(&self.scratch, last_available));
+        } else {
+            self.prev_frame = read_interleaved(&self.scratch, last_available);
+        }
+        self.underrun_frames(missing_frames)
+    }
+
+    /// Run the first `samples` of the rendered block through the latency delay line, unless it
+    /// would pass them through unchanged.
+    fn delay_block(&mut self, samples: usize) {
+        if !self.delay_line.is_bypassed() {
+            self.delay_line.process_block(&mut self.block[..samples]);
+        }
+    }
+
+    /// Frames to report as an underrun when `missing` could not be rendered. Running out at the
+    /// end of a played file is not an underrun.
+    fn underrun_frames(&self, missing: usize) -> usize {
+        if self.player.as_ref().is_some_and(FilePlayer::is_ending) {
+            0
+        } else {
+            missing
+        }
+    }
+
+    /// Report the start of an underrun, and replace any NaN or infinite samples in the rendered
+    /// block with silence so they cannot poison the processing state downstream.
+    fn report_glitches(&mut self, missing_frames: usize, events: &EventLog) {
+        if missing_frames > 0 && !self.starved {
+            events.record(MixerEventKind::SourceUnderrun {
+                source_id: self.handle.id,
+                missing_frames: missing_frames as u32,
+            });
+        }
+        self.starved = missing_frames > 0;
+
+        let samples = self.block_frames * MIX_CHANNELS;
+        let found = replace_non_finite(&mut self.block[..samples]);
+        if found && !self.non_finite {
+            events.record(MixerEventKind::NonFinite {
+                source_id: Some(self.handle.id),
+            });
+        }
+        self.non_finite = found;
+    }
+
+    /// Swap, invert and fold the rendered block's channels as configured.
+    fn apply_channel_utility(&mut self) {
+        let samples = self.block_frames * MIX_CHANNELS;
+        self.channel_utility().process(&mut self.block[..samples]);
+    }
+
+    fn denoise_strength(&self) -> f32 {
+        f32::from_bits(
+            self.denoise_strength_bits
+                .load(std::sync::atomic::Ordering::Relaxed),
+        )
+    }
+
+    /// Run the noise suppressor, if enabled, over the pre-fader block.
+    fn suppress_noise(&mut self) {
+        let strength = self.denoise_strength();
+        if let Some(suppressor) = self.denoise.as_mut() {
+            let samples = self.block_frames * MIX_CHANNELS;
+            suppressor.process(&mut self.block[..samples], strength);
+        }
+    }
+
+    /// Apply the fader gain to the rendered block, silencing it when muted.
+    fn apply_fader(&mut self) {
+        let gain = if self.is_muted() { 0.0 } else { self.gain() };
+        if gain != 1.0 {
+            let samples = self.block_frames * MIX_CHANNELS;
+            kernels::scale(&mut self.block[..samples], gain);
+        }
+    }
+
+    /// Run block-level analysis on the rendered post-fader signal.
+    fn analyse(&mut self) {
+        let samples = self.block_frames * MIX_CHANNELS;
+        self.meter.process(&self.block[..samples]);
+        self.stereo.process(&self.block[..samples]);
+        if let Some(loudness) = self.loudness.as_mut() {
+            loudness.process(&self.block[..samples]);
+        }
+        self.vad.process(&self.block[..samples]);
+        self.spectrum.write(&self.block[..samples]);
+    }
+
+    /// Add the rendered block into `output`, ramping towards the combined sidechain and automix
+    /// gain.
+    fn accumulate(&mut self, output: &mut [f32], frames: usize) {
+        self.duck_gain_bits.store(
+            self.duck_target.to_bits(),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+        let start = self.bus_gain;
+        let target = self.duck_target * self.automix.gain();
+        self.bus_gain = target;
+
+        let frames = frames.min(self.block_frames);
+        if frames == 0 {
+            return;
+        }
+        let step = (target - start) / frames as f32;
+        let samples = frames * MIX_CHANNELS;
+        kernels::ramp_mix(
+            &mut self.block[..samples],
+            &mut output[..samples],
+            start,
+            step,
+        );
+    }
+
+    /// Queue what [`accumulate`](Self::accumulate) added to the mix on the source's stem,
+    /// padded with silence to `frames` so the stem stays aligned with the master.
+    fn record_stem(&mut self, frames: usize, timestamp_ns: u64) {
+        let Some(stem) = self.stem.as_ref() else {
+            return;
+        };
+        let capacity = self.block.len() / MIX_CHANNELS;
+        let rendered = frames.min(self.block_frames);
+        let padded = frames.min(capacity);
+        self.block[rendered * MIX_CHANNELS..padded * MIX_CHANNELS].fill(0.0);
+        stem.write(&self.block[..padded * MIX_CHANNELS], timestamp_ns);
+        if frames > padded {
+            self.block[..rendered * MIX_CHANNELS].fill(0.0);
+            let mut remaining = frames - padded;
+            while remaining > 0 {
+                let chunk = remaining.min(capacity);
+                stem.write(&self.block[..chunk * MIX_CHANNELS], timestamp_ns);
+                remaining -= chunk;
+            }
+        }
+    }
+
+    /// Latest reading of the source's loudness meter, or `None` when metering is disabled.
+    fn loudness(&self) -> Option<LoudnessReading> {
+        self.loudness_enabled
+            .load(std::sync::atomic::Ordering::Relaxed)
+            .then(|| self.loudness_readout.reading())
+    }
+
+    fn duck_gain_linear(&self) -> f32 {
+        f32::from_bits(
+            self.duck_gain_bits
+                .load(std::sync::atomic::Ordering::Relaxed),
+        )
     }
 
     fn buffer_fill_ratio(&self) -> f32 {
@@ -429,29 +820,68 @@ impl Source {
         self.clock.drift_ppm()
     }
 
-    fn rms_estimate(&self) -> f32 {
-        let left = self.prev_frame[0];
-        let right = self.prev_frame[1];
-        ((left * left + right * right) * 0.5).sqrt()
-    }
-
     fn gain_linear(&self) -> f32 {
         self.gain()
     }
 }
 
+/// Display name of a source, as shown in status snapshots and session manifests.
+fn source_name(handle: SourceHandle, mic_handle: Option<SourceHandle>) -> String {
+    if Some(handle) == mic_handle {
+        "Microphone".to_string()
+    } else {
+        format!("Source #{}", handle.id)
+    }
+}
+
+fn linear_to_db(gain: f32) -> f32 {
+    if gain > 0.0 {
+        20.0 * gain.log10()
+    } else {
+        f32::NEG_INFINITY
+    }
+}
+
 fn read_interleaved(buffer: &[f32], frame_index: usize) -> Stereo<f32> {
     let base = frame_index * MIX_CHANNELS;
     [buffer[base], buffer[base + 1]]
 }
 
+/// Zero every NaN or infinite sample in `block`, returning whether any were found.
+fn replace_non_finite(block: &mut [f32]) -> bool {
+    if block.iter().all(|sample| sample.is_finite()) {
+        return false;
+    }
+    for sample in block.iter_mut().filter(|sample| !sample.is_finite()) {
+        *sample = 0.0;
+    }
+    true
+}
+
 /// Primary mixer struct orchestrating all decoding and mixing.
 pub struct Mixer {
     sample_rate: u32,
     max_block_frames: usize,
-    sources: Vec<Source>,
+    sources: Vec<Box<Source>>,
     next_source_id: u32,
     latency_probe: LatencyProbe,
+    master_history: Vec<f32>,
+    master_meter: Meter,
+    master_stereo: StereoMeter,
+    master_loudness: LoudnessMeter,
+    normalizer: LoudnessNormalizer,
+    spectrum: Option<SpectrumConfig>,
+    master_spectrum: SpectrumTap,
+    recording: Option<Recording>,
+    /// Render-side end of `recording`, handed over through `commands`.
+    recording_tap: Option<Arc<RecordTap>>,
+    multitrack: Option<Multitrack>,
+    /// Render-side end of `multitrack`'s master track, handed over through `commands`.
+    multitrack_tap: Option<Arc<RecordTap>>,
+    commands: CommandQueue,
+    events: EventLog,
+    master_non_finite: bool,
+    format_error: bool,
 }
 
 /// Per-source diagnostics exposed to developer tooling.
@@ -467,14 +897,66 @@ pub struct SourceStatus {
     pub gain_db: f32,
     /// Whether the source is muted.
     pub muted: bool,
+    /// Polarity, swap and channel folding applied before any other processing.
+    pub channel_utility: ChannelUtility,
     /// Configured latency in frames (positive adds delay, negative advances).
     pub latency_frames: i64,
     /// Estimated buffer utilisation percentage for queued audio.
     pub buffer_fill: f32,
-    /// Estimated RMS level (0-1).
+    /// Post-fader RMS level (0-1) integrated over [`meter::RMS_INTEGRATION_MS`].
     pub rms: f32,
+    /// Post-fader sample peak with decay ballistics (0-1).
+    pub peak: f32,
+    /// Held maximum sample peak (0-1).
+    pub peak_hold: f32,
+    /// 4x oversampled true peak; may exceed 1.0 for inter-sample overs.
+    pub true_peak: f32,
+    /// Whether the source clipped recently.
+    pub clipped: bool,
+    /// Post-fader phase correlation between left and right (-1 to +1).
+    pub correlation: f32,
+    /// Post-fader stereo width (0 mono, 1 unrelated channels, 2 fully out of phase).
+    pub stereo_width: f32,
+    /// Whether the source recently lost much of its signal when summed to mono, as from a
+    /// flipped channel.
+    pub mono_warning: bool,
+    /// Post-fader loudness, when per-source loudness metering is enabled.
+    pub loudness: Option<LoudnessReading>,
     /// Clock drift estimate in parts per million.
     pub drift_ppm: f32,
+    /// Current sidechain attenuation applied to the source in decibels (0 when not ducked).
+    pub duck_gain_db: f32,
+    /// Gain assigned by the automixer in decibels (0 when the source is not in the group).
+    pub automix_gain_db: f32,
+    /// Weight used when sharing automix gain (1.0 is neutral).
+    pub automix_weight: f32,
+    /// Whether voice activity detection currently considers the source to be speaking.
+    pub speaking: bool,
+    /// Smoothed voice activity probability (0-1).
+    pub voice_probability: f32,
+    /// Whether acoustic echo cancellation runs on the source.
+    pub echo_cancelling: bool,
+    /// Echo return loss enhancement achieved by the canceller in decibels.
+    pub erle_db: f32,
+    /// Noise suppression strength (0-1), or `None` when suppression is disabled.
+    pub noise_suppression: Option<f32>,
+    /// Transport state of the file the source plays, if it is a file player.
+    pub file: Option<PlayerStatus>,
+    /// Signal the source synthesises, if it is a generator.
+    pub generator: Option<GeneratorConfig>,
+}
+
+/// Voice activity of one source, the subset of [`SourceStatus`] that speech watchers poll.
+#[derive(Clone, Debug)]
+pub struct VoiceActivity {
+    /// Mixer-assigned source identifier.
+    pub id: u32,
+    /// Display name.
+    pub name: String,
+    /// Whether voice activity detection currently considers the source to be speaking.
+    pub speaking: bool,
+    /// Smoothed voice activity probability (0-1).
+    pub probability: f32,
 }
 
 /// Aggregated mixer status snapshot used by control surfaces.
@@ -486,12 +968,33 @@ pub struct MixerStatus {
     pub buffer_frames: usize,
     /// Effective render latency in milliseconds based on buffer size.
     pub latency_ms: f32,
-    /// Approximate mixer CPU utilisation (0–1 range).
+    /// Render callback load relative to the block duration, smoothed over recent callbacks
+    /// (1.0 uses the whole deadline).
     pub cpu_usage: f32,
+    /// Callback timing statistics since the mixer started or timing was last reset.
+    pub timing: CallbackTiming,
+    /// Dropout and glitch totals since the mixer was created.
+    pub events: EventCounts,
     /// Average queued buffer fill across active sources (0–1).
     pub buffer_fill: f32,
     /// Average drift estimate in parts per million.
     pub drift_ppm: f32,
+    /// Level readings of the master output.
+    pub master: MeterReading,
+    /// Phase correlation and width of the master output.
+    pub stereo: StereoReading,
+    /// Whether the master output or any source currently raises a mono-compatibility warning.
+    pub mono_warning: bool,
+    /// EBU R128 loudness of the master output.
+    pub loudness: LoudnessReading,
+    /// Active loudness normalization settings, if enabled.
+    pub normalization: Option<NormalizationParams>,
+    /// Correction gain currently applied by loudness normalization, in dB.
+    pub normalization_gain_db: f32,
+    /// Progress of the master recording, if one is running.
+    pub recording: Option<RecordingStatus>,
+    /// Progress of the multitrack session, if one is running.
+    pub multitrack: Option<MultitrackStatus>,
     /// Per-source diagnostics.
     pub sources: Vec<SourceStatus>,
 }
@@ -507,6 +1010,44 @@ pub struct LoopbackLevels {
     pub input_count: u32,
     /// Number of valid entries in `outputs`.
     pub output_count: u32,
+    /// Voice activity flag for each entry in `outputs`.
+    pub speaking: [bool; 8],
+    /// Voice activity probability (0-1) for each entry in `outputs`.
+    pub voice_probability: [f32; 8],
+    /// Sample peak with decay for each entry in `outputs`.
+    pub output_peaks: [f32; 8],
+    /// True peak for each entry in `outputs`.
+    pub output_true_peaks: [f32; 8],
+    /// Clip indicator for each entry in `outputs`.
+    pub output_clipped: [bool; 8],
+    /// Master output RMS level.
+    pub master_rms: f32,
+    /// Master output sample peak with decay.
+    pub master_peak: f32,
+    /// Master output true peak.
+    pub master_true_peak: f32,
+    /// Master output clip indicator.
+    pub master_clipped: bool,
+}
+
+impl Default for LoopbackLevels {
+    fn default() -> Self {
+        Self {
+            inputs: [0.0; 8],
+            outputs: [0.0; 8],
+            input_count: 0,
+            output_count: 0,
+            speaking: [false; 8],
+            voice_probability: [0.0; 8],
+            output_peaks: [0.0; 8],
+            output_true_peaks: [0.0; 8],
+            output_clipped: [false; 8],
+            master_rms: 0.0,
+            master_peak: 0.0,
+            master_true_peak: 0.0,
+            master_clipped: false,
+        }
+    }
 }
 
 impl Mixer {
@@ -518,54 +1059,449 @@ impl Mixer {
             sources: Vec::new(),
             next_source_id: 1,
             latency_probe: LatencyProbe::new(sample_rate, 440.0, sample_rate as usize / 10),
+            master_history: vec![0.0; max_block_frames * MIX_CHANNELS * 4],
+            master_meter: Meter::new(sample_rate),
+            master_stereo: StereoMeter::new(sample_rate),
+            master_loudness: LoudnessMeter::new(sample_rate),
+            normalizer: LoudnessNormalizer::new(sample_rate),
+            spectrum: None,
+            master_spectrum: SpectrumTap::disabled(sample_rate),
+            recording: None,
+            recording_tap: None,
+            multitrack: None,
+            multitrack_tap: None,
+            commands: CommandQueue::new(),
+            events: EventLog::new(),
+            master_non_finite: false,
+            format_error: false,
         }
     }
 
     /// Register a new source using a locally managed shared ring buffer.
     pub fn add_source(&mut self, capacity_frames: usize) -> (SourceHandle, Arc<SharedRingBuffer>) {
-        let handle = SourceHandle::new(self.next_source_id);
-        self.next_source_id += 1;
         let ring = Arc::new(SharedRingBuffer::new_local(capacity_frames, MIX_CHANNELS));
-        let source = Source::new(handle, ring.clone(), self.max_block_frames);
-        self.sources.push(source);
+        let handle = self.insert_source(ring.clone(), |_| {});
         (handle, ring)
     }
 
     /// Register a source backed by an externally provided shared memory ring.
     pub fn add_external_source(&mut self, ring: Arc<SharedRingBuffer>) -> SourceHandle {
+        self.insert_source(ring, |_| {})
+    }
+
+    /// Build a source on `ring`, let `prepare` attach what it plays, then make it visible to the
+    /// render thread. Nothing may change the source's player, generator or soundboard after that.
+    fn insert_source(
+        &mut self,
+        ring: Arc<SharedRingBuffer>,
+        prepare: impl FnOnce(&mut Source),
+    ) -> SourceHandle {
         let handle = SourceHandle::new(self.next_source_id);
         self.next_source_id += 1;
-        let source = Source::new(handle, ring, self.max_block_frames);
+        let mut source = Box::new(Source::new(
+            handle,
+            ring,
+            self.sample_rate,
+            self.max_block_frames,
+        ));
+        source.spectrum.configure(self.spectrum);
+        prepare(&mut source);
         self.sources.push(source);
         handle
     }
 
+    /// Register a source that plays the WAV or FLAC file at `path`. It starts paused; see
+    /// [`set_file_playing`](Self::set_file_playing).
+    pub fn add_file_source(&mut self, path: impl AsRef<Path>) -> Result<SourceHandle, PlayerError> {
+        let ring = Arc::new(SharedRingBuffer::new_local(
+            FilePlayer::ring_frames(self.sample_rate),
+            MIX_CHANNELS,
+        ));
+        let player = FilePlayer::open(path.as_ref(), ring.clone(), self.sample_rate)?;
+        Ok(self.insert_source(ring, |source| source.player = Some(player)))
+    }
+
+    /// Register a source that synthesises the test signal described by `config`.
+    pub fn add_generator_source(
+        &mut self,
+        config: GeneratorConfig,
+    ) -> Result<SourceHandle, MixerError> {
+        if !config.is_valid() {
+            return Err(MixerError::InvalidGenerator(config));
+        }
+        // The ring is never fed; it only keeps the source's shape uniform.
+        let ring = Arc::new(SharedRingBuffer::new_local(
+            self.max_block_frames.max(1),
+            MIX_CHANNELS,
+        ));
+        let generator = Generator::new(config, self.sample_rate);
+        Ok(self.insert_source(ring, |source| source.generator = Some(generator)))
+    }
+
+    /// Change the signal of a generator source. Tones keep their phase; sweeps and impulse
+    /// trains start over.
+    pub fn set_generator(
+        &mut self,
+        handle: SourceHandle,
+        config: GeneratorConfig,
+    ) -> Result<(), MixerError> {
+        if !config.is_valid() {
+            return Err(MixerError::InvalidGenerator(config));
+        }
+        self.source_generator(handle)?.configure(config);
+        Ok(())
+    }
+
+    /// Signal a generator source synthesises.
+    pub fn generator(&self, handle: SourceHandle) -> Result<GeneratorConfig, MixerError> {
+        Ok(self.source_generator(handle)?.config())
+    }
+
+    fn source_generator(&self, handle: SourceHandle) -> Result<&Generator, MixerError> {
+        self.source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?
+            .generator
+            .as_ref()
+            .ok_or(MixerError::NotAGenerator(handle.id))
+    }
+
+    /// Register an empty soundboard source; see [`load_clip`](Self::load_clip).
+    pub fn add_soundboard_source(&mut self) -> SourceHandle {
+        // The ring is never fed; it only keeps the source's shape uniform.
+        let ring = Arc::new(SharedRingBuffer::new_local(
+            self.max_block_frames.max(1),
+            MIX_CHANNELS,
+        ));
+        let soundboard = Soundboard::new(self.sample_rate);
+        self.insert_source(ring, |source| source.soundboard = Some(soundboard))
+    }
+
+    /// Decode the WAV or FLAC file at `path` into memory as clip `clip_id` of a soundboard
+    /// source, replacing any clip already loaded under that id.
+    pub fn load_clip(
+        &mut self,
+        handle: SourceHandle,
+        clip_id: u32,
+        path: impl AsRef<Path>,
+        config: ClipConfig,
+    ) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?
+            .load(clip_id, path.as_ref(), config)
+    }
+
+    /// Remove a clip from a soundboard source, fading out any voices it has sounding.
+    pub fn unload_clip(
+        &mut self,
+        handle: SourceHandle,
+        clip_id: u32,
+    ) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?.unload(clip_id)
+    }
+
+    /// Start a voice of a clip on the next rendered block.
+    pub fn trigger_clip(
+        &mut self,
+        handle: SourceHandle,
+        clip_id: u32,
+    ) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?.trigger(clip_id)
+    }
+
+    /// Fade out every voice of a clip.
+    pub fn stop_clip(&mut self, handle: SourceHandle, clip_id: u32) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?.stop(clip_id)
+    }
+
+    /// Fade out every voice of a soundboard source.
+    pub fn stop_all_clips(&mut self, handle: SourceHandle) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?.stop_all()
+    }
+
+    /// Change the gain, polyphony or choke group of a clip.
+    pub fn set_clip_config(
+        &mut self,
+        handle: SourceHandle,
+        clip_id: u32,
+        config: ClipConfig,
+    ) -> Result<(), SoundboardError> {
+        self.source_soundboard(handle)?.configure(clip_id, config)
+    }
+
+    /// Clips loaded into a soundboard source, ordered by id.
+    pub fn clips(&self, handle: SourceHandle) -> Result<Vec<ClipStatus>, MixerError> {
+        Ok(self.source_soundboard(handle)?.clips())
+    }
+
+    fn source_soundboard(&self, handle: SourceHandle) -> Result<&Soundboard, MixerError> {
+        self.source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?
+            .soundboard
+            .as_ref()
+            .ok_or(MixerError::NotASoundboard(handle.id))
+    }
+
+    /// Remove a source from the mix, stopping its file player if it has one. The render thread
+    /// drops the source at the next block; it is freed on the control side afterwards.
+    pub fn remove_source(&mut self, handle: SourceHandle) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        source
+            .removed
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        let sent = self.send_command(Command::RemoveSource {
+            source_id: handle.id,
+            source: None,
+        });
+        if sent.is_err() {
+            source
+                .removed
+                .store(false, std::sync::atomic::Ordering::Relaxed);
+        }
+        sent
+    }
+
     fn source_mut(&mut self, handle: SourceHandle) -> Option<&mut Source> {
-        self.sources.iter_mut().find(|s| s.handle == handle)
+        self.sources
+            .iter_mut()
+            .find(|s| s.handle == handle && !s.is_removed())
+            .map(|s| &mut **s)
     }
 
     fn source(&self, handle: SourceHandle) -> Option<&Source> {
-        self.sources.iter().find(|s| s.handle == handle)
+        self.live_sources().find(|s| s.handle == handle)
+    }
+
+    /// Sources that have not been removed, for control-side lookups and status.
+    fn live_sources(&self) -> impl Iterator<Item = &Source> {
+        self.sources
+            .iter()
+            .map(|s| &**s)
+            .filter(|s| !s.is_removed())
     }
 
     /// Mix into the provided output buffer. Returns frames rendered.
     pub fn process(&mut self, buffer: &mut AudioBuffer) -> Result<usize, MixerError> {
         if buffer.channels != MIX_CHANNELS as u32 {
+            self.report_format_error(buffer.channels, buffer.frames);
             return Err(MixerError::UnsupportedChannels(buffer.channels));
         }
         let frames = buffer.frames as usize;
         if frames == 0 {
             return Ok(0);
         }
+        if frames > self.max_block_frames {
+            // Sources cannot render more than their preallocated block; they contribute silence.
+            self.report_format_error(buffer.channels, buffer.frames);
+        } else {
+            self.format_error = false;
+        }
         let output = unsafe { std::slice::from_raw_parts_mut(buffer.data, frames * MIX_CHANNELS) };
         output.fill(0.0);
 
+        while let Some(mut command) = self.commands.receive() {
+            self.apply_command(&mut command);
+            self.commands.acknowledge(command);
+        }
+        self.mark_echo_references();
+        for source in &mut self.sources {
+            let missing = source.render(frames);
+            source.report_glitches(missing, &self.events);
+            source.apply_channel_utility();
+        }
+        self.apply_echo_cancellation(frames);
         for source in &mut self.sources {
-            source.mix_into(output, frames);
+            source.suppress_noise();
+            source.apply_fader();
+            source.analyse();
+            source.duck_target = 1.0;
         }
+        self.apply_sidechains(frames);
+        self.apply_automix(frames);
+        for source in &mut self.sources {
+            source.accumulate(output, frames);
+        }
+        let timestamp_ns = if buffer.timestamp_ns == 0
+            && (self.recording_tap.is_some() || self.multitrack_tap.is_some())
+        {
+            monotonic_timestamp_ns()
+        } else {
+            buffer.timestamp_ns
+        };
+        for source in &mut self.sources {
+            source.record_stem(frames, timestamp_ns);
+        }
+        let found = replace_non_finite(output);
+        if found && !self.master_non_finite {
+            self.events
+                .record(MixerEventKind::NonFinite { source_id: None });
+        }
+        self.master_non_finite = found;
+
+        self.normalizer.process(output);
+        self.master_meter.process(output);
+        self.master_stereo.process(output);
+        self.master_loudness.process(output);
+        self.master_spectrum.write(output);
+        if let Some(tap) = self.recording_tap.as_ref() {
+            tap.write(output, timestamp_ns);
+        }
+        if let Some(tap) = self.multitrack_tap.as_ref() {
+            tap.write(output, timestamp_ns);
+        }
+        let history = self.master_history.len().min(output.len());
+        self.master_history[..history].copy_from_slice(&output[..history]);
+        self.master_history[history..].fill(0.0);
         Ok(frames)
     }
 
+    /// Record a buffer the mixer cannot render into, once per run of bad callbacks.
+    fn report_format_error(&mut self, channels: u32, frames: u32) {
+        if !self.format_error {
+            self.events
+                .record(MixerEventKind::FormatError { channels, frames });
+            self.format_error = true;
+        }
+    }
+
+    /// Take the oldest unread event from the mixer's event log.
+    pub fn pop_event(&self) -> Option<MixerEvent> {
+        self.events.pop()
+    }
+
+    /// Totals per event kind since the mixer was created.
+    pub fn event_counts(&self) -> EventCounts {
+        self.events.counts()
+    }
+
+    /// Swap the state carried by `command` with what the render side currently holds.
+    fn apply_command(&mut self, command: &mut Command) {
+        match command {
+            Command::EchoCanceller {
+                source_id,
+                canceller,
+            } => {
+                if let Some(source) = self.sources.iter_mut().find(|s| s.handle.id == *source_id) {
+                    std::mem::swap(&mut source.aec, canceller);
+                    if source.aec.is_none() {
+                        source
+                            .erle_bits
+                            .store(0.0f32.to_bits(), std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
+            }
+            Command::NoiseSuppressor {
+                source_id,
+                suppressor,
+            } => {
+                if let Some(source) = self.sources.iter_mut().find(|s| s.handle.id == *source_id) {
+                    std::mem::swap(&mut source.denoise, suppressor);
+                }
+            }
+            Command::LoudnessMeter { source_id, meter } => {
+                if let Some(source) = self.sources.iter_mut().find(|s| s.handle.id == *source_id) {
+                    std::mem::swap(&mut source.loudness, meter);
+                    // Clear what the previous meter left in the shared readout.
+                    if let Some(meter) = source.loudness.as_mut() {
+                        meter.reset();
+                    }
+                }
+            }
+            Command::RemoveSource { source_id, source } => {
+                if let Some(index) = self.sources.iter().position(|s| s.handle.id == *source_id) {
+                    *source = Some(self.sources.remove(index));
+                }
+            }
+            Command::Recording(tap) => std::mem::swap(&mut self.recording_tap, tap),
+            Command::Multitrack { master, stems } => {
+                std::mem::swap(&mut self.multitrack_tap, master);
+                for (source_id, stem) in stems.iter_mut() {
+                    if let Some(source) =
+                        self.sources.iter_mut().find(|s| s.handle.id == *source_id)
+                    {
+                        std::mem::swap(&mut source.stem, stem);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hand `command` to the render thread, to take effect at the next block.
+    fn send_command(&self, command: Command) -> Result<(), MixerError> {
+        self.commands
+            .send(command)
+            .map_err(|_| MixerError::RenderBacklog)
+    }
+
+    /// Flag the sources some canceller takes its far-end reference from.
+    fn mark_echo_references(&mut self) {
+        for index in 0..self.sources.len() {
+            let reference = EchoReference::Source(self.sources[index].handle);
+            self.sources[index].echo_reference = self.sources.iter().any(|source| {
+                source
+                    .aec
+                    .as_ref()
+                    .is_some_and(|canceller| canceller.reference() == reference)
+            });
+        }
+    }
+
+    /// Remove echo of each canceller's reference from its source's pre-fader block.
+    fn apply_echo_cancellation(&mut self, frames: usize) {
+        for index in 0..self.sources.len() {
+            let Some(mut canceller) = self.sources[index].aec.take() else {
+                continue;
+            };
+            let samples = frames.min(self.sources[index].block_frames) * MIX_CHANNELS;
+            match canceller.reference() {
+                EchoReference::Master => {
+                    let near = &mut self.sources[index];
+                    canceller.process(&mut near.block[..samples], &self.master_history[..samples]);
+                }
+                EchoReference::Source(handle) => {
+                    if let Some(far_index) = self.sources.iter().position(|s| s.handle == handle) {
+                        let (near, far) = if far_index < index {
+                            let (head, tail) = self.sources.split_at_mut(index);
+                            (&mut tail[0], &head[far_index])
+                        } else {
+                            let (head, tail) = self.sources.split_at_mut(far_index);
+                            (&mut head[index], &tail[0])
+                        };
+                        let samples = samples.min(far.block_frames * MIX_CHANNELS);
+                        canceller.process(&mut near.block[..samples], &far.block[..samples]);
+                    }
+                }
+            }
+            let source = &mut self.sources[index];
+            source.erle_bits.store(
+                canceller.erle_db().to_bits(),
+                std::sync::atomic::Ordering::Relaxed,
+            );
+            source.aec = Some(canceller);
+        }
+    }
+
+    /// Run every enabled sidechain key and lower the gain of the sources it targets.
+    fn apply_sidechains(&mut self, frames: usize) {
+        let sample_rate = self.sample_rate;
+        for key_index in 0..self.sources.len() {
+            let (key_gain, targets) = {
+                let key = &mut self.sources[key_index];
+                if !key.ducker.is_enabled() {
+                    continue;
+                }
+                let samples = frames.min(key.block_frames) * MIX_CHANNELS;
+                let gain = key.ducker.process_key(&key.block[..samples], sample_rate);
+                (gain, key.ducker.targets())
+            };
+            for target_id in targets.into_iter().filter(|&id| id != 0) {
+                if let Some(target) = self.sources.iter_mut().find(|s| s.handle.id == target_id) {
+                    target.duck_target = target.duck_target.min(key_gain);
+                }
+            }
+        }
+    }
+
     /// Convenience method to write PCM frames into a source's ring.
     pub fn write_source(
         &mut self,
@@ -576,7 +1512,15 @@ impl Mixer {
         let source = self
             .source(handle)
             .ok_or(MixerError::UnknownSource(handle.id))?;
-        Ok(source.write_from_slice(frames, timestamp_ns))
+        let written = source.write_from_slice(frames, timestamp_ns);
+        let offered = frames.len() / MIX_CHANNELS;
+        if written < offered {
+            self.events.record(MixerEventKind::RingOverflow {
+                source_id: handle.id,
+                dropped_frames: (offered - written) as u32,
+            });
+        }
+        Ok(written)
     }
 
     /// Adjust per-source gain.
@@ -597,73 +1541,591 @@ impl Mixer {
         Ok(())
     }
 
-    /// Configure latency compensation in frames for a source. Positive delays audio, negative advances.
-    pub fn set_latency(&mut self, handle: SourceHandle, frames: i32) -> Result<(), MixerError> {
+    /// Configure polarity inversion, channel swap and mono or single-channel folding of a
+    /// source. Applied right after the source is rendered, before any other processing.
+    pub fn set_channel_utility(
+        &mut self,
+        handle: SourceHandle,
+        utility: ChannelUtility,
+    ) -> Result<(), MixerError> {
         let source = self
             .source(handle)
             .ok_or(MixerError::UnknownSource(handle.id))?;
-        source.set_latency(frames as i64);
+        source.set_channel_utility(utility);
         Ok(())
     }
 
-    /// Provide device clock feedback for drift correction.
-    pub fn submit_clock_feedback(
-        &mut self,
-        handle: SourceHandle,
-        device_timestamp_ns: u64,
-        source_timestamp_ns: u64,
-    ) -> Result<(), MixerError> {
+    /// Current channel utility settings of a source.
+    pub fn channel_utility(&self, handle: SourceHandle) -> Result<ChannelUtility, MixerError> {
+        self.source(handle)
+            .map(|source| source.channel_utility())
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Configure latency compensation in frames for a source. Positive delays audio, negative advances.
+    pub fn set_latency(&mut self, handle: SourceHandle, frames: i32) -> Result<(), MixerError> {
         let source = self
-            .source_mut(handle)
+            .source(handle)
             .ok_or(MixerError::UnknownSource(handle.id))?;
-        source.apply_clock_feedback(device_timestamp_ns, source_timestamp_ns);
+        source.set_latency(frames as i64);
         Ok(())
     }
 
-    /// Fetch the latency probe for testing.
-    pub fn latency_probe(&self) -> &LatencyProbe {
-        &self.latency_probe
-    }
-
-    /// Acquire latency metrics against recorded audio.
-    pub fn measure_latency(&self, recorded: &[f32]) -> LatencyReport {
+    /// Share gain between automix group members in proportion to their weighted power.
+    fn apply_automix(&mut self, frames: usize) {
+        let coefficient = automix_coefficient(frames, self.sample_rate);
+        let mut total = 0.0f32;
+        for source in &mut self.sources {
+            if source.automix.is_member() {
+                let samples = frames.min(source.block_frames) * MIX_CHANNELS;
+                total += source.automix.track(&source.block[..samples], coefficient);
+            }
+        }
+        for source in &mut self.sources {
+            if source.automix.is_member() {
+                source.automix.share(total);
+            } else {
+                source.automix.bypass();
+            }
+        }
+    }
+
+    /// Place exactly the listed sources in the gain-sharing automix group. An empty slice
+    /// disables automixing.
+    pub fn set_automix_group(&mut self, members: &[SourceHandle]) -> Result<(), MixerError> {
+        if let Some(unknown) = members.iter().find(|h| self.source(**h).is_none()) {
+            return Err(MixerError::UnknownSource(unknown.id));
+        }
+        for source in &self.sources {
+            source.automix.set_member(members.contains(&source.handle));
+        }
+        Ok(())
+    }
+
+    /// Set the automix weight of a source. Higher weights claim a larger share of the group
+    /// gain; `1.0` is neutral. Weights below [`dynamics::AUTOMIX_MIN_WEIGHT`] (including zero) are
+    /// raised to it, so a zero-weight channel is all but silent next to weighted ones and a group
+    /// of all-zero weights shares the gain equally. Weights above
+    /// [`dynamics::AUTOMIX_MAX_WEIGHT`] are lowered to it; infinite and NaN weights are rejected.
+    pub fn set_automix_weight(
+        &mut self,
+        handle: SourceHandle,
+        weight: f32,
+    ) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        if !weight.is_finite() {
+            return Err(MixerError::InvalidAutomixWeight(weight));
+        }
+        source.automix.set_weight(weight);
+        Ok(())
+    }
+
+    /// Configure `key` to duck each of `targets` whenever the key's level exceeds the threshold.
+    pub fn set_ducking(
+        &mut self,
+        key: SourceHandle,
+        targets: &[SourceHandle],
+        params: DuckingParams,
+    ) -> Result<(), MixerError> {
+        if targets.len() > MAX_DUCK_TARGETS {
+            return Err(MixerError::TooManyDuckTargets(targets.len()));
+        }
+        let mut target_ids = [0u32; MAX_DUCK_TARGETS];
+        for (slot, target) in targets.iter().enumerate() {
+            if self.source(*target).is_none() {
+                return Err(MixerError::UnknownSource(target.id));
+            }
+            if *target != key {
+                target_ids[slot] = target.id;
+            }
+        }
+        let source = self.source(key).ok_or(MixerError::UnknownSource(key.id))?;
+        source.ducker.configure(&target_ids, params);
+        Ok(())
+    }
+
+    /// Stop `key` from ducking any other source.
+    pub fn clear_ducking(&mut self, key: SourceHandle) -> Result<(), MixerError> {
+        let source = self.source(key).ok_or(MixerError::UnknownSource(key.id))?;
+        source.ducker.clear();
+        Ok(())
+    }
+
+    /// Cancel acoustic echo of `reference` picked up by `handle`, modelling an echo tail of
+    /// `filter_length_ms`. The source is delayed by [`aec::BLOCK_SIZE`] frames while enabled.
+    /// The canceller is built here and takes over at the next render block.
+    pub fn enable_echo_cancellation(
+        &mut self,
+        handle: SourceHandle,
+        reference: EchoReference,
+        filter_length_ms: f32,
+    ) -> Result<(), MixerError> {
+        if let EchoReference::Source(far) = reference {
+            if far == handle {
+                return Err(MixerError::SelfEchoReference(far.id));
+            }
+            if self.source(far).is_none() {
+                return Err(MixerError::UnknownSource(far.id));
+            }
+        }
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        self.send_command(Command::EchoCanceller {
+            source_id: handle.id,
+            canceller: Some(Box::new(EchoCanceller::new(
+                reference,
+                self.sample_rate,
+                filter_length_ms,
+            ))),
+        })?;
+        source
+            .aec_enabled
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Stop cancelling echo on `handle` from the next render block.
+    pub fn disable_echo_cancellation(&mut self, handle: SourceHandle) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        self.send_command(Command::EchoCanceller {
+            source_id: handle.id,
+            canceller: None,
+        })?;
+        source
+            .aec_enabled
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Enable spectral noise suppression on `handle`, or update its strength (0-1) when already
+    /// enabled. The source is delayed by [`denoise::latency_frames`] while enabled. A new
+    /// suppressor is built here and takes over at the next render block.
+    pub fn enable_noise_suppression(
+        &mut self,
+        handle: SourceHandle,
+        strength: f32,
+    ) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        if !source
+            .denoise_enabled
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            self.send_command(Command::NoiseSuppressor {
+                source_id: handle.id,
+                suppressor: Some(Box::new(NoiseSuppressor::new(self.sample_rate))),
+            })?;
+            source
+                .denoise_enabled
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+        source.denoise_strength_bits.store(
+            strength.clamp(0.0, 1.0).to_bits(),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    /// Stop suppressing noise on `handle` from the next render block.
+    pub fn disable_noise_suppression(&mut self, handle: SourceHandle) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        self.send_command(Command::NoiseSuppressor {
+            source_id: handle.id,
+            suppressor: None,
+        })?;
+        source
+            .denoise_enabled
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Provide device clock feedback for drift correction.
+    pub fn submit_clock_feedback(
+        &mut self,
+        handle: SourceHandle,
+        device_timestamp_ns: u64,
+        source_timestamp_ns: u64,
+    ) -> Result<(), MixerError> {
+        let source = self
+            .source_mut(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        source.apply_clock_feedback(device_timestamp_ns, source_timestamp_ns);
+        Ok(())
+    }
+
+    /// Latest level readings of the master output.
+    pub fn master_levels(&self) -> MeterReading {
+        self.master_meter.reading()
+    }
+
+    /// Latest post-fader level readings of a source.
+    pub fn source_levels(&self, handle: SourceHandle) -> Result<MeterReading, MixerError> {
+        self.source(handle)
+            .map(|source| source.meter.reading())
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Current clock drift estimate of a source in parts per million, from the feedback given
+    /// to [`submit_clock_feedback`](Self::submit_clock_feedback). Positive when the source
+    /// runs fast.
+    pub fn source_drift_ppm(&self, handle: SourceHandle) -> Result<f32, MixerError> {
+        self.source(handle)
+            .map(|source| source.drift_ppm())
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Latest phase correlation and width of the master output.
+    pub fn master_stereo(&self) -> StereoReading {
+        self.master_stereo.reading()
+    }
+
+    /// Latest post-fader phase correlation and width of a source.
+    pub fn source_stereo(&self, handle: SourceHandle) -> Result<StereoReading, MixerError> {
+        self.source(handle)
+            .map(|source| source.stereo.reading())
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Latest loudness of the master output.
+    pub fn master_loudness(&self) -> LoudnessReading {
+        self.master_loudness.reading()
+    }
+
+    /// Latest loudness of a source, or `None` when its loudness metering is disabled.
+    pub fn source_loudness(
+        &self,
+        handle: SourceHandle,
+    ) -> Result<Option<LoudnessReading>, MixerError> {
+        self.source(handle)
+            .map(Source::loudness)
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Enable or disable loudness metering of a source's post-fader signal from the next render
+    /// block. The master output is always measured.
+    pub fn set_loudness_metering(
+        &mut self,
+        handle: SourceHandle,
+        enabled: bool,
+    ) -> Result<(), MixerError> {
+        let source = self
+            .source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?;
+        if enabled
+            == source
+                .loudness_enabled
+                .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            return Ok(());
+        }
+        let meter = enabled.then(|| {
+            Box::new(LoudnessMeter::with_readout(
+                self.sample_rate,
+                source.loudness_readout.clone(),
+            ))
+        });
+        self.send_command(Command::LoudnessMeter {
+            source_id: handle.id,
+            meter,
+        })?;
+        source
+            .loudness_enabled
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Restart integrated loudness and loudness range on the master and every metered source.
+    /// Takes effect at the next render block.
+    pub fn reset_loudness(&self) {
+        self.master_loudness.request_reset();
+        for source in &self.sources {
+            source.loudness_readout.request_reset();
+        }
+    }
+
+    /// Steer the master output towards `params.target_lufs`, or glide back to unity gain when
+    /// `None`.
+    pub fn set_loudness_normalization(&mut self, params: Option<NormalizationParams>) {
+        match params {
+            Some(params) => self.normalizer.configure(params),
+            None => self.normalizer.disable(),
+        }
+    }
+
+    /// Correction gain currently applied by loudness normalization, in dB.
+    pub fn normalization_gain_db(&self) -> f32 {
+        self.normalizer.gain_db()
+    }
+
+    /// Start recording the master output to a WAV file at `path`, which is created or
+    /// truncated. The file becomes RF64 if it outgrows the 4 GiB WAV limit. Recording starts
+    /// with the next render block.
+    pub fn start_recording(
+        &mut self,
+        path: impl AsRef<Path>,
+        format: SampleFormat,
+    ) -> Result<(), RecorderError> {
+        if self.recording.is_some() {
+            return Err(RecorderError::AlreadyRecording);
+        }
+        let paths = [path.as_ref().to_path_buf()];
+        let recording = Recording::start(&paths, self.sample_rate, format)?;
+        if let Err(error) = self.send_command(Command::Recording(Some(recording.tap(0).clone()))) {
+            let _ = recording.finish();
+            return Err(error.into());
+        }
+        self.recording = Some(recording);
+        Ok(())
+    }
+
+    /// Stop recording, write out everything rendered so far and finalise the file.
+    pub fn stop_recording(&mut self) -> Result<RecordingStatus, RecorderError> {
+        let recording = self.recording.take().ok_or(RecorderError::NotRecording)?;
+        if let Err(error) = self.send_command(Command::Recording(None)) {
+            self.recording = Some(recording);
+            return Err(error.into());
+        }
+        // The render thread lets go of the tap at its next block; anything it writes before
+        // then is not recorded.
+        let mut tracks = recording.finish()?;
+        Ok(tracks.remove(0))
+    }
+
+    /// Progress of the running recording, if any.
+    pub fn recording_status(&self) -> Option<RecordingStatus> {
+        self.recording.as_ref().map(|recording| recording.status(0))
+    }
+
+    /// Start a multitrack session in `directory`, which is created if needed: the master goes to
+    /// [`recorder::MASTER_FILE_NAME`] and each current source's post-fader contribution to the
+    /// mix to [`recorder::stem_file_name`], all sample-aligned. Sources added during the session
+    /// are not recorded. Runs independently of [`start_recording`](Self::start_recording).
+    pub fn start_multitrack(
+        &mut self,
+        directory: impl AsRef<Path>,
+        format: SampleFormat,
+    ) -> Result<(), RecorderError> {
+        self.start_multitrack_named(directory.as_ref(), format, None)
+    }
+
+    fn start_multitrack_named(
+        &mut self,
+        directory: &Path,
+        format: SampleFormat,
+        mic_handle: Option<SourceHandle>,
+    ) -> Result<(), RecorderError> {
+        if self.multitrack.is_some() {
+            return Err(RecorderError::AlreadyRecording);
+        }
+        let sources = self
+            .sources
+            .iter()
+            .map(|source| StemSource {
+                source_id: source.handle.id,
+                name: source_name(source.handle, mic_handle),
+                gain_linear: source.gain_linear(),
+                muted: source.is_muted(),
+            })
+            .collect();
+        let multitrack = Multitrack::start(directory, self.sample_rate, format, sources)?;
+        let command = Command::Multitrack {
+            master: Some(multitrack.master_tap().clone()),
+            stems: multitrack
+                .stem_taps()
+                .map(|(source_id, tap)| (source_id, Some(tap.clone())))
+                .collect(),
+        };
+        if let Err(error) = self.send_command(command) {
+            let _ = multitrack.finish();
+            return Err(error.into());
+        }
+        self.multitrack = Some(multitrack);
+        Ok(())
+    }
+
+    /// Stop the multitrack session, finalise every file and write
+    /// [`recorder::MANIFEST_FILE_NAME`] next to them.
+    pub fn stop_multitrack(&mut self) -> Result<MultitrackStatus, RecorderError> {
+        let multitrack = self.multitrack.take().ok_or(RecorderError::NotRecording)?;
+        let command = Command::Multitrack {
+            master: None,
+            stems: multitrack
+                .stem_taps()
+                .map(|(source_id, _)| (source_id, None))
+                .collect(),
+        };
+        if let Err(error) = self.send_command(command) {
+            self.multitrack = Some(multitrack);
+            return Err(error.into());
+        }
+        // As with a single recording, blocks rendered before the render thread lets go of the
+        // taps are not recorded.
+        multitrack.finish()
+    }
+
+    /// Progress of the running multitrack session, if any.
+    pub fn multitrack_status(&self) -> Option<MultitrackStatus> {
+        self.multitrack.as_ref().map(Multitrack::status)
+    }
+
+    fn file_player(&self, handle: SourceHandle) -> Result<&FilePlayer, MixerError> {
+        self.source(handle)
+            .ok_or(MixerError::UnknownSource(handle.id))?
+            .player
+            .as_ref()
+            .ok_or(MixerError::NotAFilePlayer(handle.id))
+    }
+
+    /// Start or pause a file source. Playing a finished file starts it again from the beginning.
+    pub fn set_file_playing(
+        &mut self,
+        handle: SourceHandle,
+        playing: bool,
+    ) -> Result<(), MixerError> {
+        let player = self.file_player(handle)?;
+        if playing {
+            player.play();
+        } else {
+            player.pause();
+        }
+        Ok(())
+    }
+
+    /// Move a file source to `seconds` into its file, keeping it playing or paused.
+    pub fn seek_file(&mut self, handle: SourceHandle, seconds: f64) -> Result<(), MixerError> {
+        self.file_player(handle)?.seek(seconds);
+        Ok(())
+    }
+
+    /// Make a file source wrap to the start at the end of its file instead of stopping.
+    pub fn set_file_looping(
+        &mut self,
+        handle: SourceHandle,
+        looping: bool,
+    ) -> Result<(), MixerError> {
+        self.file_player(handle)?.set_looping(looping);
+        Ok(())
+    }
+
+    /// Transport state and position of a file source.
+    pub fn file_status(&self, handle: SourceHandle) -> Result<PlayerStatus, MixerError> {
+        Ok(self.file_player(handle)?.status())
+    }
+
+    /// Enable spectrum analysis of the master output and every source's post-fader signal with
+    /// `config`, or disable it with `None`. Sources added later inherit the setting.
+    pub fn set_spectrum(&mut self, config: Option<SpectrumConfig>) {
+        self.spectrum = config;
+        self.master_spectrum.configure(config);
+        for source in &self.sources {
+            source.spectrum.configure(config);
+        }
+    }
+
+    /// Current spectrum analysis settings, or `None` when it is disabled.
+    pub fn spectrum_config(&self) -> Option<SpectrumConfig> {
+        self.spectrum
+    }
+
+    /// Latest band levels of the master output, or `None` when spectrum analysis is disabled.
+    pub fn master_spectrum(&self) -> Option<Spectrum> {
+        let tap = &self.master_spectrum;
+        tap.is_enabled().then(|| tap.spectrum())
+    }
+
+    /// Latest post-fader band levels of a source, or `None` when spectrum analysis is disabled.
+    pub fn source_spectrum(&self, handle: SourceHandle) -> Result<Option<Spectrum>, MixerError> {
+        self.source(handle)
+            .map(|source| {
+                let tap = &source.spectrum;
+                tap.is_enabled().then(|| tap.spectrum())
+            })
+            .ok_or(MixerError::UnknownSource(handle.id))
+    }
+
+    /// Fetch the latency probe for testing.
+    pub fn latency_probe(&self) -> &LatencyProbe {
+        &self.latency_probe
+    }
+
+    /// Acquire latency metrics against recorded audio.
+    pub fn measure_latency(&self, recorded: &[f32]) -> LatencyReport {
         self.latency_probe.measure(recorded)
     }
 
+    fn collect_voice_activity(&self, mic_handle: SourceHandle) -> Vec<VoiceActivity> {
+        self.live_sources()
+            .map(|source| VoiceActivity {
+                id: source.handle.id,
+                name: source_name(source.handle, Some(mic_handle)),
+                speaking: source.vad.is_speaking(),
+                probability: source.vad.probability(),
+            })
+            .collect()
+    }
+
     fn collect_status(&self, mic_handle: SourceHandle) -> (Vec<SourceStatus>, f32, f32) {
         let mut total_fill = 0.0f32;
         let mut total_drift = 0.0f32;
         let mut statuses = Vec::with_capacity(self.sources.len());
 
-        for source in &self.sources {
-            let name = if source.handle == mic_handle {
-                "Microphone".to_string()
-            } else {
-                format!("Source #{}", source.handle.id)
-            };
+        for source in self.live_sources() {
+            let name = source_name(source.handle, Some(mic_handle));
 
             let gain_linear = source.gain_linear();
-            let gain_db = if gain_linear > 0.0 {
-                20.0 * gain_linear.log10()
-            } else {
-                f32::NEG_INFINITY
-            };
+            let gain_db = linear_to_db(gain_linear);
 
             let buffer_fill = source.buffer_fill_ratio().clamp(0.0, 1.0);
             let drift_ppm = source.drift_ppm();
             total_fill += buffer_fill;
             total_drift += drift_ppm.abs();
 
+            let meter = source.meter.reading();
+            let stereo = source.stereo.reading();
             statuses.push(SourceStatus {
                 id: source.handle.id,
                 name,
                 gain_linear,
                 gain_db,
                 muted: source.is_muted(),
+                channel_utility: source.channel_utility(),
                 latency_frames: source.latency_frames(),
                 buffer_fill,
-                rms: source.rms_estimate().clamp(0.0, 1.0),
+                rms: meter.rms,
+                peak: meter.peak,
+                peak_hold: meter.peak_hold,
+                true_peak: meter.true_peak,
+                clipped: meter.clipped,
+                correlation: stereo.correlation,
+                stereo_width: stereo.width,
+                mono_warning: stereo.mono_warning,
+                loudness: source.loudness(),
                 drift_ppm,
+                duck_gain_db: linear_to_db(source.duck_gain_linear()),
+                automix_gain_db: linear_to_db(source.automix.published_gain()),
+                automix_weight: source.automix.weight(),
+                speaking: source.vad.is_speaking(),
+                voice_probability: source.vad.probability(),
+                echo_cancelling: source
+                    .aec_enabled
+                    .load(std::sync::atomic::Ordering::Relaxed),
+                erle_db: f32::from_bits(
+                    source.erle_bits.load(std::sync::atomic::Ordering::Relaxed),
+                ),
+                noise_suppression: source
+                    .denoise_enabled
+                    .load(std::sync::atomic::Ordering::Relaxed)
+                    .then(|| source.denoise_strength()),
+                file: source.player.as_ref().map(FilePlayer::status),
+                generator: source.generator.as_ref().map(Generator::config),
             });
         }
 
@@ -693,10 +2155,24 @@ pub struct LoopbackRenderArgs {
     pub timestamp: *const AudioTimeStamp,
 }
 
+/// What feeds a source registered by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NodeSourceKind {
+    /// Frames pushed through [`LoopbackMixerFfi::push_node_frames`].
+    Pushed,
+    /// A file player's decoder thread.
+    File,
+    /// A generator rendering on the render thread.
+    Generator,
+    /// A soundboard mixing its clips on the render thread.
+    Soundboard,
+}
+
 #[derive(Clone)]
 struct NodeSourceEntry {
     handle: SourceHandle,
     ring: Arc<SharedRingBuffer>,
+    kind: NodeSourceKind,
 }
 
 /// Exposed mixer wrapper bridging the CoreAudio loopback driver with the Rust core engine.
@@ -704,6 +2180,7 @@ pub struct LoopbackMixerFfi {
     mixer: Mixer,
     mic_handle: SourceHandle,
     node_sources: RwLock<HashMap<u32, NodeSourceEntry>>,
+    timer: CallbackTimer,
 }
 
 impl LoopbackMixerFfi {
@@ -715,6 +2192,7 @@ impl LoopbackMixerFfi {
             mixer,
             mic_handle,
             node_sources: RwLock::new(HashMap::new()),
+            timer: CallbackTimer::new(),
         })
     }
 
@@ -739,20 +2217,27 @@ impl LoopbackMixerFfi {
         if args.frame_count == 0 {
             return Ok(());
         }
+        let frames = args.frame_count;
         let buffer_list = unsafe { args.buffer_list.as_mut().ok_or(MixerError::NullMixer)? };
         if buffer_list.mNumberBuffers == 0 {
+            self.mixer.report_format_error(0, frames);
             return Err(MixerError::UnsupportedChannels(0));
         }
         let buffer = unsafe { &mut *buffer_list.mBuffers.as_mut_ptr() };
         if buffer.mNumberChannels != MIX_CHANNELS as u32 {
+            self.mixer
+                .report_format_error(buffer.mNumberChannels, frames);
             return Err(MixerError::UnsupportedChannels(buffer.mNumberChannels));
         }
-        if buffer.mData.is_null() {
+        let samples = frames as usize * MIX_CHANNELS;
+        if buffer.mData.is_null()
+            || (buffer.mDataByteSize as usize) < samples * std::mem::size_of::<f32>()
+        {
+            self.mixer
+                .report_format_error(buffer.mNumberChannels, frames);
             return Err(MixerError::NullMixer);
         }
 
-        let frames = args.frame_count;
-        let samples = frames as usize * MIX_CHANNELS;
         let slice = unsafe { slice::from_raw_parts_mut(buffer.mData as *mut f32, samples) };
         let timestamp_ns = self.timestamp_ns(args.timestamp);
         let mut audio_buffer = AudioBuffer {
@@ -781,17 +2266,115 @@ impl LoopbackMixerFfi {
             return true;
         }
         let (handle, ring) = self.mixer.add_source(capacity_frames);
-        let entry = NodeSourceEntry { handle, ring };
+        let entry = NodeSourceEntry {
+            handle,
+            ring,
+            kind: NodeSourceKind::Pushed,
+        };
         self.node_sources.write().insert(source_index, entry);
         true
     }
 
+    /// Whether `source_index` can take a new source: index 0 is the microphone.
+    fn index_is_free(&self, source_index: u32) -> bool {
+        source_index != 0 && !self.node_sources.read().contains_key(&source_index)
+    }
+
+    fn insert_node_source(&self, source_index: u32, handle: SourceHandle, kind: NodeSourceKind) {
+        let ring = self
+            .mixer
+            .source(handle)
+            .map(|source| source.ring.clone())
+            .expect("source was just added");
+        let entry = NodeSourceEntry { handle, ring, kind };
+        self.node_sources.write().insert(source_index, entry);
+    }
+
+    /// Remove the source at `source_index` if it is fed by `kind`.
+    fn remove_node_source(&mut self, source_index: u32, kind: NodeSourceKind) -> bool {
+        let mut sources = self.node_sources.write();
+        match sources.get(&source_index) {
+            Some(entry) if entry.kind == kind => {
+                let removed = self.mixer.remove_source(entry.handle).is_ok();
+                if removed {
+                    sources.remove(&source_index);
+                }
+                removed
+            }
+            _ => false,
+        }
+    }
+
+    fn node_handle(&self, source_index: u32, kind: NodeSourceKind) -> Option<SourceHandle> {
+        self.node_entry(source_index)
+            .filter(|entry| entry.kind == kind)
+            .map(|entry| entry.handle)
+    }
+
+    fn load_file(&mut self, source_index: u32, path: &Path) -> Result<(), PlayerError> {
+        if !self.index_is_free(source_index) {
+            return Err(PlayerError::SourceInUse(source_index));
+        }
+        let handle = self.mixer.add_file_source(path)?;
+        self.insert_node_source(source_index, handle, NodeSourceKind::File);
+        Ok(())
+    }
+
+    fn unload_file(&mut self, source_index: u32) -> bool {
+        self.remove_node_source(source_index, NodeSourceKind::File)
+    }
+
+    fn file_handle(&self, source_index: u32) -> Option<SourceHandle> {
+        self.node_handle(source_index, NodeSourceKind::File)
+    }
+
+    fn add_generator(
+        &mut self,
+        source_index: u32,
+        config: GeneratorConfig,
+    ) -> Result<(), MixerError> {
+        if !self.index_is_free(source_index) {
+            return Err(MixerError::SourceInUse(source_index));
+        }
+        let handle = self.mixer.add_generator_source(config)?;
+        self.insert_node_source(source_index, handle, NodeSourceKind::Generator);
+        Ok(())
+    }
+
+    fn remove_generator(&mut self, source_index: u32) -> bool {
+        self.remove_node_source(source_index, NodeSourceKind::Generator)
+    }
+
+    fn generator_handle(&self, source_index: u32) -> Option<SourceHandle> {
+        self.node_handle(source_index, NodeSourceKind::Generator)
+    }
+
+    /// The soundboard at [`SOUNDBOARD_SOURCE`], added on first use.
+    fn soundboard(&mut self) -> Result<SourceHandle, MixerError> {
+        if let Some(handle) = self.soundboard_handle() {
+            return Ok(handle);
+        }
+        if !self.index_is_free(SOUNDBOARD_SOURCE) {
+            return Err(MixerError::SourceInUse(SOUNDBOARD_SOURCE));
+        }
+        let handle = self.mixer.add_soundboard_source();
+        self.insert_node_source(SOUNDBOARD_SOURCE, handle, NodeSourceKind::Soundboard);
+        Ok(handle)
+    }
+
+    fn soundboard_handle(&self) -> Option<SourceHandle> {
+        self.node_handle(SOUNDBOARD_SOURCE, NodeSourceKind::Soundboard)
+    }
+
     fn node_entry(&self, source_index: u32) -> Option<NodeSourceEntry> {
         self.node_sources.read().get(&source_index).cloned()
     }
 
     fn push_node_frames(&self, source_index: u32, data: &[f32], timestamp_ns: u64) -> bool {
-        let Some(entry) = self.node_entry(source_index) else {
+        let Some(entry) = self
+            .node_entry(source_index)
+            .filter(|entry| entry.kind == NodeSourceKind::Pushed)
+        else {
             return false;
         };
         if data.is_empty() {
@@ -804,6 +2387,10 @@ impl LoopbackMixerFfi {
         let written = entry.ring.push(data, Some(timestamp_ns));
         if written < frames {
             let drop_frames = frames - written;
+            self.mixer.events.record(MixerEventKind::RingOverflow {
+                source_id: entry.handle.id,
+                dropped_frames: drop_frames as u32,
+            });
             entry.ring.discard(drop_frames);
             let start = written * MIX_CHANNELS;
             let _ = entry.ring.push(&data[start..], Some(timestamp_ns));
@@ -811,25 +2398,135 @@ impl LoopbackMixerFfi {
         true
     }
 
-    fn set_gain(&mut self, source_index: u32, gain: f32) -> bool {
+    fn handle_for(&self, source_index: u32) -> Option<SourceHandle> {
         if source_index == 0 {
-            let _ = self.mixer.set_gain(self.mic_handle, gain);
-            return true;
-        }
-        if let Some(entry) = self.node_entry(source_index) {
-            let _ = self.mixer.set_gain(entry.handle, gain);
-            true
+            Some(self.mic_handle)
         } else {
-            false
+            self.node_entry(source_index).map(|entry| entry.handle)
         }
     }
 
-    fn set_mute(&mut self, source_index: u32, mute: bool) -> bool {
-        if source_index == 0 {
-            let _ = self.mixer.set_mute(self.mic_handle, mute);
-            return true;
-        }
-        if let Some(entry) = self.node_entry(source_index) {
+    fn set_ducking(
+        &mut self,
+        key_index: u32,
+        target_indices: &[u32],
+        params: DuckingParams,
+    ) -> bool {
+        let Some(key) = self.handle_for(key_index) else {
+            return false;
+        };
+        let mut targets = Vec::with_capacity(target_indices.len());
+        for index in target_indices {
+            match self.handle_for(*index) {
+                Some(handle) => targets.push(handle),
+                None => return false,
+            }
+        }
+        self.mixer.set_ducking(key, &targets, params).is_ok()
+    }
+
+    fn clear_ducking(&mut self, key_index: u32) -> bool {
+        match self.handle_for(key_index) {
+            Some(key) => self.mixer.clear_ducking(key).is_ok(),
+            None => false,
+        }
+    }
+
+    fn set_automix_group(&mut self, member_indices: &[u32]) -> bool {
+        let mut members = Vec::with_capacity(member_indices.len());
+        for index in member_indices {
+            match self.handle_for(*index) {
+                Some(handle) => members.push(handle),
+                None => return false,
+            }
+        }
+        self.mixer.set_automix_group(&members).is_ok()
+    }
+
+    fn set_automix_weight(&mut self, source_index: u32, weight: f32) -> bool {
+        match self.handle_for(source_index) {
+            Some(handle) => self.mixer.set_automix_weight(handle, weight).is_ok(),
+            None => false,
+        }
+    }
+
+    fn enable_echo_cancellation(
+        &mut self,
+        source_index: u32,
+        reference_index: u32,
+        filter_length_ms: f32,
+    ) -> bool {
+        let reference = if reference_index == ECHO_REFERENCE_MASTER {
+            EchoReference::Master
+        } else {
+            match self.handle_for(reference_index) {
+                Some(handle) => EchoReference::Source(handle),
+                None => return false,
+            }
+        };
+        match self.handle_for(source_index) {
+            Some(handle) => self
+                .mixer
+                .enable_echo_cancellation(handle, reference, filter_length_ms)
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    fn disable_echo_cancellation(&mut self, source_index: u32) -> bool {
+        match self.handle_for(source_index) {
+            Some(handle) => self.mixer.disable_echo_cancellation(handle).is_ok(),
+            None => false,
+        }
+    }
+
+    fn set_noise_suppression(&mut self, source_index: u32, enabled: bool, strength: f32) -> bool {
+        let Some(handle) = self.handle_for(source_index) else {
+            return false;
+        };
+        if enabled {
+            self.mixer
+                .enable_noise_suppression(handle, strength)
+                .is_ok()
+        } else {
+            self.mixer.disable_noise_suppression(handle).is_ok()
+        }
+    }
+
+    fn set_loudness_metering(&mut self, source_index: u32, enabled: bool) -> bool {
+        match self.handle_for(source_index) {
+            Some(handle) => self.mixer.set_loudness_metering(handle, enabled).is_ok(),
+            None => false,
+        }
+    }
+
+    fn spectrum(&self, source_index: u32) -> Option<Spectrum> {
+        if source_index == LOOPBACK_SPECTRUM_MASTER {
+            return self.mixer.master_spectrum();
+        }
+        let handle = self.handle_for(source_index)?;
+        self.mixer.source_spectrum(handle).ok().flatten()
+    }
+
+    fn set_gain(&mut self, source_index: u32, gain: f32) -> bool {
+        if source_index == 0 {
+            let _ = self.mixer.set_gain(self.mic_handle, gain);
+            return true;
+        }
+        if let Some(entry) = self.node_entry(source_index) {
+            let _ = self.mixer.set_gain(entry.handle, gain);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set_mute(&mut self, source_index: u32, mute: bool) -> bool {
+        if source_index == 0 {
+            let _ = self.mixer.set_mute(self.mic_handle, mute);
+            return true;
+        }
+        if let Some(entry) = self.node_entry(source_index) {
             let _ = self.mixer.set_mute(entry.handle, mute);
             true
         } else {
@@ -837,8 +2534,29 @@ impl LoopbackMixerFfi {
         }
     }
 
+    fn set_channel_utility(&mut self, source_index: u32, utility: ChannelUtility) -> bool {
+        match self.handle_for(source_index) {
+            Some(handle) => self.mixer.set_channel_utility(handle, utility).is_ok(),
+            None => false,
+        }
+    }
+
+    fn start_multitrack(
+        &mut self,
+        directory: &Path,
+        format: SampleFormat,
+    ) -> Result<(), RecorderError> {
+        self.mixer
+            .start_multitrack_named(directory, format, Some(self.mic_handle))
+    }
+
+    fn voice_activity(&self) -> Vec<VoiceActivity> {
+        self.mixer.collect_voice_activity(self.mic_handle)
+    }
+
     fn status(&self) -> MixerStatus {
         let (sources, avg_fill, avg_drift) = self.mixer.collect_status(self.mic_handle);
+        let stereo = self.mixer.master_stereo.reading();
         let sample_rate = self.mixer.sample_rate;
         let buffer_frames = self.mixer.max_block_frames;
         let latency_ms = if sample_rate == 0 {
@@ -851,9 +2569,19 @@ impl LoopbackMixerFfi {
             sample_rate,
             buffer_frames,
             latency_ms,
-            cpu_usage: 0.0,
+            cpu_usage: self.timer.smoothed_load(),
+            timing: self.timer.timing(),
+            events: self.mixer.event_counts(),
             buffer_fill: avg_fill,
             drift_ppm: avg_drift,
+            master: self.mixer.master_meter.reading(),
+            stereo,
+            mono_warning: stereo.mono_warning || sources.iter().any(|s| s.mono_warning),
+            loudness: self.mixer.master_loudness.reading(),
+            normalization: self.mixer.normalizer.params(),
+            normalization_gain_db: self.mixer.normalizer.gain_db(),
+            recording: self.mixer.recording_status(),
+            multitrack: self.mixer.multitrack_status(),
             sources,
         }
     }
@@ -863,239 +2591,1247 @@ static LOOPBACK_GLOBAL: AtomicPtr<LoopbackMixerFfi> = AtomicPtr::new(ptr::null_m
 static SOURCE_ENABLE_STATE: Lazy<Mutex<HashMap<u32, bool>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Create a new mixer instance.
-#[unsafe(no_mangle)]
-pub extern "C" fn device_kit_mixer_new(sample_rate: u32, max_block_frames: u32) -> *mut Mixer {
-    Box::into_raw(Box::new(Mixer::new(sample_rate, max_block_frames as usize)))
+/// Create a new mixer instance.
+#[unsafe(no_mangle)]
+pub extern "C" fn device_kit_mixer_new(sample_rate: u32, max_block_frames: u32) -> *mut Mixer {
+    Box::into_raw(Box::new(Mixer::new(sample_rate, max_block_frames as usize)))
+}
+
+/// Create a loopback mixer handle suitable for DriverKit.
+#[unsafe(no_mangle)]
+pub extern "C" fn loopback_mixer_create(
+    sample_rate: f64,
+    max_frames: u32,
+) -> *mut LoopbackMixerFfi {
+    init_logging();
+    let Some(mixer) = LoopbackMixerFfi::new(sample_rate, max_frames) else {
+        return ptr::null_mut();
+    };
+    log::record(
+        LogLevel::Info,
+        LogCode::MixerCreated,
+        &[mixer.mixer.sample_rate as f64, max_frames as f64],
+    );
+    let raw = Box::into_raw(Box::new(mixer));
+    LOOPBACK_GLOBAL.store(raw, Ordering::SeqCst);
+    raw
+}
+
+/// Free an allocated mixer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn device_kit_mixer_free(ptr: *mut Mixer) {
+    if !ptr.is_null() {
+        unsafe {
+            drop(Box::from_raw(ptr));
+        }
+    }
+}
+
+/// Destroy a loopback mixer handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_destroy(handle: *mut LoopbackMixerFfi) {
+    if !handle.is_null() {
+        unsafe {
+            let stored = LOOPBACK_GLOBAL.load(Ordering::SeqCst);
+            if stored == handle {
+                LOOPBACK_GLOBAL.store(ptr::null_mut(), Ordering::SeqCst);
+            }
+            drop(Box::from_raw(handle));
+        }
+    }
+}
+
+/// Process a render quantum for the loopback device.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_process(
+    handle: *mut LoopbackMixerFfi,
+    args: *const LoopbackRenderArgs,
+) -> OSStatus {
+    if handle.is_null() || args.is_null() {
+        return kAudioHardwareUnspecifiedError.try_into().unwrap();
+    }
+    let (result, frames) = unsafe {
+        let mixer = &mut *handle;
+        let args = &*args;
+        let frames = args.frame_count;
+        let started = monotonic_timestamp_ns();
+        let result = mixer.process(args);
+        let elapsed = monotonic_timestamp_ns().saturating_sub(started);
+        let load = mixer.timer.record(elapsed, frames, mixer.mixer.sample_rate);
+        if load > 1.0 {
+            mixer
+                .mixer
+                .events
+                .record(MixerEventKind::CallbackOverrun { load });
+        }
+        (result, frames)
+    };
+    let channels = match &result {
+        Err(MixerError::UnsupportedChannels(channels)) => *channels,
+        _ => MIX_CHANNELS as u32,
+    };
+    let status = translate_status(result);
+    if status == 0 {
+        log::record(LogLevel::Trace, LogCode::RenderOk, &[frames as f64]);
+    } else {
+        log::record(
+            LogLevel::Error,
+            LogCode::RenderFailed,
+            &[frames as f64, status as f64, channels as f64],
+        );
+    }
+    status
+}
+
+fn translate_status(result: Result<(), MixerError>) -> OSStatus {
+    match result {
+        Ok(()) => 0,
+        Err(_) => kAudioHardwareUnspecifiedError.try_into().unwrap(),
+    }
+}
+
+/// Submit microphone input frames into the loopback mixer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_submit_input(
+    handle: *mut LoopbackMixerFfi,
+    data: *const f32,
+    frames: u32,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.submit_input(data, frames);
+    }
+}
+
+/// Adjust per-source gain on the loopback mixer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_gain(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    gain: f32,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let _ = mixer.set_gain(source_index, gain);
+    }
+}
+
+/// Adjust per-source mute state on the loopback mixer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_mute(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    mute: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let _ = mixer.set_mute(source_index, mute);
+    }
+}
+
+/// Register a node-managed source that can be fed from NodeJS.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_register_node_source(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    capacity_frames: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.register_node_source(source_index, capacity_frames as usize)
+    }
+}
+
+/// Push PCM frames supplied by NodeJS into the async ring buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_push_node_frames(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    data: *const f32,
+    frames: u32,
+    timestamp_ns: u64,
+) -> bool {
+    if handle.is_null() || data.is_null() || frames == 0 {
+        return false;
+    }
+    unsafe {
+        let mixer = &*handle;
+        let samples = frames as usize * MIX_CHANNELS;
+        let slice = slice::from_raw_parts(data, samples);
+        mixer.push_node_frames(source_index, slice, timestamp_ns)
+    }
+}
+
+/// Update gain for a NodeJS-driven source.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_node_gain(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    gain: f32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_gain(source_index, gain)
+    }
+}
+
+/// Update mute state for a NodeJS-driven source.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_node_mute(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    mute: bool,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_mute(source_index, mute)
+    }
+}
+
+/// Start recording the master output to the UTF-8 `path` in `format`, one of the
+/// `LOOPBACK_RECORD_FORMAT_*` codes. Fails if a recording is already running or the file cannot
+/// be created.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_start_recording(
+    handle: *mut LoopbackMixerFfi,
+    path: *const c_char,
+    format: u32,
+) -> bool {
+    if handle.is_null() || path.is_null() {
+        return false;
+    }
+    let Some(format) = SampleFormat::from_code(format) else {
+        return false;
+    };
+    unsafe {
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            return false;
+        };
+        let mixer = &mut *handle;
+        mixer.mixer.start_recording(path, format).is_ok()
+    }
+}
+
+/// Stop the master recording and finalise its file. When `out_frames` is not null it receives
+/// the number of frames written.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_stop_recording(
+    handle: *mut LoopbackMixerFfi,
+    out_frames: *mut u64,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let Ok(status) = mixer.mixer.stop_recording() else {
+            return false;
+        };
+        if !out_frames.is_null() {
+            *out_frames = status.frames;
+        }
+    }
+    true
+}
+
+/// Start a multitrack session in the UTF-8 `directory`, recording the master and every current
+/// source's post-fader signal to separate files in `format`, one of the
+/// `LOOPBACK_RECORD_FORMAT_*` codes. Fails if a session is already running or the files cannot
+/// be created.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_start_multitrack(
+    handle: *mut LoopbackMixerFfi,
+    directory: *const c_char,
+    format: u32,
+) -> bool {
+    if handle.is_null() || directory.is_null() {
+        return false;
+    }
+    let Some(format) = SampleFormat::from_code(format) else {
+        return false;
+    };
+    unsafe {
+        let Ok(directory) = CStr::from_ptr(directory).to_str() else {
+            return false;
+        };
+        let mixer = &mut *handle;
+        mixer.start_multitrack(Path::new(directory), format).is_ok()
+    }
+}
+
+/// Stop the multitrack session, finalise its files and write the session manifest. When
+/// `out_frames` is not null it receives the number of frames written per track.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_stop_multitrack(
+    handle: *mut LoopbackMixerFfi,
+    out_frames: *mut u64,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let Ok(status) = mixer.mixer.stop_multitrack() else {
+            return false;
+        };
+        if !out_frames.is_null() {
+            *out_frames = status.master.frames;
+        }
+    }
+    true
+}
+
+/// Load the WAV or FLAC file at the UTF-8 `path` as a new, paused source at `source_index`, which
+/// must not be in use. The source takes gain, mute and processing settings like any other.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_load_file(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    path: *const c_char,
+) -> bool {
+    if handle.is_null() || path.is_null() {
+        return false;
+    }
+    unsafe {
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            return false;
+        };
+        let mixer = &mut *handle;
+        mixer.load_file(source_index, Path::new(path)).is_ok()
+    }
+}
+
+/// Stop and remove the file source at `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_unload_file(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.unload_file(source_index)
+    }
+}
+
+/// Play or pause the file source at `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_file_playing(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    playing: bool,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        match mixer.file_handle(source_index) {
+            Some(source) => mixer.mixer.set_file_playing(source, playing).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Move the file source at `source_index` to `seconds` into its file.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_seek_file(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    seconds: f64,
+) -> bool {
+    if handle.is_null() || !seconds.is_finite() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        match mixer.file_handle(source_index) {
+            Some(source) => mixer.mixer.seek_file(source, seconds).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Make the file source at `source_index` loop, or stop at the end of its file.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_file_looping(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    looping: bool,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        match mixer.file_handle(source_index) {
+            Some(source) => mixer.mixer.set_file_looping(source, looping).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Copy the transport state of the file source at `source_index` into `out`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_get_file_status(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    out: *mut LoopbackFileStatus,
+) -> bool {
+    if handle.is_null() || out.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &*handle;
+        let Some(status) = mixer
+            .file_handle(source_index)
+            .and_then(|source| mixer.mixer.file_status(source).ok())
+        else {
+            return false;
+        };
+        *out = LoopbackFileStatus::from(&status);
+    }
+    true
+}
+
+/// Add a generator source at `source_index`, which must not be in use, synthesising the signal
+/// described by `config`. Unknown kinds and invalid values are rejected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_add_generator(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    config: LoopbackGenerator,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(config) = GeneratorConfig::from_ffi(&config) else {
+        return false;
+    };
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.add_generator(source_index, config).is_ok()
+    }
+}
+
+/// Change the signal of the generator source at `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_generator(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    config: LoopbackGenerator,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(config) = GeneratorConfig::from_ffi(&config) else {
+        return false;
+    };
+    unsafe {
+        let mixer = &mut *handle;
+        match mixer.generator_handle(source_index) {
+            Some(source) => mixer.mixer.set_generator(source, config).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Copy the settings of the generator source at `source_index` into `out`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_get_generator(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    out: *mut LoopbackGenerator,
+) -> bool {
+    if handle.is_null() || out.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &*handle;
+        let Some(config) = mixer
+            .generator_handle(source_index)
+            .and_then(|source| mixer.mixer.generator(source).ok())
+        else {
+            return false;
+        };
+        *out = config.to_ffi();
+    }
+    true
+}
+
+/// Remove the generator source at `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_remove_generator(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.remove_generator(source_index)
+    }
+}
+
+/// Configure polarity inversion, channel swap and channel folding of `source_index`. `mode` is
+/// one of the `LOOPBACK_CHANNEL_MODE_*` codes; unknown codes are rejected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_channel_utility(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    invert_left: bool,
+    invert_right: bool,
+    swap_channels: bool,
+    mode: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    let Some(mode) = ChannelMode::from_code(mode) else {
+        return false;
+    };
+    let utility = ChannelUtility {
+        invert_left,
+        invert_right,
+        swap_channels,
+        mode,
+    };
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_channel_utility(source_index, utility)
+    }
+}
+
+/// Configure sidechain ducking so `key_index` attenuates each source listed in `targets`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_ducking(
+    handle: *mut LoopbackMixerFfi,
+    key_index: u32,
+    targets: *const u32,
+    target_count: u32,
+    params: DuckingParams,
+) -> bool {
+    if handle.is_null() || (targets.is_null() && target_count > 0) {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let targets = if target_count == 0 {
+            &[][..]
+        } else {
+            slice::from_raw_parts(targets, target_count as usize)
+        };
+        mixer.set_ducking(key_index, targets, params)
+    }
+}
+
+/// Remove sidechain ducking configured on `key_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_clear_ducking(
+    handle: *mut LoopbackMixerFfi,
+    key_index: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.clear_ducking(key_index)
+    }
+}
+
+/// Replace the automix group with the listed sources. Passing no sources disables automixing.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_automix_group(
+    handle: *mut LoopbackMixerFfi,
+    members: *const u32,
+    member_count: u32,
+) -> bool {
+    if handle.is_null() || (members.is_null() && member_count > 0) {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        let members = if member_count == 0 {
+            &[][..]
+        } else {
+            slice::from_raw_parts(members, member_count as usize)
+        };
+        mixer.set_automix_group(members)
+    }
+}
+
+/// Set the automix weight for a source on the loopback mixer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_automix_weight(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    weight: f32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_automix_weight(source_index, weight)
+    }
+}
+
+/// Cancel echo of `reference_index` (or the master output when it is
+/// [`ECHO_REFERENCE_MASTER`]) picked up by `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_enable_echo_cancellation(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    reference_index: u32,
+    filter_length_ms: f32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.enable_echo_cancellation(source_index, reference_index, filter_length_ms)
+    }
+}
+
+/// Disable echo cancellation on `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_disable_echo_cancellation(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.disable_echo_cancellation(source_index)
+    }
+}
+
+/// Enable noise suppression on `source_index` with `strength` (0-1), or disable it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_noise_suppression(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    enabled: bool,
+    strength: f32,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_noise_suppression(source_index, enabled, strength)
+    }
+}
+
+/// Enable or disable loudness metering on `source_index`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_loudness_metering(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    enabled: bool,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.set_loudness_metering(source_index, enabled)
+    }
+}
+
+/// Restart integrated loudness and loudness range measurement.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_reset_loudness(handle: *mut LoopbackMixerFfi) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &*handle;
+        mixer.mixer.reset_loudness();
+    }
+    true
+}
+
+/// Copy up to `capacity` of the oldest unread events into `out`, returning how many were
+/// written.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_pop_events(
+    handle: *mut LoopbackMixerFfi,
+    out: *mut LoopbackEvent,
+    capacity: u32,
+) -> u32 {
+    if handle.is_null() || out.is_null() {
+        return 0;
+    }
+    unsafe {
+        let mixer = &*handle;
+        let out = slice::from_raw_parts_mut(out, capacity as usize);
+        let mut written = 0;
+        while written < out.len() {
+            let Some(event) = mixer.mixer.pop_event() else {
+                break;
+            };
+            out[written] = event.into();
+            written += 1;
+        }
+        written as u32
+    }
+}
+
+/// Clear the callback timing statistics reported in the mixer status.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_reset_timing(handle: *mut LoopbackMixerFfi) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &*handle;
+        mixer.timer.request_reset();
+    }
+    true
+}
+
+/// Enable loudness normalization of the master output with `params`, or disable it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_loudness_normalization(
+    handle: *mut LoopbackMixerFfi,
+    enabled: bool,
+    params: NormalizationParams,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer
+            .mixer
+            .set_loudness_normalization(enabled.then_some(params));
+    }
+    true
+}
+
+/// Enable spectrum analysis of the master and every source with `config`, or disable it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_set_spectrum(
+    handle: *mut LoopbackMixerFfi,
+    enabled: bool,
+    config: SpectrumConfig,
+) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        let mixer = &mut *handle;
+        mixer.mixer.set_spectrum(enabled.then_some(config));
+    }
+    true
+}
+
+/// Copy up to `capacity` band levels (dBFS) of `source_index`, or of the master output when it
+/// is `LOOPBACK_SPECTRUM_MASTER`, into `levels_db`, and the matching band centres into
+/// `frequencies_hz` unless it is null. Returns the number of bands written, or 0 when spectrum
+/// analysis is disabled or the source is unknown.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn loopback_mixer_get_spectrum(
+    handle: *mut LoopbackMixerFfi,
+    source_index: u32,
+    levels_db: *mut f32,
+    frequencies_hz: *mut f32,
+    capacity: u32,
+) -> u32 {
+    if handle.is_null() || levels_db.is_null() {
+        return 0;
+    }
+    unsafe {
+        let mixer = &*handle;
+        let Some(spectrum) = mixer.spectrum(source_index) else {
+            return 0;
+        };
+        let count = spectrum.levels_db.len().min(capacity as usize);
+        slice::from_raw_parts_mut(levels_db, count).copy_from_slice(&spectrum.levels_db[..count]);
+        if !frequencies_hz.is_null() {
+            slice::from_raw_parts_mut(frequencies_hz, count)
+                .copy_from_slice(&spectrum.frequencies_hz[..count]);
+        }
+        count as u32
+    }
+}
+
+/// Fetch the currently active loopback mixer handle, if any.
+#[unsafe(no_mangle)]
+pub extern "C" fn loopback_mixer_global_handle() -> *mut LoopbackMixerFfi {
+    LOOPBACK_GLOBAL.load(Ordering::SeqCst)
+}
+
+/// Retrieve the current mixer status if a mixer is active.
+pub fn get_mixer_status() -> Option<MixerStatus> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
+    }
+    unsafe { Some((&*handle).status()) }
+}
+
+/// Set source gain expressed in decibels. Returns `false` if no mixer is active.
+pub fn set_source_gain_db(source_id: u32, gain_db: f32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    let amplitude = if gain_db <= -120.0 {
+        0.0
+    } else {
+        10f32.powf(gain_db / 20.0)
+    };
+    unsafe {
+        loopback_mixer_set_gain(handle, source_id, amplitude);
+    }
+    true
+}
+
+/// Set the mute state of a mixer source. Returns `false` if no mixer is active.
+pub fn set_source_mute(source_id: u32, muted: bool) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        loopback_mixer_set_mute(handle, source_id, muted);
+    }
+    true
+}
+
+/// Configure sidechain ducking on the global mixer. Returns `false` if no mixer is active or a
+/// source is unknown.
+pub fn set_source_ducking(key_id: u32, targets: &[u32], params: DuckingParams) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    let Ok(count) = u32::try_from(targets.len()) else {
+        return false;
+    };
+    unsafe { loopback_mixer_set_ducking(handle, key_id, targets.as_ptr(), count, params) }
+}
+
+/// Remove sidechain ducking from a source on the global mixer.
+pub fn clear_source_ducking(key_id: u32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { loopback_mixer_clear_ducking(handle, key_id) }
+}
+
+/// Replace the automix group on the global mixer. Returns `false` if no mixer is active or a
+/// source is unknown.
+pub fn set_automix_sources(source_ids: &[u32]) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    let Ok(count) = u32::try_from(source_ids.len()) else {
+        return false;
+    };
+    unsafe { loopback_mixer_set_automix_group(handle, source_ids.as_ptr(), count) }
+}
+
+/// Set a source's automix weight on the global mixer.
+pub fn set_source_automix_weight(source_id: u32, weight: f32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { loopback_mixer_set_automix_weight(handle, source_id, weight) }
+}
+
+/// Enable echo cancellation on the global mixer. `reference_id` of `None` uses the master output
+/// as the far-end reference.
+pub fn enable_source_echo_cancellation(
+    source_id: u32,
+    reference_id: Option<u32>,
+    filter_length_ms: f32,
+) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    let reference = reference_id.unwrap_or(ECHO_REFERENCE_MASTER);
+    unsafe {
+        loopback_mixer_enable_echo_cancellation(handle, source_id, reference, filter_length_ms)
+    }
+}
+
+/// Disable echo cancellation on a source of the global mixer.
+pub fn disable_source_echo_cancellation(source_id: u32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { loopback_mixer_disable_echo_cancellation(handle, source_id) }
+}
+
+/// Configure noise suppression on the global mixer. `None` disables it, otherwise the value is
+/// the suppression strength (0-1).
+pub fn set_source_noise_suppression(source_id: u32, strength: Option<f32>) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe {
+        loopback_mixer_set_noise_suppression(
+            handle,
+            source_id,
+            strength.is_some(),
+            strength.unwrap_or(0.0),
+        )
+    }
+}
+
+/// Enable or disable loudness metering on a source of the global mixer.
+pub fn set_source_loudness_metering(source_id: u32, enabled: bool) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { loopback_mixer_set_loudness_metering(handle, source_id, enabled) }
+}
+
+/// Restart loudness measurement on the global mixer. Returns `false` if no mixer is active.
+pub fn reset_loudness_measurement() -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { loopback_mixer_reset_loudness(handle) }
 }
 
-/// Create a loopback mixer handle suitable for DriverKit.
-#[unsafe(no_mangle)]
-pub extern "C" fn loopback_mixer_create(
-    sample_rate: f64,
-    max_frames: u32,
-) -> *mut LoopbackMixerFfi {
-    init_tracing();
-    let Some(mixer) = LoopbackMixerFfi::new(sample_rate, max_frames) else {
-        return ptr::null_mut();
-    };
-    let raw = Box::into_raw(Box::new(mixer));
-    LOOPBACK_GLOBAL.store(raw, Ordering::SeqCst);
-    raw
+/// Voice activity of every source on the global mixer, without the cost of a full
+/// [`MixerStatus`]. Returns `None` if no mixer is active.
+pub fn get_voice_activity() -> Option<Vec<VoiceActivity>> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
+    }
+    unsafe { Some((&*handle).voice_activity()) }
 }
 
-/// Free an allocated mixer.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn device_kit_mixer_free(ptr: *mut Mixer) {
-    if !ptr.is_null() {
-        unsafe {
-            drop(Box::from_raw(ptr));
-        }
+/// Drain unread dropout and glitch events from the global mixer, oldest first. Returns `None`
+/// if no mixer is active.
+pub fn drain_mixer_events() -> Option<Vec<MixerEvent>> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
     }
+    let mixer = unsafe { &*handle };
+    Some(std::iter::from_fn(|| mixer.mixer.pop_event()).collect())
 }
 
-/// Destroy a loopback mixer handle.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_destroy(handle: *mut LoopbackMixerFfi) {
-    if !handle.is_null() {
-        unsafe {
-            let stored = LOOPBACK_GLOBAL.load(Ordering::SeqCst);
-            if stored == handle {
-                LOOPBACK_GLOBAL.store(ptr::null_mut(), Ordering::SeqCst);
-            }
-            drop(Box::from_raw(handle));
-        }
+/// Clear callback timing statistics on the global mixer. Returns `false` if no mixer is active.
+pub fn reset_callback_timing() -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
     }
+    unsafe { loopback_mixer_reset_timing(handle) }
 }
 
-/// Process a render quantum for the loopback device.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_process(
-    handle: *mut LoopbackMixerFfi,
-    args: *const LoopbackRenderArgs,
-) -> OSStatus {
-    if handle.is_null() || args.is_null() {
-        return kAudioHardwareUnspecifiedError.try_into().unwrap();
+/// Configure loudness normalization on the global mixer. Returns `false` if no mixer is active.
+pub fn set_master_normalization(params: Option<NormalizationParams>) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
     }
-    let (result, frames) = unsafe {
-        let mixer = &mut *handle;
-        let args = &*args;
-        let frames = args.frame_count;
-        (mixer.process(args), frames)
-    };
-    debug!(frames = frames, "process_audio");
-    match &result {
-        Ok(_) => push_log(format!("process_audio ok frames={frames}")),
-        Err(err) => push_log(format!("process_audio error frames={frames}: {err}")),
+    unsafe {
+        loopback_mixer_set_loudness_normalization(
+            handle,
+            params.is_some(),
+            params.unwrap_or_default(),
+        )
     }
-    translate_status(result)
 }
 
-fn translate_status(result: Result<(), MixerError>) -> OSStatus {
-    match result {
-        Ok(()) => 0,
-        Err(_) => kAudioHardwareUnspecifiedError.try_into().unwrap(),
+/// Start recording the master output of the global mixer to `path`.
+pub fn start_master_recording(path: &Path, format: SampleFormat) -> Result<(), RecorderError> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return Err(RecorderError::NoMixer);
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.mixer.start_recording(path, format)
 }
 
-/// Submit microphone input frames into the loopback mixer.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_submit_input(
-    handle: *mut LoopbackMixerFfi,
-    data: *const f32,
-    frames: u32,
-) {
+/// Stop recording the master output of the global mixer and finalise the file.
+pub fn stop_master_recording() -> Result<RecordingStatus, RecorderError> {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
-        return;
-    }
-    unsafe {
-        let mixer = &mut *handle;
-        mixer.submit_input(data, frames);
+        return Err(RecorderError::NoMixer);
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.mixer.stop_recording()
 }
 
-/// Adjust per-source gain on the loopback mixer.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_set_gain(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    gain: f32,
-) {
+/// Start a multitrack session of the global mixer in `directory`.
+pub fn start_mixer_multitrack(directory: &Path, format: SampleFormat) -> Result<(), RecorderError> {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
-        return;
+        return Err(RecorderError::NoMixer);
     }
-    unsafe {
-        let mixer = &mut *handle;
-        let _ = mixer.set_gain(source_index, gain);
+    let mixer = unsafe { &mut *handle };
+    mixer.start_multitrack(directory, format)
+}
+
+/// Stop the multitrack session of the global mixer and write its manifest.
+pub fn stop_mixer_multitrack() -> Result<MultitrackStatus, RecorderError> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return Err(RecorderError::NoMixer);
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.mixer.stop_multitrack()
 }
 
-/// Adjust per-source mute state on the loopback mixer.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_set_mute(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    mute: bool,
-) {
+/// Load a file as a paused source of the global mixer at `source_id`.
+pub fn load_source_file(source_id: u32, path: &Path) -> Result<(), PlayerError> {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
-        return;
+        return Err(PlayerError::NoMixer);
     }
-    unsafe {
-        let mixer = &mut *handle;
-        let _ = mixer.set_mute(source_index, mute);
+    let mixer = unsafe { &mut *handle };
+    mixer.load_file(source_id, path)
+}
+
+/// Remove the file source `source_id` from the global mixer.
+pub fn unload_source_file(source_id: u32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.unload_file(source_id)
 }
 
-/// Register a node-managed source that can be fed from NodeJS.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_register_node_source(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    capacity_frames: u32,
+/// Apply `control` to the player of file source `source_id` of the global mixer.
+fn with_source_file(
+    source_id: u32,
+    control: impl FnOnce(&mut Mixer, SourceHandle) -> Result<(), MixerError>,
 ) -> bool {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
         return false;
     }
-    unsafe {
-        let mixer = &mut *handle;
-        mixer.register_node_source(source_index, capacity_frames as usize)
+    let mixer = unsafe { &mut *handle };
+    match mixer.file_handle(source_id) {
+        Some(source) => control(&mut mixer.mixer, source).is_ok(),
+        None => false,
     }
 }
 
-/// Push PCM frames supplied by NodeJS into the async ring buffer.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_push_node_frames(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    data: *const f32,
-    frames: u32,
-    timestamp_ns: u64,
-) -> bool {
-    if handle.is_null() || data.is_null() || frames == 0 {
-        return false;
+/// Play or pause file source `source_id` of the global mixer.
+pub fn set_source_file_playing(source_id: u32, playing: bool) -> bool {
+    with_source_file(source_id, |mixer, source| {
+        mixer.set_file_playing(source, playing)
+    })
+}
+
+/// Seek file source `source_id` of the global mixer to `seconds`.
+pub fn seek_source_file(source_id: u32, seconds: f64) -> bool {
+    seconds.is_finite()
+        && with_source_file(source_id, |mixer, source| mixer.seek_file(source, seconds))
+}
+
+/// Make file source `source_id` of the global mixer loop or stop at the end.
+pub fn set_source_file_looping(source_id: u32, looping: bool) -> bool {
+    with_source_file(source_id, |mixer, source| {
+        mixer.set_file_looping(source, looping)
+    })
+}
+
+/// Transport state of file source `source_id` of the global mixer.
+pub fn get_source_file_status(source_id: u32) -> Option<PlayerStatus> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
     }
-    unsafe {
-        let mixer = &*handle;
-        let samples = frames as usize * MIX_CHANNELS;
-        let slice = slice::from_raw_parts(data, samples);
-        mixer.push_node_frames(source_index, slice, timestamp_ns)
+    let mixer = unsafe { &*handle };
+    let source = mixer.file_handle(source_id)?;
+    mixer.mixer.file_status(source).ok()
+}
+
+/// Add a generator source to the global mixer at `source_id`.
+pub fn add_source_generator(source_id: u32, config: GeneratorConfig) -> Result<(), MixerError> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return Err(MixerError::NullMixer);
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.add_generator(source_id, config)
 }
 
-/// Update gain for a NodeJS-driven source.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_set_node_gain(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    gain: f32,
-) -> bool {
+/// Change the signal of generator source `source_id` of the global mixer.
+pub fn set_source_generator(source_id: u32, config: GeneratorConfig) -> bool {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
         return false;
     }
-    unsafe {
-        let mixer = &mut *handle;
-        mixer.set_gain(source_index, gain)
+    let mixer = unsafe { &mut *handle };
+    match mixer.generator_handle(source_id) {
+        Some(source) => mixer.mixer.set_generator(source, config).is_ok(),
+        None => false,
     }
 }
 
-/// Update mute state for a NodeJS-driven source.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn loopback_mixer_set_node_mute(
-    handle: *mut LoopbackMixerFfi,
-    source_index: u32,
-    mute: bool,
-) -> bool {
+/// Settings of generator source `source_id` of the global mixer.
+pub fn get_source_generator(source_id: u32) -> Option<GeneratorConfig> {
+    let handle = loopback_mixer_global_handle();
     if handle.is_null() {
-        return false;
+        return None;
     }
-    unsafe {
-        let mixer = &mut *handle;
-        mixer.set_mute(source_index, mute)
+    let mixer = unsafe { &*handle };
+    let source = mixer.generator_handle(source_id)?;
+    mixer.mixer.generator(source).ok()
+}
+
+/// Remove generator source `source_id` from the global mixer.
+pub fn remove_source_generator(source_id: u32) -> bool {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return false;
     }
+    let mixer = unsafe { &mut *handle };
+    mixer.remove_generator(source_id)
 }
 
-/// Fetch the currently active loopback mixer handle, if any.
-#[unsafe(no_mangle)]
-pub extern "C" fn loopback_mixer_global_handle() -> *mut LoopbackMixerFfi {
-    LOOPBACK_GLOBAL.load(Ordering::SeqCst)
+/// Run `f` against the global mixer and its soundboard, if both exist.
+fn with_global_soundboard<T>(f: impl FnOnce(&mut Mixer, SourceHandle) -> T) -> Option<T> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
+    }
+    let mixer = unsafe { &mut *handle };
+    let source = mixer.soundboard_handle()?;
+    Some(f(&mut mixer.mixer, source))
 }
 
-/// Retrieve the current mixer status if a mixer is active.
-pub fn get_mixer_status() -> Option<MixerStatus> {
+/// Preload a clip into the global mixer's soundboard, adding the soundboard at
+/// [`SOUNDBOARD_SOURCE`] on first use.
+pub fn load_soundboard_clip(
+    clip_id: u32,
+    path: &Path,
+    config: ClipConfig,
+) -> Result<(), SoundboardError> {
     let handle = loopback_mixer_global_handle();
     if handle.is_null() {
+        return Err(SoundboardError::NoMixer);
+    }
+    let mixer = unsafe { &mut *handle };
+    let source = mixer.soundboard()?;
+    mixer.mixer.load_clip(source, clip_id, path, config)
+}
+
+/// Remove a clip from the global mixer's soundboard.
+pub fn unload_soundboard_clip(clip_id: u32) -> bool {
+    with_global_soundboard(|mixer, source| mixer.unload_clip(source, clip_id).is_ok())
+        .unwrap_or(false)
+}
+
+/// Trigger a clip of the global mixer's soundboard.
+pub fn trigger_soundboard_clip(clip_id: u32) -> bool {
+    with_global_soundboard(|mixer, source| mixer.trigger_clip(source, clip_id).is_ok())
+        .unwrap_or(false)
+}
+
+/// Fade out a clip of the global mixer's soundboard.
+pub fn stop_soundboard_clip(clip_id: u32) -> bool {
+    with_global_soundboard(|mixer, source| mixer.stop_clip(source, clip_id).is_ok())
+        .unwrap_or(false)
+}
+
+/// Fade out every clip of the global mixer's soundboard.
+pub fn stop_soundboard() -> bool {
+    with_global_soundboard(|mixer, source| mixer.stop_all_clips(source).is_ok()).unwrap_or(false)
+}
+
+/// Change how a clip of the global mixer's soundboard plays.
+pub fn set_soundboard_clip_config(clip_id: u32, config: ClipConfig) -> bool {
+    with_global_soundboard(|mixer, source| mixer.set_clip_config(source, clip_id, config).is_ok())
+        .unwrap_or(false)
+}
+
+/// Clips loaded into the global mixer's soundboard; empty before the first load and `None`
+/// without a mixer.
+pub fn get_soundboard_clips() -> Option<Vec<ClipStatus>> {
+    if loopback_mixer_global_handle().is_null() {
         return None;
     }
-    unsafe { Some((&*handle).status()) }
+    Some(
+        with_global_soundboard(|mixer, source| mixer.clips(source).unwrap_or_default())
+            .unwrap_or_default(),
+    )
 }
 
-/// Set source gain expressed in decibels. Returns `false` if no mixer is active.
-pub fn set_source_gain_db(source_id: u32, gain_db: f32) -> bool {
+/// Configure the channel utility stage of a source of the global mixer.
+pub fn set_source_channel_utility(source_id: u32, utility: ChannelUtility) -> bool {
     let handle = loopback_mixer_global_handle();
     if handle.is_null() {
         return false;
     }
-    let amplitude = if gain_db <= -120.0 {
-        0.0
-    } else {
-        10f32.powf(gain_db / 20.0)
-    };
     unsafe {
-        loopback_mixer_set_gain(handle, source_id, amplitude);
+        loopback_mixer_set_channel_utility(
+            handle,
+            source_id,
+            utility.invert_left,
+            utility.invert_right,
+            utility.swap_channels,
+            utility.mode.code(),
+        )
     }
-    true
 }
 
-/// Set the mute state of a mixer source. Returns `false` if no mixer is active.
-pub fn set_source_mute(source_id: u32, muted: bool) -> bool {
+/// Configure spectrum analysis on the global mixer. Returns `false` if no mixer is active.
+pub fn set_mixer_spectrum(config: Option<SpectrumConfig>) -> bool {
     let handle = loopback_mixer_global_handle();
     if handle.is_null() {
         return false;
     }
-    unsafe {
-        loopback_mixer_set_mute(handle, source_id, muted);
+    unsafe { loopback_mixer_set_spectrum(handle, config.is_some(), config.unwrap_or_default()) }
+}
+
+/// Latest band levels of a source of the global mixer, or of its master output when
+/// `source_id` is `None`. Returns `None` if no mixer is active, spectrum analysis is disabled or
+/// the source is unknown.
+pub fn get_mixer_spectrum(source_id: Option<u32>) -> Option<Spectrum> {
+    let handle = loopback_mixer_global_handle();
+    if handle.is_null() {
+        return None;
     }
-    true
+    let mixer = unsafe { &*handle };
+    mixer.spectrum(source_id.unwrap_or(LOOPBACK_SPECTRUM_MASTER))
 }
 
 #[unsafe(no_mangle)]
@@ -1104,18 +3840,22 @@ pub extern "C" fn device_kit_get_levels(levels_out: *mut LoopbackLevels) -> bool
     if levels_out.is_null() {
         return false;
     }
-    let mut levels = LoopbackLevels {
-        inputs: [0.0; 8],
-        outputs: [0.0; 8],
-        input_count: 0,
-        output_count: 0,
-    };
+    let mut levels = LoopbackLevels::default();
 
     if let Some(status) = get_mixer_status() {
         for (idx, src) in status.sources.iter().enumerate().take(8) {
             levels.outputs[idx] = src.rms;
+            levels.output_peaks[idx] = src.peak;
+            levels.output_true_peaks[idx] = src.true_peak;
+            levels.output_clipped[idx] = src.clipped;
+            levels.speaking[idx] = src.speaking;
+            levels.voice_probability[idx] = src.voice_probability;
         }
         levels.output_count = status.sources.len().min(8) as u32;
+        levels.master_rms = status.master.rms;
+        levels.master_peak = status.master.peak;
+        levels.master_true_peak = status.master.true_peak;
+        levels.master_clipped = status.master.clipped;
     } else {
         unsafe {
             *levels_out = levels;
@@ -1154,12 +3894,12 @@ pub extern "C" fn device_kit_latency_ms() -> f64 {
 }
 
 #[unsafe(no_mangle)]
-/// Start the loopback driver, initialising tracing on first activation.
+/// Start the loopback driver, initialising the log on first activation.
 pub extern "C" fn device_kit_start_driver() -> bool {
-    init_tracing();
+    init_logging();
     let was_running = DRIVER_RUNNING.swap(true, Ordering::SeqCst);
     if !was_running {
-        push_log("driver started");
+        log::record(LogLevel::Info, LogCode::DriverStarted, &[]);
     }
     true
 }
@@ -1168,7 +3908,7 @@ pub extern "C" fn device_kit_start_driver() -> bool {
 /// Stop the loopback driver and record the transition.
 pub extern "C" fn device_kit_stop_driver() {
     if DRIVER_RUNNING.swap(false, Ordering::SeqCst) {
-        push_log("driver stopped");
+        log::record(LogLevel::Info, LogCode::DriverStopped, &[]);
     }
 }
 
@@ -1177,7 +3917,7 @@ pub extern "C" fn device_kit_stop_driver() {
 pub extern "C" fn device_kit_start_engine() -> bool {
     let was_running = ENGINE_RUNNING.swap(true, Ordering::SeqCst);
     if !was_running {
-        push_log("engine started");
+        log::record(LogLevel::Info, LogCode::EngineStarted, &[]);
     }
     true
 }
@@ -1186,7 +3926,7 @@ pub extern "C" fn device_kit_start_engine() -> bool {
 /// Stop the loopback engine and record the transition.
 pub extern "C" fn device_kit_stop_engine() {
     if ENGINE_RUNNING.swap(false, Ordering::SeqCst) {
-        push_log("engine stopped");
+        log::record(LogLevel::Info, LogCode::EngineStopped, &[]);
     }
 }
 
@@ -1216,17 +3956,58 @@ pub extern "C" fn device_kit_set_source_enabled(source_index: u32, enabled: bool
 }
 
 #[unsafe(no_mangle)]
-/// Pop the next log entry produced by the mixer. Returns `NULL` when no logs remain.
+/// Pop the next log entry produced by the mixer, formatted. Returns `NULL` when no logs remain.
+///
+/// The returned string is only valid until the next call from any thread; prefer
+/// [`device_kit_pop_log_into`] or [`device_kit_pop_log_record`].
 pub extern "C" fn device_kit_pop_log() -> *const c_char {
-    if let Some(message) = LOG_BUFFER.lock().pop_front() {
+    if let Some(record) = log::pop() {
         let mut cache = LOG_CACHE.lock();
-        *cache = Some(CString::new(message).unwrap_or_default());
+        *cache = Some(CString::new(record.to_string()).unwrap_or_default());
         cache.as_ref().map(|c| c.as_ptr()).unwrap_or(ptr::null())
     } else {
         ptr::null()
     }
 }
 
+/// Pop the next log entry and copy it, formatted and NUL-terminated, into `buffer`.
+///
+/// Returns the length of the full message excluding the terminator, or `0` when no logs remain
+/// (or `buffer` is null). A return value of `capacity` or more means the message was truncated;
+/// the entry is consumed either way.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn device_kit_pop_log_into(buffer: *mut c_char, capacity: usize) -> usize {
+    if buffer.is_null() || capacity == 0 {
+        return 0;
+    }
+    let Some(record) = log::pop() else {
+        return 0;
+    };
+    let message = record.to_string();
+    let copied = message.len().min(capacity - 1);
+    unsafe {
+        let out = slice::from_raw_parts_mut(buffer as *mut u8, capacity);
+        out[..copied].copy_from_slice(&message.as_bytes()[..copied]);
+        out[copied] = 0;
+    }
+    message.len()
+}
+
+/// Pop the next structured log entry into `out`. Returns `false` when no logs remain.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn device_kit_pop_log_record(out: *mut LogRecord) -> bool {
+    if out.is_null() {
+        return false;
+    }
+    match log::pop() {
+        Some(record) => {
+            unsafe { out.write(record) };
+            true
+        }
+        None => false,
+    }
+}
+
 /// Register a NodeJS source via the global mixer handle.
 pub fn node_register_source(source_index: u32, capacity_frames: u32) -> bool {
     let handle = loopback_mixer_global_handle();
@@ -1278,6 +4059,21 @@ pub fn node_set_mute(source_index: u32, mute: bool) -> bool {
     unsafe { loopback_mixer_set_node_mute(handle, source_index, mute) }
 }
 
+/// Configure sidechain ducking for a NodeJS-managed source on the global mixer.
+pub fn node_set_ducking(source_index: u32, targets: &[u32], params: DuckingParams) -> bool {
+    set_source_ducking(source_index, targets, params)
+}
+
+/// Configure the channel utility stage of a NodeJS-managed source on the global mixer.
+pub fn node_set_channel_utility(source_index: u32, utility: ChannelUtility) -> bool {
+    set_source_channel_utility(source_index, utility)
+}
+
+/// Remove sidechain ducking from a NodeJS-managed source on the global mixer.
+pub fn node_clear_ducking(source_index: u32) -> bool {
+    clear_source_ducking(source_index)
+}
+
 /// Add a new local ring buffer backed source and return its handle.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn device_kit_mixer_add_source(
@@ -0,0 +1,185 @@
+//! Structured, real-time safe diagnostic log.
+//!
+//! Writers, including the render callback, push fixed-size [`LogRecord`]s (level, code, up to
+//! [`MAX_LOG_ARGS`] numeric arguments and a host timestamp) into a preallocated lock-free queue.
+//! Nothing is formatted or allocated on the writing side: records become text only when a
+//! consumer asks for it through [`LogRecord`]'s `Display` implementation or
+//! [`device_kit_pop_log_into`](crate::device_kit_pop_log_into). Records above the level set with
+//! [`set_level`] are discarded before they reach the queue, and records that arrive while the
+//! queue is full are counted as dropped.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use once_cell::sync::Lazy;
+
+use crate::queue::BoundedQueue;
+use crate::ring::monotonic_timestamp_ns;
+
+/// Number of records the log holds before new ones are dropped.
+pub const LOG_CAPACITY: usize = 256;
+/// Numeric arguments carried by each record.
+pub const MAX_LOG_ARGS: usize = 4;
+
+/// Severity of a record; lower values are more severe.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Something failed.
+    Error = 1,
+    /// Something unexpected that the mixer recovered from.
+    Warn = 2,
+    /// Lifecycle transitions.
+    Info = 3,
+    /// Per-operation detail.
+    Debug = 4,
+    /// Per-callback detail.
+    Trace = 5,
+}
+
+impl LogLevel {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Error,
+            2 => Self::Warn,
+            3 => Self::Info,
+            4 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+
+    /// Upper-case label used when formatting records.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+/// What a record describes; determines how its arguments are formatted.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogCode {
+    /// The loopback driver started.
+    DriverStarted = 1,
+    /// The loopback driver stopped.
+    DriverStopped = 2,
+    /// The loopback engine started.
+    EngineStarted = 3,
+    /// The loopback engine stopped.
+    EngineStopped = 4,
+    /// A mixer was created. Args: sample rate, maximum block frames.
+    MixerCreated = 5,
+    /// A render callback completed. Args: frames.
+    RenderOk = 6,
+    /// A render callback failed. Args: frames, returned OSStatus, buffer channel count.
+    RenderFailed = 7,
+}
+
+/// One log entry. The layout is shared with C as `DeviceKitLogRecord`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogRecord {
+    /// Host time at which the record was written, in nanoseconds.
+    pub timestamp_ns: u64,
+    /// Severity.
+    pub level: LogLevel,
+    /// What happened.
+    pub code: LogCode,
+    /// Numeric arguments; their meaning depends on `code`, unused ones are zero.
+    pub args: [f64; MAX_LOG_ARGS],
+}
+
+impl LogRecord {
+    /// Format the record's message without the level prefix.
+    pub fn message(&self) -> impl fmt::Display + '_ {
+        Message(self)
+    }
+}
+
+struct Message<'a>(&'a LogRecord);
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = &self.0.args;
+        match self.0.code {
+            LogCode::DriverStarted => f.write_str("driver started"),
+            LogCode::DriverStopped => f.write_str("driver stopped"),
+            LogCode::EngineStarted => f.write_str("engine started"),
+            LogCode::EngineStopped => f.write_str("engine stopped"),
+            LogCode::MixerCreated => write!(
+                f,
+                "mixer created sample_rate={} max_frames={}",
+                args[0], args[1]
+            ),
+            LogCode::RenderOk => write!(f, "process_audio ok frames={}", args[0]),
+            LogCode::RenderFailed => write!(
+                f,
+                "process_audio error frames={} status={} channels={}",
+                args[0], args[1], args[2]
+            ),
+        }
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.level.label(), self.message())
+    }
+}
+
+static LOG: Lazy<BoundedQueue<LogRecord>> = Lazy::new(|| BoundedQueue::new(LOG_CAPACITY));
+static LEVEL: AtomicU32 = AtomicU32::new(LogLevel::Info as u32);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Allocate the log queue so the first write from the render thread does not have to.
+pub(crate) fn init() {
+    Lazy::force(&LOG);
+}
+
+/// Keep records at `level` and more severe, discarding the rest at the call site.
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u32, Ordering::Relaxed);
+}
+
+/// Current level threshold.
+pub fn level() -> LogLevel {
+    LogLevel::from_u32(LEVEL.load(Ordering::Relaxed))
+}
+
+/// Whether a record at `level` would be kept.
+pub fn enabled(level: LogLevel) -> bool {
+    level as u32 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Write a record. Never blocks, allocates (once [`init`] has run) or formats.
+pub(crate) fn record(level: LogLevel, code: LogCode, args: &[f64]) {
+    if !enabled(level) {
+        return;
+    }
+    let mut record = LogRecord {
+        timestamp_ns: monotonic_timestamp_ns(),
+        level,
+        code,
+        args: [0.0; MAX_LOG_ARGS],
+    };
+    let count = args.len().min(MAX_LOG_ARGS);
+    record.args[..count].copy_from_slice(&args[..count]);
+    if LOG.push(record).is_err() {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Take the oldest unread record.
+pub fn pop() -> Option<LogRecord> {
+    LOG.pop()
+}
+
+/// Records lost because the queue was full.
+pub fn dropped() -> u64 {
+    DROPPED.load(Ordering::Relaxed)
+}
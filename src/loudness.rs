@@ -0,0 +1,517 @@
+//! EBU R128 loudness metering following ITU-R BS.1770-4 and EBU Tech 3341/3342.
+//!
+//! Audio is K-weighted (high-shelf plus high-pass biquads) and its mean square accumulated in
+//! 100 ms sub-blocks. Momentary loudness uses the last 400 ms, short-term the last 3 s. Every
+//! momentary block feeds a fixed-size histogram for gated integrated loudness, and every
+//! short-term value feeds a second histogram for loudness range, so measurement can run for hours
+//! on the render thread without allocating. Bins keep their summed energy, so only the gate
+//! thresholds are quantised to [`HISTOGRAM_STEP_LU`].
+//!
+//! [`LoudnessNormalizer`] builds on the meter: it measures the master before its own gain and
+//! slowly steers that gain so short-term loudness approaches a target, within configured limits.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Absolute gate applied to both integrated loudness and loudness range.
+pub const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+/// Relative gate for integrated loudness, below the ungated mean.
+pub const INTEGRATED_RELATIVE_GATE_LU: f32 = -10.0;
+/// Relative gate for loudness range, below the mean short-term loudness.
+pub const RANGE_RELATIVE_GATE_LU: f32 = -20.0;
+/// Width of each histogram bin.
+pub const HISTOGRAM_STEP_LU: f32 = 0.05;
+
+const HISTOGRAM_MAX_LUFS: f32 = 10.0;
+const HISTOGRAM_BINS: usize =
+    ((HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP_LU) as usize;
+const MOMENTARY_SUB_BLOCKS: usize = 4;
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+const RANGE_LOW_PERCENTILE: f64 = 0.10;
+const RANGE_HIGH_PERCENTILE: f64 = 0.95;
+
+/// Latest loudness values. Loudness is in LUFS and range in LU; values are negative infinity
+/// until enough audio has been measured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoudnessReading {
+    /// Momentary loudness over the last 400 ms.
+    pub momentary: f32,
+    /// Short-term loudness over the last 3 s.
+    pub short_term: f32,
+    /// Gated integrated loudness since the last reset.
+    pub integrated: f32,
+    /// Loudness range (LRA) since the last reset.
+    pub range: f32,
+}
+
+impl Default for LoudnessReading {
+    fn default() -> Self {
+        Self {
+            momentary: f32::NEG_INFINITY,
+            short_term: f32::NEG_INFINITY,
+            integrated: f32::NEG_INFINITY,
+            range: f32::NEG_INFINITY,
+        }
+    }
+}
+
+/// Direct form I biquad.
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// K-weighting filter pair for one channel, designed for any sample rate from the BS.1770
+/// analogue prototypes.
+#[derive(Clone, Copy)]
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: f64) -> Self {
+        let k = (PI * 1_681.974_450_955_533 / sample_rate).tan();
+        let q = 0.707_175_236_955_419_6;
+        let vh = 10f64.powf(3.999_843_853_973_347 / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            ..Biquad::default()
+        };
+
+        let k = (PI * 38.135_470_876_024_44 / sample_rate).tan();
+        let q = 0.500_327_037_323_877_3;
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            ..Biquad::default()
+        };
+        Self { shelf, high_pass }
+    }
+
+    fn process(&mut self, sample: f32) -> f64 {
+        self.high_pass.process(self.shelf.process(sample as f64))
+    }
+}
+
+/// Block energies bucketed by loudness.
+struct Histogram {
+    counts: Vec<u64>,
+    energies: Vec<f64>,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; HISTOGRAM_BINS],
+            energies: vec![0.0; HISTOGRAM_BINS],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.counts.fill(0);
+        self.energies.fill(0.0);
+    }
+
+    /// Record a block; blocks under the absolute gate are dropped.
+    fn add(&mut self, energy: f64) {
+        let loudness = energy_to_lufs(energy);
+        if loudness <= ABSOLUTE_GATE_LUFS as f64 {
+            return;
+        }
+        let bin = bin_for(loudness);
+        self.counts[bin] += 1;
+        self.energies[bin] += energy;
+    }
+
+    /// Mean energy of all recorded blocks at or above `gate_lufs`, with their count.
+    fn gated_mean(&self, gate_lufs: f64) -> (f64, u64) {
+        let first = if gate_lufs.is_finite() {
+            bin_for(gate_lufs)
+        } else {
+            0
+        };
+        let mut energy = 0.0;
+        let mut count = 0;
+        for bin in first..HISTOGRAM_BINS {
+            energy += self.energies[bin];
+            count += self.counts[bin];
+        }
+        if count == 0 {
+            (0.0, 0)
+        } else {
+            (energy / count as f64, count)
+        }
+    }
+
+    fn integrated(&self) -> f32 {
+        let (mean, count) = self.gated_mean(f64::NEG_INFINITY);
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        let gate = energy_to_lufs(mean) + INTEGRATED_RELATIVE_GATE_LU as f64;
+        let (mean, count) = self.gated_mean(gate);
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        energy_to_lufs(mean) as f32
+    }
+
+    fn range(&self) -> f32 {
+        let (mean, count) = self.gated_mean(f64::NEG_INFINITY);
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        let first = bin_for(energy_to_lufs(mean) + RANGE_RELATIVE_GATE_LU as f64);
+        let total: u64 = self.counts[first..].iter().sum();
+        if total == 0 {
+            return f32::NEG_INFINITY;
+        }
+        let low_target = (total as f64 * RANGE_LOW_PERCENTILE).ceil().max(1.0) as u64;
+        let high_target = (total as f64 * RANGE_HIGH_PERCENTILE).ceil().max(1.0) as u64;
+        let (mut low, mut high) = (None, None);
+        let mut cumulative = 0;
+        for bin in first..HISTOGRAM_BINS {
+            cumulative += self.counts[bin];
+            if low.is_none() && cumulative >= low_target {
+                low = Some(bin);
+            }
+            if cumulative >= high_target {
+                high = Some(bin);
+                break;
+            }
+        }
+        match (low, high) {
+            (Some(low), Some(high)) => (high - low) as f32 * HISTOGRAM_STEP_LU,
+            _ => f32::NEG_INFINITY,
+        }
+    }
+}
+
+fn bin_for(loudness: f64) -> usize {
+    let offset = (loudness - ABSOLUTE_GATE_LUFS as f64) / HISTOGRAM_STEP_LU as f64;
+    (offset.max(0.0) as usize).min(HISTOGRAM_BINS - 1)
+}
+
+fn energy_to_lufs(energy: f64) -> f64 {
+    if energy > 0.0 {
+        -0.691 + 10.0 * energy.log10()
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+/// Stereo BS.1770 loudness meter fed from the render thread.
+pub(crate) struct LoudnessMeter {
+    filters: [KWeighting; 2],
+    sub_block_frames: usize,
+    sub_block_position: usize,
+    sub_block_sum: f64,
+    sub_blocks: [f64; SHORT_TERM_SUB_BLOCKS],
+    sub_block_index: usize,
+    sub_blocks_filled: usize,
+    momentary_histogram: Histogram,
+    short_term_histogram: Histogram,
+    readout: Arc<LoudnessReadout>,
+}
+
+impl LoudnessMeter {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self::with_readout(sample_rate, Arc::new(LoudnessReadout::new()))
+    }
+
+    /// Meter publishing to `readout`, which the control side may share with earlier meters.
+    pub(crate) fn with_readout(sample_rate: u32, readout: Arc<LoudnessReadout>) -> Self {
+        let sample_rate = sample_rate.max(8_000);
+        let filter = KWeighting::new(sample_rate as f64);
+        Self {
+            filters: [filter; 2],
+            sub_block_frames: (sample_rate as usize + 5) / 10,
+            sub_block_position: 0,
+            sub_block_sum: 0.0,
+            sub_blocks: [0.0; SHORT_TERM_SUB_BLOCKS],
+            sub_block_index: 0,
+            sub_blocks_filled: 0,
+            momentary_histogram: Histogram::new(),
+            short_term_histogram: Histogram::new(),
+            readout,
+        }
+    }
+
+    /// Ask the render thread to restart measurement at the next block.
+    pub(crate) fn request_reset(&self) {
+        self.readout.request_reset();
+    }
+
+    /// Measure an interleaved stereo block.
+    pub(crate) fn process(&mut self, block: &[f32]) {
+        if self.readout.reset_requested.swap(false, Ordering::Acquire) {
+            self.reset();
+        }
+        for frame in block.chunks_exact(2) {
+            let left = self.filters[0].process(frame[0]);
+            let right = self.filters[1].process(frame[1]);
+            self.sub_block_sum += left * left + right * right;
+            self.sub_block_position += 1;
+            if self.sub_block_position == self.sub_block_frames {
+                self.finish_sub_block();
+            }
+        }
+    }
+
+    pub(crate) fn reading(&self) -> LoudnessReading {
+        self.readout.reading()
+    }
+
+    /// Restart measurement from silence.
+    pub(crate) fn reset(&mut self) {
+        for filter in &mut self.filters {
+            for stage in [&mut filter.shelf, &mut filter.high_pass] {
+                stage.x = [0.0; 2];
+                stage.y = [0.0; 2];
+            }
+        }
+        self.sub_block_position = 0;
+        self.sub_block_sum = 0.0;
+        self.sub_blocks = [0.0; SHORT_TERM_SUB_BLOCKS];
+        self.sub_block_index = 0;
+        self.sub_blocks_filled = 0;
+        self.momentary_histogram.clear();
+        self.short_term_histogram.clear();
+        self.readout.publish(LoudnessReading::default());
+    }
+
+    fn finish_sub_block(&mut self) {
+        self.sub_blocks[self.sub_block_index] = self.sub_block_sum / self.sub_block_frames as f64;
+        self.sub_block_index = (self.sub_block_index + 1) % SHORT_TERM_SUB_BLOCKS;
+        self.sub_blocks_filled = (self.sub_blocks_filled + 1).min(SHORT_TERM_SUB_BLOCKS);
+        self.sub_block_position = 0;
+        self.sub_block_sum = 0.0;
+
+        let mut reading = self.reading();
+        if self.sub_blocks_filled >= MOMENTARY_SUB_BLOCKS {
+            let energy = self.recent_energy(MOMENTARY_SUB_BLOCKS);
+            self.momentary_histogram.add(energy);
+            reading.momentary = energy_to_lufs(energy) as f32;
+            reading.integrated = self.momentary_histogram.integrated();
+        }
+        if self.sub_blocks_filled >= SHORT_TERM_SUB_BLOCKS {
+            let energy = self.recent_energy(SHORT_TERM_SUB_BLOCKS);
+            self.short_term_histogram.add(energy);
+            reading.short_term = energy_to_lufs(energy) as f32;
+            reading.range = self.short_term_histogram.range();
+        }
+        self.readout.publish(reading);
+    }
+
+    /// Mean energy of the most recent `count` sub-blocks.
+    fn recent_energy(&self, count: usize) -> f64 {
+        let sum: f64 = (1..=count)
+            .map(|back| {
+                let index =
+                    (self.sub_block_index + SHORT_TERM_SUB_BLOCKS - back) % SHORT_TERM_SUB_BLOCKS;
+                self.sub_blocks[index]
+            })
+            .sum();
+        sum / count as f64
+    }
+}
+
+/// Latest reading of a [`LoudnessMeter`] and its pending reset request, shared with readers.
+pub(crate) struct LoudnessReadout {
+    reset_requested: AtomicBool,
+    momentary_bits: AtomicU32,
+    short_term_bits: AtomicU32,
+    integrated_bits: AtomicU32,
+    range_bits: AtomicU32,
+}
+
+impl LoudnessReadout {
+    pub(crate) fn new() -> Self {
+        let silent = f32::NEG_INFINITY.to_bits();
+        Self {
+            reset_requested: AtomicBool::new(false),
+            momentary_bits: AtomicU32::new(silent),
+            short_term_bits: AtomicU32::new(silent),
+            integrated_bits: AtomicU32::new(silent),
+            range_bits: AtomicU32::new(silent),
+        }
+    }
+
+    /// Ask the meter publishing here to restart measurement at its next block.
+    pub(crate) fn request_reset(&self) {
+        self.reset_requested.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn reading(&self) -> LoudnessReading {
+        LoudnessReading {
+            momentary: f32::from_bits(self.momentary_bits.load(Ordering::Relaxed)),
+            short_term: f32::from_bits(self.short_term_bits.load(Ordering::Relaxed)),
+            integrated: f32::from_bits(self.integrated_bits.load(Ordering::Relaxed)),
+            range: f32::from_bits(self.range_bits.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn publish(&self, reading: LoudnessReading) {
+        self.momentary_bits
+            .store(reading.momentary.to_bits(), Ordering::Relaxed);
+        self.short_term_bits
+            .store(reading.short_term.to_bits(), Ordering::Relaxed);
+        self.integrated_bits
+            .store(reading.integrated.to_bits(), Ordering::Relaxed);
+        self.range_bits
+            .store(reading.range.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Short-term loudness below which the normalizer holds its gain instead of boosting silence.
+pub const NORMALIZATION_GATE_LUFS: f32 = -50.0;
+/// Fastest rate at which the normalizer raises its gain.
+pub const NORMALIZATION_RISE_DB_PER_S: f32 = 1.5;
+/// Fastest rate at which the normalizer lowers its gain.
+pub const NORMALIZATION_FALL_DB_PER_S: f32 = 3.0;
+
+/// Target and limits for master loudness normalization.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalizationParams {
+    /// Short-term loudness the master is steered towards, in LUFS.
+    pub target_lufs: f32,
+    /// Largest boost the normalizer may apply, in dB.
+    pub max_gain_db: f32,
+    /// Largest cut the normalizer may apply, in dB (positive values).
+    pub max_cut_db: f32,
+}
+
+impl Default for NormalizationParams {
+    fn default() -> Self {
+        Self {
+            target_lufs: -16.0,
+            max_gain_db: 12.0,
+            max_cut_db: 20.0,
+        }
+    }
+}
+
+/// Slow-acting master gain stage. Configuration lives in atomics; the meter and gain state are
+/// owned by the render thread.
+pub(crate) struct LoudnessNormalizer {
+    meter: LoudnessMeter,
+    sample_rate: f32,
+    gain_db: f32,
+    enabled: AtomicBool,
+    target_bits: AtomicU32,
+    max_gain_bits: AtomicU32,
+    max_cut_bits: AtomicU32,
+    gain_bits: AtomicU32,
+}
+
+impl LoudnessNormalizer {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        let defaults = NormalizationParams::default();
+        Self {
+            meter: LoudnessMeter::new(sample_rate),
+            sample_rate: sample_rate.max(1) as f32,
+            gain_db: 0.0,
+            enabled: AtomicBool::new(false),
+            target_bits: AtomicU32::new(defaults.target_lufs.to_bits()),
+            max_gain_bits: AtomicU32::new(defaults.max_gain_db.to_bits()),
+            max_cut_bits: AtomicU32::new(defaults.max_cut_db.to_bits()),
+            gain_bits: AtomicU32::new(0.0f32.to_bits()),
+        }
+    }
+
+    pub(crate) fn configure(&self, params: NormalizationParams) {
+        self.target_bits
+            .store(params.target_lufs.to_bits(), Ordering::Relaxed);
+        self.max_gain_bits
+            .store(params.max_gain_db.max(0.0).to_bits(), Ordering::Relaxed);
+        self.max_cut_bits
+            .store(params.max_cut_db.max(0.0).to_bits(), Ordering::Relaxed);
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Disable normalization; the gain glides back to unity.
+    pub(crate) fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn params(&self) -> Option<NormalizationParams> {
+        if !self.enabled.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(NormalizationParams {
+            target_lufs: f32::from_bits(self.target_bits.load(Ordering::Relaxed)),
+            max_gain_db: f32::from_bits(self.max_gain_bits.load(Ordering::Relaxed)),
+            max_cut_db: f32::from_bits(self.max_cut_bits.load(Ordering::Relaxed)),
+        })
+    }
+
+    /// Correction gain currently applied, in dB.
+    pub(crate) fn gain_db(&self) -> f32 {
+        f32::from_bits(self.gain_bits.load(Ordering::Relaxed))
+    }
+
+    /// Measure the interleaved stereo `block` and apply the correction gain in place.
+    pub(crate) fn process(&mut self, block: &mut [f32]) {
+        let frames = block.len() / 2;
+        if frames == 0 {
+            return;
+        }
+        let desired_db = match self.params() {
+            Some(params) => {
+                self.meter.process(block);
+                let short_term = self.meter.reading().short_term;
+                if short_term.is_finite() && short_term > NORMALIZATION_GATE_LUFS {
+                    (params.target_lufs - short_term).clamp(-params.max_cut_db, params.max_gain_db)
+                } else {
+                    self.gain_db.clamp(-params.max_cut_db, params.max_gain_db)
+                }
+            }
+            None if self.gain_db == 0.0 => return,
+            None => 0.0,
+        };
+
+        let seconds = frames as f32 / self.sample_rate;
+        let start_db = self.gain_db;
+        let step = desired_db - start_db;
+        self.gain_db += step.clamp(
+            -NORMALIZATION_FALL_DB_PER_S * seconds,
+            NORMALIZATION_RISE_DB_PER_S * seconds,
+        );
+        self.gain_bits
+            .store(self.gain_db.to_bits(), Ordering::Relaxed);
+
+        let start = 10f32.powf(start_db / 20.0);
+        let end = 10f32.powf(self.gain_db / 20.0);
+        let increment = (end - start) / frames as f32;
+        for (index, frame) in block.chunks_exact_mut(2).enumerate() {
+            let gain = start + increment * (index + 1) as f32;
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Block-based level metering with ballistics, 4x oversampled true peak and clip detection.
+//!
+//! [`Meter::process`] runs on the render thread once per block. RMS integrates the block mean
+//! square with a 300 ms time constant; sample peak has instant attack and a fixed dB/s fall, with a
+//! separate peak-hold value. True peak follows ITU-R BS.1770-4 Annex 2 using the 48-tap, 4-phase
+//! interpolation filter. Readings are published through atomics so control surfaces can read them
+//! without locking.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Integration time of the RMS reading.
+pub const RMS_INTEGRATION_MS: f32 = 300.0;
+/// Fall rate of the peak and true-peak readings.
+pub const PEAK_DECAY_DB_PER_S: f32 = 20.0;
+/// How long the peak-hold reading stays at a new maximum.
+pub const PEAK_HOLD_MS: f32 = 1_500.0;
+/// How long the clip indicator stays lit after an over.
+pub const CLIP_HOLD_MS: f32 = 3_000.0;
+/// Level at or above which a sample (or interpolated true peak) counts as clipped.
+pub const CLIP_LEVEL: f32 = 1.0;
+
+const OVERSAMPLING: usize = 4;
+const PHASE_TAPS: usize = 12;
+
+/// BS.1770-4 true-peak interpolation filter, one row per oversampling phase.
+const TRUE_PEAK_PHASES: [[f32; PHASE_TAPS]; OVERSAMPLING] = [
+    [
+        0.001_708_984_4,
+        0.010_986_328,
+        -0.019_653_32,
+        0.033_203_125,
+        -0.059_448_242,
+        0.137_329_1,
+        0.972_167_97,
+        -0.102_294_92,
+        0.047_607_42,
+        -0.026_611_328,
+        0.014_892_578,
+        -0.008_300_781,
+    ],
+    [
+        -0.029_174_805,
+        0.029_296_875,
+        -0.051_757_812,
+        0.089_111_33,
+        -0.166_503_9,
+        0.465_087_9,
+        0.779_785_16,
+        -0.200_317_38,
+        0.101_562_5,
+        -0.058_227_54,
+        0.033_081_055,
+        -0.018_920_898,
+    ],
+    [
+        -0.018_920_898,
+        0.033_081_055,
+        -0.058_227_54,
+        0.101_562_5,
+        -0.200_317_38,
+        0.779_785_16,
+        0.465_087_9,
+        -0.166_503_9,
+        0.089_111_33,
+        -0.051_757_812,
+        0.029_296_875,
+        -0.029_174_805,
+    ],
+    [
+        -0.008_300_781,
+        0.014_892_578,
+        -0.026_611_328,
+        0.047_607_42,
+        -0.102_294_92,
+        0.137_329_1,
+        0.972_167_97,
+        -0.059_448_242,
+        0.033_203_125,
+        -0.019_653_32,
+        0.010_986_328,
+        0.001_708_984_4,
+    ],
+];
+
+/// Snapshot of a meter's published readings. Levels are linear full-scale amplitudes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MeterReading {
+    /// Integrated RMS level.
+    pub rms: f32,
+    /// Sample peak with instant attack and timed decay.
+    pub peak: f32,
+    /// Highest recent sample peak, held for [`PEAK_HOLD_MS`].
+    pub peak_hold: f32,
+    /// 4x oversampled true peak with the same ballistics as `peak`.
+    pub true_peak: f32,
+    /// Whether a sample or true peak reached [`CLIP_LEVEL`] within [`CLIP_HOLD_MS`].
+    pub clipped: bool,
+}
+
+/// Stereo level meter fed from the render thread.
+pub(crate) struct Meter {
+    sample_rate: f32,
+    mean_square: f32,
+    peak: f32,
+    peak_hold: f32,
+    hold_remaining: f32,
+    true_peak: f32,
+    clip_remaining: f32,
+    history: [[f32; PHASE_TAPS]; 2],
+    rms_bits: AtomicU32,
+    peak_bits: AtomicU32,
+    peak_hold_bits: AtomicU32,
+    true_peak_bits: AtomicU32,
+    clipped: AtomicBool,
+}
+
+impl Meter {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1) as f32,
+            mean_square: 0.0,
+            peak: 0.0,
+            peak_hold: 0.0,
+            hold_remaining: 0.0,
+            true_peak: 0.0,
+            clip_remaining: 0.0,
+            history: [[0.0; PHASE_TAPS]; 2],
+            rms_bits: AtomicU32::new(0.0f32.to_bits()),
+            peak_bits: AtomicU32::new(0.0f32.to_bits()),
+            peak_hold_bits: AtomicU32::new(0.0f32.to_bits()),
+            true_peak_bits: AtomicU32::new(0.0f32.to_bits()),
+            clipped: AtomicBool::new(false),
+        }
+    }
+
+    /// Measure an interleaved stereo block and publish the updated readings.
+    pub(crate) fn process(&mut self, block: &[f32]) {
+        let frames = block.len() / 2;
+        if frames == 0 {
+            return;
+        }
+        let mut sum_squares = 0.0f32;
+        let mut block_peak = 0.0f32;
+        let mut block_true_peak = 0.0f32;
+        for frame in block.chunks_exact(2) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                sum_squares += sample * sample;
+                block_peak = block_peak.max(sample.abs());
+                block_true_peak = block_true_peak.max(self.oversample(channel, sample));
+            }
+        }
+        // The interpolated signal includes the original samples as well.
+        block_true_peak = block_true_peak.max(block_peak);
+
+        let elapsed_ms = frames as f32 * 1_000.0 / self.sample_rate;
+        let coefficient = (-elapsed_ms / RMS_INTEGRATION_MS).exp();
+        let block_mean_square = sum_squares / block.len() as f32;
+        self.mean_square = coefficient * self.mean_square + (1.0 - coefficient) * block_mean_square;
+
+        let decay = 10f32.powf(-PEAK_DECAY_DB_PER_S * elapsed_ms * 0.001 / 20.0);
+        self.peak = block_peak.max(self.peak * decay);
+        self.true_peak = block_true_peak.max(self.true_peak * decay);
+
+        if block_peak >= self.peak_hold {
+            self.peak_hold = block_peak;
+            self.hold_remaining = PEAK_HOLD_MS;
+        } else {
+            self.hold_remaining -= elapsed_ms;
+            if self.hold_remaining <= 0.0 {
+                self.peak_hold = self.peak;
+            }
+        }
+
+        if block_peak >= CLIP_LEVEL || block_true_peak >= CLIP_LEVEL {
+            self.clip_remaining = CLIP_HOLD_MS;
+        } else {
+            self.clip_remaining = (self.clip_remaining - elapsed_ms).max(0.0);
+        }
+
+        self.rms_bits
+            .store(self.mean_square.sqrt().to_bits(), Ordering::Relaxed);
+        self.peak_bits.store(self.peak.to_bits(), Ordering::Relaxed);
+        self.peak_hold_bits
+            .store(self.peak_hold.to_bits(), Ordering::Relaxed);
+        self.true_peak_bits
+            .store(self.true_peak.to_bits(), Ordering::Relaxed);
+        self.clipped
+            .store(self.clip_remaining > 0.0, Ordering::Relaxed);
+    }
+
+    /// Latest published readings.
+    pub(crate) fn reading(&self) -> MeterReading {
+        MeterReading {
+            rms: f32::from_bits(self.rms_bits.load(Ordering::Relaxed)),
+            peak: f32::from_bits(self.peak_bits.load(Ordering::Relaxed)),
+            peak_hold: f32::from_bits(self.peak_hold_bits.load(Ordering::Relaxed)),
+            true_peak: f32::from_bits(self.true_peak_bits.load(Ordering::Relaxed)),
+            clipped: self.clipped.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Push `sample` into the channel's interpolator and return the largest magnitude among the
+    /// four oversampled points.
+    fn oversample(&mut self, channel: usize, sample: f32) -> f32 {
+        let history = &mut self.history[channel];
+        history.copy_within(..PHASE_TAPS - 1, 1);
+        history[0] = sample;
+        let mut peak = 0.0f32;
+        for phase in &TRUE_PEAK_PHASES {
+            let value: f32 = phase.iter().zip(history.iter()).map(|(c, x)| c * x).sum();
+            peak = peak.max(value.abs());
+        }
+        peak
+    }
+}
@@ -0,0 +1,512 @@
+//! OpenMetrics exporter for mixer telemetry (behind the `metrics` feature).
+//!
+//! [`render`] turns a [`MixerStatus`] into OpenMetrics text: engine settings, callback timing
+//! (including a load histogram), dropout and glitch counters, master levels and loudness, and the
+//! per-source values of [`SourceStatus`](crate::SourceStatus) labelled by source id and name.
+//! [`MetricsServer`] serves that text at `/metrics` over plain HTTP on a local socket so the
+//! mixer can be scraped like any other service. Levels are linear amplitudes, gains and ERLE
+//! are in dB, loudness in LUFS and loads are fractions of the block duration.
+
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::timing::{LOAD_BUCKET_WIDTH, LOAD_HISTOGRAM_BUCKETS};
+use crate::{MixerStatus, SourceStatus, get_mixer_status};
+
+/// Content type of the exposition format produced by [`render`].
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+const ACCEPT_POLL: Duration = Duration::from_millis(20);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Metric family writer that keeps samples of a family together, as OpenMetrics requires.
+struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (index, (key, value)) in labels.iter().enumerate() {
+                if index > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{key}=\"{}\"", escape_label(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.family(name, "counter", help);
+        self.sample(&format!("{name}_total"), &[], value as f64);
+    }
+
+    fn labelled_gauge(&mut self, name: &str, help: &str, label: &str, samples: &[(&str, f64)]) {
+        self.family(name, "gauge", help);
+        for (value_label, value) in samples {
+            self.sample(name, &[(label, value_label)], *value);
+        }
+    }
+
+    /// One gauge family with a sample per source for which `value` returns something.
+    fn source_gauge(
+        &mut self,
+        sources: &[SourceStatus],
+        name: &str,
+        help: &str,
+        value: impl Fn(&SourceStatus) -> Option<f64>,
+    ) {
+        self.family(name, "gauge", help);
+        for source in sources {
+            if let Some(value) = value(source) {
+                let id = source.id.to_string();
+                self.sample(name, &[("source", &id), ("name", &source.name)], value);
+            }
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn flag(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+/// Render `status` as an OpenMetrics text exposition, terminated by `# EOF`.
+pub fn render(status: &MixerStatus) -> String {
+    let mut out = Exposition { out: String::new() };
+
+    out.gauge(
+        "loopback_sample_rate_hertz",
+        "Mixer sample rate.",
+        status.sample_rate as f64,
+    );
+    out.gauge(
+        "loopback_buffer_frames",
+        "Maximum block size requested by the host.",
+        status.buffer_frames as f64,
+    );
+    out.gauge(
+        "loopback_latency_seconds",
+        "Render latency implied by the block size.",
+        status.latency_ms as f64 / 1_000.0,
+    );
+    out.gauge(
+        "loopback_buffer_fill_ratio",
+        "Average queued buffer fill across sources.",
+        status.buffer_fill as f64,
+    );
+    out.gauge(
+        "loopback_drift_ppm",
+        "Average absolute clock drift across sources.",
+        status.drift_ppm as f64,
+    );
+
+    let timing = &status.timing;
+    out.gauge(
+        "loopback_cpu_load_ratio",
+        "Recent render callback load relative to the block duration.",
+        status.cpu_usage as f64,
+    );
+    out.labelled_gauge(
+        "loopback_callback_load_ratio",
+        "Render callback load statistics since the last timing reset.",
+        "stat",
+        &[
+            ("min", timing.min_load as f64),
+            ("avg", timing.avg_load as f64),
+            ("max", timing.max_load as f64),
+            ("p99", timing.p99_load as f64),
+        ],
+    );
+    out.family(
+        "loopback_callback_load",
+        "histogram",
+        "Distribution of render callback load since the last timing reset.",
+    );
+    let mut cumulative = 0u64;
+    for (index, count) in timing.histogram.iter().enumerate() {
+        cumulative += count;
+        if index + 1 == LOAD_HISTOGRAM_BUCKETS {
+            break;
+        }
+        let bound = format!("{:.3}", (index + 1) as f32 * LOAD_BUCKET_WIDTH);
+        out.sample(
+            "loopback_callback_load_bucket",
+            &[("le", &bound)],
+            cumulative as f64,
+        );
+    }
+    out.sample(
+        "loopback_callback_load_bucket",
+        &[("le", "+Inf")],
+        cumulative as f64,
+    );
+    out.sample("loopback_callback_load_count", &[], cumulative as f64);
+    out.sample(
+        "loopback_callback_load_sum",
+        &[],
+        timing.avg_load as f64 * timing.callbacks as f64,
+    );
+
+    let events = &status.events;
+    out.counter(
+        "loopback_source_underruns",
+        "Source underruns (one per dropout).",
+        events.source_underruns,
+    );
+    out.counter(
+        "loopback_ring_overflows",
+        "Producer writes that overflowed a source ring.",
+        events.ring_overflows,
+    );
+    out.counter(
+        "loopback_callback_overruns",
+        "Render callbacks that missed their deadline.",
+        events.callback_overruns,
+    );
+    out.counter(
+        "loopback_non_finite",
+        "Blocks in which NaN or infinite samples appeared.",
+        events.non_finite,
+    );
+    out.counter(
+        "loopback_format_errors",
+        "Render callbacks with an unsupported buffer format.",
+        events.format_errors,
+    );
+    out.counter(
+        "loopback_events_dropped",
+        "Events lost because the event log was full.",
+        events.dropped,
+    );
+
+    out.labelled_gauge(
+        "loopback_master_level",
+        "Master output level as a linear amplitude.",
+        "kind",
+        &[
+            ("rms", status.master.rms as f64),
+            ("peak", status.master.peak as f64),
+            ("peak_hold", status.master.peak_hold as f64),
+            ("true_peak", status.master.true_peak as f64),
+        ],
+    );
+    out.gauge(
+        "loopback_master_clipped",
+        "Whether the master output clipped recently.",
+        flag(status.master.clipped),
+    );
+    out.gauge(
+        "loopback_master_correlation",
+        "Phase correlation between the master output channels.",
+        status.stereo.correlation as f64,
+    );
+    out.gauge(
+        "loopback_master_stereo_width",
+        "Stereo width of the master output (0 mono, 1 unrelated, 2 out of phase).",
+        status.stereo.width as f64,
+    );
+    out.gauge(
+        "loopback_mono_warning",
+        "Whether the master output or any source is not mono compatible.",
+        flag(status.mono_warning),
+    );
+    out.labelled_gauge(
+        "loopback_master_loudness_lufs",
+        "EBU R128 loudness of the master output.",
+        "window",
+        &[
+            ("momentary", status.loudness.momentary as f64),
+            ("short_term", status.loudness.short_term as f64),
+            ("integrated", status.loudness.integrated as f64),
+        ],
+    );
+    out.gauge(
+        "loopback_master_loudness_range_lu",
+        "EBU R128 loudness range of the master output.",
+        status.loudness.range as f64,
+    );
+    out.gauge(
+        "loopback_normalization_gain_db",
+        "Correction gain applied by loudness normalization.",
+        status.normalization_gain_db as f64,
+    );
+
+    let sources = &status.sources;
+    out.source_gauge(sources, "loopback_source_gain_db", "Fader gain.", |s| {
+        Some(s.gain_db as f64)
+    });
+    out.source_gauge(sources, "loopback_source_muted", "Whether muted.", |s| {
+        Some(flag(s.muted))
+    });
+    out.source_gauge(
+        sources,
+        "loopback_source_latency_frames",
+        "Configured latency offset.",
+        |s| Some(s.latency_frames as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_buffer_fill_ratio",
+        "Queued buffer fill.",
+        |s| Some(s.buffer_fill as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_drift_ppm",
+        "Clock drift estimate.",
+        |s| Some(s.drift_ppm as f64),
+    );
+    out.family(
+        "loopback_source_level",
+        "gauge",
+        "Post-fader level as a linear amplitude.",
+    );
+    for source in sources {
+        let id = source.id.to_string();
+        for (kind, value) in [
+            ("rms", source.rms),
+            ("peak", source.peak),
+            ("peak_hold", source.peak_hold),
+            ("true_peak", source.true_peak),
+        ] {
+            out.sample(
+                "loopback_source_level",
+                &[("source", &id), ("name", &source.name), ("kind", kind)],
+                value as f64,
+            );
+        }
+    }
+    out.source_gauge(
+        sources,
+        "loopback_source_clipped",
+        "Whether the source clipped recently.",
+        |s| Some(flag(s.clipped)),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_correlation",
+        "Post-fader phase correlation.",
+        |s| Some(s.correlation as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_stereo_width",
+        "Post-fader stereo width.",
+        |s| Some(s.stereo_width as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_mono_warning",
+        "Whether the source is not mono compatible.",
+        |s| Some(flag(s.mono_warning)),
+    );
+    out.family(
+        "loopback_source_loudness_lufs",
+        "gauge",
+        "Post-fader EBU R128 loudness, for sources with loudness metering enabled.",
+    );
+    for source in sources {
+        let Some(loudness) = source.loudness else {
+            continue;
+        };
+        let id = source.id.to_string();
+        for (window, value) in [
+            ("momentary", loudness.momentary),
+            ("short_term", loudness.short_term),
+            ("integrated", loudness.integrated),
+        ] {
+            out.sample(
+                "loopback_source_loudness_lufs",
+                &[("source", &id), ("name", &source.name), ("window", window)],
+                value as f64,
+            );
+        }
+    }
+    out.source_gauge(
+        sources,
+        "loopback_source_duck_gain_db",
+        "Sidechain ducking gain.",
+        |s| Some(s.duck_gain_db as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_automix_gain_db",
+        "Automix gain.",
+        |s| Some(s.automix_gain_db as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_speaking",
+        "Whether voice activity is detected.",
+        |s| Some(flag(s.speaking)),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_voice_probability",
+        "Smoothed voice probability.",
+        |s| Some(s.voice_probability as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_erle_db",
+        "Echo return loss enhancement, for sources with echo cancellation.",
+        |s| s.echo_cancelling.then_some(s.erle_db as f64),
+    );
+    out.source_gauge(
+        sources,
+        "loopback_source_noise_suppression_strength",
+        "Noise suppression strength, for sources with noise suppression.",
+        |s| s.noise_suppression.map(|strength| strength as f64),
+    );
+
+    out.out.push_str("# EOF\n");
+    out.out
+}
+
+/// Background HTTP server answering `GET /metrics` with [`render`]ed telemetry.
+///
+/// The server stops when dropped.
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Serve the global mixer's status on `addr` (for example `127.0.0.1:9464`; port 0 picks a
+    /// free port).
+    pub fn start(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::with_source(addr, get_mixer_status)
+    }
+
+    /// Serve the status returned by `source`; `None` answers 503.
+    pub fn with_source(
+        addr: impl ToSocketAddrs,
+        source: impl Fn() -> Option<MixerStatus> + Send + 'static,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread = thread::Builder::new()
+            .name("loopback-metrics".to_string())
+            .spawn(move || {
+                while thread_running.load(Ordering::Acquire) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let _ = serve(stream, &source);
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(ACCEPT_POLL);
+                        }
+                        Err(_) => thread::sleep(ACCEPT_POLL),
+                    }
+                }
+            })?;
+        Ok(Self {
+            local_addr,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, source: &impl Fn() -> Option<MixerStatus>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 || request.len() + read > MAX_REQUEST_BYTES {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status_line, content_type, body) = if method != "GET" {
+        (
+            "405 Method Not Allowed",
+            "text/plain",
+            "only GET is supported\n".to_string(),
+        )
+    } else if path != "/metrics" {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    } else {
+        match source() {
+            Some(status) => ("200 OK", CONTENT_TYPE, render(&status)),
+            None => (
+                "503 Service Unavailable",
+                "text/plain",
+                "no active mixer\n".to_string(),
+            ),
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status_line}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,989 @@
+//! Offline rendering: drive a [`Mixer`] faster than real time from a virtual clock.
+//!
+//! An [`OfflineRenderer`] owns a mixer and a [`VirtualClock`] that advances by exactly one block
+//! per render, so every timestamp the mixer sees is derived from the frame count rather than the
+//! wall clock. Inputs are preloaded sample buffers, decoded files or synthesised generator signals,
+//! each scheduled to start at a given time. Before each block the renderer pushes into every
+//! input's ring the chunks that are due by the end of that block, the way a real-time producer
+//! running in lockstep with the device would; a chunk size other than the block size models a
+//! producer with its own period.
+//!
+//! Producers with a clock of their own are simulated with [`ProducerClock`]: a generator signal
+//! delivered in periods whose timing runs a set number of parts per million off the device clock,
+//! with optional wake-up jitter, each delivery reported through
+//! [`Mixer::submit_clock_feedback`] the way a real driver would.
+//!
+//! Given the same inputs and mixer settings a render is bit-for-bit repeatable. Only the
+//! timestamps of [`MixerEvent`](crate::events::MixerEvent)s still come from the host clock.
+//!
+//! A [`RenderSession`] describes a whole render in a small TOML file, so a bug report can ship
+//! the exact inputs and settings that reproduce it:
+//!
+//! ```toml
+//! [session]
+//! sample_rate = 48000    # default 48000
+//! block_frames = 256     # default 480
+//! duration = 2.0         # seconds; default: until every input has played out
+//!
+//! [[source]]
+//! file = "voice.wav"     # WAV or FLAC, relative to the session file
+//! start = 0.25           # seconds into the render
+//! gain_db = -6.0
+//! latency_frames = 32
+//! chunk_frames = 441     # push in 441-frame chunks instead of one block at a time
+//!
+//! [[source]]
+//! generator = "sweep"    # sine, sweep, white, pink, impulse or reference
+//! frequency = 20.0       # sine frequency, sweep start or impulse rate
+//! end_frequency = 20000.0
+//! period = 1.0           # sweep duration
+//! level = -12.0          # dBFS
+//! length = 1.5           # seconds; default: the session duration
+//! muted = false
+//! ```
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::generator::{Generator, GeneratorConfig, Waveform};
+use crate::player::decode_file;
+use crate::ring::SharedRingBuffer;
+use crate::wav::{SampleFormat, WavSpec, WavWriter};
+use crate::{AudioBuffer, MIX_CHANNELS, Mixer, MixerError, SourceHandle};
+
+/// Host time the virtual clock starts from, in nanoseconds. Zero would read as "no timestamp".
+pub const VIRTUAL_CLOCK_START_NS: u64 = 1_000_000_000;
+
+/// Sample rate of a [`RenderSession`] that does not name one.
+pub const DEFAULT_SESSION_SAMPLE_RATE: u32 = 48_000;
+
+/// Block size of a [`RenderSession`] that does not name one.
+pub const DEFAULT_SESSION_BLOCK_FRAMES: usize = 480;
+
+/// Largest block a [`RenderSession`] may ask for.
+pub const MAX_SESSION_BLOCK_FRAMES: usize = 16_384;
+
+/// Longest duration, generator length or start time a [`RenderSession`] may ask for, in seconds.
+pub const MAX_SESSION_SECONDS: f64 = 3_600.0;
+
+/// Why an offline render failed.
+#[derive(Debug, thiserror::Error)]
+pub enum OfflineError {
+    /// The mixer rejected a source setting or a block.
+    #[error(transparent)]
+    Mixer(#[from] MixerError),
+    /// Reading an input or writing the output failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A session file could not be parsed or described an impossible render.
+    #[error("{0}")]
+    Session(String),
+}
+
+/// Frame counter standing in for the host clock during offline rendering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VirtualClock {
+    sample_rate: u32,
+    start_ns: u64,
+    frames: u64,
+}
+
+impl VirtualClock {
+    /// A clock at frame 0 reading [`VIRTUAL_CLOCK_START_NS`].
+    pub fn new(sample_rate: u32) -> Self {
+        Self::starting_at(sample_rate, VIRTUAL_CLOCK_START_NS)
+    }
+
+    /// A clock at frame 0 reading `start_ns`.
+    pub fn starting_at(sample_rate: u32, start_ns: u64) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            start_ns,
+            frames: 0,
+        }
+    }
+
+    /// Frames elapsed since the clock started.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Current reading in nanoseconds.
+    pub fn now_ns(&self) -> u64 {
+        self.ns_at(self.frames)
+    }
+
+    /// Reading at `frame` frames after the start, in nanoseconds.
+    pub fn ns_at(&self, frame: u64) -> u64 {
+        let elapsed = frame as u128 * 1_000_000_000 / self.sample_rate as u128;
+        self.start_ns + elapsed as u64
+    }
+
+    /// Move the clock forward by `frames`.
+    pub fn advance(&mut self, frames: usize) {
+        self.frames += frames as u64;
+    }
+}
+
+/// Audio fed to an offline render, with when and how it is pushed.
+#[derive(Clone, Debug)]
+pub struct OfflineInput {
+    samples: Arc<[f32]>,
+    start_frame: u64,
+    chunk_frames: Option<usize>,
+}
+
+impl OfflineInput {
+    /// Interleaved stereo `samples` at the mixer rate, starting at frame 0.
+    pub fn from_samples(samples: impl Into<Arc<[f32]>>) -> Self {
+        Self {
+            samples: samples.into(),
+            start_frame: 0,
+            chunk_frames: None,
+        }
+    }
+
+    /// The WAV or FLAC file at `path`, converted to stereo at `sample_rate`.
+    pub fn from_file(path: impl AsRef<Path>, sample_rate: u32) -> io::Result<Self> {
+        let samples = decode_file(path.as_ref(), sample_rate, usize::MAX / MIX_CHANNELS)?;
+        Ok(Self::from_samples(samples))
+    }
+
+    /// `seconds` of the signal described by `config`, synthesised at `sample_rate`.
+    pub fn from_generator(config: GeneratorConfig, sample_rate: u32, seconds: f64) -> Self {
+        let frames = (seconds.max(0.0) * sample_rate as f64).round() as usize;
+        let mut samples = vec![0.0f32; frames * MIX_CHANNELS];
+        Generator::new(config, sample_rate).render(&mut samples);
+        Self::from_samples(samples)
+    }
+
+    /// Start pushing the input `frame` frames into the render.
+    pub fn starting_at_frame(mut self, frame: u64) -> Self {
+        self.start_frame = frame;
+        self
+    }
+
+    /// Push the input `frames` frames at a time instead of one mixer block at a time.
+    pub fn in_chunks_of(mut self, frames: usize) -> Self {
+        self.chunk_frames = Some(frames.max(1));
+        self
+    }
+
+    /// Length of the input in frames.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / MIX_CHANNELS
+    }
+
+    /// Frame at which the input has been pushed completely.
+    pub fn end_frame(&self) -> u64 {
+        self.start_frame + self.frames() as u64
+    }
+}
+
+/// Timing of a simulated producer that runs on its own sample clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProducerClock {
+    /// Rate of the producer clock relative to the device clock in parts per million; positive
+    /// runs fast.
+    pub drift_ppm: f64,
+    /// Frames delivered per wake-up.
+    pub period_frames: usize,
+    /// Longest a wake-up may run late, in nanoseconds. Lateness is drawn uniformly from a
+    /// fixed-seed sequence, so jittered renders still repeat exactly.
+    pub jitter_ns: u64,
+    /// Whether each delivery is reported through [`Mixer::submit_clock_feedback`]; without
+    /// feedback the mixer cannot follow the drift.
+    pub feedback: bool,
+    /// Frames already delivered when the producer is added, which the mixer keeps as its margin
+    /// against late wake-ups.
+    pub head_start_frames: usize,
+}
+
+impl ProducerClock {
+    /// A producer `drift_ppm` off the device clock that delivers 10 ms periods on time with a
+    /// two-period head start, and reports them.
+    pub fn new(drift_ppm: f64, sample_rate: u32) -> Self {
+        let period_frames = (sample_rate as usize / 100).max(1);
+        Self {
+            drift_ppm,
+            period_frames,
+            jitter_ns: 0,
+            feedback: true,
+            head_start_frames: period_frames * 2,
+        }
+    }
+}
+
+/// Seed of the jitter sequence of every [`ProducerClock`].
+const JITTER_SEED: u64 = 0x853C_49E6_748F_EA9B;
+
+/// A generator delivered to a mixer source on a drifting clock.
+struct Producer {
+    handle: SourceHandle,
+    ring: Arc<SharedRingBuffer>,
+    clock: ProducerClock,
+    generator: Generator,
+    period: Vec<f32>,
+    sample_rate: f64,
+    /// Device time the producer started at.
+    start_ns: u64,
+    /// Periods delivered so far.
+    delivered: u64,
+    /// Device time at which the next period arrives.
+    next_arrival_ns: u64,
+    rng: u64,
+}
+
+/// An input attached to a mixer source.
+struct Feed {
+    handle: SourceHandle,
+    /// The input preceded by silence back to the start of the block it begins in.
+    samples: Arc<[f32]>,
+    /// Block-aligned frame at which `samples` begins.
+    origin_frame: u64,
+    ring: Arc<SharedRingBuffer>,
+    chunk_frames: usize,
+    /// Frames of `samples` pushed so far.
+    pushed: usize,
+}
+
+/// A mixer rendered block by block against a [`VirtualClock`].
+pub struct OfflineRenderer {
+    mixer: Mixer,
+    sample_rate: u32,
+    clock: VirtualClock,
+    block_frames: usize,
+    feeds: Vec<Feed>,
+    producers: Vec<Producer>,
+    block: Vec<f32>,
+}
+
+impl OfflineRenderer {
+    /// A renderer with an empty mixer producing `block_frames` frames per block.
+    pub fn new(sample_rate: u32, block_frames: usize) -> Self {
+        let block_frames = block_frames.max(1);
+        Self {
+            mixer: Mixer::new(sample_rate, block_frames),
+            sample_rate,
+            clock: VirtualClock::new(sample_rate),
+            block_frames,
+            feeds: Vec::new(),
+            producers: Vec::new(),
+            block: vec![0.0; block_frames * MIX_CHANNELS],
+        }
+    }
+
+    /// The mixer being rendered, for inspection.
+    pub fn mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+    /// The mixer being rendered, for changing source and master settings between blocks.
+    pub fn mixer_mut(&mut self) -> &mut Mixer {
+        &mut self.mixer
+    }
+
+    /// The clock the render is driven by.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Rate the mixer renders at.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Frames rendered per block.
+    pub fn block_frames(&self) -> usize {
+        self.block_frames
+    }
+
+    /// Register a source fed by `input` and return its handle.
+    pub fn add_input(&mut self, input: OfflineInput) -> SourceHandle {
+        let chunk_frames = input.chunk_frames.unwrap_or(self.block_frames);
+        // Room for a few periods of either side, so lockstep feeding never overflows.
+        let capacity = (chunk_frames.max(self.block_frames) * 8).max(1_024);
+        let (handle, ring) = self.mixer.add_source(capacity);
+        // The mixer plays whatever a ring holds from the start of each block, so an input that
+        // begins mid-block is led in with silence to land on its scheduled frame.
+        let lead_frames = (input.start_frame % self.block_frames as u64) as usize;
+        let samples = if lead_frames == 0 {
+            input.samples
+        } else {
+            let mut padded = vec![0.0; lead_frames * MIX_CHANNELS];
+            padded.extend_from_slice(&input.samples);
+            padded.into()
+        };
+        self.feeds.push(Feed {
+            handle,
+            samples,
+            origin_frame: input.start_frame - lead_frames as u64,
+            ring,
+            chunk_frames,
+            pushed: 0,
+        });
+        handle
+    }
+
+    /// Register a source fed indefinitely with `config` by a producer running on `clock`, starting
+    /// now, and return its handle.
+    pub fn add_producer(&mut self, config: GeneratorConfig, clock: ProducerClock) -> SourceHandle {
+        let clock = ProducerClock {
+            period_frames: clock.period_frames.max(1),
+            ..clock
+        };
+        // A second of headroom, so a producer the mixer fails to follow takes a while to overflow.
+        let capacity = (self.sample_rate as usize).max(clock.period_frames * 8);
+        let (handle, ring) = self.mixer.add_source(capacity);
+        let mut producer = Producer {
+            handle,
+            ring,
+            clock,
+            generator: Generator::new(config, self.sample_rate),
+            period: vec![0.0; clock.period_frames * MIX_CHANNELS],
+            sample_rate: self.sample_rate.max(1) as f64,
+            start_ns: self.clock.now_ns(),
+            delivered: 0,
+            next_arrival_ns: 0,
+            rng: JITTER_SEED,
+        };
+        producer.next_arrival_ns = producer.arrival_ns(0);
+        self.producers.push(producer);
+        handle
+    }
+
+    /// Frames queued in the ring of an input or producer source, or `None` for other handles.
+    pub fn queued_frames(&self, handle: SourceHandle) -> Option<usize> {
+        let feeds = self.feeds.iter().map(|feed| (feed.handle, &feed.ring));
+        let producers = self
+            .producers
+            .iter()
+            .map(|producer| (producer.handle, &producer.ring));
+        feeds
+            .chain(producers)
+            .find(|(candidate, _)| *candidate == handle)
+            .map(|(_, ring)| ring.available_read())
+    }
+
+    /// Frame at which the last input has been pushed completely.
+    pub fn inputs_end_frame(&self) -> u64 {
+        self.feeds
+            .iter()
+            .map(|feed| feed.origin_frame + feed.frames() as u64)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Render one block of up to [`block_frames`](Self::block_frames) frames into `out`, which
+    /// holds interleaved stereo, and advance the clock past it.
+    pub fn render_block(&mut self, out: &mut [f32]) -> Result<usize, MixerError> {
+        let frames = (out.len() / MIX_CHANNELS).min(self.block_frames);
+        let block_end = self.clock.frames() + frames as u64;
+        for feed in &mut self.feeds {
+            feed.push_until(block_end, &self.clock);
+        }
+        let block_end_ns = self.clock.ns_at(block_end);
+        for producer in &mut self.producers {
+            producer.deliver_until(block_end_ns, &mut self.mixer)?;
+        }
+        let mut buffer = AudioBuffer {
+            data: out.as_mut_ptr(),
+            frames: frames as u32,
+            channels: MIX_CHANNELS as u32,
+            timestamp_ns: self.clock.now_ns(),
+        };
+        let rendered = self.mixer.process(&mut buffer)?;
+        self.clock.advance(frames);
+        Ok(rendered)
+    }
+
+    /// Render `frames` frames and return them as interleaved stereo.
+    pub fn render(&mut self, frames: usize) -> Result<Vec<f32>, MixerError> {
+        let mut rendered = Vec::with_capacity(frames * MIX_CHANNELS);
+        let mut block = std::mem::take(&mut self.block);
+        let mut remaining = frames;
+        while remaining > 0 {
+            let count = remaining.min(self.block_frames);
+            let result = self.render_block(&mut block[..count * MIX_CHANNELS]);
+            if let Err(err) = result {
+                self.block = block;
+                return Err(err);
+            }
+            rendered.extend_from_slice(&block[..count * MIX_CHANNELS]);
+            remaining -= count;
+        }
+        self.block = block;
+        Ok(rendered)
+    }
+
+    /// Render `frames` frames into a WAV file at `path`.
+    pub fn render_to_wav(
+        &mut self,
+        path: impl AsRef<Path>,
+        frames: usize,
+        format: SampleFormat,
+    ) -> Result<(), OfflineError> {
+        let spec = WavSpec {
+            sample_rate: self.sample_rate,
+            channels: MIX_CHANNELS as u16,
+            format,
+        };
+        let mut writer = WavWriter::new(BufWriter::new(File::create(path)?), spec)?;
+        let mut remaining = frames;
+        while remaining > 0 {
+            let count = remaining.min(self.block_frames * 16);
+            writer.write_samples(&self.render(count)?)?;
+            remaining -= count;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+impl Producer {
+    /// Frames the producer clock has counted since the start once period `index` is complete,
+    /// or `None` while the period is part of the head start.
+    fn counted_after(&self, index: u64) -> Option<u64> {
+        let produced = (index + 1) * self.clock.period_frames as u64;
+        produced
+            .checked_sub(self.clock.head_start_frames as u64)
+            .filter(|&counted| counted > 0)
+    }
+
+    /// Device time at which period `index` arrives: when the producer clock has counted past
+    /// its last frame, plus the wake-up lateness drawn for it.
+    fn arrival_ns(&mut self, index: u64) -> u64 {
+        let Some(counted) = self.counted_after(index) else {
+            return self.start_ns;
+        };
+        let rate = self.sample_rate * (1.0 + self.clock.drift_ppm / 1_000_000.0);
+        let on_time = self.start_ns + (counted as f64 / rate * 1e9).round() as u64;
+        on_time + self.jitter_ns()
+    }
+
+    /// Next wake-up lateness in `[0, jitter_ns]`, from a xorshift64* sequence.
+    fn jitter_ns(&mut self) -> u64 {
+        if self.clock.jitter_ns == 0 {
+            return 0;
+        }
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) % (self.clock.jitter_ns + 1)
+    }
+
+    /// Deliver every period that arrives before `block_end_ns`, in the order it arrives.
+    fn deliver_until(&mut self, block_end_ns: u64, mixer: &mut Mixer) -> Result<(), MixerError> {
+        while self.next_arrival_ns < block_end_ns {
+            let arrival_ns = self.next_arrival_ns;
+            self.generator.render(&mut self.period);
+            // A full ring drops the period, as a real-time producer would.
+            self.ring.push(&self.period, Some(arrival_ns));
+            if self.clock.feedback
+                && let Some(counted) = self.counted_after(self.delivered)
+            {
+                // The producer stamps the period with its own clock, which counts nominal frames.
+                let source_ns = self.start_ns + (counted as f64 / self.sample_rate * 1e9) as u64;
+                mixer.submit_clock_feedback(self.handle, arrival_ns, source_ns)?;
+            }
+            self.delivered += 1;
+            // Late wake-ups never overtake the next one.
+            self.next_arrival_ns = self.arrival_ns(self.delivered).max(arrival_ns);
+        }
+        Ok(())
+    }
+}
+
+impl Feed {
+    fn frames(&self) -> usize {
+        self.samples.len() / MIX_CHANNELS
+    }
+
+    /// Push every chunk due to start before `block_end`, stopping early if the ring is full.
+    fn push_until(&mut self, block_end: u64, clock: &VirtualClock) {
+        let total = self.frames();
+        while self.pushed < total {
+            let frame = self.origin_frame + self.pushed as u64;
+            if frame >= block_end {
+                break;
+            }
+            // Chunks keep their size even when the input starts mid-block.
+            let chunk_start = self.pushed - self.pushed % self.chunk_frames;
+            let chunk_end = (chunk_start + self.chunk_frames).min(total);
+            let samples = &self.samples[self.pushed * MIX_CHANNELS..chunk_end * MIX_CHANNELS];
+            let written = self.ring.push(samples, Some(clock.ns_at(frame)));
+            self.pushed += written;
+            if self.pushed < chunk_end {
+                break;
+            }
+        }
+    }
+}
+
+/// How far a render strays from a reference render of the same scenario.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    /// Largest absolute difference between corresponding samples.
+    pub max_abs_error: f32,
+    /// Reference power over difference power in dB; infinite when the renders are identical.
+    pub snr_db: f32,
+    /// Normalised correlation of the two renders, from -1 to 1.
+    pub correlation: f32,
+}
+
+/// Limits a [`Comparison`] must stay within for a render to count as unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// Largest acceptable absolute sample difference.
+    pub max_abs_error: f32,
+    /// Smallest acceptable signal-to-noise ratio in dB.
+    pub min_snr_db: f32,
+    /// Smallest acceptable correlation.
+    pub min_correlation: f32,
+}
+
+impl Tolerance {
+    /// Limits that absorb float rounding differences between platforms and compilers but catch
+    /// any audible change.
+    pub const fn strict() -> Self {
+        Self {
+            max_abs_error: 1e-5,
+            min_snr_db: 90.0,
+            min_correlation: 0.999_999,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+impl Comparison {
+    /// Compare `actual` against `reference` sample by sample. Samples past the end of the
+    /// shorter render count as silence in it.
+    pub fn between(reference: &[f32], actual: &[f32]) -> Self {
+        let len = reference.len().max(actual.len());
+        let sample =
+            |samples: &[f32], index: usize| f64::from(samples.get(index).copied().unwrap_or(0.0));
+        let mut max_abs_error = 0.0f64;
+        let mut reference_power = 0.0f64;
+        let mut actual_power = 0.0f64;
+        let mut error_power = 0.0f64;
+        let mut dot = 0.0f64;
+        for index in 0..len {
+            let expected = sample(reference, index);
+            let got = sample(actual, index);
+            let error = got - expected;
+            max_abs_error = max_abs_error.max(error.abs());
+            reference_power += expected * expected;
+            actual_power += got * got;
+            error_power += error * error;
+            dot += expected * got;
+        }
+        let snr_db = if error_power == 0.0 {
+            f32::INFINITY
+        } else {
+            (10.0 * (reference_power / error_power).log10()) as f32
+        };
+        let correlation = if reference_power == 0.0 && actual_power == 0.0 {
+            1.0
+        } else if reference_power == 0.0 || actual_power == 0.0 {
+            0.0
+        } else {
+            (dot / (reference_power * actual_power).sqrt()) as f32
+        };
+        Self {
+            max_abs_error: max_abs_error as f32,
+            snr_db,
+            correlation,
+        }
+    }
+
+    /// Whether every metric is within `tolerance`.
+    pub fn is_within(&self, tolerance: &Tolerance) -> bool {
+        self.max_abs_error <= tolerance.max_abs_error
+            && self.snr_db >= tolerance.min_snr_db
+            && self.correlation >= tolerance.min_correlation
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "max abs error {:.3e}, SNR {:.1} dB, correlation {:.6}",
+            self.max_abs_error, self.snr_db, self.correlation
+        )
+    }
+}
+
+/// A render described by a session file; see the [module documentation](self) for the format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderSession {
+    /// Rate the mixer renders at.
+    pub sample_rate: u32,
+    /// Frames rendered per block.
+    pub block_frames: usize,
+    /// Length of the render, or `None` to stop once every input has played out.
+    pub duration_seconds: Option<f64>,
+    /// Sources in the order they are added to the mixer.
+    pub sources: Vec<SessionSource>,
+}
+
+/// One `[[source]]` of a [`RenderSession`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionSource {
+    /// Audio the source plays.
+    pub input: SessionInput,
+    /// When the source starts, in seconds from the beginning of the render.
+    pub start_seconds: f64,
+    /// Producer period in frames, or `None` to push one mixer block at a time.
+    pub chunk_frames: Option<usize>,
+    /// Source gain in dB.
+    pub gain_db: f32,
+    /// Whether the source starts muted.
+    pub muted: bool,
+    /// Latency compensation in frames; see [`Mixer::set_latency`].
+    pub latency_frames: i32,
+}
+
+/// Where a [`SessionSource`] gets its audio.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionInput {
+    /// A WAV or FLAC file.
+    File(PathBuf),
+    /// A synthesised signal lasting `length_seconds`, or the whole render when `None`.
+    Generator {
+        /// Signal to synthesise.
+        config: GeneratorConfig,
+        /// How long the signal lasts.
+        length_seconds: Option<f64>,
+    },
+}
+
+impl RenderSession {
+    /// Read the session file at `path`. File inputs are resolved against its directory.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, OfflineError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parse a session from `text`, resolving file inputs against `base_dir`.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Self, OfflineError> {
+        let file: SessionFile = toml::from_str(text).map_err(|err| {
+            let line = err.span().map_or(1, |span| line_at(text, span.start));
+            let message: Vec<_> = err.message().lines().collect();
+            session_error(line, message.join("; "))
+        })?;
+        let settings = file.session.unwrap_or_default();
+        let mut session = Self {
+            sample_rate: DEFAULT_SESSION_SAMPLE_RATE,
+            block_frames: DEFAULT_SESSION_BLOCK_FRAMES,
+            duration_seconds: None,
+            sources: Vec::with_capacity(file.source.len()),
+        };
+        if let Some(rate) = &settings.sample_rate {
+            session.sample_rate = integer(text, "sample_rate", rate, 1, 768_000)? as u32;
+        }
+        if let Some(frames) = &settings.block_frames {
+            session.block_frames = integer(
+                text,
+                "block_frames",
+                frames,
+                1,
+                MAX_SESSION_BLOCK_FRAMES as i64,
+            )? as usize;
+        }
+        if let Some(duration) = &settings.duration {
+            session.duration_seconds = Some(seconds(text, "duration", duration)?);
+        }
+        for table in &file.source {
+            let source = SessionSource::from_table(text, table, base_dir)?;
+            if session.duration_seconds.is_none()
+                && matches!(
+                    source.input,
+                    SessionInput::Generator {
+                        length_seconds: None,
+                        ..
+                    }
+                )
+            {
+                return Err(session_error(
+                    line_at(text, table.span().start),
+                    "a generator needs a length when the session has no duration",
+                ));
+            }
+            session.sources.push(source);
+        }
+        Ok(session)
+    }
+
+    /// Build the renderer and work out how many frames to render.
+    pub fn renderer(&self) -> Result<(OfflineRenderer, usize), OfflineError> {
+        let mut renderer = OfflineRenderer::new(self.sample_rate, self.block_frames);
+        let mut tail = 0usize;
+        for source in &self.sources {
+            let input = match &source.input {
+                SessionInput::File(path) => OfflineInput::from_file(path, self.sample_rate)
+                    .map_err(|err| {
+                        io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+                    })?,
+                SessionInput::Generator {
+                    config,
+                    length_seconds,
+                } => {
+                    let seconds = length_seconds.or(self.duration_seconds).unwrap_or(0.0);
+                    OfflineInput::from_generator(*config, self.sample_rate, seconds)
+                }
+            };
+            let mut input = input.starting_at_frame(self.frames_at(source.start_seconds) as u64);
+            if let Some(chunk_frames) = source.chunk_frames {
+                input = input.in_chunks_of(chunk_frames);
+            }
+            let handle = renderer.add_input(input);
+            let mixer = renderer.mixer_mut();
+            mixer.set_gain(handle, 10f32.powf(source.gain_db / 20.0))?;
+            mixer.set_mute(handle, source.muted)?;
+            mixer.set_latency(handle, source.latency_frames)?;
+            tail = tail.max(source.latency_frames.max(0) as usize);
+        }
+        let frames = match self.duration_seconds {
+            Some(seconds) => self.frames_at(seconds),
+            // One extra block lets the last input drain through the interpolator.
+            None => renderer.inputs_end_frame() as usize + tail + self.block_frames,
+        };
+        Ok((renderer, frames))
+    }
+
+    /// Render the session and return the mix as interleaved stereo.
+    pub fn render(&self) -> Result<Vec<f32>, OfflineError> {
+        let (mut renderer, frames) = self.renderer()?;
+        Ok(renderer.render(frames)?)
+    }
+
+    /// Render the session into a WAV file at `path`.
+    pub fn render_to_wav(
+        &self,
+        path: impl AsRef<Path>,
+        format: SampleFormat,
+    ) -> Result<(), OfflineError> {
+        let (mut renderer, frames) = self.renderer()?;
+        renderer.render_to_wav(path, frames, format)
+    }
+
+    fn frames_at(&self, seconds: f64) -> usize {
+        (seconds * self.sample_rate as f64).round() as usize
+    }
+}
+
+impl SessionSource {
+    fn from_table(
+        text: &str,
+        table: &Spanned<SourceTable>,
+        base_dir: &Path,
+    ) -> Result<Self, OfflineError> {
+        let raw = table.get_ref();
+        let optional = |key, value: &Option<Spanned<f64>>| {
+            value
+                .as_ref()
+                .map(|value| positive(text, key, value))
+                .transpose()
+        };
+        let frequency = optional("frequency", &raw.frequency)?.map(|value| value as f32);
+        let end_frequency =
+            optional("end_frequency", &raw.end_frequency)?.map(|value| value as f32);
+        let period = optional("period", &raw.period)?.map(|value| value as f32);
+        let length_seconds = raw
+            .length
+            .as_ref()
+            .map(|length| seconds(text, "length", length))
+            .transpose()?;
+        let mut source = Self {
+            input: SessionInput::File(PathBuf::new()),
+            start_seconds: 0.0,
+            chunk_frames: None,
+            gain_db: 0.0,
+            muted: raw.muted.unwrap_or(false),
+            latency_frames: 0,
+        };
+        if let Some(start) = &raw.start {
+            source.start_seconds = number(text, "start", start)?;
+            if source.start_seconds < 0.0 {
+                return Err(session_error(
+                    line_at(text, start.span().start),
+                    "start must not be negative",
+                ));
+            }
+            if source.start_seconds > MAX_SESSION_SECONDS {
+                return Err(expected(
+                    text,
+                    "start",
+                    start.span(),
+                    &format!("at most {MAX_SESSION_SECONDS} seconds"),
+                ));
+            }
+        }
+        if let Some(frames) = &raw.chunk_frames {
+            source.chunk_frames = Some(integer(
+                text,
+                "chunk_frames",
+                frames,
+                1,
+                MAX_SESSION_BLOCK_FRAMES as i64,
+            )? as usize);
+        }
+        if let Some(gain) = &raw.gain_db {
+            source.gain_db = number(text, "gain_db", gain)? as f32;
+        }
+        if let Some(frames) = &raw.latency_frames {
+            source.latency_frames = integer(
+                text,
+                "latency_frames",
+                frames,
+                i32::MIN as i64,
+                i32::MAX as i64,
+            )? as i32;
+        }
+        source.input = match (&raw.file, &raw.generator) {
+            (Some(path), None) => SessionInput::File(base_dir.join(path.get_ref())),
+            (None, Some(name)) => {
+                let line = line_at(text, name.span().start);
+                let name = name.get_ref();
+                let waveform = Waveform::from_name(name, frequency, end_frequency, period)
+                    .ok_or_else(|| {
+                        session_error(
+                            line,
+                            format!(
+                                "unknown generator \"{name}\", expected sine, sweep, white, pink, impulse or reference"
+                            ),
+                        )
+                    })?;
+                let mut config = GeneratorConfig::new(waveform);
+                if let Some(level) = &raw.level {
+                    config.level_dbfs = number(text, "level", level)? as f32;
+                }
+                if !config.is_valid() {
+                    return Err(session_error(
+                        line,
+                        format!("invalid generator {config}; the level must be at most 0 dBFS"),
+                    ));
+                }
+                SessionInput::Generator {
+                    config,
+                    length_seconds,
+                }
+            }
+            _ => {
+                return Err(session_error(
+                    line_at(text, table.span().start),
+                    "a source needs exactly one of file or generator",
+                ));
+            }
+        };
+        Ok(source)
+    }
+}
+
+/// A session file as written, before its values are checked.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionFile {
+    session: Option<SessionTable>,
+    #[serde(default)]
+    source: Vec<Spanned<SourceTable>>,
+}
+
+/// The `[session]` table of a session file.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionTable {
+    sample_rate: Option<Spanned<i64>>,
+    block_frames: Option<Spanned<i64>>,
+    duration: Option<Spanned<f64>>,
+}
+
+/// A `[[source]]` table of a session file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SourceTable {
+    file: Option<Spanned<String>>,
+    generator: Option<Spanned<String>>,
+    frequency: Option<Spanned<f64>>,
+    end_frequency: Option<Spanned<f64>>,
+    period: Option<Spanned<f64>>,
+    level: Option<Spanned<f64>>,
+    length: Option<Spanned<f64>>,
+    start: Option<Spanned<f64>>,
+    chunk_frames: Option<Spanned<i64>>,
+    gain_db: Option<Spanned<f64>>,
+    muted: Option<bool>,
+    latency_frames: Option<Spanned<i64>>,
+}
+
+fn session_error(line: usize, message: impl fmt::Display) -> OfflineError {
+    OfflineError::Session(format!("line {line}: {message}"))
+}
+
+/// One-based line of the byte at `offset` in `text`.
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+fn number(text: &str, key: &str, value: &Spanned<f64>) -> Result<f64, OfflineError> {
+    match *value.get_ref() {
+        number if number.is_finite() => Ok(number),
+        _ => Err(expected(text, key, value.span(), "a finite number")),
+    }
+}
+
+fn positive(text: &str, key: &str, value: &Spanned<f64>) -> Result<f64, OfflineError> {
+    match *value.get_ref() {
+        number if number.is_finite() && number > 0.0 => Ok(number),
+        _ => Err(expected(text, key, value.span(), "a positive number")),
+    }
+}
+
+/// A positive number of seconds no longer than [`MAX_SESSION_SECONDS`].
+fn seconds(text: &str, key: &str, value: &Spanned<f64>) -> Result<f64, OfflineError> {
+    match positive(text, key, value)? {
+        seconds if seconds <= MAX_SESSION_SECONDS => Ok(seconds),
+        _ => Err(expected(
+            text,
+            key,
+            value.span(),
+            &format!("at most {MAX_SESSION_SECONDS} seconds"),
+        )),
+    }
+}
+
+fn integer(
+    text: &str,
+    key: &str,
+    value: &Spanned<i64>,
+    min: i64,
+    max: i64,
+) -> Result<i64, OfflineError> {
+    match *value.get_ref() {
+        integer if (min..=max).contains(&integer) => Ok(integer),
+        _ => Err(expected(
+            text,
+            key,
+            value.span(),
+            &format!("an integer from {min} to {max}"),
+        )),
+    }
+}
+
+fn expected(text: &str, key: &str, span: Range<usize>, what: &str) -> OfflineError {
+    session_error(line_at(text, span.start), format!("{key} must be {what}"))
+}
@@ -0,0 +1,564 @@
+//! File-player sources: WAV or FLAC files streamed from disk into the mix.
+//!
+//! A [`FilePlayer`] owns a background decoder thread that reads the file with
+//! [`WavReader`](crate::wav::WavReader) or [`FlacReader`](crate::flac::FlacReader), converts it
+//! to stereo at the mixer rate and keeps the source's [`SharedRingBuffer`] topped up with
+//! [`PLAYER_BUFFER_MS`] of audio. Mono files are copied to both sides and files with more than
+//! two channels play their first two.
+//!
+//! Transport state lives in atomics shared with the render thread. While paused the render thread
+//! leaves the ring untouched, so playback resumes on the exact frame it stopped at. A seek is
+//! carried out by the decoder thread, which then asks the render thread to discard the frames
+//! queued before it. When the file ends the queued tail still plays, after which the player
+//! stops and reports itself finished; with looping enabled the decoder wraps to the start instead.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::MIX_CHANNELS;
+use crate::flac::FlacReader;
+use crate::ring::SharedRingBuffer;
+use crate::wav::WavReader;
+
+/// Audio the decoder keeps queued ahead of the render thread.
+pub const PLAYER_BUFFER_MS: u32 = 250;
+/// How often the decoder thread checks for room in the ring.
+pub const DECODER_POLL_MS: u64 = 5;
+
+const DECODE_CHUNK_FRAMES: usize = 1_024;
+const NO_SEEK: u64 = u64::MAX;
+
+/// Why a file could not be loaded into a player source.
+#[derive(Debug, thiserror::Error)]
+pub enum PlayerError {
+    /// No mixer is active to play into.
+    #[error("no active mixer")]
+    NoMixer,
+    /// The requested source index already belongs to another source.
+    #[error("source {0} is already in use")]
+    SourceInUse(u32),
+    /// Opening or decoding the file failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Transport state and progress of a file player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerStatus {
+    /// File being played.
+    pub path: PathBuf,
+    /// Frame rate of the file.
+    pub file_sample_rate: u32,
+    /// Channels in the file.
+    pub file_channels: u16,
+    /// Length of the file in seconds, or 0 when the file does not declare it.
+    pub duration_seconds: f64,
+    /// Position of the audio currently being rendered, in seconds.
+    pub position_seconds: f64,
+    /// Decoded audio queued ahead of the render position, in seconds.
+    pub buffered_seconds: f64,
+    /// Whether the player is running.
+    pub playing: bool,
+    /// Whether the player wraps to the start at the end of the file.
+    pub looping: bool,
+    /// Whether playback ran to the end of the file and stopped.
+    pub finished: bool,
+    /// Decoding error that stopped playback, if any.
+    pub error: Option<String>,
+}
+
+/// Player state reported across the FFI boundary.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoopbackFileStatus {
+    /// Position of the audio currently being rendered, in seconds.
+    pub position_seconds: f64,
+    /// Length of the file in seconds, or 0 when unknown.
+    pub duration_seconds: f64,
+    /// Whether the player is running.
+    pub playing: bool,
+    /// Whether the player wraps to the start at the end of the file.
+    pub looping: bool,
+    /// Whether playback ran to the end of the file and stopped.
+    pub finished: bool,
+    /// Whether a decoding error stopped playback.
+    pub failed: bool,
+}
+
+impl From<&PlayerStatus> for LoopbackFileStatus {
+    fn from(status: &PlayerStatus) -> Self {
+        Self {
+            position_seconds: status.position_seconds,
+            duration_seconds: status.duration_seconds,
+            playing: status.playing,
+            looping: status.looping,
+            finished: status.finished,
+            failed: status.error.is_some(),
+        }
+    }
+}
+
+/// A WAV or FLAC file being decoded.
+enum Decoder {
+    Wav(WavReader<BufReader<File>>),
+    Flac(FlacReader<BufReader<File>>),
+}
+
+impl Decoder {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+        match &magic {
+            b"fLaC" => Ok(Self::Flac(FlacReader::new(file)?)),
+            b"RIFF" | b"RF64" => Ok(Self::Wav(WavReader::new(file)?)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a WAV or FLAC file",
+            )),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Wav(reader) => reader.spec().sample_rate,
+            Self::Flac(reader) => reader.sample_rate(),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        match self {
+            Self::Wav(reader) => reader.spec().channels,
+            Self::Flac(reader) => reader.channels(),
+        }
+    }
+
+    fn frames(&self) -> u64 {
+        match self {
+            Self::Wav(reader) => reader.frames(),
+            Self::Flac(reader) => reader.frames(),
+        }
+    }
+
+    fn read_samples(&mut self, out: &mut [f32]) -> io::Result<usize> {
+        match self {
+            Self::Wav(reader) => reader.read_samples(out),
+            Self::Flac(reader) => reader.read_samples(out),
+        }
+    }
+
+    fn seek(&mut self, frame: u64) -> io::Result<()> {
+        match self {
+            Self::Wav(reader) => reader.seek(frame),
+            Self::Flac(reader) => reader.seek(frame),
+        }
+    }
+}
+
+/// Linear-interpolating rate converter for interleaved stereo.
+struct Resampler {
+    step: f64,
+    phase: f64,
+    previous: [f32; 2],
+}
+
+impl Resampler {
+    fn new(input_rate: u32, output_rate: u32) -> Self {
+        Self {
+            step: input_rate as f64 / output_rate as f64,
+            phase: 0.0,
+            previous: [0.0; 2],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.previous = [0.0; 2];
+    }
+
+    /// Append the converted `input` to `out`.
+    fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        if self.step == 1.0 {
+            out.extend_from_slice(input);
+            return;
+        }
+        let frames = input.len() / MIX_CHANNELS;
+        if frames == 0 {
+            return;
+        }
+        // Input frame `index`, counting the last frame of the previous call as 0.
+        let previous = self.previous;
+        let frame = |index: usize| -> [f32; 2] {
+            if index == 0 {
+                previous
+            } else {
+                let base = (index - 1) * MIX_CHANNELS;
+                [input[base], input[base + 1]]
+            }
+        };
+        let mut phase = self.phase;
+        while phase < frames as f64 {
+            let index = phase as usize;
+            let t = (phase - index as f64) as f32;
+            let (a, b) = (frame(index), frame(index + 1));
+            out.push(a[0] + (b[0] - a[0]) * t);
+            out.push(a[1] + (b[1] - a[1]) * t);
+            phase += self.step;
+        }
+        self.phase = phase - frames as f64;
+        self.previous = frame(frames);
+    }
+}
+
+/// State shared by the control side, the decoder thread and the render thread.
+struct Shared {
+    playing: AtomicBool,
+    looping: AtomicBool,
+    /// The decoder reached the end of the file; the ring holds the last of it.
+    ended: AtomicBool,
+    /// The decoder has queued audio, or the end of the file, since opening or the last seek.
+    primed: AtomicBool,
+    finished: AtomicBool,
+    stop: AtomicBool,
+    /// File frame to seek to, or [`NO_SEEK`].
+    seek_to: AtomicU64,
+    /// File frame after the last one decoded.
+    decoded_frames: AtomicU64,
+    /// Decoded output frames still waiting for room in the ring.
+    unpushed_frames: AtomicU64,
+    /// Stale frames the render thread must drop after a seek.
+    flush_frames: AtomicU64,
+    error: Mutex<Option<String>>,
+}
+
+/// Decoder thread and transport controls of a file source.
+pub(crate) struct FilePlayer {
+    shared: Arc<Shared>,
+    ring: Arc<SharedRingBuffer>,
+    path: PathBuf,
+    file_sample_rate: u32,
+    file_channels: u16,
+    file_frames: u64,
+    sample_rate: u32,
+    decoder: Option<JoinHandle<()>>,
+}
+
+impl FilePlayer {
+    /// Ring capacity in frames for a player rendering at `sample_rate`.
+    pub(crate) fn ring_frames(sample_rate: u32) -> usize {
+        (sample_rate as usize * PLAYER_BUFFER_MS as usize / 1_000).max(DECODE_CHUNK_FRAMES * 2)
+    }
+
+    /// Open `path` and start a paused decoder thread feeding `ring` at `sample_rate`.
+    pub(crate) fn open(
+        path: &Path,
+        ring: Arc<SharedRingBuffer>,
+        sample_rate: u32,
+    ) -> Result<Self, PlayerError> {
+        let mut decoder = Decoder::open(path)?;
+        let file_sample_rate = decoder.sample_rate();
+        let file_channels = decoder.channels();
+        let file_frames = decoder.frames();
+        let shared = Arc::new(Shared {
+            playing: AtomicBool::new(false),
+            looping: AtomicBool::new(false),
+            ended: AtomicBool::new(false),
+            primed: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+            seek_to: AtomicU64::new(NO_SEEK),
+            decoded_frames: AtomicU64::new(0),
+            unpushed_frames: AtomicU64::new(0),
+            flush_frames: AtomicU64::new(0),
+            error: Mutex::new(None),
+        });
+
+        let thread_shared = shared.clone();
+        let thread_ring = ring.clone();
+        let handle = thread::Builder::new()
+            .name("loopback-player".to_string())
+            .spawn(move || {
+                let mut resampler = Resampler::new(file_sample_rate, sample_rate);
+                let result =
+                    decode_loop(&mut decoder, &mut resampler, &thread_ring, &thread_shared);
+                if let Err(err) = result {
+                    *thread_shared.error.lock() = Some(err.to_string());
+                    thread_shared.ended.store(true, Ordering::Release);
+                    thread_shared.primed.store(true, Ordering::Release);
+                }
+            })?;
+
+        Ok(Self {
+            shared,
+            ring,
+            path: path.to_path_buf(),
+            file_sample_rate,
+            file_channels,
+            file_frames,
+            sample_rate,
+            decoder: Some(handle),
+        })
+    }
+
+    /// Start or resume playback, from the beginning if the file had finished.
+    pub(crate) fn play(&self) {
+        if self.shared.finished.swap(false, Ordering::AcqRel) {
+            self.seek_frame(0);
+        }
+        self.shared.playing.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn pause(&self) {
+        self.shared.playing.store(false, Ordering::Release);
+    }
+
+    /// Jump to `seconds` into the file, clamped to its length.
+    pub(crate) fn seek(&self, seconds: f64) {
+        let mut frame = (seconds.max(0.0) * self.file_sample_rate as f64) as u64;
+        if self.file_frames > 0 {
+            frame = frame.min(self.file_frames);
+        }
+        self.shared.finished.store(false, Ordering::Release);
+        self.seek_frame(frame);
+    }
+
+    fn seek_frame(&self, frame: u64) {
+        self.shared.seek_to.store(frame, Ordering::Release);
+    }
+
+    pub(crate) fn set_looping(&self, looping: bool) {
+        self.shared.looping.store(looping, Ordering::Release);
+    }
+
+    /// Called by the render thread before it reads the ring. Drops frames made stale by a seek
+    /// and returns whether the source should read the ring this block; the block is silent while
+    /// a seek is being carried out.
+    pub(crate) fn admit(&self) -> bool {
+        let flush = self.shared.flush_frames.swap(0, Ordering::AcqRel);
+        if flush > 0 {
+            // The decoder refills from the new position once the stale queue is gone.
+            self.ring.discard(flush as usize);
+            return false;
+        }
+        if !self.shared.playing.load(Ordering::Acquire)
+            || self.shared.seek_to.load(Ordering::Acquire) != NO_SEEK
+        {
+            // Nothing queued is worth playing while a seek is on its way to the decoder.
+            return false;
+        }
+        if !self.shared.primed.load(Ordering::Acquire) {
+            // Still waiting for the first audio from the new position; not an underrun.
+            return false;
+        }
+        if self.shared.ended.load(Ordering::Acquire) && self.ring.available_read() == 0 {
+            self.shared.playing.store(false, Ordering::Release);
+            self.shared.finished.store(true, Ordering::Release);
+            return false;
+        }
+        true
+    }
+
+    /// Whether the decoder has queued the end of the file, so a short ring is expected.
+    pub(crate) fn is_ending(&self) -> bool {
+        self.shared.ended.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn status(&self) -> PlayerStatus {
+        let file_rate = self.file_sample_rate.max(1) as f64;
+        let seek_to = self.shared.seek_to.load(Ordering::Acquire);
+        // Frames queued before a seek are about to be flushed and no longer count.
+        let (buffered, unpushed) = if seek_to == NO_SEEK {
+            let flush = self.shared.flush_frames.load(Ordering::Acquire) as usize;
+            let unpushed = self.shared.unpushed_frames.load(Ordering::Acquire) as usize;
+            (self.ring.available_read().saturating_sub(flush), unpushed)
+        } else {
+            (0, 0)
+        };
+        let render_rate = self.sample_rate.max(1) as f64;
+        let buffered_seconds = buffered as f64 / render_rate;
+        let queued = (buffered + unpushed) as f64 * file_rate / render_rate;
+        let decoded = if seek_to == NO_SEEK {
+            self.shared.decoded_frames.load(Ordering::Acquire)
+        } else {
+            seek_to
+        } as f64;
+        PlayerStatus {
+            path: self.path.clone(),
+            file_sample_rate: self.file_sample_rate,
+            file_channels: self.file_channels,
+            duration_seconds: self.file_frames as f64 / file_rate,
+            position_seconds: (decoded - queued).max(0.0) / file_rate,
+            buffered_seconds,
+            playing: self.shared.playing.load(Ordering::Acquire),
+            looping: self.shared.looping.load(Ordering::Acquire),
+            finished: self.shared.finished.load(Ordering::Acquire),
+            error: self.shared.error.lock().clone(),
+        }
+    }
+}
+
+impl Drop for FilePlayer {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.decoder.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Keep `ring` filled from `decoder` until asked to stop.
+fn decode_loop(
+    decoder: &mut Decoder,
+    resampler: &mut Resampler,
+    ring: &SharedRingBuffer,
+    shared: &Shared,
+) -> io::Result<()> {
+    let channels = decoder.channels() as usize;
+    let mut input = vec![0.0f32; DECODE_CHUNK_FRAMES * channels];
+    let mut stereo = Vec::with_capacity(DECODE_CHUNK_FRAMES * MIX_CHANNELS);
+    let mut output = Vec::new();
+    let mut pending = 0usize;
+    // Whether anything was decoded since the last wrap, so an empty file cannot loop forever.
+    let mut decoded_since_wrap = false;
+
+    while !shared.stop.load(Ordering::Acquire) {
+        let target = shared.seek_to.load(Ordering::Acquire);
+        if target != NO_SEEK {
+            // Clear `ended` while the request is still pending, so the render thread cannot take
+            // the old end of file for the end of the new position.
+            shared.ended.store(false, Ordering::Release);
+            shared.primed.store(false, Ordering::Release);
+            decoder.seek(target)?;
+            resampler.reset();
+            output.clear();
+            pending = 0;
+            shared.unpushed_frames.store(0, Ordering::Release);
+            shared.decoded_frames.store(target, Ordering::Release);
+            shared
+                .flush_frames
+                .store(ring.available_read() as u64, Ordering::Release);
+            // A newer request stays queued for the next pass.
+            let _ = shared.seek_to.compare_exchange(
+                target,
+                NO_SEEK,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+        }
+
+        if shared.flush_frames.load(Ordering::Acquire) > 0 {
+            // Wait for the render thread to drop the audio queued before the seek.
+            thread::sleep(Duration::from_millis(DECODER_POLL_MS));
+            continue;
+        }
+        // Push whatever the last pass could not fit before decoding more.
+        if pending < output.len() {
+            let written = ring.push(&output[pending..], None);
+            pending += written * MIX_CHANNELS;
+            if written > 0 {
+                shared.primed.store(true, Ordering::Release);
+            }
+            shared.unpushed_frames.store(
+                ((output.len() - pending) / MIX_CHANNELS) as u64,
+                Ordering::Release,
+            );
+            if pending < output.len() {
+                thread::sleep(Duration::from_millis(DECODER_POLL_MS));
+                continue;
+            }
+        }
+        let looping = shared.looping.load(Ordering::Acquire) && decoded_since_wrap;
+        if shared.ended.load(Ordering::Acquire) {
+            // Looping may be switched on after the end was queued but before it played out.
+            if looping && !shared.finished.load(Ordering::Acquire) {
+                shared.ended.store(false, Ordering::Release);
+            } else {
+                thread::sleep(Duration::from_millis(DECODER_POLL_MS));
+                continue;
+            }
+        }
+
+        let count = decoder.read_samples(&mut input)?;
+        if count == 0 {
+            if looping {
+                decoded_since_wrap = false;
+                decoder.seek(0)?;
+                shared.decoded_frames.store(0, Ordering::Release);
+            } else {
+                shared.ended.store(true, Ordering::Release);
+                shared.primed.store(true, Ordering::Release);
+            }
+            continue;
+        }
+        decoded_since_wrap = true;
+        let frames = count / channels;
+        stereo.clear();
+        fold_to_stereo(&input[..count], channels, &mut stereo);
+        output.clear();
+        pending = 0;
+        resampler.process(&stereo, &mut output);
+        shared
+            .unpushed_frames
+            .store((output.len() / MIX_CHANNELS) as u64, Ordering::Release);
+        shared
+            .decoded_frames
+            .fetch_add(frames as u64, Ordering::AcqRel);
+    }
+    Ok(())
+}
+
+/// Append `input`, interleaved with `channels` channels, to `out` as stereo.
+fn fold_to_stereo(input: &[f32], channels: usize, out: &mut Vec<f32>) {
+    for frame in input.chunks_exact(channels) {
+        let right = if channels == 1 { frame[0] } else { frame[1] };
+        out.extend_from_slice(&[frame[0], right]);
+    }
+}
+
+/// Decode all of `path` to interleaved stereo at `sample_rate`, failing once the result would
+/// exceed `max_frames` frames.
+pub(crate) fn decode_file(
+    path: &Path,
+    sample_rate: u32,
+    max_frames: usize,
+) -> io::Result<Vec<f32>> {
+    let too_long = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "longer than {:.1} s",
+                max_frames as f64 / sample_rate.max(1) as f64
+            ),
+        )
+    };
+    let mut decoder = Decoder::open(path)?;
+    let channels = decoder.channels() as usize;
+    let mut resampler = Resampler::new(decoder.sample_rate(), sample_rate);
+    let declared = decoder.frames() as f64 * sample_rate as f64 / decoder.sample_rate() as f64;
+    if declared.ceil() > max_frames as f64 {
+        return Err(too_long());
+    }
+    let mut input = vec![0.0f32; DECODE_CHUNK_FRAMES * channels];
+    let mut stereo = Vec::with_capacity(DECODE_CHUNK_FRAMES * MIX_CHANNELS);
+    let mut output = Vec::with_capacity(declared.ceil() as usize * MIX_CHANNELS);
+    loop {
+        let count = decoder.read_samples(&mut input)?;
+        if count == 0 {
+            return Ok(output);
+        }
+        stereo.clear();
+        fold_to_stereo(&input[..count], channels, &mut stereo);
+        resampler.process(&stereo, &mut output);
+        if output.len() > max_frames * MIX_CHANNELS {
+            return Err(too_long());
+        }
+    }
+}
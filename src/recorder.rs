@@ -0,0 +1,479 @@
+//! Recording of the master output and per-source stems to WAV/RF64 files.
+//!
+//! The render thread copies each finished block into a [`RecordTap`], a lock-free
+//! single-producer ring sized for [`RECORD_BUFFER_SECONDS`] of audio. A background writer thread
+//! drains the rings every [`WRITER_POLL_MS`] and encodes the samples with a
+//! [`WavWriter`](crate::wav::WavWriter), so disk latency never reaches the callback. If the writer
+//! falls so far behind that a ring fills, the render thread drops the excess frames and counts
+//! them instead of waiting. Stopping a recording drains what is left and finalises the headers.
+//!
+//! A multitrack session records the master to [`MASTER_FILE_NAME`] and each source's post-fader
+//! contribution to the mix to [`stem_file_name`] in one directory. Every tap receives exactly one
+//! block per render callback, padded with silence when a source rendered less, so all files stay
+//! sample-aligned. Stopping the session writes a [`MANIFEST_FILE_NAME`] describing the tracks.
+
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::ring::SharedRingBuffer;
+use crate::wav::{SampleFormat, WavSpec, WavWriter};
+use crate::{MIX_CHANNELS, MixerError};
+
+/// Audio the tap buffers while the writer thread catches up.
+pub const RECORD_BUFFER_SECONDS: usize = 2;
+/// How often the writer thread drains the tap.
+pub const WRITER_POLL_MS: u64 = 10;
+
+/// File name of the master track in a multitrack session directory.
+pub const MASTER_FILE_NAME: &str = "master.wav";
+/// File name of the manifest written when a multitrack session stops.
+pub const MANIFEST_FILE_NAME: &str = "session.json";
+
+/// Most frames the writer thread takes from a tap per write.
+pub(crate) const WRITER_CHUNK_FRAMES: usize = 4_096;
+
+/// File name of the stem recorded for the source with mixer id `source_id`.
+pub fn stem_file_name(source_id: u32) -> String {
+    format!("source-{source_id}.wav")
+}
+
+/// Why a recording could not be started or finished.
+#[derive(Debug, thiserror::Error)]
+pub enum RecorderError {
+    /// No mixer is active to record from.
+    #[error("no active mixer")]
+    NoMixer,
+    /// A recording is already running.
+    #[error("a recording is already running")]
+    AlreadyRecording,
+    /// No recording is running.
+    #[error("no recording is running")]
+    NotRecording,
+    /// Only [`SampleFormat::Float32`] and [`SampleFormat::Pcm24`] can be recorded.
+    #[error("cannot record {}", .0.name())]
+    UnsupportedFormat(SampleFormat),
+    /// Creating or writing the file failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The render thread could not be handed the recording's tap.
+    #[error(transparent)]
+    Mixer(#[from] MixerError),
+}
+
+/// Progress of a running recording, or the outcome of a finished one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordingStatus {
+    /// File being written.
+    pub path: PathBuf,
+    /// Sample encoding.
+    pub format: SampleFormat,
+    /// Frames written to the file.
+    pub frames: u64,
+    /// Frames lost because the writer fell behind.
+    pub dropped_frames: u64,
+    /// Host time of the first recorded block, in nanoseconds, or 0 before it arrives.
+    pub start_timestamp_ns: u64,
+}
+
+/// A source's track in a multitrack session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StemStatus {
+    /// Mixer id of the source.
+    pub source_id: u32,
+    /// Display name of the source.
+    pub name: String,
+    /// Fader gain when the session started.
+    pub gain_linear: f32,
+    /// Whether the source was muted when the session started.
+    pub muted: bool,
+    /// Progress of the stem file.
+    pub recording: RecordingStatus,
+}
+
+/// Progress of a multitrack session, or the outcome of a finished one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultitrackStatus {
+    /// Directory holding the session's files.
+    pub directory: PathBuf,
+    /// Frames per second of every track.
+    pub sample_rate: u32,
+    /// Sample encoding of every track.
+    pub format: SampleFormat,
+    /// The master track.
+    pub master: RecordingStatus,
+    /// One track per source present when the session started.
+    pub stems: Vec<StemStatus>,
+}
+
+impl MultitrackStatus {
+    /// The session manifest as JSON. File names are relative to [`directory`](Self::directory).
+    pub fn manifest_json(&self) -> String {
+        let mut json = String::new();
+        let _ = writeln!(json, "{{");
+        let _ = writeln!(json, "  \"sample_rate\": {},", self.sample_rate);
+        let _ = writeln!(json, "  \"format\": {},", json_string(self.format.name()));
+        let _ = writeln!(json, "  \"master\": {{{}}},", track_json(&self.master));
+        let _ = writeln!(json, "  \"sources\": [");
+        for (index, stem) in self.stems.iter().enumerate() {
+            let separator = if index + 1 < self.stems.len() {
+                ","
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                json,
+                "    {{\"id\": {}, \"name\": {}, \"gain_linear\": {}, \"gain_db\": {}, \"muted\": {}, {}}}{separator}",
+                stem.source_id,
+                json_string(&stem.name),
+                json_number(stem.gain_linear),
+                json_number(if stem.gain_linear > 0.0 {
+                    20.0 * stem.gain_linear.log10()
+                } else {
+                    f32::NEG_INFINITY
+                }),
+                stem.muted,
+                track_json(&stem.recording),
+            );
+        }
+        let _ = writeln!(json, "  ]");
+        let _ = writeln!(json, "}}");
+        json
+    }
+}
+
+fn track_json(track: &RecordingStatus) -> String {
+    let file = track
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!(
+        "\"file\": {}, \"frames\": {}, \"dropped_frames\": {}, \"start_timestamp_ns\": {}",
+        json_string(&file),
+        track.frames,
+        track.dropped_frames,
+        track.start_timestamp_ns
+    )
+}
+
+/// `value` as a JSON number, or `null` when it has no JSON representation.
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{value}")
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Render-side end of a recording.
+pub(crate) struct RecordTap {
+    ring: SharedRingBuffer,
+    dropped: AtomicU64,
+    start_timestamp_ns: AtomicU64,
+}
+
+impl RecordTap {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            ring: SharedRingBuffer::new_local(
+                sample_rate as usize * RECORD_BUFFER_SECONDS,
+                MIX_CHANNELS,
+            ),
+            dropped: AtomicU64::new(0),
+            start_timestamp_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Queue an interleaved stereo block stamped `timestamp_ns`. Called from the render thread.
+    pub(crate) fn write(&self, block: &[f32], timestamp_ns: u64) {
+        let _ = self.start_timestamp_ns.compare_exchange(
+            0,
+            timestamp_ns.max(1),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+        let frames = block.len() / MIX_CHANNELS;
+        let written = self.ring.push(block, Some(timestamp_ns));
+        if written < frames {
+            self.dropped
+                .fetch_add((frames - written) as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn start_timestamp_ns(&self) -> u64 {
+        self.start_timestamp_ns.load(Ordering::Relaxed)
+    }
+}
+
+/// A running recording: one tap per track and the thread writing them all out.
+pub(crate) struct Recording {
+    tracks: Vec<Track>,
+    format: SampleFormat,
+    stop: Arc<AtomicBool>,
+    writer: Option<JoinHandle<io::Result<()>>>,
+}
+
+struct Track {
+    tap: Arc<RecordTap>,
+    path: PathBuf,
+    frames: Arc<AtomicU64>,
+}
+
+impl Recording {
+    /// Create one file per path and start the writer thread.
+    pub(crate) fn start(
+        paths: &[PathBuf],
+        sample_rate: u32,
+        format: SampleFormat,
+    ) -> Result<Self, RecorderError> {
+        if !matches!(format, SampleFormat::Float32 | SampleFormat::Pcm24) {
+            return Err(RecorderError::UnsupportedFormat(format));
+        }
+        let spec = WavSpec {
+            sample_rate,
+            channels: MIX_CHANNELS as u16,
+            format,
+        };
+        let mut tracks = Vec::with_capacity(paths.len());
+        let mut writers = Vec::with_capacity(paths.len());
+        for path in paths {
+            // Unbuffered: every sample the header counts has reached the file, so the header can
+            // still be finalised after a failed write (the patch overwrites existing bytes).
+            let writer = WavWriter::new(File::create(path)?, spec)?;
+            let track = Track {
+                tap: Arc::new(RecordTap::new(sample_rate)),
+                path: path.clone(),
+                frames: Arc::new(AtomicU64::new(0)),
+            };
+            writers.push((track.tap.clone(), writer, track.frames.clone()));
+            tracks.push(track);
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        let handle = thread::Builder::new()
+            .name("loopback-recorder".to_string())
+            .spawn(move || write_tracks(writers, &thread_stop))?;
+
+        Ok(Self {
+            tracks,
+            format,
+            stop,
+            writer: Some(handle),
+        })
+    }
+
+    /// Tap feeding track `index`, in the order the paths were given.
+    pub(crate) fn tap(&self, index: usize) -> &Arc<RecordTap> {
+        &self.tracks[index].tap
+    }
+
+    pub(crate) fn status(&self, index: usize) -> RecordingStatus {
+        let track = &self.tracks[index];
+        RecordingStatus {
+            path: track.path.clone(),
+            format: self.format,
+            frames: track.frames.load(Ordering::Relaxed),
+            dropped_frames: track.tap.dropped_frames(),
+            start_timestamp_ns: track.tap.start_timestamp_ns(),
+        }
+    }
+
+    /// Drain the taps, finalise the files and return the final status of every track.
+    pub(crate) fn finish(mut self) -> Result<Vec<RecordingStatus>, RecorderError> {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.writer.take() {
+            handle
+                .join()
+                .map_err(|_| io::Error::other("recorder thread panicked"))??;
+        }
+        Ok((0..self.tracks.len())
+            .map(|index| self.status(index))
+            .collect())
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.writer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Writer-thread end of one track: its tap, the file and the published frame count.
+pub(crate) type TrackWriter<W> = (Arc<RecordTap>, WavWriter<W>, Arc<AtomicU64>);
+
+/// Drain the taps into their files until `stop` is set, then finalise every header. A failed
+/// write ends the recording early, but each file is still finalised with the audio written
+/// before the failure; the first error is returned.
+pub(crate) fn write_tracks<W: Write + Seek>(
+    mut writers: Vec<TrackWriter<W>>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let written = drain_taps(&mut writers, stop);
+    let finished = writers
+        .into_iter()
+        .map(|(_, writer, _)| writer.finish().map(drop))
+        .fold(Ok(()), io::Result::and);
+    written.and(finished)
+}
+
+fn drain_taps<W: Write + Seek>(
+    writers: &mut [TrackWriter<W>],
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let mut chunk = vec![0.0f32; WRITER_CHUNK_FRAMES * MIX_CHANNELS];
+    loop {
+        // Read the flag first so frames queued before a stop are still drained.
+        let stopping = stop.load(Ordering::Acquire);
+        for (tap, writer, frames) in writers.iter_mut() {
+            loop {
+                let count = tap.ring.pop(&mut chunk);
+                if count == 0 {
+                    break;
+                }
+                writer.write_samples(&chunk[..count * MIX_CHANNELS])?;
+                frames.store(writer.frames(), Ordering::Relaxed);
+            }
+        }
+        if stopping {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(WRITER_POLL_MS));
+    }
+}
+
+/// Source details captured when a multitrack session starts.
+pub(crate) struct StemSource {
+    pub(crate) source_id: u32,
+    pub(crate) name: String,
+    pub(crate) gain_linear: f32,
+    pub(crate) muted: bool,
+}
+
+/// A running multitrack session: the master on track 0 and one stem per source after it.
+pub(crate) struct Multitrack {
+    recording: Recording,
+    directory: PathBuf,
+    sample_rate: u32,
+    sources: Vec<StemSource>,
+}
+
+impl Multitrack {
+    /// Create `directory` if needed and start recording the master and every source in `sources`.
+    pub(crate) fn start(
+        directory: &Path,
+        sample_rate: u32,
+        format: SampleFormat,
+        sources: Vec<StemSource>,
+    ) -> Result<Self, RecorderError> {
+        if !matches!(format, SampleFormat::Float32 | SampleFormat::Pcm24) {
+            return Err(RecorderError::UnsupportedFormat(format));
+        }
+        fs::create_dir_all(directory)?;
+        let paths: Vec<PathBuf> = std::iter::once(directory.join(MASTER_FILE_NAME))
+            .chain(
+                sources
+                    .iter()
+                    .map(|source| directory.join(stem_file_name(source.source_id))),
+            )
+            .collect();
+        Ok(Self {
+            recording: Recording::start(&paths, sample_rate, format)?,
+            directory: directory.to_path_buf(),
+            sample_rate,
+            sources,
+        })
+    }
+
+    pub(crate) fn master_tap(&self) -> &Arc<RecordTap> {
+        self.recording.tap(0)
+    }
+
+    /// Mixer id and tap of every stem.
+    pub(crate) fn stem_taps(&self) -> impl Iterator<Item = (u32, &Arc<RecordTap>)> {
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| (source.source_id, self.recording.tap(index + 1)))
+    }
+
+    pub(crate) fn status(&self) -> MultitrackStatus {
+        let tracks = (0..=self.sources.len())
+            .map(|index| self.recording.status(index))
+            .collect();
+        summarise(&self.directory, self.sample_rate, &self.sources, tracks)
+    }
+
+    /// Finalise every track and write the manifest.
+    pub(crate) fn finish(self) -> Result<MultitrackStatus, RecorderError> {
+        let tracks = self.recording.finish()?;
+        let status = summarise(&self.directory, self.sample_rate, &self.sources, tracks);
+        fs::write(
+            self.directory.join(MANIFEST_FILE_NAME),
+            status.manifest_json(),
+        )?;
+        Ok(status)
+    }
+}
+
+fn summarise(
+    directory: &Path,
+    sample_rate: u32,
+    sources: &[StemSource],
+    tracks: Vec<RecordingStatus>,
+) -> MultitrackStatus {
+    let mut tracks = tracks.into_iter();
+    let master = tracks
+        .next()
+        .expect("multitrack session has a master track");
+    MultitrackStatus {
+        directory: directory.to_path_buf(),
+        sample_rate,
+        format: master.format,
+        master,
+        stems: sources
+            .iter()
+            .zip(tracks)
+            .map(|(source, recording)| StemStatus {
+                source_id: source.source_id,
+                name: source.name.clone(),
+                gain_linear: source.gain_linear,
+                muted: source.muted,
+                recording,
+            })
+            .collect(),
+    }
+}
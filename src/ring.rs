@@ -107,17 +107,48 @@ impl SharedRingBuffer {
         })
     }
 
-    /// Create from an existing `MmapMut` region that follows the header+data layout.
-    pub fn from_mmap(mut mmap: MmapMut, channels: usize) -> Self {
+    /// Create from an existing `MmapMut` region that follows the header+data layout. The region
+    /// usually comes from another process, so a header that does not describe a `channels`-wide
+    /// ring fitting inside the mapping, or whose indices are out of step, is rejected with
+    /// [`std::io::ErrorKind::InvalidData`].
+    pub fn from_mmap(mut mmap: MmapMut, channels: usize) -> std::io::Result<Self> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let header_bytes = size_of::<RingBufferHeader>();
+        if mmap.len() < header_bytes {
+            return Err(invalid(format!(
+                "mapping of {} bytes is smaller than the {header_bytes}-byte ring header",
+                mmap.len()
+            )));
+        }
         let header_ptr = mmap.as_mut_ptr() as *mut RingBufferHeader;
-        let capacity_frames = unsafe { (*header_ptr).capacity_frames() };
-        debug_assert_eq!(
-            channels,
-            unsafe { (*header_ptr).channels() },
-            "channel mismatch between header and constructor"
-        );
-        let data_ptr = unsafe { mmap.as_mut_ptr().add(size_of::<RingBufferHeader>()) as *mut f32 };
-        Self {
+        let header = unsafe { &*header_ptr };
+        if channels == 0 || header.channels() != channels {
+            return Err(invalid(format!(
+                "ring header has {} channels, expected {channels}",
+                header.channels()
+            )));
+        }
+        let capacity_frames = header.capacity_frames();
+        let data_bytes = capacity_frames
+            .checked_mul(channels * size_of::<f32>())
+            .filter(|&bytes| bytes <= mmap.len() - header_bytes);
+        if data_bytes.is_none() {
+            return Err(invalid(format!(
+                "{capacity_frames} frames of {channels} channels do not fit in a mapping of {} bytes",
+                mmap.len()
+            )));
+        }
+        let write_index = header.write_index.load(Ordering::Acquire);
+        let read_index = header.read_index.load(Ordering::Acquire);
+        if write_index.wrapping_sub(read_index) > capacity_frames as u64 {
+            return Err(invalid(format!(
+                "ring indices out of step: read {read_index}, write {write_index}, capacity \
+                 {capacity_frames}"
+            )));
+        }
+        let data_ptr = unsafe { mmap.as_mut_ptr().add(header_bytes) as *mut f32 };
+        Ok(Self {
             storage: RingStorage::Shared {
                 mmap: UnsafeCell::new(mmap),
                 header_ptr,
@@ -125,7 +156,7 @@ impl SharedRingBuffer {
             },
             capacity_frames,
             channels,
-        }
+        })
     }
 
     fn header(&self) -> &RingBufferHeader {
@@ -200,7 +231,7 @@ impl SharedRingBuffer {
     /// Push frames into the ring, returning frames written.
     pub fn push(&self, frames: &[f32], timestamp_ns: Option<u64>) -> usize {
         let header = self.header_mut();
-        let frames_count = frames.len() / self.channels;
+        let frames_count = frames.len().checked_div(self.channels).unwrap_or(0);
         if frames_count == 0 {
             return 0;
         }
@@ -208,7 +239,7 @@ impl SharedRingBuffer {
         let capacity = self.capacity_frames as u64;
         let write_index = header.write_index.load(Ordering::Acquire);
         let read_index = header.read_index.load(Ordering::Acquire);
-        let used = write_index.saturating_sub(read_index).min(capacity);
+        let used = queued_frames(write_index, read_index, capacity);
         let free = capacity.saturating_sub(used);
         if free == 0 {
             return 0;
@@ -231,7 +262,7 @@ impl SharedRingBuffer {
                 .copy_from_slice(&frames[src_offset..src_offset + remaining_samples]);
         }
 
-        let new_write = write_index + frames_to_write as u64;
+        let new_write = write_index.wrapping_add(frames_to_write as u64);
         header.write_index.store(new_write, Ordering::Release);
         let timestamp = timestamp_ns.unwrap_or_else(monotonic_timestamp_ns);
         header.last_timestamp_ns.store(timestamp, Ordering::Release);
@@ -241,14 +272,14 @@ impl SharedRingBuffer {
     /// Pop frames into the provided buffer, returning frames read.
     pub fn pop(&self, out: &mut [f32]) -> usize {
         let header = self.header_mut();
-        let requested_frames = out.len() / self.channels;
+        let requested_frames = out.len().checked_div(self.channels).unwrap_or(0);
         if requested_frames == 0 {
             return 0;
         }
         let capacity = self.capacity_frames as u64;
         let write_index = header.write_index.load(Ordering::Acquire);
         let read_index = header.read_index.load(Ordering::Acquire);
-        let available = write_index.saturating_sub(read_index).min(capacity);
+        let available = queued_frames(write_index, read_index, capacity);
         if available == 0 {
             return 0;
         }
@@ -270,9 +301,10 @@ impl SharedRingBuffer {
                 .copy_from_slice(&data[0..remaining_samples]);
         }
 
-        header
-            .read_index
-            .store(read_index + frames_to_read as u64, Ordering::Release);
+        header.read_index.store(
+            read_index.wrapping_add(frames_to_read as u64),
+            Ordering::Release,
+        );
         frames_to_read
     }
 
@@ -282,14 +314,14 @@ impl SharedRingBuffer {
         let capacity = self.capacity_frames as u64;
         let write_index = header.write_index.load(Ordering::Acquire);
         let read_index = header.read_index.load(Ordering::Acquire);
-        let available = write_index.saturating_sub(read_index).min(capacity);
+        let available = queued_frames(write_index, read_index, capacity);
         if available == 0 {
             return 0;
         }
         let frames = frames.min(available as usize);
         header
             .read_index
-            .store(read_index + frames as u64, Ordering::Release);
+            .store(read_index.wrapping_add(frames as u64), Ordering::Release);
         frames
     }
 
@@ -299,7 +331,7 @@ impl SharedRingBuffer {
         let capacity = self.capacity_frames as u64;
         let write_index = header.write_index.load(Ordering::Acquire);
         let read_index = header.read_index.load(Ordering::Acquire);
-        write_index.saturating_sub(read_index).min(capacity) as usize
+        queued_frames(write_index, read_index, capacity) as usize
     }
 
     /// Timestamp of the last write.
@@ -308,6 +340,12 @@ impl SharedRingBuffer {
     }
 }
 
+/// Frames between the read and write indices. Both wrap at `u64::MAX`, so the distance is taken
+/// modulo 2^64 and clamped to the capacity.
+fn queued_frames(write_index: u64, read_index: u64, capacity: u64) -> u64 {
+    write_index.wrapping_sub(read_index).min(capacity)
+}
+
 #[cfg(target_os = "macos")]
 fn timebase() -> (u64, u64) {
     static TIMEBASE: Lazy<(u64, u64)> = Lazy::new(|| unsafe {
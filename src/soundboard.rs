@@ -0,0 +1,378 @@
+//! Soundboard sources: preloaded clips fired by id for instant sound effects.
+//!
+//! A [`Soundboard`] decodes each clip once, through the same WAV/FLAC path as the file player,
+//! into stereo at the mixer rate and keeps it in memory, so a trigger starts sounding on the very
+//! next block. Triggers travel to the render thread over a bounded channel and start a voice;
+//! up to [`MAX_VOICES`] voices play at once across all clips.
+//!
+//! Each clip has a [`ClipConfig`]:
+//!
+//! - `gain_db` scales every voice of the clip and can be changed while they play.
+//! - `polyphony` caps the voices the clip may have sounding at once. Triggering a clip that is
+//!   at its cap releases its oldest voice, so a polyphony of 1 restarts the clip.
+//! - `choke_group` ties clips together: triggering one releases the voices of every other clip
+//!   in the same group, like an open and a closed hi-hat.
+//!
+//! Released voices fade out over [`RELEASE_MS`] rather than cutting off with a click.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crossbeam_channel::{Receiver, Sender};
+use parking_lot::Mutex;
+
+use crate::player::decode_file;
+use crate::{MIX_CHANNELS, MixerError};
+
+/// Voices that can sound at once on one soundboard.
+pub const MAX_VOICES: usize = 32;
+/// Longest clip a soundboard preloads, in seconds.
+pub const MAX_CLIP_SECONDS: u32 = 60;
+/// Fade applied when a voice is released early.
+pub const RELEASE_MS: u32 = 5;
+/// Source index the global mixer's soundboard occupies.
+pub const SOUNDBOARD_SOURCE: u32 = 2_000;
+
+/// Triggers and stops that can be queued before the render thread picks them up.
+const COMMAND_CAPACITY: usize = 64;
+/// Highest gain a clip may be boosted by.
+const MAX_CLIP_GAIN_DB: f32 = 24.0;
+
+/// Why a soundboard request failed.
+#[derive(Debug, thiserror::Error)]
+pub enum SoundboardError {
+    /// No mixer is active to play into.
+    #[error("no active mixer")]
+    NoMixer,
+    /// The soundboard source could not be found or set up.
+    #[error(transparent)]
+    Mixer(#[from] MixerError),
+    /// No clip is loaded under the id.
+    #[error("clip {0} is not loaded")]
+    UnknownClip(u32),
+    /// The gain was not finite or above the boost limit, or the polyphony was out of range.
+    #[error("invalid clip settings: {0:?}")]
+    InvalidClip(ClipConfig),
+    /// Too many triggers are waiting for the render thread.
+    #[error("soundboard command queue is full")]
+    Busy,
+    /// Opening or decoding the clip failed, or it is longer than [`MAX_CLIP_SECONDS`].
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// How a clip plays when triggered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClipConfig {
+    /// Gain applied to the clip in decibels.
+    pub gain_db: f32,
+    /// Voices of the clip that may sound at once, from 1 to [`MAX_VOICES`].
+    pub polyphony: u32,
+    /// Group whose other clips are silenced when this one is triggered.
+    pub choke_group: Option<u32>,
+}
+
+impl ClipConfig {
+    /// Whether the gain is finite and at most +24 dB and the polyphony is in range.
+    pub fn is_valid(&self) -> bool {
+        self.gain_db.is_finite()
+            && self.gain_db <= MAX_CLIP_GAIN_DB
+            && (1..=MAX_VOICES as u32).contains(&self.polyphony)
+    }
+}
+
+impl Default for ClipConfig {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            polyphony: 1,
+            choke_group: None,
+        }
+    }
+}
+
+/// A loaded clip and what it is doing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipStatus {
+    /// Id the clip is triggered by.
+    pub id: u32,
+    /// File the clip was loaded from.
+    pub path: PathBuf,
+    /// Length of the clip in seconds.
+    pub duration_seconds: f64,
+    /// Playback settings.
+    pub config: ClipConfig,
+    /// Voices of the clip currently sounding, including ones fading out.
+    pub voices: u32,
+}
+
+/// Decoded audio and the state shared between the control side and the render thread.
+struct Clip {
+    id: u32,
+    path: PathBuf,
+    samples: Box<[f32]>,
+    config: Mutex<ClipConfig>,
+    /// Linear gain as `f32` bits, read by the render thread every block.
+    gain: AtomicU32,
+    voices: AtomicU32,
+}
+
+impl Clip {
+    fn frames(&self) -> usize {
+        self.samples.len() / MIX_CHANNELS
+    }
+}
+
+enum Command {
+    Trigger {
+        clip: Arc<Clip>,
+        polyphony: u32,
+        choke_group: Option<u32>,
+    },
+    Stop(u32),
+    StopAll,
+}
+
+struct Voice {
+    clip: Arc<Clip>,
+    choke_group: Option<u32>,
+    position: usize,
+    /// Frames left of the release fade, if the voice has been released.
+    release: Option<usize>,
+}
+
+/// Control side and render state of a soundboard source.
+pub(crate) struct Soundboard {
+    sample_rate: u32,
+    clips: Mutex<HashMap<u32, Arc<Clip>>>,
+    /// Unloaded clips still referenced by voices, kept so their memory is never freed on the
+    /// render thread.
+    retired: Mutex<Vec<Arc<Clip>>>,
+    commands: Sender<Command>,
+    received: Receiver<Command>,
+    voices: Vec<Voice>,
+    release_frames: usize,
+}
+
+impl Soundboard {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        let (commands, received) = crossbeam_channel::bounded(COMMAND_CAPACITY);
+        Self {
+            sample_rate,
+            clips: Mutex::new(HashMap::new()),
+            retired: Mutex::new(Vec::new()),
+            commands,
+            received,
+            voices: Vec::with_capacity(MAX_VOICES),
+            release_frames: (sample_rate as usize * RELEASE_MS as usize / 1_000).max(1),
+        }
+    }
+
+    /// Decode `path` and make it available as clip `id`, replacing any clip loaded under it.
+    pub(crate) fn load(
+        &self,
+        id: u32,
+        path: &Path,
+        config: ClipConfig,
+    ) -> Result<(), SoundboardError> {
+        if !config.is_valid() {
+            return Err(SoundboardError::InvalidClip(config));
+        }
+        let max_frames = self.sample_rate as usize * MAX_CLIP_SECONDS as usize;
+        let samples = decode_file(path, self.sample_rate, max_frames)?;
+        let clip = Arc::new(Clip {
+            id,
+            path: path.to_path_buf(),
+            samples: samples.into_boxed_slice(),
+            config: Mutex::new(config),
+            gain: AtomicU32::new(linear_gain(config.gain_db).to_bits()),
+            voices: AtomicU32::new(0),
+        });
+        if let Some(previous) = self.clips.lock().insert(id, clip) {
+            self.retire(previous);
+        }
+        Ok(())
+    }
+
+    /// Forget clip `id`, releasing any voices it has sounding.
+    pub(crate) fn unload(&self, id: u32) -> Result<(), SoundboardError> {
+        let clip = self
+            .clips
+            .lock()
+            .remove(&id)
+            .ok_or(SoundboardError::UnknownClip(id))?;
+        self.retire(clip);
+        Ok(())
+    }
+
+    fn retire(&self, clip: Arc<Clip>) {
+        // Best effort: a full queue only means the old voices play out instead.
+        let _ = self.commands.try_send(Command::Stop(clip.id));
+        let mut retired = self.retired.lock();
+        retired.retain(|clip| Arc::strong_count(clip) > 1);
+        retired.push(clip);
+    }
+
+    /// Start a voice of clip `id` on the next block.
+    pub(crate) fn trigger(&self, id: u32) -> Result<(), SoundboardError> {
+        let clip = self.clip(id)?;
+        let config = *clip.config.lock();
+        self.send(Command::Trigger {
+            clip,
+            polyphony: config.polyphony,
+            choke_group: config.choke_group,
+        })
+    }
+
+    /// Release every voice of clip `id`.
+    pub(crate) fn stop(&self, id: u32) -> Result<(), SoundboardError> {
+        self.clip(id)?;
+        self.send(Command::Stop(id))
+    }
+
+    /// Release every voice of every clip.
+    pub(crate) fn stop_all(&self) -> Result<(), SoundboardError> {
+        self.send(Command::StopAll)
+    }
+
+    /// Change how clip `id` plays. The gain applies to sounding voices straight away; polyphony
+    /// and choke group from the next trigger.
+    pub(crate) fn configure(&self, id: u32, config: ClipConfig) -> Result<(), SoundboardError> {
+        if !config.is_valid() {
+            return Err(SoundboardError::InvalidClip(config));
+        }
+        let clip = self.clip(id)?;
+        *clip.config.lock() = config;
+        clip.gain
+            .store(linear_gain(config.gain_db).to_bits(), Ordering::Release);
+        Ok(())
+    }
+
+    /// Every loaded clip, ordered by id.
+    pub(crate) fn clips(&self) -> Vec<ClipStatus> {
+        let mut clips: Vec<ClipStatus> = self
+            .clips
+            .lock()
+            .values()
+            .map(|clip| ClipStatus {
+                id: clip.id,
+                path: clip.path.clone(),
+                duration_seconds: clip.frames() as f64 / self.sample_rate as f64,
+                config: *clip.config.lock(),
+                voices: clip.voices.load(Ordering::Acquire),
+            })
+            .collect();
+        clips.sort_by_key(|clip| clip.id);
+        clips
+    }
+
+    fn clip(&self, id: u32) -> Result<Arc<Clip>, SoundboardError> {
+        self.clips
+            .lock()
+            .get(&id)
+            .cloned()
+            .ok_or(SoundboardError::UnknownClip(id))
+    }
+
+    fn send(&self, command: Command) -> Result<(), SoundboardError> {
+        // The receiver lives as long as `self`, so the queue can only be full.
+        self.commands
+            .try_send(command)
+            .map_err(|_| SoundboardError::Busy)
+    }
+
+    /// Apply queued commands, then render the sounding voices as interleaved stereo into `out`,
+    /// replacing its contents.
+    pub(crate) fn render(&mut self, out: &mut [f32]) {
+        while let Ok(command) = self.received.try_recv() {
+            match command {
+                Command::Trigger {
+                    clip,
+                    polyphony,
+                    choke_group,
+                } => self.start(clip, polyphony, choke_group),
+                Command::Stop(id) => self.release_where(|voice| voice.clip.id == id),
+                Command::StopAll => self.release_where(|_| true),
+            }
+        }
+
+        out.fill(0.0);
+        let frames = out.len() / MIX_CHANNELS;
+        for voice in &mut self.voices {
+            let gain = f32::from_bits(voice.clip.gain.load(Ordering::Acquire));
+            let count = frames.min(voice.clip.frames() - voice.position);
+            let count = voice.release.map_or(count, |left| count.min(left));
+            let start = voice.position * MIX_CHANNELS;
+            let samples = &voice.clip.samples[start..start + count * MIX_CHANNELS];
+            for (frame, (out, input)) in out
+                .chunks_exact_mut(MIX_CHANNELS)
+                .zip(samples.chunks_exact(MIX_CHANNELS))
+                .enumerate()
+            {
+                let fade = match voice.release {
+                    Some(left) => (left - frame) as f32 / self.release_frames as f32,
+                    None => 1.0,
+                };
+                out[0] += input[0] * gain * fade;
+                out[1] += input[1] * gain * fade;
+            }
+            voice.position += count;
+            if let Some(left) = voice.release.as_mut() {
+                *left -= count;
+            }
+        }
+        self.voices.retain(|voice| {
+            let done = voice.position >= voice.clip.frames() || voice.release == Some(0);
+            if done {
+                voice.clip.voices.fetch_sub(1, Ordering::AcqRel);
+            }
+            !done
+        });
+        for sample in out.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+
+    fn start(&mut self, clip: Arc<Clip>, polyphony: u32, choke_group: Option<u32>) {
+        if let Some(group) = choke_group {
+            self.release_where(|voice| {
+                voice.choke_group == Some(group) && voice.clip.id != clip.id
+            });
+        }
+        // Voices are kept oldest first, so the first match is the one to steal.
+        let held = |voice: &Voice| voice.release.is_none() && voice.clip.id == clip.id;
+        let sounding = self.voices.iter().filter(|voice| held(voice)).count();
+        if sounding >= polyphony as usize
+            && let Some(oldest) = self.voices.iter_mut().find(|voice| held(voice))
+        {
+            oldest.release = Some(self.release_frames);
+        }
+        if self.voices.len() == MAX_VOICES {
+            let stolen = self.voices.remove(0);
+            stolen.clip.voices.fetch_sub(1, Ordering::AcqRel);
+        }
+        clip.voices.fetch_add(1, Ordering::AcqRel);
+        self.voices.push(Voice {
+            clip,
+            choke_group,
+            position: 0,
+            release: None,
+        });
+    }
+
+    fn release_where(&mut self, mut matches: impl FnMut(&Voice) -> bool) {
+        let release = self.release_frames;
+        for voice in &mut self.voices {
+            if voice.release.is_none() && matches(voice) {
+                voice.release = Some(release);
+            }
+        }
+    }
+}
+
+fn linear_gain(gain_db: f32) -> f32 {
+    10f32.powf(gain_db / 20.0)
+}
@@ -0,0 +1,278 @@
+//! Log-frequency spectrum analysis fed from the render path.
+//!
+//! The render thread only copies each post-fader block, downmixed to mono, into a
+//! [`SpectrumTap`]: a power-of-two history of atomically stored samples plus a running write
+//! count, so writing never blocks or allocates. Analysis happens on the reading side. A reader
+//! snapshots the latest [`fft_size`] samples, retrying if the render thread lapped it mid-copy,
+//! applies a Hann window and sums FFT bin power into `bands` log-spaced bands from
+//! [`SPECTRUM_MIN_HZ`] to Nyquist (capped at [`SPECTRUM_MAX_HZ`]). Results are cached and only
+//! recomputed `rate_hz` times per second, however often they are read. Levels are band RMS in
+//! dBFS, on the same scale as the level meters: a full-scale sine reads about -3 dB in the band
+//! that contains it.
+//!
+//! Taps stay allocated for the life of their source. Switching analysis off only clears an atomic
+//! flag that makes [`SpectrumTap::write`] return straight away, and reconfiguring rebuilds the
+//! reader-side analysis, so the render thread never sees a tap appear or disappear.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::fft::{Fft, hann_window};
+use crate::ring::monotonic_timestamp_ns;
+
+/// Lowest band edge.
+pub const SPECTRUM_MIN_HZ: f32 = 20.0;
+/// Highest band edge, when Nyquist is above it.
+pub const SPECTRUM_MAX_HZ: f32 = 20_000.0;
+/// Largest supported band count.
+pub const MAX_SPECTRUM_BANDS: u32 = 128;
+/// Level reported for bands with no energy.
+pub const SPECTRUM_FLOOR_DB: f32 = -120.0;
+/// [`loopback_mixer_get_spectrum`](crate::loopback_mixer_get_spectrum) source index of the
+/// master output.
+pub const LOOPBACK_SPECTRUM_MASTER: u32 = u32::MAX;
+
+const SNAPSHOT_RETRIES: usize = 4;
+
+/// Spectrum analysis settings.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrumConfig {
+    /// Number of log-spaced bands (1 to [`MAX_SPECTRUM_BANDS`]).
+    pub bands: u32,
+    /// How many times per second band levels are recomputed.
+    pub rate_hz: f32,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            bands: 32,
+            rate_hz: 20.0,
+        }
+    }
+}
+
+impl SpectrumConfig {
+    fn sanitized(self) -> Self {
+        Self {
+            bands: self.bands.clamp(1, MAX_SPECTRUM_BANDS),
+            rate_hz: if self.rate_hz.is_finite() {
+                self.rate_hz.clamp(0.1, 1_000.0)
+            } else {
+                Self::default().rate_hz
+            },
+        }
+    }
+}
+
+/// Band levels of one analysis.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spectrum {
+    /// Geometric centre of each band in Hertz.
+    pub frequencies_hz: Vec<f32>,
+    /// RMS level of each band in dBFS, floored at [`SPECTRUM_FLOOR_DB`].
+    pub levels_db: Vec<f32>,
+    /// Host time of the analysis, in nanoseconds.
+    pub timestamp_ns: u64,
+}
+
+/// FFT length used at `sample_rate`: about 85 ms of audio, 4096 points at 48 kHz.
+pub fn fft_size(sample_rate: u32) -> usize {
+    (sample_rate as usize / 12)
+        .next_power_of_two()
+        .clamp(1_024, 16_384)
+}
+
+/// Render-side sample history with the reader's analysis state.
+pub(crate) struct SpectrumTap {
+    sample_rate: u32,
+    samples: Box<[AtomicU32]>,
+    mask: usize,
+    written: AtomicU64,
+    enabled: AtomicBool,
+    analysis: Mutex<Analysis>,
+}
+
+impl SpectrumTap {
+    /// Tap analysing with `config`.
+    pub(crate) fn new(sample_rate: u32, config: SpectrumConfig) -> Self {
+        let size = fft_size(sample_rate);
+        let capacity = size * 2;
+        Self {
+            sample_rate,
+            samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            mask: capacity - 1,
+            written: AtomicU64::new(0),
+            enabled: AtomicBool::new(true),
+            analysis: Mutex::new(Analysis::new(sample_rate, size, config.sanitized(), 0)),
+        }
+    }
+
+    /// Tap that ignores writes until it is [configured](Self::configure).
+    pub(crate) fn disabled(sample_rate: u32) -> Self {
+        let tap = Self::new(sample_rate, SpectrumConfig::default());
+        tap.enabled.store(false, Ordering::Relaxed);
+        tap
+    }
+
+    /// Start analysing with `config`, discarding earlier samples and results, or stop with
+    /// `None`. Called from the control side.
+    pub(crate) fn configure(&self, config: Option<SpectrumConfig>) {
+        let Some(config) = config else {
+            self.enabled.store(false, Ordering::Relaxed);
+            return;
+        };
+        let since = self.written.load(Ordering::Acquire);
+        *self.analysis.lock() = Analysis::new(
+            self.sample_rate,
+            fft_size(self.sample_rate),
+            config.sanitized(),
+            since,
+        );
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Append an interleaved stereo block, downmixed to mono, unless the tap is disabled. Called
+    /// from the render thread.
+    pub(crate) fn write(&self, block: &[f32]) {
+        if !self.is_enabled() {
+            return;
+        }
+        let start = self.written.load(Ordering::Relaxed);
+        let mut position = start;
+        for frame in block.chunks_exact(2) {
+            let mono = 0.5 * (frame[0] + frame[1]);
+            self.samples[position as usize & self.mask].store(mono.to_bits(), Ordering::Relaxed);
+            position += 1;
+        }
+        self.written.store(position, Ordering::Release);
+    }
+
+    /// Latest analysis, recomputed if it is older than the configured rate allows.
+    pub(crate) fn spectrum(&self) -> Spectrum {
+        let mut analysis = self.analysis.lock();
+        let now = monotonic_timestamp_ns();
+        let fresh = analysis
+            .result
+            .as_ref()
+            .is_some_and(|result| now.saturating_sub(result.timestamp_ns) < analysis.interval_ns);
+        if !fresh {
+            let since = analysis.since;
+            self.snapshot(&mut analysis.input, since);
+            analysis.analyse(now);
+        }
+        analysis.result.clone().unwrap_or_default()
+    }
+
+    /// Copy the newest samples into `out`, zero-padding the front if fewer have been written
+    /// since write count `since`.
+    fn snapshot(&self, out: &mut [f32], since: u64) {
+        let length = out.len() as u64;
+        for _ in 0..SNAPSHOT_RETRIES {
+            let end = self.written.load(Ordering::Acquire);
+            let start = end.saturating_sub(length).max(since).min(end);
+            let padding = (length - (end - start)) as usize;
+            out[..padding].fill(0.0);
+            for (slot, position) in out[padding..].iter_mut().zip(start..end) {
+                *slot = f32::from_bits(
+                    self.samples[position as usize & self.mask].load(Ordering::Relaxed),
+                );
+            }
+            // Samples from `start` on are intact unless the writer wrapped past them meanwhile.
+            let now = self.written.load(Ordering::Acquire);
+            if now - start <= self.samples.len() as u64 {
+                return;
+            }
+        }
+    }
+}
+
+/// Reader-side analysis buffers and the cached result.
+struct Analysis {
+    fft: Fft,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    /// First and one-past-last FFT bin of each band.
+    bins: Vec<(usize, usize)>,
+    frequencies_hz: Vec<f32>,
+    /// Converts summed bin power into squared band amplitude.
+    power_scale: f32,
+    interval_ns: u64,
+    /// Write count when the analysis was configured; older samples are read as silence.
+    since: u64,
+    result: Option<Spectrum>,
+}
+
+impl Analysis {
+    fn new(sample_rate: u32, size: usize, config: SpectrumConfig, since: u64) -> Self {
+        let window = hann_window(size);
+        let window_energy: f32 = window.iter().map(|w| w * w).sum();
+        let nyquist = sample_rate as f32 / 2.0;
+        let top = nyquist.min(SPECTRUM_MAX_HZ);
+        let bin_hz = sample_rate as f32 / size as f32;
+        let bands = config.bands as usize;
+        let ratio = (top / SPECTRUM_MIN_HZ).powf(1.0 / bands as f32);
+        let last_bin = size / 2;
+        let mut bins = Vec::with_capacity(bands);
+        let mut frequencies_hz = Vec::with_capacity(bands);
+        for band in 0..bands {
+            let low = SPECTRUM_MIN_HZ * ratio.powi(band as i32);
+            let high = low * ratio;
+            let first = ((low / bin_hz).round() as usize).clamp(1, last_bin - 1);
+            let end = ((high / bin_hz).round() as usize).clamp(first + 1, last_bin);
+            bins.push((first, end));
+            frequencies_hz.push((low * high).sqrt());
+        }
+        Self {
+            fft: Fft::new(size),
+            window,
+            input: vec![0.0; size],
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            bins,
+            frequencies_hz,
+            power_scale: 4.0 / (size as f32 * window_energy),
+            interval_ns: (1e9 / config.rate_hz as f64) as u64,
+            since,
+            result: None,
+        }
+    }
+
+    fn analyse(&mut self, timestamp_ns: u64) {
+        for ((re, im), (sample, window)) in self
+            .re
+            .iter_mut()
+            .zip(self.im.iter_mut())
+            .zip(self.input.iter().zip(&self.window))
+        {
+            *re = sample * window;
+            *im = 0.0;
+        }
+        self.fft.forward(&mut self.re, &mut self.im);
+        let levels_db = self
+            .bins
+            .iter()
+            .map(|&(first, end)| {
+                let power: f32 = (first..end)
+                    .map(|bin| self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin])
+                    .sum();
+                // Band amplitude of a sine is its peak; report RMS like the level meters.
+                let mean_square = 0.5 * power * self.power_scale;
+                (10.0 * mean_square.max(1e-30).log10()).max(SPECTRUM_FLOOR_DB)
+            })
+            .collect();
+        self.result = Some(Spectrum {
+            frequencies_hz: self.frequencies_hz.clone(),
+            levels_db,
+            timestamp_ns,
+        });
+    }
+}
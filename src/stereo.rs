@@ -0,0 +1,289 @@
+//! Stereo phase correlation and width metering, and the per-source channel utility stage.
+//!
+//! [`StereoMeter::process`] runs on the render thread next to the level meter. It integrates the
+//! left and right energies and their cross product with the same time constant as the RMS
+//! reading, and derives two values from them:
+//!
+//! * correlation, `E[LR] / sqrt(E[L²] E[R²])`: +1 for mono, around 0 for unrelated channels or
+//!   a missing channel, -1 when one channel has flipped polarity;
+//! * width, `E[S²] / E[M²+S²]` scaled to 0-2: 0 for mono, 1 for unrelated channels, 2 when the
+//!   channels cancel completely in a mono downmix.
+//!
+//! Correlation staying below [`MONO_WARNING_CORRELATION`] means a mono downmix loses much of the
+//! signal; it raises the mono-compatibility warning, which is held for [`MONO_WARNING_HOLD_MS`]
+//! like the clip indicator. Signals quieter than [`STEREO_SILENCE_LEVEL`] read as correlation 0,
+//! width 0 and never warn.
+//!
+//! [`ChannelUtility`] repairs badly produced sources before any other processing: it swaps the
+//! channels, then inverts the polarity of either side, then optionally folds the result to mono
+//! or to a single channel copied to both sides. The settings are packed into one atomic so the
+//! render thread reads them without locking.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::meter::RMS_INTEGRATION_MS;
+
+/// Correlation below which the mono-compatibility warning is raised.
+pub const MONO_WARNING_CORRELATION: f32 = -0.3;
+/// How long the mono-compatibility warning stays raised after the condition clears.
+pub const MONO_WARNING_HOLD_MS: f32 = 1_000.0;
+/// RMS level (linear) under which the signal is treated as silence.
+pub const STEREO_SILENCE_LEVEL: f32 = 0.001;
+
+/// [`ChannelMode`] code of [`ChannelMode::Stereo`] for the C ABI.
+pub const LOOPBACK_CHANNEL_MODE_STEREO: u32 = 0;
+/// [`ChannelMode`] code of [`ChannelMode::Mono`] for the C ABI.
+pub const LOOPBACK_CHANNEL_MODE_MONO: u32 = 1;
+/// [`ChannelMode`] code of [`ChannelMode::LeftOnly`] for the C ABI.
+pub const LOOPBACK_CHANNEL_MODE_LEFT_ONLY: u32 = 2;
+/// [`ChannelMode`] code of [`ChannelMode::RightOnly`] for the C ABI.
+pub const LOOPBACK_CHANNEL_MODE_RIGHT_ONLY: u32 = 3;
+
+const INVERT_LEFT: u32 = 1;
+const INVERT_RIGHT: u32 = 1 << 1;
+const SWAP_CHANNELS: u32 = 1 << 2;
+const MODE_SHIFT: u32 = 3;
+
+/// How a source's two channels are folded after swapping and polarity inversion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Pass both channels through.
+    #[default]
+    Stereo,
+    /// Average of both channels on both sides.
+    Mono,
+    /// Left channel on both sides.
+    LeftOnly,
+    /// Right channel on both sides.
+    RightOnly,
+}
+
+impl ChannelMode {
+    /// Mode for a `LOOPBACK_CHANNEL_MODE_*` code, or `None` for an unknown code.
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            LOOPBACK_CHANNEL_MODE_STEREO => Some(Self::Stereo),
+            LOOPBACK_CHANNEL_MODE_MONO => Some(Self::Mono),
+            LOOPBACK_CHANNEL_MODE_LEFT_ONLY => Some(Self::LeftOnly),
+            LOOPBACK_CHANNEL_MODE_RIGHT_ONLY => Some(Self::RightOnly),
+            _ => None,
+        }
+    }
+
+    /// Stable numeric code, one of the `LOOPBACK_CHANNEL_MODE_*` constants.
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::Stereo => LOOPBACK_CHANNEL_MODE_STEREO,
+            Self::Mono => LOOPBACK_CHANNEL_MODE_MONO,
+            Self::LeftOnly => LOOPBACK_CHANNEL_MODE_LEFT_ONLY,
+            Self::RightOnly => LOOPBACK_CHANNEL_MODE_RIGHT_ONLY,
+        }
+    }
+
+    /// Mode for a [`name`](Self::name), or `None` for an unknown name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Stereo, Self::Mono, Self::LeftOnly, Self::RightOnly]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+
+    /// Short machine-friendly name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Stereo => "stereo",
+            Self::Mono => "mono",
+            Self::LeftOnly => "left",
+            Self::RightOnly => "right",
+        }
+    }
+}
+
+/// Per-source channel repair settings. The default passes audio through unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelUtility {
+    /// Invert the polarity of the left channel.
+    pub invert_left: bool,
+    /// Invert the polarity of the right channel.
+    pub invert_right: bool,
+    /// Exchange left and right.
+    pub swap_channels: bool,
+    /// Fold applied last.
+    pub mode: ChannelMode,
+}
+
+impl ChannelUtility {
+    /// Whether the settings leave audio unchanged.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub(crate) fn to_bits(self) -> u32 {
+        let mut bits = self.mode.code() << MODE_SHIFT;
+        if self.invert_left {
+            bits |= INVERT_LEFT;
+        }
+        if self.invert_right {
+            bits |= INVERT_RIGHT;
+        }
+        if self.swap_channels {
+            bits |= SWAP_CHANNELS;
+        }
+        bits
+    }
+
+    pub(crate) fn from_bits(bits: u32) -> Self {
+        Self {
+            invert_left: bits & INVERT_LEFT != 0,
+            invert_right: bits & INVERT_RIGHT != 0,
+            swap_channels: bits & SWAP_CHANNELS != 0,
+            mode: ChannelMode::from_code(bits >> MODE_SHIFT).unwrap_or_default(),
+        }
+    }
+
+    /// Apply the settings in place to an interleaved stereo block.
+    pub(crate) fn process(&self, block: &mut [f32]) {
+        if self.is_identity() {
+            return;
+        }
+        let left_sign = if self.invert_left { -1.0 } else { 1.0 };
+        let right_sign = if self.invert_right { -1.0 } else { 1.0 };
+        for frame in block.chunks_exact_mut(2) {
+            let (left, right) = if self.swap_channels {
+                (frame[1], frame[0])
+            } else {
+                (frame[0], frame[1])
+            };
+            let (left, right) = (left * left_sign, right * right_sign);
+            let (left, right) = match self.mode {
+                ChannelMode::Stereo => (left, right),
+                ChannelMode::Mono => {
+                    let mono = 0.5 * (left + right);
+                    (mono, mono)
+                }
+                ChannelMode::LeftOnly => (left, left),
+                ChannelMode::RightOnly => (right, right),
+            };
+            frame[0] = left;
+            frame[1] = right;
+        }
+    }
+}
+
+impl fmt::Display for ChannelUtility {
+    /// Compact summary such as `swap -L mono`, or `stereo` when the settings are the default.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.swap_channels {
+            parts.push("swap");
+        }
+        if self.invert_left {
+            parts.push("-L");
+        }
+        if self.invert_right {
+            parts.push("-R");
+        }
+        if self.mode != ChannelMode::Stereo || parts.is_empty() {
+            parts.push(self.mode.name());
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// Snapshot of a stereo meter's published readings.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StereoReading {
+    /// Phase correlation between left and right (-1 to +1).
+    pub correlation: f32,
+    /// Side energy relative to the total, scaled so 0 is mono, 1 is unrelated channels and 2 is
+    /// fully out of phase.
+    pub width: f32,
+    /// Whether correlation fell below [`MONO_WARNING_CORRELATION`] within
+    /// [`MONO_WARNING_HOLD_MS`].
+    pub mono_warning: bool,
+}
+
+/// Correlation and width meter fed from the render thread.
+pub(crate) struct StereoMeter {
+    sample_rate: f32,
+    left: f32,
+    right: f32,
+    cross: f32,
+    warning_remaining: f32,
+    correlation_bits: AtomicU32,
+    width_bits: AtomicU32,
+    mono_warning: AtomicBool,
+}
+
+impl StereoMeter {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1) as f32,
+            left: 0.0,
+            right: 0.0,
+            cross: 0.0,
+            warning_remaining: 0.0,
+            correlation_bits: AtomicU32::new(0.0f32.to_bits()),
+            width_bits: AtomicU32::new(0.0f32.to_bits()),
+            mono_warning: AtomicBool::new(false),
+        }
+    }
+
+    /// Measure an interleaved stereo block and publish the updated readings.
+    pub(crate) fn process(&mut self, block: &[f32]) {
+        let frames = block.len() / 2;
+        if frames == 0 {
+            return;
+        }
+        let (mut left, mut right, mut cross) = (0.0f32, 0.0f32, 0.0f32);
+        for frame in block.chunks_exact(2) {
+            left += frame[0] * frame[0];
+            right += frame[1] * frame[1];
+            cross += frame[0] * frame[1];
+        }
+        let elapsed_ms = frames as f32 * 1_000.0 / self.sample_rate;
+        let coefficient = (-elapsed_ms / RMS_INTEGRATION_MS).exp();
+        let scale = (1.0 - coefficient) / frames as f32;
+        self.left = coefficient * self.left + scale * left;
+        self.right = coefficient * self.right + scale * right;
+        self.cross = coefficient * self.cross + scale * cross;
+
+        let energy = self.left + self.right;
+        let audible = energy * 0.5 >= STEREO_SILENCE_LEVEL * STEREO_SILENCE_LEVEL;
+        let (correlation, width) = if audible {
+            let product = (self.left * self.right).sqrt();
+            let correlation = if product > f32::EPSILON * energy {
+                (self.cross / product).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+            (
+                correlation,
+                (1.0 - 2.0 * self.cross / energy).clamp(0.0, 2.0),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        if audible && correlation < MONO_WARNING_CORRELATION {
+            self.warning_remaining = MONO_WARNING_HOLD_MS;
+        } else {
+            self.warning_remaining = (self.warning_remaining - elapsed_ms).max(0.0);
+        }
+
+        self.correlation_bits
+            .store(correlation.to_bits(), Ordering::Relaxed);
+        self.width_bits.store(width.to_bits(), Ordering::Relaxed);
+        self.mono_warning
+            .store(self.warning_remaining > 0.0, Ordering::Relaxed);
+    }
+
+    /// Latest published readings.
+    pub(crate) fn reading(&self) -> StereoReading {
+        StereoReading {
+            correlation: f32::from_bits(self.correlation_bits.load(Ordering::Relaxed)),
+            width: f32::from_bits(self.width_bits.load(Ordering::Relaxed)),
+            mono_warning: self.mono_warning.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::ClockState;
+
+/// 10 ms between reports on the device clock.
+const INTERVAL_NS: u64 = 10_000_000;
+
+/// Feed `reports` reports from a source running `ppm` off the device clock, with the device
+/// report times displaced by `jitter(index)` nanoseconds. Returns the estimate after each report.
+fn feed(clock: &mut ClockState, ppm: f64, reports: u64, jitter: impl Fn(u64) -> u64) -> Vec<f32> {
+    let ratio = 1.0 + ppm / 1_000_000.0;
+    (0..reports)
+        .filter_map(|index| {
+            let device_ts = 1_000_000_000 + index * INTERVAL_NS + jitter(index);
+            let source_ts = 1_000_000_000 + (index as f64 * INTERVAL_NS as f64 * ratio) as u64;
+            clock.submit_feedback(device_ts, source_ts)
+        })
+        .collect()
+}
+
+#[test]
+fn the_first_report_only_sets_the_reference() {
+    let mut clock = ClockState::new();
+    assert_eq!(clock.submit_feedback(1_000, 1_000), None);
+    assert_eq!(clock.drift_ppm(), 0.0);
+    // Reports that do not move both clocks forward start over from the new reference.
+    assert_eq!(clock.submit_feedback(1_000, 2_000), None);
+    assert_eq!(clock.submit_feedback(900, 3_000), None);
+    assert!(clock.submit_feedback(1_900, 4_000).is_some());
+}
+
+#[test]
+fn converges_on_the_source_rate() {
+    for ppm in [-800.0, 0.0, 120.0, 1_500.0] {
+        let mut clock = ClockState::new();
+        let ratios = feed(&mut clock, ppm, 400, |_| 0);
+        assert_eq!(ratios.len(), 399);
+        // Each report closes 5% of the remaining gap.
+        let after_one = ratios[0] as f64 - 1.0;
+        assert!(
+            (after_one * 1e6 - ppm * 0.05).abs() < 0.5,
+            "{ppm}: {after_one}"
+        );
+        assert!(
+            (clock.drift_ppm() as f64 - ppm).abs() < 0.5,
+            "{ppm}: {}",
+            clock.drift_ppm()
+        );
+    }
+}
+
+#[test]
+fn implausible_rates_are_clamped_to_two_percent() {
+    let mut clock = ClockState::new();
+    feed(&mut clock, 50_000.0, 400, |_| 0);
+    assert!((clock.drift_ppm() - 20_000.0).abs() < 1.0);
+    let mut clock = ClockState::new();
+    feed(&mut clock, -50_000.0, 400, |_| 0);
+    assert!((clock.drift_ppm() + 20_000.0).abs() < 1.0);
+}
+
+#[test]
+fn jittered_reports_do_not_bias_the_estimate() {
+    // Up to 200 µs of lateness on a 10 ms interval from a fixed LCG sequence.
+    let jitter = |index: u64| {
+        let state = index
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 33) % 200_001
+    };
+    let mut clock = ClockState::new();
+    let ratios = feed(&mut clock, 250.0, 20_000, jitter);
+    let settled = &ratios[1_000..];
+    let mean_ppm = settled
+        .iter()
+        .map(|&ratio| (ratio as f64 - 1.0) * 1e6)
+        .sum::<f64>()
+        / settled.len() as f64;
+    assert!((mean_ppm - 250.0).abs() < 5.0, "{mean_ppm}");
+}
@@ -0,0 +1,360 @@
+use std::io::Cursor;
+
+use crate::flac::FlacReader;
+
+const SAMPLE_RATE: u32 = 48_000;
+const BITS: u32 = 16;
+const BLOCK: usize = 64;
+
+/// Big-endian bit writer for hand-assembling FLAC streams.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, n: u32) {
+        for bit in (0..n).rev() {
+            self.bits = (self.bits << 1) | ((value >> bit) & 1);
+            self.count += 1;
+            if self.count == 8 {
+                self.bytes.push(self.bits as u8);
+                self.bits = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, n: u32) {
+        self.write(value as u64 & ((1u64 << n) - 1), n);
+    }
+
+    fn write_unary(&mut self, zeros: u64) {
+        for _ in 0..zeros {
+            self.write(0, 1);
+        }
+        self.write(1, 1);
+    }
+
+    fn align(&mut self) {
+        while self.count != 0 {
+            self.write(0, 1);
+        }
+    }
+}
+
+enum Subframe {
+    Constant,
+    Verbatim,
+    Fixed { order: usize, rice: u32 },
+    Lpc { coefficients: Vec<i64>, shift: u32 },
+    Escaped { order: usize, wasted: u32 },
+}
+
+/// Rice-code `residual` as two partitions, the first one shortened by the warm-up samples.
+fn write_rice(writer: &mut BitWriter, residual: &[i64], parameter: u32) {
+    writer.write(0, 2);
+    writer.write(1, 4);
+    let (first, second) = residual.split_at(residual.len() - BLOCK / 2);
+    for partition in [first, second] {
+        writer.write(parameter as u64, 4);
+        for &value in partition {
+            let folded = ((value << 1) ^ (value >> 63)) as u64;
+            writer.write_unary(folded >> parameter);
+            writer.write(folded & ((1u64 << parameter) - 1), parameter);
+        }
+    }
+}
+
+fn fixed_prediction(samples: &[i64], order: usize, i: usize) -> i64 {
+    match order {
+        0 => 0,
+        1 => samples[i - 1],
+        2 => 2 * samples[i - 1] - samples[i - 2],
+        3 => 3 * samples[i - 1] - 3 * samples[i - 2] + samples[i - 3],
+        _ => 4 * samples[i - 1] - 6 * samples[i - 2] + 4 * samples[i - 3] - samples[i - 4],
+    }
+}
+
+fn write_subframe(writer: &mut BitWriter, kind: &Subframe, bits: u32, samples: &[i64]) {
+    writer.write(0, 1);
+    match kind {
+        Subframe::Constant => {
+            writer.write(0, 6);
+            writer.write(0, 1);
+            writer.write_signed(samples[0], bits);
+        }
+        Subframe::Verbatim => {
+            writer.write(1, 6);
+            writer.write(0, 1);
+            for &sample in samples {
+                writer.write_signed(sample, bits);
+            }
+        }
+        Subframe::Fixed { order, rice } => {
+            writer.write(8 + *order as u64, 6);
+            writer.write(0, 1);
+            for &sample in &samples[..*order] {
+                writer.write_signed(sample, bits);
+            }
+            let residual: Vec<i64> = (*order..samples.len())
+                .map(|i| samples[i] - fixed_prediction(samples, *order, i))
+                .collect();
+            write_rice(writer, &residual, *rice);
+        }
+        Subframe::Lpc {
+            coefficients,
+            shift,
+        } => {
+            let order = coefficients.len();
+            let precision = 14;
+            writer.write(31 + order as u64, 6);
+            writer.write(0, 1);
+            for &sample in &samples[..order] {
+                writer.write_signed(sample, bits);
+            }
+            writer.write(precision as u64 - 1, 4);
+            writer.write_signed(*shift as i64, 5);
+            for &coefficient in coefficients {
+                writer.write_signed(coefficient, precision);
+            }
+            let residual: Vec<i64> = (order..samples.len())
+                .map(|i| {
+                    let prediction: i64 = coefficients
+                        .iter()
+                        .enumerate()
+                        .map(|(j, c)| c * samples[i - 1 - j])
+                        .sum();
+                    samples[i] - (prediction >> shift)
+                })
+                .collect();
+            write_rice(writer, &residual, 6);
+        }
+        Subframe::Escaped { order, wasted } => {
+            writer.write(8 + *order as u64, 6);
+            writer.write(1, 1);
+            writer.write_unary(*wasted as u64 - 1);
+            let bits = bits - wasted;
+            let shifted: Vec<i64> = samples.iter().map(|sample| sample >> wasted).collect();
+            for &sample in &shifted[..*order] {
+                writer.write_signed(sample, bits);
+            }
+            // One escaped partition holding raw residuals.
+            writer.write(0, 2);
+            writer.write(0, 4);
+            writer.write(0xF, 4);
+            writer.write(bits as u64 + 1, 5);
+            for i in *order..shifted.len() {
+                writer.write_signed(shifted[i] - fixed_prediction(&shifted, *order, i), bits + 1);
+            }
+        }
+    }
+}
+
+/// One frame: its channel assignment code and the subframes as stored.
+struct Frame {
+    assignment: u64,
+    subframes: Vec<(Subframe, Vec<i64>)>,
+}
+
+fn encode(channels: u16, frames: &[Frame]) -> Vec<u8> {
+    let total: usize = frames.iter().map(|frame| frame.subframes[0].1.len()).sum();
+    let mut writer = BitWriter::default();
+    writer.bytes.extend_from_slice(b"fLaC");
+    // STREAMINFO, followed by a PADDING block the decoder has to skip.
+    writer.write(0, 8);
+    writer.write(34, 24);
+    writer.write(BLOCK as u64, 16);
+    writer.write(BLOCK as u64, 16);
+    writer.write(0, 24);
+    writer.write(0, 24);
+    writer.write(SAMPLE_RATE as u64, 20);
+    writer.write(channels as u64 - 1, 3);
+    writer.write(BITS as u64 - 1, 5);
+    writer.write(total as u64, 36);
+    writer.write(0, 64);
+    writer.write(0, 64);
+    writer.write(0x81, 8);
+    writer.write(5, 24);
+    writer.write(0, 40);
+
+    for (index, frame) in frames.iter().enumerate() {
+        let block = frame.subframes[0].1.len();
+        writer.write(0xFFF8, 16);
+        writer.write(7, 4);
+        writer.write(0, 4);
+        writer.write(frame.assignment, 4);
+        writer.write(0, 3);
+        writer.write(0, 1);
+        writer.write(index as u64, 8);
+        writer.write(block as u64 - 1, 16);
+        writer.write(0, 8);
+        for (channel, (kind, samples)) in frame.subframes.iter().enumerate() {
+            let side = match frame.assignment {
+                8 | 10 => channel == 1,
+                9 => channel == 0,
+                _ => false,
+            };
+            write_subframe(&mut writer, kind, BITS + u32::from(side), samples);
+        }
+        writer.align();
+        writer.write(0, 16);
+    }
+    writer.bytes
+}
+
+fn tone(phase: usize, len: usize) -> Vec<i64> {
+    (0..len)
+        .map(|i| ((((phase + i) as f64) * 0.19).sin() * 12_000.0) as i64)
+        .collect()
+}
+
+fn decode_all(bytes: Vec<u8>, chunk: usize) -> Vec<f32> {
+    let mut reader = FlacReader::new(Cursor::new(bytes)).unwrap();
+    let mut decoded = Vec::new();
+    let mut buffer = vec![0.0; chunk];
+    loop {
+        let read = reader.read_samples(&mut buffer).unwrap();
+        if read == 0 {
+            break;
+        }
+        decoded.extend_from_slice(&buffer[..read]);
+    }
+    decoded
+}
+
+fn scaled(samples: &[i64]) -> Vec<f32> {
+    samples
+        .iter()
+        .map(|&sample| sample as f32 / 32_768.0)
+        .collect()
+}
+
+#[test]
+fn decodes_every_subframe_type() {
+    let wasted: Vec<i64> = tone(0, BLOCK).iter().map(|sample| sample & !3).collect();
+    let frames = vec![
+        Frame {
+            assignment: 0,
+            subframes: vec![(Subframe::Constant, vec![-1_234; BLOCK])],
+        },
+        Frame {
+            assignment: 0,
+            subframes: vec![(Subframe::Verbatim, tone(0, BLOCK))],
+        },
+        Frame {
+            assignment: 0,
+            subframes: vec![(Subframe::Fixed { order: 2, rice: 5 }, tone(64, BLOCK))],
+        },
+        Frame {
+            assignment: 0,
+            subframes: vec![(Subframe::Fixed { order: 4, rice: 3 }, tone(128, BLOCK))],
+        },
+        Frame {
+            assignment: 0,
+            subframes: vec![(
+                Subframe::Lpc {
+                    coefficients: vec![3_800, -2_000],
+                    shift: 11,
+                },
+                tone(192, BLOCK),
+            )],
+        },
+        Frame {
+            assignment: 0,
+            subframes: vec![(
+                Subframe::Escaped {
+                    order: 1,
+                    wasted: 2,
+                },
+                wasted.clone(),
+            )],
+        },
+    ];
+    let bytes = encode(1, &frames);
+    let mut expected = vec![-1_234; BLOCK];
+    expected.extend(tone(0, BLOCK));
+    expected.extend(tone(64, BLOCK));
+    expected.extend(tone(128, BLOCK));
+    expected.extend(tone(192, BLOCK));
+    expected.extend(wasted);
+
+    let reader = FlacReader::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(reader.sample_rate(), SAMPLE_RATE);
+    assert_eq!(reader.channels(), 1);
+    assert_eq!(reader.bits_per_sample(), BITS);
+    assert_eq!(reader.frames(), 6 * BLOCK as u64);
+    assert_eq!(decode_all(bytes, 100), scaled(&expected));
+}
+
+#[test]
+fn undoes_stereo_decorrelation() {
+    let left = tone(0, BLOCK);
+    let right: Vec<i64> = tone(40, BLOCK).iter().map(|sample| sample / 2).collect();
+    let side: Vec<i64> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+    let mid: Vec<i64> = left.iter().zip(&right).map(|(l, r)| (l + r) >> 1).collect();
+    let stored = |assignment, first: &[i64], second: &[i64]| Frame {
+        assignment,
+        subframes: vec![
+            (Subframe::Fixed { order: 1, rice: 8 }, first.to_vec()),
+            (Subframe::Verbatim, second.to_vec()),
+        ],
+    };
+    let frames = vec![
+        stored(1, &left, &right),
+        stored(8, &left, &side),
+        stored(9, &side, &right),
+        stored(10, &mid, &side),
+    ];
+    let mut expected = Vec::new();
+    for _ in 0..frames.len() {
+        for (l, r) in left.iter().zip(&right) {
+            expected.push(*l);
+            expected.push(*r);
+        }
+    }
+    assert_eq!(decode_all(encode(2, &frames), 2 * 37), scaled(&expected));
+}
+
+#[test]
+fn seeks_within_and_across_frames() {
+    let frames: Vec<Frame> = (0..4)
+        .map(|index| Frame {
+            assignment: 0,
+            subframes: vec![(Subframe::Verbatim, tone(index * BLOCK, BLOCK))],
+        })
+        .collect();
+    let expected = scaled(&tone(0, 4 * BLOCK));
+    let mut reader = FlacReader::new(Cursor::new(encode(1, &frames))).unwrap();
+    let mut buffer = [0.0; 10];
+
+    reader.seek(150).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 10);
+    assert_eq!(buffer[..], expected[150..160]);
+
+    reader.seek(3).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 10);
+    assert_eq!(buffer[..], expected[3..13]);
+
+    reader.seek(250).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 6);
+    assert_eq!(buffer[..6], expected[250..]);
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn rejects_other_streams_and_truncated_frames() {
+    assert!(FlacReader::new(Cursor::new(b"RIFF\0\0\0\0WAVE".to_vec())).is_err());
+
+    let frames = vec![Frame {
+        assignment: 0,
+        subframes: vec![(Subframe::Verbatim, tone(0, BLOCK))],
+    }];
+    let mut bytes = encode(1, &frames);
+    bytes.truncate(bytes.len() - 40);
+    let mut reader = FlacReader::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = vec![0.0; BLOCK];
+    assert!(reader.read_samples(&mut buffer).is_err());
+}
@@ -0,0 +1,110 @@
+use crate::DelayLine;
+use crate::kernels::{self, scalar};
+
+/// Deterministic test signal with values of both signs and varied magnitudes.
+fn signal(samples: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed.wrapping_mul(2_654_435_761).max(1);
+    (0..samples)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state as f32 / u32::MAX as f32 - 0.5) * 3.0
+        })
+        .collect()
+}
+
+fn bits(samples: &[f32]) -> Vec<u32> {
+    samples.iter().map(|sample| sample.to_bits()).collect()
+}
+
+// Lengths cover empty input, partial vectors and several whole vectors plus a tail.
+const FRAME_COUNTS: [usize; 9] = [0, 1, 2, 3, 4, 5, 17, 64, 131];
+
+#[test]
+fn scale_matches_the_scalar_kernel() {
+    for frames in FRAME_COUNTS {
+        for gain in [0.0, 0.5, 1.0, -1.25, 7.3] {
+            let mut expected = signal(frames * 2, frames as u32);
+            let mut actual = expected.clone();
+            scalar::scale(&mut expected, gain);
+            kernels::scale(&mut actual, gain);
+            assert_eq!(
+                bits(&actual),
+                bits(&expected),
+                "{frames} frames, gain {gain}"
+            );
+        }
+    }
+}
+
+#[test]
+fn ramp_mix_matches_the_scalar_kernel() {
+    for frames in FRAME_COUNTS {
+        for (start, target) in [(1.0, 1.0), (1.0, 0.2), (0.0, 1.0), (0.37, 0.91)] {
+            let step = if frames == 0 {
+                0.0
+            } else {
+                (target - start) / frames as f32
+            };
+            let block = signal(frames * 2, 1 + frames as u32);
+            let output = signal(frames * 2, 100 + frames as u32);
+            let (mut expected_block, mut expected_output) = (block.clone(), output.clone());
+            let (mut actual_block, mut actual_output) = (block, output);
+            scalar::ramp_mix(&mut expected_block, &mut expected_output, start, step);
+            kernels::ramp_mix(&mut actual_block, &mut actual_output, start, step);
+            assert_eq!(
+                bits(&actual_block),
+                bits(&expected_block),
+                "{frames} frames"
+            );
+            assert_eq!(
+                bits(&actual_output),
+                bits(&expected_output),
+                "{frames} frames"
+            );
+        }
+    }
+}
+
+#[test]
+fn ramp_mix_reaches_the_target_on_the_last_frame() {
+    let mut block = vec![1.0; 8];
+    let mut output = vec![0.0; 8];
+    kernels::ramp_mix(&mut block, &mut output, 0.0, 0.25);
+    assert_eq!(output, [0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0]);
+}
+
+#[test]
+fn lerp_matches_the_scalar_kernel() {
+    for frames in FRAME_COUNTS {
+        for t in [0.0, 0.25, 0.5, 0.999_999] {
+            let input = signal(frames * 2 + 2, 7 + frames as u32);
+            let mut expected = vec![0.0; frames * 2];
+            let mut actual = vec![0.0; frames * 2];
+            scalar::lerp(&mut expected, &input, t);
+            kernels::lerp(&mut actual, &input, t);
+            assert_eq!(bits(&actual), bits(&expected), "{frames} frames, t {t}");
+        }
+    }
+}
+
+#[test]
+fn delay_lines_without_latency_are_bypassed_until_a_delay_is_set() {
+    let mut line = DelayLine::new(64);
+    assert!(line.is_bypassed());
+    let mut block = signal(32, 3);
+    let input = block.clone();
+    line.process_block(&mut block);
+    assert_eq!(block, input);
+    assert!(line.is_bypassed());
+
+    line.set_target(4);
+    assert!(!line.is_bypassed());
+    line.process_block(&mut block);
+    assert!(block[..8].iter().all(|&sample| sample == 0.0));
+    assert_eq!(block[8..], input[..24]);
+    // Frames still queued from the old delay keep the line engaged.
+    line.set_target(0);
+    assert!(!line.is_bypassed());
+}
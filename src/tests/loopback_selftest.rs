@@ -1,13 +1,11 @@
 use std::f32::consts::TAU;
 
-use crate::{AudioBuffer, Mixer, monotonic_timestamp_ns};
+use crate::offline::{OfflineInput, OfflineRenderer};
 
 #[test]
 fn loopback_selftest_sine_through_mixer() {
     let sample_rate = 48_000u32;
     let block_frames = 256usize;
-    let mut mixer = Mixer::new(sample_rate, block_frames);
-    let (_handle, ring) = mixer.add_source(block_frames * 8);
 
     let frequency_hz = 1_000.0f32;
     let total_frames = (sample_rate / 10) as usize; // 100ms
@@ -20,23 +18,9 @@ fn loopback_selftest_sine_through_mixer() {
         input.push(sample);
     }
 
-    let mut recorded = Vec::with_capacity(input.len());
-    for chunk in input.chunks(block_frames * 2) {
-        let timestamp = monotonic_timestamp_ns();
-        let frames_in_chunk = chunk.len() / 2;
-        ring.push(chunk, Some(timestamp));
-
-        let mut buffer = vec![0.0f32; block_frames * 2];
-        let mut audio = AudioBuffer {
-            data: buffer.as_mut_ptr(),
-            frames: block_frames as u32,
-            channels: 2,
-            timestamp_ns: timestamp,
-        };
-
-        mixer.process(&mut audio).expect("process block");
-        recorded.extend_from_slice(&buffer[..frames_in_chunk * 2]);
-    }
+    let mut renderer = OfflineRenderer::new(sample_rate, block_frames);
+    renderer.add_input(OfflineInput::from_samples(input.clone()));
+    let recorded = renderer.render(total_frames).expect("render");
 
     assert_eq!(recorded.len(), input.len());
 
@@ -0,0 +1,168 @@
+//! Conformance checks against the EBU Tech 3341 and Tech 3342 test signals, generated here
+//! instead of read from the reference WAV files.
+
+use std::f32::consts::TAU;
+
+use crate::loudness::{LoudnessMeter, LoudnessReading};
+use crate::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+/// Streams stereo 1 kHz sine segments (level in dBFS per channel, duration in seconds) through
+/// a meter, keeping phase continuous across segments.
+struct SignalGenerator {
+    meter: LoudnessMeter,
+    phase: f32,
+    block: Vec<f32>,
+}
+
+impl SignalGenerator {
+    fn new() -> Self {
+        Self {
+            meter: LoudnessMeter::new(SAMPLE_RATE),
+            phase: 0.0,
+            block: vec![0.0; BLOCK_FRAMES * 2],
+        }
+    }
+
+    fn play(&mut self, segments: &[(f32, f32)]) -> LoudnessReading {
+        for &(level_db, seconds) in segments {
+            let amplitude = 10f32.powf(level_db / 20.0);
+            let frames = (seconds * SAMPLE_RATE as f32).round() as usize;
+            let mut remaining = frames;
+            while remaining > 0 {
+                let count = remaining.min(BLOCK_FRAMES);
+                for frame in self.block[..count * 2].chunks_exact_mut(2) {
+                    frame.fill(amplitude * (TAU * self.phase).sin());
+                    self.phase = (self.phase + 1_000.0 / SAMPLE_RATE as f32).fract();
+                }
+                self.meter.process(&self.block[..count * 2]);
+                remaining -= count;
+            }
+        }
+        self.meter.reading()
+    }
+}
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32, what: &str) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{what}: measured {actual:.2}, expected {expected:.1} ± {tolerance}"
+    );
+}
+
+#[test]
+fn tech_3341_steady_tones() {
+    for level in [-23.0f32, -33.0] {
+        let reading = SignalGenerator::new().play(&[(level, 20.0)]);
+        assert_close(reading.momentary, level, 0.1, "momentary");
+        assert_close(reading.short_term, level, 0.1, "short-term");
+        assert_close(reading.integrated, level, 0.1, "integrated");
+    }
+}
+
+#[test]
+fn tech_3341_gating() {
+    // Case 3: the quiet segments fall below the relative gate.
+    let reading = SignalGenerator::new().play(&[(-36.0, 10.0), (-23.0, 60.0), (-36.0, 10.0)]);
+    assert_close(reading.integrated, -23.0, 0.1, "case 3 integrated");
+
+    // Case 4: adds segments below the absolute gate.
+    let reading = SignalGenerator::new().play(&[
+        (-72.0, 10.0),
+        (-36.0, 10.0),
+        (-23.0, 60.0),
+        (-36.0, 10.0),
+        (-72.0, 10.0),
+    ]);
+    assert_close(reading.integrated, -23.0, 0.1, "case 4 integrated");
+
+    // Case 5: louder and quieter segments average out.
+    let reading = SignalGenerator::new().play(&[(-26.0, 20.0), (-20.0, 20.1), (-26.0, 20.0)]);
+    assert_close(reading.integrated, -23.0, 0.1, "case 5 integrated");
+}
+
+#[test]
+fn tech_3342_loudness_range() {
+    let cases: [(&[(f32, f32)], f32); 4] = [
+        (&[(-20.0, 20.0), (-30.0, 20.0)], 10.0),
+        (&[(-20.0, 20.0), (-15.0, 20.0)], 5.0),
+        (&[(-40.0, 20.0), (-20.0, 20.0)], 20.0),
+        (
+            &[
+                (-50.0, 20.0),
+                (-35.0, 20.0),
+                (-20.0, 20.0),
+                (-35.0, 20.0),
+                (-50.0, 20.0),
+            ],
+            15.0,
+        ),
+    ];
+    for (index, (segments, expected)) in cases.iter().enumerate() {
+        let reading = SignalGenerator::new().play(segments);
+        assert_close(
+            reading.range,
+            *expected,
+            1.0,
+            &format!("Tech 3342 case {}", index + 1),
+        );
+    }
+}
+
+#[test]
+fn mixer_measures_master_and_resets() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.set_loudness_metering(source, true).unwrap();
+    assert!(mixer.source_loudness(source).unwrap().is_some());
+
+    let amplitude = 10f32.powf(-23.0 / 20.0);
+    let mut phase = 0.0f32;
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut render = |mixer: &mut Mixer, blocks: usize| {
+        for _ in 0..blocks {
+            for frame in input.chunks_exact_mut(2) {
+                frame.fill(amplitude * (TAU * phase).sin());
+                phase = (phase + 1_000.0 / SAMPLE_RATE as f32).fract();
+            }
+            ring.push(&input, None);
+            let mut buffer = AudioBuffer {
+                data: output.as_mut_ptr(),
+                frames: BLOCK_FRAMES as u32,
+                channels: 2,
+                timestamp_ns: 0,
+            };
+            mixer.process(&mut buffer).unwrap();
+        }
+    };
+
+    render(&mut mixer, 500);
+    assert_close(mixer.master_loudness().integrated, -23.0, 0.1, "master");
+    let source_reading = mixer.source_loudness(source).unwrap().unwrap();
+    assert_close(source_reading.short_term, -23.0, 0.1, "source");
+
+    mixer.reset_loudness();
+    render(&mut mixer, 1);
+    assert_eq!(mixer.master_loudness(), LoudnessReading::default());
+    render(&mut mixer, 100);
+    assert_close(
+        mixer.master_loudness().integrated,
+        -23.0,
+        0.1,
+        "after reset",
+    );
+
+    // A meter handed over after metering was switched off starts again from silence.
+    mixer.set_loudness_metering(source, false).unwrap();
+    assert_eq!(mixer.source_loudness(source).unwrap(), None);
+    render(&mut mixer, 1);
+    mixer.set_loudness_metering(source, true).unwrap();
+    render(&mut mixer, 1);
+    assert_eq!(
+        mixer.source_loudness(source).unwrap(),
+        Some(LoudnessReading::default())
+    );
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use crate::meter::{CLIP_HOLD_MS, Meter, PEAK_DECAY_DB_PER_S, PEAK_HOLD_MS};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+fn block_seconds() -> f32 {
+    BLOCK_FRAMES as f32 / SAMPLE_RATE as f32
+}
+
+fn db(level: f32) -> f32 {
+    20.0 * level.log10()
+}
+
+/// Feed `blocks` blocks of a stereo sine at `frequency` with the given starting phase.
+fn feed_sine(meter: &mut Meter, frequency: f32, amplitude: f32, phase: f32, blocks: usize) {
+    let mut n = 0usize;
+    let mut block = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        for frame in block.chunks_exact_mut(2) {
+            let value = amplitude * (TAU * frequency * n as f32 / SAMPLE_RATE as f32 + phase).sin();
+            frame.fill(value);
+            n += 1;
+        }
+        meter.process(&block);
+    }
+}
+
+fn feed_silence(meter: &mut Meter, blocks: usize) {
+    let block = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        meter.process(&block);
+    }
+}
+
+#[test]
+fn sine_reads_expected_rms_and_peak() {
+    let mut meter = Meter::new(SAMPLE_RATE);
+    feed_sine(&mut meter, 997.0, 0.5, 0.0, 400);
+    let reading = meter.reading();
+    assert!(
+        (db(reading.rms) - db(0.5 / 2f32.sqrt())).abs() < 0.1,
+        "rms read {:.2} dBFS",
+        db(reading.rms)
+    );
+    assert!((reading.peak - 0.5).abs() < 0.005, "peak {}", reading.peak);
+    assert!((reading.peak_hold - 0.5).abs() < 0.005);
+    assert!(!reading.clipped);
+}
+
+#[test]
+fn true_peak_catches_inter_sample_overs() {
+    // A quarter-rate sine sampled 45 degrees off its crest never shows its real peak in the
+    // samples: they sit at 0.707 of the amplitude.
+    let mut meter = Meter::new(SAMPLE_RATE);
+    let frequency = SAMPLE_RATE as f32 / 4.0;
+    feed_sine(&mut meter, frequency, 1.0, FRAC_PI_4, 40);
+    let reading = meter.reading();
+    assert!(
+        (db(reading.peak) + 3.01).abs() < 0.05,
+        "sample peak {:.2} dBFS",
+        db(reading.peak)
+    );
+    assert!(
+        db(reading.true_peak).abs() < 0.6,
+        "true peak {:.2} dBTP",
+        db(reading.true_peak)
+    );
+    assert!(
+        reading.clipped,
+        "inter-sample over should light the clip flag"
+    );
+}
+
+#[test]
+fn peak_holds_then_decays() {
+    let mut meter = Meter::new(SAMPLE_RATE);
+    feed_sine(&mut meter, 440.0, 0.8, 0.0, 20);
+    let hold_blocks = (PEAK_HOLD_MS * 0.001 / block_seconds()) as usize;
+
+    // Half-way through the hold time the peak has fallen but the hold has not.
+    feed_silence(&mut meter, hold_blocks / 2);
+    let reading = meter.reading();
+    assert!((reading.peak_hold - 0.8).abs() < 0.005);
+    let elapsed = (hold_blocks / 2) as f32 * block_seconds();
+    let expected_fall = PEAK_DECAY_DB_PER_S * elapsed;
+    assert!(
+        (db(0.8) - db(reading.peak) - expected_fall).abs() < 0.2,
+        "peak fell {:.2} dB, expected {expected_fall:.2} dB",
+        db(0.8) - db(reading.peak)
+    );
+
+    // After the hold expires it drops to the decaying peak.
+    feed_silence(&mut meter, hold_blocks);
+    let reading = meter.reading();
+    assert_eq!(reading.peak_hold, reading.peak);
+    assert!(reading.peak < 0.1);
+}
+
+#[test]
+fn clip_indicator_latches_then_clears() {
+    let mut meter = Meter::new(SAMPLE_RATE);
+    let mut block = vec![0.0f32; BLOCK_FRAMES * 2];
+    block[10] = 1.0;
+    meter.process(&block);
+    assert!(meter.reading().clipped);
+
+    let clip_blocks = (CLIP_HOLD_MS * 0.001 / block_seconds()) as usize;
+    feed_silence(&mut meter, clip_blocks / 2);
+    assert!(meter.reading().clipped);
+    feed_silence(&mut meter, clip_blocks);
+    assert!(!meter.reading().clipped);
+}
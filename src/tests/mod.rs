@@ -1 +1,13 @@
+pub mod clock;
+pub mod flac;
+pub mod kernels;
 pub mod loopback_selftest;
+pub mod loudness;
+pub mod meter;
+pub mod queue;
+pub mod recorder;
+pub mod spectrum;
+pub mod stereo;
+pub mod timing;
+pub mod vad;
+pub mod wav;
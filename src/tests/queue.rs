@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::thread;
+
+use crate::queue::BoundedQueue;
+
+#[test]
+fn rejects_when_full_and_preserves_order() {
+    let queue = BoundedQueue::new(3);
+    for value in 0..4u32 {
+        assert!(queue.push(value).is_ok());
+    }
+    assert_eq!(queue.push(4), Err(4));
+    assert_eq!(queue.pop(), Some(0));
+    assert!(queue.push(4).is_ok());
+    let drained: Vec<u32> = std::iter::from_fn(|| queue.pop()).collect();
+    assert_eq!(drained, vec![1, 2, 3, 4]);
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn concurrent_producers_lose_nothing_that_was_accepted() {
+    const PRODUCERS: u64 = 4;
+    const PER_PRODUCER: u64 = 20_000;
+    let queue = Arc::new(BoundedQueue::<(u64, u64)>::new(64));
+    let handles: Vec<_> = (0..PRODUCERS)
+        .map(|producer| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for sequence in 0..PER_PRODUCER {
+                    while queue.push((producer, sequence)).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let mut next = [0u64; PRODUCERS as usize];
+    let mut received = 0;
+    while received < PRODUCERS * PER_PRODUCER {
+        match queue.pop() {
+            Some((producer, sequence)) => {
+                // Each producer's records arrive in the order it pushed them.
+                assert_eq!(sequence, next[producer as usize]);
+                next[producer as usize] += 1;
+                received += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn records_left_in_the_queue_are_dropped_with_it() {
+    let record = Arc::new(());
+    let queue = BoundedQueue::new(4);
+    for _ in 0..3 {
+        assert!(queue.push(record.clone()).is_ok());
+    }
+    drop(queue.pop());
+    assert_eq!(Arc::strong_count(&record), 3);
+    drop(queue);
+    assert_eq!(Arc::strong_count(&record), 1);
+}
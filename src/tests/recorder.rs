@@ -0,0 +1,36 @@
+use std::io::{Cursor, ErrorKind};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::recorder::{RecordTap, WRITER_CHUNK_FRAMES, write_tracks};
+use crate::wav::{SampleFormat, WavSpec, WavWriter, read_wav};
+
+const SPEC: WavSpec = WavSpec {
+    sample_rate: 48_000,
+    channels: 2,
+    format: SampleFormat::Float32,
+};
+
+#[test]
+fn a_failed_write_still_finalises_the_header() {
+    // Two writer chunks are queued, but the "disk" only has room for the first one.
+    let tap = Arc::new(RecordTap::new(SPEC.sample_rate));
+    let samples: Vec<f32> = (0..WRITER_CHUNK_FRAMES * 2 * 2)
+        .map(|index| index as f32 / 65_536.0)
+        .collect();
+    tap.write(&samples, 1);
+    let first_chunk = WRITER_CHUNK_FRAMES * 2;
+    let mut disk = vec![0u8; 80 + first_chunk * 4 + 1_000];
+
+    let writer = WavWriter::new(Cursor::new(&mut disk[..]), SPEC).unwrap();
+    let frames = Arc::new(AtomicU64::new(0));
+    let stop = AtomicBool::new(true);
+    let err = write_tracks(vec![(tap, writer, frames.clone())], &stop).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+    assert_eq!(frames.load(Ordering::Relaxed), WRITER_CHUNK_FRAMES as u64);
+
+    // The header describes exactly the chunk that made it to disk.
+    let (spec, recorded) = read_wav(Cursor::new(&disk)).unwrap();
+    assert_eq!(spec, SPEC);
+    assert_eq!(recorded, samples[..first_chunk]);
+}
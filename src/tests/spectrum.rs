@@ -0,0 +1,184 @@
+use std::f32::consts::TAU;
+
+use crate::spectrum::{SPECTRUM_FLOOR_DB, Spectrum, SpectrumConfig, SpectrumTap, fft_size};
+use crate::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+fn sine_block(frequency: f32, amplitude: f32, phase: &mut f32, frames: usize) -> Vec<f32> {
+    let mut block = vec![0.0; frames * 2];
+    for frame in block.chunks_exact_mut(2) {
+        frame.fill(amplitude * (TAU * *phase).sin());
+        *phase = (*phase + frequency / SAMPLE_RATE as f32).fract();
+    }
+    block
+}
+
+fn loudest_band(spectrum: &Spectrum) -> usize {
+    spectrum
+        .levels_db
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(band, _)| band)
+        .unwrap()
+}
+
+#[test]
+fn sine_lands_in_its_band_at_its_rms_level() {
+    let tap = SpectrumTap::new(SAMPLE_RATE, SpectrumConfig::default());
+    let mut phase = 0.0;
+    tap.write(&sine_block(1_000.0, 0.5, &mut phase, fft_size(SAMPLE_RATE)));
+
+    let spectrum = tap.spectrum();
+    assert_eq!(spectrum.levels_db.len(), 32);
+    assert_eq!(spectrum.frequencies_hz.len(), 32);
+    assert!(
+        spectrum
+            .frequencies_hz
+            .windows(2)
+            .all(|pair| pair[0] < pair[1])
+    );
+
+    let band = loudest_band(&spectrum);
+    let ratio = spectrum.frequencies_hz[1] / spectrum.frequencies_hz[0];
+    let centre = spectrum.frequencies_hz[band];
+    assert!(
+        (centre / ratio.sqrt()..centre * ratio.sqrt()).contains(&1_000.0),
+        "1 kHz landed in the band centred on {centre} Hz"
+    );
+    let expected = 20.0 * (0.5 / 2f32.sqrt()).log10();
+    assert!(
+        (spectrum.levels_db[band] - expected).abs() < 1.0,
+        "band level {} dB, expected {expected:.1} dB",
+        spectrum.levels_db[band]
+    );
+    // Bands an octave or more away only see window leakage.
+    let far = spectrum
+        .frequencies_hz
+        .iter()
+        .position(|&hz| hz > 4_000.0)
+        .unwrap();
+    assert!(spectrum.levels_db[far] < expected - 60.0);
+}
+
+#[test]
+fn silence_reads_floor() {
+    let tap = SpectrumTap::new(SAMPLE_RATE, SpectrumConfig::default());
+    let spectrum = tap.spectrum();
+    assert!(spectrum.levels_db.iter().all(|&db| db == SPECTRUM_FLOOR_DB));
+}
+
+#[test]
+fn analysis_is_rate_limited() {
+    let tap = SpectrumTap::new(
+        SAMPLE_RATE,
+        SpectrumConfig {
+            bands: 16,
+            rate_hz: 0.1,
+        },
+    );
+    let first = tap.spectrum();
+    let mut phase = 0.0;
+    tap.write(&sine_block(1_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+    assert_eq!(
+        tap.spectrum(),
+        first,
+        "result was recomputed before the interval elapsed"
+    );
+
+    let tap = SpectrumTap::new(
+        SAMPLE_RATE,
+        SpectrumConfig {
+            bands: 16,
+            rate_hz: 1_000.0,
+        },
+    );
+    let first = tap.spectrum();
+    tap.write(&sine_block(1_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    assert_ne!(tap.spectrum().levels_db, first.levels_db);
+}
+
+#[test]
+fn tap_analyses_only_the_latest_samples() {
+    let tap = SpectrumTap::new(SAMPLE_RATE, SpectrumConfig::default());
+    let mut phase = 0.0;
+    // Several times the history length of a low tone, then exactly one window of a high one.
+    for _ in 0..8 {
+        tap.write(&sine_block(100.0, 1.0, &mut phase, BLOCK_FRAMES * 5));
+    }
+    tap.write(&sine_block(5_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+
+    let spectrum = tap.spectrum();
+    let band = loudest_band(&spectrum);
+    assert!(spectrum.frequencies_hz[band] > 3_000.0);
+    let low = spectrum
+        .frequencies_hz
+        .iter()
+        .position(|&hz| hz > 100.0)
+        .unwrap();
+    assert!(spectrum.levels_db[low] < -60.0);
+}
+
+#[test]
+fn disabled_taps_ignore_writes_and_reconfiguring_forgets_old_samples() {
+    let tap = SpectrumTap::disabled(SAMPLE_RATE);
+    assert!(!tap.is_enabled());
+    let mut phase = 0.0;
+    tap.write(&sine_block(1_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+    tap.configure(Some(SpectrumConfig::default()));
+    assert!(
+        tap.spectrum()
+            .levels_db
+            .iter()
+            .all(|&db| db == SPECTRUM_FLOOR_DB)
+    );
+
+    tap.write(&sine_block(1_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+    tap.configure(None);
+    tap.write(&sine_block(5_000.0, 1.0, &mut phase, fft_size(SAMPLE_RATE)));
+    tap.configure(Some(SpectrumConfig {
+        bands: 16,
+        rate_hz: 20.0,
+    }));
+    let spectrum = tap.spectrum();
+    assert_eq!(spectrum.levels_db.len(), 16);
+    assert!(spectrum.levels_db.iter().all(|&db| db == SPECTRUM_FLOOR_DB));
+}
+
+#[test]
+fn mixer_analyses_master_and_sources_when_enabled() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    assert!(mixer.master_spectrum().is_none());
+    assert_eq!(mixer.source_spectrum(source).unwrap(), None);
+
+    mixer.set_spectrum(Some(SpectrumConfig::default()));
+    let (late, _) = mixer.add_source(BLOCK_FRAMES * 8);
+    assert!(mixer.source_spectrum(late).unwrap().is_some());
+
+    let mut phase = 0.0;
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..20 {
+        ring.push(&sine_block(1_000.0, 0.5, &mut phase, BLOCK_FRAMES), None);
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+    }
+
+    let master = mixer.master_spectrum().unwrap();
+    let band = loudest_band(&master);
+    assert!(master.levels_db[band] > -12.0);
+    let source_spectrum = mixer.source_spectrum(source).unwrap().unwrap();
+    assert_eq!(loudest_band(&source_spectrum), band);
+
+    mixer.set_spectrum(None);
+    assert!(mixer.master_spectrum().is_none());
+    assert_eq!(mixer.source_spectrum(source).unwrap(), None);
+}
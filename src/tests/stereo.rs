@@ -0,0 +1,282 @@
+use std::f32::consts::TAU;
+
+use crate::stereo::{
+    ChannelMode, ChannelUtility, MONO_WARNING_HOLD_MS, StereoMeter, StereoReading,
+};
+use crate::{AudioBuffer, Mixer, MixerError, SourceHandle};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+/// Feed `blocks` blocks in which each frame is `map` applied to a 997 Hz sine sample.
+fn feed(meter: &mut StereoMeter, blocks: usize, map: impl Fn(f32, usize) -> [f32; 2]) {
+    let mut n = 0usize;
+    let mut block = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        for frame in block.chunks_exact_mut(2) {
+            let value = 0.5 * (TAU * 997.0 * n as f32 / SAMPLE_RATE as f32).sin();
+            frame.copy_from_slice(&map(value, n));
+            n += 1;
+        }
+        meter.process(&block);
+    }
+}
+
+fn measure(map: impl Fn(f32, usize) -> [f32; 2]) -> StereoReading {
+    let mut meter = StereoMeter::new(SAMPLE_RATE);
+    feed(&mut meter, 400, map);
+    meter.reading()
+}
+
+/// Deterministic white noise in [-0.5, 0.5).
+fn noise(seed: usize) -> f32 {
+    let mut x = seed as u64 ^ 0x9e37_79b9_7f4a_7c15;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+}
+
+#[test]
+fn mono_is_fully_correlated_and_narrow() {
+    let reading = measure(|s, _| [s, s]);
+    assert!((reading.correlation - 1.0).abs() < 1e-3);
+    assert!(reading.width < 1e-3);
+    assert!(!reading.mono_warning);
+}
+
+#[test]
+fn flipped_polarity_warns() {
+    let reading = measure(|s, _| [s, -s]);
+    assert!((reading.correlation + 1.0).abs() < 1e-3);
+    assert!((reading.width - 2.0).abs() < 1e-3);
+    assert!(reading.mono_warning);
+}
+
+#[test]
+fn missing_channel_reads_uncorrelated() {
+    let reading = measure(|s, _| [s, 0.0]);
+    assert_eq!(reading.correlation, 0.0);
+    assert!((reading.width - 1.0).abs() < 1e-3);
+    assert!(!reading.mono_warning);
+}
+
+#[test]
+fn unrelated_channels_read_near_zero() {
+    let reading = measure(|_, n| [noise(2 * n), noise(2 * n + 1)]);
+    assert!(reading.correlation.abs() < 0.05, "{}", reading.correlation);
+    assert!((reading.width - 1.0).abs() < 0.05, "{}", reading.width);
+    assert!(!reading.mono_warning);
+}
+
+#[test]
+fn silence_does_not_warn() {
+    let reading = measure(|s, _| [s * 1e-4, -s * 1e-4]);
+    assert_eq!(reading, StereoReading::default());
+}
+
+#[test]
+fn warning_is_held_then_released() {
+    let mut meter = StereoMeter::new(SAMPLE_RATE);
+    feed(&mut meter, 100, |s, _| [s, -s]);
+    assert!(meter.reading().mono_warning);
+
+    let block_ms = BLOCK_FRAMES as f32 * 1_000.0 / SAMPLE_RATE as f32;
+    // Long enough for the integrated correlation to recover, but inside the hold time.
+    feed(&mut meter, (300.0 / block_ms) as usize, |s, _| [s, s]);
+    assert!(meter.reading().correlation > 0.0);
+    assert!(meter.reading().mono_warning);
+
+    feed(
+        &mut meter,
+        (MONO_WARNING_HOLD_MS / block_ms) as usize + 1,
+        |s, _| [s, s],
+    );
+    assert!(!meter.reading().mono_warning);
+}
+
+#[test]
+fn mixer_reports_source_and_master_correlation() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (flipped, flipped_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let (mono, mono_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut n = 0usize;
+    for _ in 0..100 {
+        let start = n;
+        for frame in input.chunks_exact_mut(2) {
+            let value = 0.25 * (TAU * 440.0 * n as f32 / SAMPLE_RATE as f32).sin();
+            frame.copy_from_slice(&[value, -value]);
+            n += 1;
+        }
+        flipped_ring.push(&input, None);
+        n = start;
+        for frame in input.chunks_exact_mut(2) {
+            let value = 0.5 * (TAU * 440.0 * n as f32 / SAMPLE_RATE as f32).sin();
+            frame.fill(value);
+            n += 1;
+        }
+        mono_ring.push(&input, None);
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+    }
+
+    assert!(mixer.source_stereo(flipped).unwrap().mono_warning);
+    let mono_reading = mixer.source_stereo(mono).unwrap();
+    assert!(mono_reading.correlation > 0.99);
+    assert!(!mono_reading.mono_warning);
+    // Left is 0.75 and right 0.25 of the same sine: still in phase.
+    let master = mixer.master_stereo();
+    assert!(master.correlation > 0.99);
+    assert!(!master.mono_warning);
+}
+
+fn utility(
+    invert_left: bool,
+    invert_right: bool,
+    swap_channels: bool,
+    mode: ChannelMode,
+) -> ChannelUtility {
+    ChannelUtility {
+        invert_left,
+        invert_right,
+        swap_channels,
+        mode,
+    }
+}
+
+#[test]
+fn channel_utility_swaps_then_inverts_then_folds() {
+    let cases = [
+        (ChannelUtility::default(), [0.5, 0.25]),
+        (
+            utility(true, false, false, ChannelMode::Stereo),
+            [-0.5, 0.25],
+        ),
+        (
+            utility(false, true, false, ChannelMode::Stereo),
+            [0.5, -0.25],
+        ),
+        (
+            utility(false, false, true, ChannelMode::Stereo),
+            [0.25, 0.5],
+        ),
+        // Inversion follows the swap, so it applies to the new left channel.
+        (
+            utility(true, false, true, ChannelMode::Stereo),
+            [-0.25, 0.5],
+        ),
+        (
+            utility(false, false, false, ChannelMode::Mono),
+            [0.375, 0.375],
+        ),
+        (
+            utility(false, true, false, ChannelMode::Mono),
+            [0.125, 0.125],
+        ),
+        (
+            utility(false, false, false, ChannelMode::LeftOnly),
+            [0.5, 0.5],
+        ),
+        (
+            utility(false, false, false, ChannelMode::RightOnly),
+            [0.25, 0.25],
+        ),
+        (
+            utility(false, false, true, ChannelMode::LeftOnly),
+            [0.25, 0.25],
+        ),
+    ];
+    for (settings, expected) in cases {
+        let mut block = [0.5, 0.25, 0.5, 0.25];
+        settings.process(&mut block);
+        assert_eq!(
+            block,
+            [expected[0], expected[1], expected[0], expected[1]],
+            "{settings}"
+        );
+    }
+}
+
+#[test]
+fn channel_utility_round_trips_through_bits_and_names() {
+    for mode in [
+        ChannelMode::Stereo,
+        ChannelMode::Mono,
+        ChannelMode::LeftOnly,
+        ChannelMode::RightOnly,
+    ] {
+        assert_eq!(ChannelMode::from_code(mode.code()), Some(mode));
+        assert_eq!(ChannelMode::from_name(mode.name()), Some(mode));
+        for bits in 0..8 {
+            let settings = utility(bits & 1 != 0, bits & 2 != 0, bits & 4 != 0, mode);
+            assert_eq!(ChannelUtility::from_bits(settings.to_bits()), settings);
+        }
+    }
+    assert_eq!(ChannelMode::from_code(4), None);
+    assert_eq!(ChannelMode::from_name("surround"), None);
+    assert_eq!(ChannelUtility::default().to_string(), "stereo");
+    assert_eq!(
+        utility(true, true, true, ChannelMode::Mono).to_string(),
+        "swap -L -R mono"
+    );
+}
+
+#[test]
+fn mixer_applies_channel_utility_before_metering() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    assert_eq!(
+        mixer.channel_utility(source).unwrap(),
+        ChannelUtility::default()
+    );
+    let flip = utility(false, true, false, ChannelMode::Stereo);
+    mixer.set_channel_utility(source, flip).unwrap();
+    assert_eq!(mixer.channel_utility(source).unwrap(), flip);
+
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut n = 0usize;
+    // Renders `blocks` blocks and returns the last one.
+    let mut render = |mixer: &mut Mixer, blocks: usize| {
+        for _ in 0..blocks {
+            for frame in input.chunks_exact_mut(2) {
+                frame.fill(0.5 * (TAU * 440.0 * n as f32 / SAMPLE_RATE as f32).sin());
+                n += 1;
+            }
+            ring.push(&input, None);
+            let mut buffer = AudioBuffer {
+                data: output.as_mut_ptr(),
+                frames: BLOCK_FRAMES as u32,
+                channels: 2,
+                timestamp_ns: 0,
+            };
+            mixer.process(&mut buffer).unwrap();
+        }
+        output.clone()
+    };
+
+    let output = render(&mut mixer, 50);
+    assert!(mixer.source_stereo(source).unwrap().mono_warning);
+    assert!(mixer.master_stereo().correlation < -0.99);
+    assert!(output.chunks_exact(2).all(|frame| frame[0] == -frame[1]));
+
+    // Folding the flipped source to mono cancels it completely.
+    mixer
+        .set_channel_utility(source, utility(false, true, false, ChannelMode::Mono))
+        .unwrap();
+    let output = render(&mut mixer, 1);
+    assert!(output.iter().all(|&sample| sample == 0.0));
+
+    assert!(matches!(
+        mixer.set_channel_utility(SourceHandle::new(99), flip),
+        Err(MixerError::UnknownSource(99))
+    ));
+}
@@ -0,0 +1,86 @@
+use crate::timing::{CallbackTimer, LOAD_BUCKET_WIDTH, LOAD_HISTOGRAM_BUCKETS};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: u32 = 480;
+/// Duration of one 480-frame block at 48 kHz.
+const BLOCK_NS: u64 = 10_000_000;
+
+fn record_load(timer: &mut CallbackTimer, load: f64) {
+    timer.record((BLOCK_NS as f64 * load) as u64, BLOCK_FRAMES, SAMPLE_RATE);
+}
+
+#[test]
+fn load_is_elapsed_time_over_block_duration() {
+    let mut timer = CallbackTimer::new();
+    for load in [0.21, 0.41, 0.61] {
+        record_load(&mut timer, load);
+    }
+    let timing = timer.timing();
+    assert_eq!(timing.callbacks, 3);
+    assert_eq!(timing.overruns, 0);
+    assert!((timing.min_load - 0.21).abs() < 1e-4);
+    assert!((timing.avg_load - 0.41).abs() < 1e-4);
+    assert!((timing.max_load - 0.61).abs() < 1e-4);
+    assert!((timing.last_load - 0.61).abs() < 1e-4);
+    assert_eq!(timing.histogram.iter().sum::<u64>(), 3);
+    assert_eq!(timing.histogram[(0.41 / LOAD_BUCKET_WIDTH) as usize], 1);
+}
+
+#[test]
+fn p99_ignores_rare_spikes_and_overruns_are_counted() {
+    let mut timer = CallbackTimer::new();
+    for index in 0..1_000 {
+        let load = if index % 200 == 0 { 1.5 } else { 0.3 };
+        record_load(&mut timer, load);
+    }
+    // Five spikes in a thousand callbacks stay above the 99th percentile.
+    let timing = timer.timing();
+    assert_eq!(timing.overruns, 5);
+    assert!((timing.max_load - 1.5).abs() < 1e-4);
+    assert!(
+        timing.p99_load >= 0.3 && timing.p99_load <= 0.3 + LOAD_BUCKET_WIDTH,
+        "p99 {:.3}",
+        timing.p99_load
+    );
+
+    for _ in 0..20 {
+        record_load(&mut timer, 1.5);
+    }
+    let timing = timer.timing();
+    assert_eq!(timing.overruns, 25);
+    assert!(timing.p99_load > 1.4, "p99 {:.3}", timing.p99_load);
+}
+
+#[test]
+fn extreme_loads_land_in_last_bucket() {
+    let mut timer = CallbackTimer::new();
+    record_load(&mut timer, 10.0);
+    let timing = timer.timing();
+    assert_eq!(timing.histogram[LOAD_HISTOGRAM_BUCKETS - 1], 1);
+    assert!((timing.max_load - 10.0).abs() < 1e-3);
+    // The histogram saturates, so the percentile can only say "beyond its range".
+    let range = LOAD_HISTOGRAM_BUCKETS as f32 * LOAD_BUCKET_WIDTH;
+    assert!(timing.p99_load > range - LOAD_BUCKET_WIDTH && timing.p99_load <= range);
+}
+
+#[test]
+fn smoothed_load_follows_recent_callbacks_and_reset_clears() {
+    let mut timer = CallbackTimer::new();
+    for _ in 0..100 {
+        record_load(&mut timer, 0.8);
+    }
+    for _ in 0..500 {
+        record_load(&mut timer, 0.1);
+    }
+    // Five seconds of light load is ten time constants.
+    assert!((timer.smoothed_load() - 0.1).abs() < 0.01);
+    assert!(timer.timing().max_load > 0.79);
+
+    timer.request_reset();
+    record_load(&mut timer, 0.25);
+    let timing = timer.timing();
+    assert_eq!(timing.callbacks, 1);
+    assert!((timing.min_load - 0.25).abs() < 1e-4);
+    assert!((timing.max_load - 0.25).abs() < 1e-4);
+    assert!((timer.smoothed_load() - 0.25).abs() < 1e-4);
+}
@@ -0,0 +1,93 @@
+use std::f32::consts::TAU;
+
+use crate::vad::VoiceActivityDetector;
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+/// Deterministic white noise from a 32-bit LCG.
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+    }
+}
+
+/// Voiced, speech-like signal: a 140 Hz harmonic series shaped by two formants with a 4 Hz
+/// syllabic envelope.
+fn speech_sample(n: usize) -> f32 {
+    let t = n as f32 / SAMPLE_RATE as f32;
+    let f0 = 140.0;
+    let mut value = 0.0;
+    let mut harmonic = 1;
+    while f0 * harmonic as f32 <= 3_500.0 {
+        let freq = f0 * harmonic as f32;
+        let formant = (-((freq - 700.0) / 300.0).powi(2)).exp()
+            + 0.6 * (-((freq - 1_800.0) / 400.0).powi(2)).exp()
+            + 0.05;
+        value += formant * (TAU * freq * t).sin();
+        harmonic += 1;
+    }
+    let syllable = 0.55 + 0.45 * (TAU * 4.0 * t).sin();
+    value * syllable * 0.08
+}
+
+fn feed(vad: &mut VoiceActivityDetector, samples: impl Iterator<Item = f32>) -> Vec<bool> {
+    let mut block = Vec::with_capacity(BLOCK_FRAMES * 2);
+    let mut flags = Vec::new();
+    for sample in samples {
+        block.push(sample);
+        block.push(sample);
+        if block.len() == BLOCK_FRAMES * 2 {
+            vad.process(&block);
+            flags.push(vad.is_speaking());
+            block.clear();
+        }
+    }
+    flags
+}
+
+#[test]
+fn vad_ignores_steady_noise_and_detects_speech() {
+    let mut vad = VoiceActivityDetector::new(SAMPLE_RATE);
+    let mut noise = Noise(7);
+    let noise_level = 0.03;
+    let second = SAMPLE_RATE as usize;
+
+    let noise_only = feed(
+        &mut vad,
+        (0..3 * second).map(|_| noise.next() * noise_level),
+    );
+    assert!(
+        noise_only.iter().all(|speaking| !speaking),
+        "noise alone triggered speech"
+    );
+    assert!(vad.probability() < 0.4);
+
+    let speech = feed(
+        &mut vad,
+        (0..second).map(|n| speech_sample(n) + noise.next() * noise_level),
+    );
+    let detected = speech.iter().filter(|&&speaking| speaking).count();
+    assert!(
+        detected as f32 > speech.len() as f32 * 0.7,
+        "speech detected in only {detected}/{} blocks",
+        speech.len()
+    );
+
+    let tail = feed(&mut vad, (0..second).map(|_| noise.next() * noise_level));
+    assert!(
+        !tail.last().copied().unwrap_or(true),
+        "speaking flag never released"
+    );
+}
+
+#[test]
+fn vad_reports_silence_for_digital_zero() {
+    let mut vad = VoiceActivityDetector::new(SAMPLE_RATE);
+    let flags = feed(&mut vad, std::iter::repeat_n(0.0, SAMPLE_RATE as usize));
+    assert!(flags.iter().all(|speaking| !speaking));
+    assert_eq!(vad.probability(), 0.0);
+}
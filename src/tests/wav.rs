@@ -0,0 +1,195 @@
+use std::io::Cursor;
+
+use crate::wav::{SampleFormat, WavReader, WavSpec, WavWriter, read_wav};
+
+const SPEC: WavSpec = WavSpec {
+    sample_rate: 44_100,
+    channels: 2,
+    format: SampleFormat::Float32,
+};
+
+fn ramp(samples: usize) -> Vec<f32> {
+    (0..samples)
+        .map(|i| i as f32 / samples as f32 * 2.0 - 1.0)
+        .collect()
+}
+
+#[test]
+fn small_files_stay_riff() {
+    let samples = ramp(64);
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), SPEC).unwrap();
+    writer.write_samples(&samples).unwrap();
+    assert_eq!(writer.frames(), 32);
+    let bytes = writer.finish().unwrap().into_inner();
+
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(
+        u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+        bytes.len() - 8
+    );
+    assert_eq!(&bytes[12..16], b"JUNK");
+    let (spec, decoded) = read_wav(Cursor::new(bytes)).unwrap();
+    assert_eq!(spec, SPEC);
+    assert_eq!(decoded, samples);
+}
+
+#[test]
+fn large_files_become_rf64() {
+    let samples = ramp(64);
+    let mut writer = WavWriter::with_riff_limit(Cursor::new(Vec::new()), SPEC, 100).unwrap();
+    writer.write_samples(&samples).unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+
+    assert_eq!(&bytes[..4], b"RF64");
+    assert_eq!(&bytes[4..8], &u32::MAX.to_le_bytes());
+    assert_eq!(&bytes[12..16], b"ds64");
+    let riff_size = u64::from_le_bytes(bytes[20..28].try_into().unwrap());
+    let data_size = u64::from_le_bytes(bytes[28..36].try_into().unwrap());
+    let sample_count = u64::from_le_bytes(bytes[36..44].try_into().unwrap());
+    assert_eq!(riff_size as usize, bytes.len() - 8);
+    assert_eq!(data_size, 64 * 4);
+    assert_eq!(sample_count, 32);
+    let (spec, decoded) = read_wav(Cursor::new(bytes)).unwrap();
+    assert_eq!(spec, SPEC);
+    assert_eq!(decoded, samples);
+}
+
+#[test]
+fn pcm24_clamps_and_round_trips() {
+    let spec = WavSpec {
+        format: SampleFormat::Pcm24,
+        ..SPEC
+    };
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+    writer
+        .write_samples(&[0.0, 0.5, -0.5, 2.0, -2.0, 1e-7])
+        .unwrap();
+    let (read_spec, decoded) =
+        read_wav(Cursor::new(writer.finish().unwrap().into_inner())).unwrap();
+    assert_eq!(read_spec, spec);
+    // Writing and reading use the same scale, so values on the 24-bit grid come back exactly
+    // and only the positive peak is one step short of full scale.
+    let expected = [
+        0.0,
+        0.5,
+        -0.5,
+        8_388_607.0 / 8_388_608.0,
+        -1.0,
+        1.0 / 8_388_608.0,
+    ];
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn pcm24_grid_values_round_trip_exactly() {
+    let spec = WavSpec {
+        format: SampleFormat::Pcm24,
+        ..SPEC
+    };
+    let samples: Vec<f32> = [-8_388_608, -8_388_607, -12_345, -1, 1, 4_194_304, 8_388_607]
+        .into_iter()
+        .map(|code| code as f32 / 8_388_608.0)
+        .collect();
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+    writer.write_samples(&samples).unwrap();
+    writer.write_samples(&[0.0]).unwrap();
+    let (_, decoded) = read_wav(Cursor::new(writer.finish().unwrap().into_inner())).unwrap();
+    assert_eq!(decoded[..samples.len()], samples);
+}
+
+#[test]
+fn reader_skips_unknown_chunks_and_reads_pcm16() {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&8_000u32.to_le_bytes());
+    bytes.extend_from_slice(&16_000u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    // Odd-sized chunks are padded to an even length.
+    bytes.extend_from_slice(b"LIST");
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(b"abc\0");
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&4u32.to_le_bytes());
+    bytes.extend_from_slice(&16_384i16.to_le_bytes());
+    bytes.extend_from_slice(&(-32_768i16).to_le_bytes());
+
+    let (spec, samples) = read_wav(Cursor::new(bytes)).unwrap();
+    assert_eq!(
+        spec,
+        WavSpec {
+            sample_rate: 8_000,
+            channels: 1,
+            format: SampleFormat::Pcm16,
+        }
+    );
+    assert_eq!(samples, vec![0.5, -1.0]);
+}
+
+#[test]
+fn writer_rejects_read_only_formats() {
+    let spec = WavSpec {
+        format: SampleFormat::Pcm16,
+        ..SPEC
+    };
+    assert!(WavWriter::new(Cursor::new(Vec::new()), spec).is_err());
+    assert!(read_wav(Cursor::new(b"RIFX\0\0\0\0WAVE".to_vec())).is_err());
+}
+
+#[test]
+fn reader_streams_and_seeks_by_frame() {
+    let samples = ramp(200);
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), SPEC).unwrap();
+    writer.write_samples(&samples).unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.spec(), SPEC);
+    assert_eq!(reader.frames(), 100);
+
+    // Odd buffer lengths are rounded down to whole frames.
+    let mut buffer = [0.0; 7];
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 6);
+    assert_eq!(buffer[..6], samples[..6]);
+
+    reader.seek(95).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 6);
+    assert_eq!(buffer[..6], samples[190..196]);
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 4);
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 0);
+
+    reader.seek(1_000).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 0);
+    reader.seek(0).unwrap();
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 6);
+    assert_eq!(buffer[..6], samples[..6]);
+}
+
+#[test]
+fn reader_stops_at_the_last_whole_frame_of_a_truncated_file() {
+    let samples = ramp(20);
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), SPEC).unwrap();
+    writer.write_samples(&samples).unwrap();
+    let mut bytes = writer.finish().unwrap().into_inner();
+    bytes.truncate(bytes.len() - 6);
+
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.frames(), 10);
+    let mut buffer = [0.0; 32];
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 18);
+    assert_eq!(buffer[..18], samples[..18]);
+    assert_eq!(reader.read_samples(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn reader_rejects_a_zero_sample_rate() {
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), SPEC).unwrap();
+    writer.write_samples(&ramp(4)).unwrap();
+    let mut bytes = writer.finish().unwrap().into_inner();
+    let fmt = bytes.windows(4).position(|tag| tag == b"fmt ").unwrap();
+    bytes[fmt + 12..fmt + 16].fill(0);
+    assert!(WavReader::new(Cursor::new(bytes)).is_err());
+}
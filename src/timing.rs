@@ -0,0 +1,199 @@
+//! Render callback timing against the block deadline.
+//!
+//! [`CallbackTimer::record`] runs on the render thread after every callback with the time the
+//! callback took. Load is that time as a fraction of the block duration (`frames / sample_rate`),
+//! so 1.0 means the callback used its whole deadline and anything above is an overrun. The timer
+//! keeps min/avg/max since the last reset, a smoothed recent load and a histogram from which the
+//! control side derives the 99th percentile. Everything is published through atomics.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+/// Width of one load histogram bucket (2.5% of the block duration).
+pub const LOAD_BUCKET_WIDTH: f32 = 0.025;
+/// Number of load histogram buckets; the last one also collects every load above 200%.
+pub const LOAD_HISTOGRAM_BUCKETS: usize = 80;
+/// Time constant of the smoothed load reported as CPU usage.
+pub const LOAD_SMOOTHING_MS: f32 = 500.0;
+
+/// Snapshot of callback timing since the last reset. Loads are fractions of the block duration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallbackTiming {
+    /// Number of timed callbacks.
+    pub callbacks: u64,
+    /// Callbacks that took longer than their block duration.
+    pub overruns: u64,
+    /// Load of the most recent callback.
+    pub last_load: f32,
+    /// Lowest callback load.
+    pub min_load: f32,
+    /// Mean callback load.
+    pub avg_load: f32,
+    /// Highest callback load.
+    pub max_load: f32,
+    /// 99th percentile callback load, interpolated within its histogram bucket.
+    pub p99_load: f32,
+    /// Callback counts per [`LOAD_BUCKET_WIDTH`] load bucket.
+    pub histogram: [u64; LOAD_HISTOGRAM_BUCKETS],
+}
+
+impl Default for CallbackTiming {
+    fn default() -> Self {
+        Self {
+            callbacks: 0,
+            overruns: 0,
+            last_load: 0.0,
+            min_load: 0.0,
+            avg_load: 0.0,
+            max_load: 0.0,
+            p99_load: 0.0,
+            histogram: [0; LOAD_HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+/// Callback load statistics written by the render thread.
+pub(crate) struct CallbackTimer {
+    load_sum: f64,
+    smoothed: f32,
+    callbacks: AtomicU64,
+    overruns: AtomicU64,
+    last_bits: AtomicU32,
+    min_bits: AtomicU32,
+    max_bits: AtomicU32,
+    avg_bits: AtomicU32,
+    smoothed_bits: AtomicU32,
+    histogram: [AtomicU64; LOAD_HISTOGRAM_BUCKETS],
+    reset_requested: AtomicBool,
+}
+
+impl CallbackTimer {
+    pub(crate) fn new() -> Self {
+        Self {
+            load_sum: 0.0,
+            smoothed: 0.0,
+            callbacks: AtomicU64::new(0),
+            overruns: AtomicU64::new(0),
+            last_bits: AtomicU32::new(0),
+            min_bits: AtomicU32::new(0),
+            max_bits: AtomicU32::new(0),
+            avg_bits: AtomicU32::new(0),
+            smoothed_bits: AtomicU32::new(0),
+            histogram: std::array::from_fn(|_| AtomicU64::new(0)),
+            reset_requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Ask the render thread to clear the statistics before its next measurement.
+    pub(crate) fn request_reset(&self) {
+        self.reset_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Record a callback that rendered `frames` at `sample_rate` in `elapsed_ns`, returning its
+    /// load.
+    pub(crate) fn record(&mut self, elapsed_ns: u64, frames: u32, sample_rate: u32) -> f32 {
+        if frames == 0 || sample_rate == 0 {
+            return 0.0;
+        }
+        if self.reset_requested.swap(false, Ordering::Relaxed) {
+            self.clear();
+        }
+
+        let block_seconds = frames as f64 / sample_rate as f64;
+        let load = (elapsed_ns as f64 * 1e-9 / block_seconds) as f32;
+        let callbacks = self.callbacks.load(Ordering::Relaxed) + 1;
+        let (min, max) = if callbacks == 1 {
+            (load, load)
+        } else {
+            (
+                f32::from_bits(self.min_bits.load(Ordering::Relaxed)).min(load),
+                f32::from_bits(self.max_bits.load(Ordering::Relaxed)).max(load),
+            )
+        };
+        self.load_sum += load as f64;
+        let alpha = 1.0 - (-(block_seconds as f32) * 1_000.0 / LOAD_SMOOTHING_MS).exp();
+        self.smoothed = if callbacks == 1 {
+            load
+        } else {
+            self.smoothed + alpha * (load - self.smoothed)
+        };
+
+        let bucket = ((load / LOAD_BUCKET_WIDTH) as usize).min(LOAD_HISTOGRAM_BUCKETS - 1);
+        self.histogram[bucket].fetch_add(1, Ordering::Relaxed);
+        if load > 1.0 {
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+        }
+        self.last_bits.store(load.to_bits(), Ordering::Relaxed);
+        self.min_bits.store(min.to_bits(), Ordering::Relaxed);
+        self.max_bits.store(max.to_bits(), Ordering::Relaxed);
+        self.avg_bits.store(
+            ((self.load_sum / callbacks as f64) as f32).to_bits(),
+            Ordering::Relaxed,
+        );
+        self.smoothed_bits
+            .store(self.smoothed.to_bits(), Ordering::Relaxed);
+        self.callbacks.store(callbacks, Ordering::Release);
+        load
+    }
+
+    /// Recent load, smoothed over [`LOAD_SMOOTHING_MS`].
+    pub(crate) fn smoothed_load(&self) -> f32 {
+        f32::from_bits(self.smoothed_bits.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn timing(&self) -> CallbackTiming {
+        let callbacks = self.callbacks.load(Ordering::Acquire);
+        let mut histogram = [0u64; LOAD_HISTOGRAM_BUCKETS];
+        for (count, bucket) in histogram.iter_mut().zip(&self.histogram) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+        let max_load = f32::from_bits(self.max_bits.load(Ordering::Relaxed));
+        CallbackTiming {
+            callbacks,
+            overruns: self.overruns.load(Ordering::Relaxed),
+            last_load: f32::from_bits(self.last_bits.load(Ordering::Relaxed)),
+            min_load: f32::from_bits(self.min_bits.load(Ordering::Relaxed)),
+            avg_load: f32::from_bits(self.avg_bits.load(Ordering::Relaxed)),
+            max_load,
+            p99_load: percentile(&histogram, 0.99).min(max_load),
+            histogram,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.load_sum = 0.0;
+        self.smoothed = 0.0;
+        self.callbacks.store(0, Ordering::Relaxed);
+        self.overruns.store(0, Ordering::Relaxed);
+        for bucket in &self.histogram {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        for bits in [
+            &self.last_bits,
+            &self.min_bits,
+            &self.max_bits,
+            &self.avg_bits,
+            &self.smoothed_bits,
+        ] {
+            bits.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Load below which `fraction` of the histogram's callbacks fall, interpolated linearly within
+/// the bucket that crosses it.
+fn percentile(histogram: &[u64; LOAD_HISTOGRAM_BUCKETS], fraction: f64) -> f32 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let rank = fraction * total as f64;
+    let mut below = 0u64;
+    for (index, &count) in histogram.iter().enumerate() {
+        if count > 0 && (below + count) as f64 >= rank {
+            let within = ((rank - below as f64) / count as f64).clamp(0.0, 1.0) as f32;
+            return (index as f32 + within) * LOAD_BUCKET_WIDTH;
+        }
+        below += count;
+    }
+    LOAD_HISTOGRAM_BUCKETS as f32 * LOAD_BUCKET_WIDTH
+}
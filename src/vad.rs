@@ -0,0 +1,177 @@
+//! Lightweight voice activity detection run per source on the render thread.
+//!
+//! Each analysis frame (~10 ms) combines three cues: frame energy relative to an adaptive noise
+//! floor, the share of energy inside the 300–3400 Hz speech band, and spectral flatness within
+//! that band (voiced speech is harmonic, broadband noise is flat). The cues are blended into a
+//! probability which is smoothed and passed through hysteresis with a hangover to produce the
+//! `speaking` flag. Results are published through atomics for control surfaces.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::fft::{Fft, hann_window};
+
+const SPEECH_LOW_HZ: f32 = 300.0;
+const SPEECH_HIGH_HZ: f32 = 3_400.0;
+/// Frames quieter than this are never treated as speech.
+const SILENCE_FLOOR_DB: f32 = -60.0;
+/// Noise floor rise per second while the signal stays above it.
+const FLOOR_RISE_DB_PER_S: f32 = 6.0;
+const SNR_MIDPOINT_DB: f32 = 9.0;
+const SNR_SLOPE_DB: f32 = 2.5;
+/// Scales the spectral score so clearly voiced frames saturate at 1.
+const SPECTRAL_GAIN: f32 = 1.25;
+const PROBABILITY_SMOOTHING: f32 = 0.6;
+const ONSET_THRESHOLD: f32 = 0.6;
+const RELEASE_THRESHOLD: f32 = 0.4;
+const HANGOVER_MS: f32 = 200.0;
+
+/// Per-source voice activity detector.
+pub(crate) struct VoiceActivityDetector {
+    fft: Fft,
+    window: Vec<f32>,
+    frame: Vec<f32>,
+    filled: usize,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    band: (usize, usize),
+    frame_seconds: f32,
+    noise_floor_db: f32,
+    smoothed: f32,
+    hangover_frames: usize,
+    hangover_remaining: usize,
+    speaking: bool,
+    probability_bits: AtomicU32,
+    speaking_flag: AtomicBool,
+}
+
+impl VoiceActivityDetector {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(8_000);
+        let size = (sample_rate as usize / 100)
+            .next_power_of_two()
+            .clamp(128, 2048);
+        let fft = Fft::new(size);
+        let bin_hz = sample_rate as f32 / size as f32;
+        let low = ((SPEECH_LOW_HZ / bin_hz).round() as usize).max(1);
+        let high = ((SPEECH_HIGH_HZ / bin_hz).round() as usize).min(size / 2 - 1);
+        let frame_seconds = size as f32 / sample_rate as f32;
+        Self {
+            fft,
+            window: hann_window(size),
+            frame: vec![0.0; size],
+            filled: 0,
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            band: (low, high.max(low + 1)),
+            frame_seconds,
+            noise_floor_db: SILENCE_FLOOR_DB,
+            smoothed: 0.0,
+            hangover_frames: (HANGOVER_MS * 0.001 / frame_seconds).ceil() as usize,
+            hangover_remaining: 0,
+            speaking: false,
+            probability_bits: AtomicU32::new(0.0f32.to_bits()),
+            speaking_flag: AtomicBool::new(false),
+        }
+    }
+
+    /// Feed an interleaved stereo block; analysis runs whenever a frame fills up.
+    pub(crate) fn process(&mut self, block: &[f32]) {
+        for pair in block.chunks_exact(2) {
+            self.frame[self.filled] = 0.5 * (pair[0] + pair[1]);
+            self.filled += 1;
+            if self.filled == self.frame.len() {
+                self.filled = 0;
+                self.analyse_frame();
+            }
+        }
+    }
+
+    pub(crate) fn probability(&self) -> f32 {
+        f32::from_bits(self.probability_bits.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn is_speaking(&self) -> bool {
+        self.speaking_flag.load(Ordering::Relaxed)
+    }
+
+    fn analyse_frame(&mut self) {
+        let size = self.fft.size();
+        let mut energy = 0.0f32;
+        for i in 0..size {
+            let sample = self.frame[i];
+            energy += sample * sample;
+            self.re[i] = sample * self.window[i];
+            self.im[i] = 0.0;
+        }
+        let energy_db = 10.0 * (energy / size as f32 + 1e-12).log10();
+
+        let instant = if energy_db <= SILENCE_FLOOR_DB {
+            0.0
+        } else {
+            self.fft.forward(&mut self.re, &mut self.im);
+            let snr_db = energy_db - self.noise_floor_db;
+            let snr_score = 1.0 / (1.0 + (-(snr_db - SNR_MIDPOINT_DB) / SNR_SLOPE_DB).exp());
+            let (band_ratio, flatness) = self.spectral_features();
+            let spectral = (0.5 * band_ratio + 0.5 * (1.0 - flatness)) * SPECTRAL_GAIN;
+            snr_score * spectral.min(1.0)
+        };
+
+        self.track_noise_floor(energy_db);
+        self.smoothed =
+            PROBABILITY_SMOOTHING * self.smoothed + (1.0 - PROBABILITY_SMOOTHING) * instant;
+
+        if self.smoothed >= ONSET_THRESHOLD {
+            self.speaking = true;
+            self.hangover_remaining = self.hangover_frames;
+        } else if self.speaking && self.smoothed < RELEASE_THRESHOLD {
+            if self.hangover_remaining > 0 {
+                self.hangover_remaining -= 1;
+            } else {
+                self.speaking = false;
+            }
+        }
+
+        self.probability_bits
+            .store(self.smoothed.to_bits(), Ordering::Relaxed);
+        self.speaking_flag.store(self.speaking, Ordering::Relaxed);
+    }
+
+    /// Returns the speech-band energy ratio and the band's spectral flatness, both in 0–1.
+    fn spectral_features(&self) -> (f32, f32) {
+        let half = self.fft.size() / 2;
+        let (low, high) = self.band;
+        let mut total = 0.0f32;
+        let mut band = 0.0f32;
+        let mut log_sum = 0.0f32;
+        for bin in 1..half {
+            let power = self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin] + 1e-12;
+            total += power;
+            if (low..=high).contains(&bin) {
+                band += power;
+                log_sum += power.ln();
+            }
+        }
+        let bins = (high - low + 1) as f32;
+        let ratio = if total > 0.0 { band / total } else { 0.0 };
+        let arithmetic = band / bins;
+        let geometric = (log_sum / bins).exp();
+        let flatness = if arithmetic > 0.0 {
+            (geometric / arithmetic).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        (ratio, flatness)
+    }
+
+    /// Floor drops instantly to quieter frames and creeps upwards otherwise, so it follows the
+    /// background level between words.
+    fn track_noise_floor(&mut self, energy_db: f32) {
+        let energy_db = energy_db.max(SILENCE_FLOOR_DB);
+        if energy_db < self.noise_floor_db {
+            self.noise_floor_db = energy_db;
+        } else {
+            let rise = FLOOR_RISE_DB_PER_S * self.frame_seconds;
+            self.noise_floor_db = (self.noise_floor_db + rise).min(energy_db);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Minimal WAV and RF64 (EBU Tech 3306) reading and writing.
+//!
+//! [`WavWriter`] streams interleaved `f32` samples as 32-bit float or 24-bit PCM. Its header
+//! reserves a `JUNK` chunk the size of an RF64 `ds64` chunk, so when [`WavWriter::finish`] finds
+//! the file has outgrown the 4 GiB RIFF limit it rewrites the header in place as RF64 instead of
+//! copying the data. [`WavReader`] streams what the writer produces plus 16- and 32-bit PCM,
+//! skipping chunks it does not understand; [`read_wav`] reads a whole file at once.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// `WAVE_FORMAT_PCM`.
+const FORMAT_PCM: u16 = 1;
+/// `WAVE_FORMAT_IEEE_FLOAT`.
+const FORMAT_IEEE_FLOAT: u16 = 3;
+/// `WAVE_FORMAT_EXTENSIBLE`; the real format is in the sub-format GUID.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+/// Bytes before the first sample: RIFF header, JUNK/ds64, fmt and the data chunk header.
+const HEADER_BYTES: u64 = 80;
+const DS64_BYTES: u32 = 28;
+/// Full scale of 24-bit PCM. Samples are written as `sample * 2^23` clipped to the positive peak
+/// and read back divided by the same value, as for 16- and 32-bit PCM.
+const PCM24_SCALE: f32 = 8_388_608.0;
+
+/// [`SampleFormat`] code of [`SampleFormat::Float32`] for the C ABI.
+pub const LOOPBACK_RECORD_FORMAT_F32: u32 = 0;
+/// [`SampleFormat`] code of [`SampleFormat::Pcm24`] for the C ABI.
+pub const LOOPBACK_RECORD_FORMAT_PCM24: u32 = 1;
+
+/// Sample encoding of a WAV file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// 32-bit IEEE float, written unchanged.
+    #[default]
+    Float32,
+    /// 24-bit signed PCM; samples are clamped to ±1.0.
+    Pcm24,
+    /// 16-bit signed PCM. Read only.
+    Pcm16,
+    /// 32-bit signed PCM. Read only.
+    Pcm32,
+}
+
+impl SampleFormat {
+    /// Recording format for a `LOOPBACK_RECORD_FORMAT_*` code, or `None` for an unknown code.
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            LOOPBACK_RECORD_FORMAT_F32 => Some(Self::Float32),
+            LOOPBACK_RECORD_FORMAT_PCM24 => Some(Self::Pcm24),
+            _ => None,
+        }
+    }
+
+    /// Recording format for a [`name`](Self::name), or `None` for an unknown or read-only one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Float32, Self::Pcm24]
+            .into_iter()
+            .find(|format| format.name() == name)
+    }
+
+    /// Short machine-friendly name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Float32 => "f32",
+            Self::Pcm24 => "pcm24",
+            Self::Pcm16 => "pcm16",
+            Self::Pcm32 => "pcm32",
+        }
+    }
+
+    /// Bytes per sample.
+    pub fn bytes_per_sample(&self) -> u16 {
+        match self {
+            Self::Pcm16 => 2,
+            Self::Pcm24 => 3,
+            Self::Float32 | Self::Pcm32 => 4,
+        }
+    }
+
+    fn format_tag(&self) -> u16 {
+        match self {
+            Self::Float32 => FORMAT_IEEE_FLOAT,
+            _ => FORMAT_PCM,
+        }
+    }
+}
+
+/// Layout of a WAV file's samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WavSpec {
+    /// Frames per second.
+    pub sample_rate: u32,
+    /// Interleaved channels per frame.
+    pub channels: u16,
+    /// Sample encoding.
+    pub format: SampleFormat,
+}
+
+/// Streaming WAV writer that upgrades to RF64 on [`finish`](Self::finish) when needed.
+pub struct WavWriter<W: Write + Seek> {
+    inner: W,
+    spec: WavSpec,
+    data_bytes: u64,
+    riff_limit: u64,
+    scratch: Vec<u8>,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write a header for `spec` and prepare to append samples. Only [`SampleFormat::Float32`]
+    /// and [`SampleFormat::Pcm24`] can be written.
+    pub fn new(inner: W, spec: WavSpec) -> io::Result<Self> {
+        Self::with_riff_limit(inner, spec, u32::MAX as u64)
+    }
+
+    /// Like [`new`](Self::new), switching to RF64 once the RIFF size exceeds `riff_limit`.
+    pub(crate) fn with_riff_limit(
+        mut inner: W,
+        spec: WavSpec,
+        riff_limit: u64,
+    ) -> io::Result<Self> {
+        if !matches!(spec.format, SampleFormat::Float32 | SampleFormat::Pcm24) || spec.channels == 0
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot write {} channels of {}",
+                    spec.channels,
+                    spec.format.name()
+                ),
+            ));
+        }
+        let bytes_per_sample = spec.format.bytes_per_sample();
+        let block_align = spec.channels * bytes_per_sample;
+        let mut header = Vec::with_capacity(HEADER_BYTES as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(b"WAVE");
+        header.extend_from_slice(b"JUNK");
+        header.extend_from_slice(&DS64_BYTES.to_le_bytes());
+        header.extend_from_slice(&[0; DS64_BYTES as usize]);
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&spec.format.format_tag().to_le_bytes());
+        header.extend_from_slice(&spec.channels.to_le_bytes());
+        header.extend_from_slice(&spec.sample_rate.to_le_bytes());
+        header.extend_from_slice(&(spec.sample_rate * block_align as u32).to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&(bytes_per_sample * 8).to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        debug_assert_eq!(header.len() as u64, HEADER_BYTES);
+        inner.write_all(&header)?;
+        Ok(Self {
+            inner,
+            spec,
+            data_bytes: 0,
+            riff_limit,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Layout being written.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Complete frames written so far.
+    pub fn frames(&self) -> u64 {
+        self.data_bytes / (self.spec.channels as u64 * self.spec.format.bytes_per_sample() as u64)
+    }
+
+    /// Append interleaved samples.
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        self.scratch.clear();
+        match self.spec.format {
+            SampleFormat::Pcm24 => {
+                for &sample in samples {
+                    let value = (sample.clamp(-1.0, 1.0) * PCM24_SCALE)
+                        .round()
+                        .min(PCM24_SCALE - 1.0) as i32;
+                    self.scratch.extend_from_slice(&value.to_le_bytes()[..3]);
+                }
+            }
+            _ => {
+                for &sample in samples {
+                    self.scratch.extend_from_slice(&sample.to_le_bytes());
+                }
+            }
+        }
+        self.inner.write_all(&self.scratch)?;
+        self.data_bytes += self.scratch.len() as u64;
+        Ok(())
+    }
+
+    /// Fill in the chunk sizes, as RF64 if the file is too large for RIFF, and return the
+    /// underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.data_bytes % 2 == 1 {
+            self.inner.write_all(&[0])?;
+        }
+        let riff_bytes = HEADER_BYTES - 8 + self.data_bytes.next_multiple_of(2);
+        if riff_bytes <= self.riff_limit {
+            self.inner.seek(SeekFrom::Start(4))?;
+            self.inner.write_all(&(riff_bytes as u32).to_le_bytes())?;
+            self.inner.seek(SeekFrom::Start(HEADER_BYTES - 4))?;
+            self.inner
+                .write_all(&(self.data_bytes as u32).to_le_bytes())?;
+        } else {
+            let mut ds64 = Vec::with_capacity(DS64_BYTES as usize + 8);
+            ds64.extend_from_slice(b"ds64");
+            ds64.extend_from_slice(&DS64_BYTES.to_le_bytes());
+            ds64.extend_from_slice(&riff_bytes.to_le_bytes());
+            ds64.extend_from_slice(&self.data_bytes.to_le_bytes());
+            ds64.extend_from_slice(&self.frames().to_le_bytes());
+            ds64.extend_from_slice(&0u32.to_le_bytes());
+            self.inner.seek(SeekFrom::Start(0))?;
+            self.inner.write_all(b"RF64")?;
+            self.inner.write_all(&u32::MAX.to_le_bytes())?;
+            self.inner.seek(SeekFrom::Start(12))?;
+            self.inner.write_all(&ds64)?;
+            self.inner.seek(SeekFrom::Start(HEADER_BYTES - 4))?;
+            self.inner.write_all(&u32::MAX.to_le_bytes())?;
+        }
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// Streaming reader over the samples of a WAV or RF64 file.
+pub struct WavReader<R: Read> {
+    inner: R,
+    spec: WavSpec,
+    data_offset: u64,
+    data_bytes: u64,
+    remaining: u64,
+    scratch: Vec<u8>,
+}
+
+impl<R: Read> WavReader<R> {
+    /// Parse the header and position the reader at the first sample.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0u8; 12];
+        inner.read_exact(&mut header)?;
+        let rf64 = match &header[..4] {
+            b"RIFF" => false,
+            b"RF64" => true,
+            _ => return Err(invalid("not a RIFF or RF64 file")),
+        };
+        if &header[8..] != b"WAVE" {
+            return Err(invalid("not a WAVE file"));
+        }
+
+        let mut offset = 12u64;
+        let mut spec = None;
+        let mut ds64_data_bytes = None;
+        loop {
+            let mut chunk = [0u8; 8];
+            inner.read_exact(&mut chunk)?;
+            offset += 8;
+            let size = read_u32(&chunk, 4);
+            let padded = size as u64 + (size as u64 % 2);
+            match &chunk[..4] {
+                b"ds64" => {
+                    let body = read_body(&mut inner, padded)?;
+                    if body.len() < 16 {
+                        return Err(invalid("truncated ds64 chunk"));
+                    }
+                    ds64_data_bytes = Some(read_u64(&body, 8));
+                }
+                b"fmt " => {
+                    let body = read_body(&mut inner, padded)?;
+                    if body.len() < 16 {
+                        return Err(invalid("truncated fmt chunk"));
+                    }
+                    let mut tag = read_u16(&body, 0);
+                    if tag == FORMAT_EXTENSIBLE && body.len() >= 26 {
+                        tag = read_u16(&body, 24);
+                    }
+                    let channels = read_u16(&body, 2);
+                    let bits = read_u16(&body, 14);
+                    let format = match (tag, bits) {
+                        (FORMAT_IEEE_FLOAT, 32) => SampleFormat::Float32,
+                        (FORMAT_PCM, 16) => SampleFormat::Pcm16,
+                        (FORMAT_PCM, 24) => SampleFormat::Pcm24,
+                        (FORMAT_PCM, 32) => SampleFormat::Pcm32,
+                        _ => return Err(invalid("unsupported sample format")),
+                    };
+                    if channels == 0 {
+                        return Err(invalid("file has no channels"));
+                    }
+                    let sample_rate = read_u32(&body, 4);
+                    if sample_rate == 0 {
+                        return Err(invalid("file has no sample rate"));
+                    }
+                    spec = Some(WavSpec {
+                        sample_rate,
+                        channels,
+                        format,
+                    });
+                }
+                b"data" => {
+                    let spec = spec.ok_or_else(|| invalid("data chunk before fmt chunk"))?;
+                    let data_bytes = match ds64_data_bytes {
+                        Some(bytes) if rf64 && size == u32::MAX => bytes,
+                        _ => size as u64,
+                    };
+                    return Ok(Self {
+                        inner,
+                        spec,
+                        data_offset: offset,
+                        data_bytes,
+                        remaining: data_bytes,
+                        scratch: Vec::new(),
+                    });
+                }
+                _ => {
+                    let skipped = io::copy(&mut (&mut inner).take(padded), &mut io::sink())?;
+                    if skipped < padded {
+                        return Err(io::ErrorKind::UnexpectedEof.into());
+                    }
+                }
+            }
+            offset += padded;
+        }
+    }
+
+    /// Layout of the file.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Total frames in the data chunk, as declared by the header.
+    pub fn frames(&self) -> u64 {
+        self.data_bytes / self.frame_bytes()
+    }
+
+    fn frame_bytes(&self) -> u64 {
+        self.spec.channels as u64 * self.spec.format.bytes_per_sample() as u64
+    }
+
+    /// Decode up to `out.len()` interleaved samples, rounded down to whole frames, returning how
+    /// many were written. Returns 0 at the end of the data.
+    pub fn read_samples(&mut self, out: &mut [f32]) -> io::Result<usize> {
+        let channels = self.spec.channels as usize;
+        let width = self.spec.format.bytes_per_sample() as usize;
+        let frames = (out.len() / channels).min((self.remaining / self.frame_bytes()) as usize);
+        if frames == 0 {
+            return Ok(0);
+        }
+        let bytes = frames * channels * width;
+        self.scratch.resize(bytes, 0);
+        let mut filled = 0;
+        while filled < bytes {
+            match self.inner.read(&mut self.scratch[filled..]) {
+                Ok(0) => break,
+                Ok(count) => filled += count,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if filled < bytes {
+            // A truncated file ends at its last complete frame.
+            self.remaining = 0;
+        } else {
+            self.remaining -= bytes as u64;
+        }
+        let frames = filled / (channels * width);
+        let bytes = frames * channels * width;
+        decode_into(&self.scratch[..bytes], self.spec.format, out);
+        Ok(frames * channels)
+    }
+}
+
+impl<R: Read + Seek> WavReader<R> {
+    /// Move to `frame`, clamped to the end of the data.
+    pub fn seek(&mut self, frame: u64) -> io::Result<()> {
+        let bytes = frame.min(self.frames()) * self.frame_bytes();
+        self.inner.seek(SeekFrom::Start(self.data_offset + bytes))?;
+        self.remaining = self.data_bytes - bytes;
+        Ok(())
+    }
+}
+
+fn read_body(reader: &mut impl Read, bytes: u64) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    reader.take(bytes).read_to_end(&mut body)?;
+    if (body.len() as u64) < bytes {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(body)
+}
+
+/// Read a whole WAV or RF64 stream, returning its layout and interleaved samples scaled to ±1.0.
+pub fn read_wav(reader: impl Read) -> io::Result<(WavSpec, Vec<f32>)> {
+    let mut reader = WavReader::new(reader)?;
+    let spec = reader.spec();
+    let mut samples = Vec::new();
+    let mut chunk = vec![0.0f32; 4_096 * spec.channels as usize];
+    loop {
+        let count = reader.read_samples(&mut chunk)?;
+        if count == 0 {
+            return Ok((spec, samples));
+        }
+        samples.extend_from_slice(&chunk[..count]);
+    }
+}
+
+fn decode_into(data: &[u8], format: SampleFormat, out: &mut [f32]) {
+    let width = format.bytes_per_sample() as usize;
+    for (sample, bytes) in out.iter_mut().zip(data.chunks_exact(width)) {
+        *sample = match format {
+            SampleFormat::Float32 => f32::from_le_bytes(bytes.try_into().unwrap()),
+            SampleFormat::Pcm16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32_768.0,
+            SampleFormat::Pcm24 => {
+                // Place the 24 bits at the top of an i32 so the shift sign-extends.
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / PCM24_SCALE
+            }
+            SampleFormat::Pcm32 => {
+                i32::from_le_bytes(bytes.try_into().unwrap()) as f32 / 2_147_483_648.0
+            }
+        };
+    }
+}
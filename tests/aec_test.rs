@@ -0,0 +1,278 @@
+use device_kit::aec::{DEFAULT_FILTER_LENGTH_MS, EchoReference};
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer, MixerError, SourceHandle};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+const FAR_LEVEL: f32 = 0.1;
+
+/// Deterministic uniform noise in [-1, 1).
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+}
+
+/// Synthetic room impulse response: a direct path after `delay` samples followed by an
+/// exponentially decaying noise tail reaching -60 dB at `rt60_ms`.
+fn room_impulse_response(delay: usize, rt60_ms: f32, length: usize, seed: u32) -> Vec<f32> {
+    let mut noise = Noise(seed);
+    let decay_per_sample =
+        (-60.0f32 / 20.0 * std::f32::consts::LN_10) / (rt60_ms * 0.001 * SAMPLE_RATE as f32);
+    let mut rir = vec![0.0f32; length];
+    rir[delay] = 0.6;
+    for (n, tap) in rir.iter_mut().enumerate().skip(delay + 1) {
+        let t = (n - delay) as f32;
+        *tap = 0.3 * noise.next() * (decay_per_sample * t).exp();
+    }
+    rir
+}
+
+/// Far-end signal played into the room and the echo it produces at the microphone.
+struct Room {
+    rir: Vec<f32>,
+    /// Far-end history stored twice so the newest `rir.len()` samples are always contiguous.
+    history: Vec<f32>,
+    position: usize,
+}
+
+impl Room {
+    fn new(mut rir: Vec<f32>) -> Self {
+        rir.reverse();
+        let history = vec![0.0; rir.len() * 2];
+        Self {
+            rir,
+            history,
+            position: 0,
+        }
+    }
+
+    fn echo(&mut self, far: f32) -> f32 {
+        let len = self.rir.len();
+        self.history[self.position] = far;
+        self.history[self.position + len] = far;
+        self.position = (self.position + 1) % len;
+        let window = &self.history[self.position..self.position + len];
+        window.iter().zip(&self.rir).map(|(x, h)| x * h).sum()
+    }
+}
+
+fn render(mixer: &mut Mixer) -> Vec<f32> {
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut buffer = AudioBuffer {
+        data: output.as_mut_ptr(),
+        frames: BLOCK_FRAMES as u32,
+        channels: 2,
+        timestamp_ns: 0,
+    };
+    mixer.process(&mut buffer).unwrap();
+    output
+}
+
+/// Push one block of far-end audio and its echo (plus optional near-end speech) and render it.
+/// Returns the microphone input energy and the rendered output energy of the block.
+fn run_block(
+    mixer: &mut Mixer,
+    mic: &SharedRingBuffer,
+    far: &SharedRingBuffer,
+    room: &mut Room,
+    far_source: &mut dyn FnMut() -> f32,
+    near_source: &mut dyn FnMut() -> f32,
+) -> (f32, f32) {
+    let mut far_block = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut mic_block = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut input_energy = 0.0f32;
+    for (far_frame, mic_frame) in far_block
+        .chunks_exact_mut(2)
+        .zip(mic_block.chunks_exact_mut(2))
+    {
+        let x = far_source();
+        let d = room.echo(x) + near_source();
+        far_frame.fill(x);
+        mic_frame.fill(d);
+        input_energy += d * d;
+    }
+    far.push(&far_block, None);
+    mic.push(&mic_block, None);
+    let output = render(mixer);
+    let output_energy = output.chunks_exact(2).map(|f| f[0] * f[0]).sum();
+    (input_energy, output_energy)
+}
+
+fn setup() -> (
+    Mixer,
+    SourceHandle,
+    SourceHandle,
+    [std::sync::Arc<SharedRingBuffer>; 2],
+) {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (mic, mic_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let (far, far_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    // The far end is monitored elsewhere; only the microphone reaches the master.
+    mixer.set_mute(far, true).unwrap();
+    (mixer, mic, far, [mic_ring, far_ring])
+}
+
+#[test]
+fn echo_canceller_converges_on_synthetic_rooms() {
+    for (seed, delay, rt60_ms) in [(7u32, 96usize, 20.0f32), (42, 240, 25.0)] {
+        let (mut mixer, mic, far, [mic_ring, far_ring]) = setup();
+        mixer
+            .enable_echo_cancellation(mic, EchoReference::Source(far), DEFAULT_FILTER_LENGTH_MS)
+            .unwrap();
+        let mut room = Room::new(room_impulse_response(delay, rt60_ms, 1_536, seed));
+        let mut noise = Noise(seed ^ 0x5555);
+        let mut far_source = || noise.next() * FAR_LEVEL;
+        let mut silence = || 0.0;
+
+        let blocks = 2 * SAMPLE_RATE as usize / BLOCK_FRAMES;
+        let tail = SAMPLE_RATE as usize / 2 / BLOCK_FRAMES;
+        let (mut echo, mut residual) = (0.0f32, 0.0f32);
+        for block in 0..blocks {
+            let (input, output) = run_block(
+                &mut mixer,
+                &mic_ring,
+                &far_ring,
+                &mut room,
+                &mut far_source,
+                &mut silence,
+            );
+            if block >= blocks - tail {
+                echo += input;
+                residual += output;
+            }
+        }
+        let erle_db = 10.0 * (echo / residual.max(1e-12)).log10();
+        assert!(
+            erle_db > 30.0,
+            "room seed {seed}: echo only reduced by {erle_db:.1} dB"
+        );
+    }
+}
+
+#[test]
+fn near_end_speech_passes_through_after_convergence() {
+    let (mut mixer, mic, far, [mic_ring, far_ring]) = setup();
+    mixer
+        .enable_echo_cancellation(mic, EchoReference::Source(far), DEFAULT_FILTER_LENGTH_MS)
+        .unwrap();
+    let mut room = Room::new(room_impulse_response(120, 25.0, 1_536, 3));
+    let mut noise = Noise(11);
+    let mut silence = || 0.0;
+    {
+        let mut far_source = || noise.next() * FAR_LEVEL;
+        for _ in 0..SAMPLE_RATE as usize / BLOCK_FRAMES {
+            run_block(
+                &mut mixer,
+                &mic_ring,
+                &far_ring,
+                &mut room,
+                &mut far_source,
+                &mut silence,
+            );
+        }
+    }
+
+    // Far end goes quiet while the local talker speaks; the tone must survive untouched.
+    let mut phase = 0.0f32;
+    let mut tone = || {
+        phase = (phase + 300.0 / SAMPLE_RATE as f32).fract();
+        (phase * std::f32::consts::TAU).sin() * 0.2
+    };
+    let (mut input, mut output) = (0.0f32, 0.0f32);
+    let blocks = SAMPLE_RATE as usize / BLOCK_FRAMES;
+    for block in 0..blocks {
+        let (i, o) = run_block(
+            &mut mixer,
+            &mic_ring,
+            &far_ring,
+            &mut room,
+            &mut silence,
+            &mut tone,
+        );
+        // Skip the room tail and the canceller's block latency.
+        if block >= 4 {
+            input += i;
+            output += o;
+        }
+    }
+    let change_db = 10.0 * (output / input).log10();
+    assert!(
+        change_db.abs() < 1.0,
+        "near-end speech changed by {change_db:.2} dB"
+    );
+}
+
+#[test]
+fn echo_reference_must_be_another_known_source() {
+    let (mut mixer, mic, _far, _rings) = setup();
+    assert!(matches!(
+        mixer.enable_echo_cancellation(mic, EchoReference::Source(mic), 32.0),
+        Err(MixerError::SelfEchoReference(_))
+    ));
+    let mut other = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let foreign = (0..3).map(|_| other.add_source(64).0).last().unwrap();
+    assert!(matches!(
+        mixer.enable_echo_cancellation(mic, EchoReference::Source(foreign), 32.0),
+        Err(MixerError::UnknownSource(3))
+    ));
+    assert!(
+        mixer
+            .enable_echo_cancellation(mic, EchoReference::Master, 32.0)
+            .is_ok()
+    );
+    assert!(mixer.disable_echo_cancellation(mic).is_ok());
+}
+
+#[test]
+fn canceller_changes_wait_for_the_render_thread() {
+    let (mut mixer, mic, _far, _rings) = setup();
+    // Nothing renders, so nothing is picked up and the queue eventually pushes back.
+    let mut accepted = 0;
+    let refused = loop {
+        match mixer.enable_echo_cancellation(mic, EchoReference::Master, 32.0) {
+            Ok(()) => accepted += 1,
+            Err(error) => break error,
+        }
+        assert!(accepted < 10_000, "changes never backed up");
+    };
+    assert!(matches!(refused, MixerError::RenderBacklog));
+    assert!(matches!(
+        mixer.disable_echo_cancellation(mic),
+        Err(MixerError::RenderBacklog)
+    ));
+
+    // One block applies every queued change and hands the replaced cancellers back.
+    render(&mut mixer);
+    assert!(mixer.disable_echo_cancellation(mic).is_ok());
+}
+
+#[test]
+fn muted_sources_leave_their_ring_alone_unless_they_are_echo_references() {
+    let (mut mixer, mic, far, [mic_ring, far_ring]) = setup();
+    mixer.set_mute(mic, true).unwrap();
+    let block = vec![0.25f32; BLOCK_FRAMES * 2];
+    for _ in 0..4 {
+        mic_ring.push(&block, None);
+        far_ring.push(&block, None);
+        render(&mut mixer);
+    }
+    // Neither source is read while muted, so their queued audio survives an unmute.
+    assert_eq!(mic_ring.available_read(), BLOCK_FRAMES * 4);
+    assert_eq!(far_ring.available_read(), BLOCK_FRAMES * 4);
+
+    // Once the muted far end is the microphone's echo reference it is read every block.
+    mixer
+        .enable_echo_cancellation(mic, EchoReference::Source(far), DEFAULT_FILTER_LENGTH_MS)
+        .unwrap();
+    far_ring.discard(BLOCK_FRAMES * 4);
+    for _ in 0..4 {
+        far_ring.push(&block, None);
+        render(&mut mixer);
+    }
+    assert!(far_ring.available_read() <= 1);
+    assert_eq!(mic_ring.available_read(), BLOCK_FRAMES * 4);
+}
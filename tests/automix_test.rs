@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer, MixerError, SourceHandle};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+/// Push a constant-level block into each ring (one value per source) and render it.
+fn render(mixer: &mut Mixer, rings: &[(Arc<SharedRingBuffer>, f32)]) -> Vec<f32> {
+    for (ring, level) in rings {
+        ring.push(&vec![*level; BLOCK_FRAMES * 2], None);
+    }
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut buffer = AudioBuffer {
+        data: output.as_mut_ptr(),
+        frames: BLOCK_FRAMES as u32,
+        channels: 2,
+        timestamp_ns: 0,
+    };
+    mixer.process(&mut buffer).unwrap();
+    output
+}
+
+fn add_sources(mixer: &mut Mixer, count: usize) -> Vec<(SourceHandle, Arc<SharedRingBuffer>)> {
+    (0..count)
+        .map(|_| mixer.add_source(BLOCK_FRAMES * 8))
+        .collect()
+}
+
+fn settle(mixer: &mut Mixer, rings: &[(Arc<SharedRingBuffer>, f32)]) -> Vec<f32> {
+    let mut last = Vec::new();
+    for _ in 0..60 {
+        last = render(mixer, rings);
+    }
+    last
+}
+
+#[test]
+fn equal_open_mics_share_gain_evenly() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sources = add_sources(&mut mixer, 4);
+    let handles: Vec<_> = sources.iter().map(|(h, _)| *h).collect();
+    mixer.set_automix_group(&handles).unwrap();
+
+    let level = 0.1f32;
+    let rings: Vec<_> = sources
+        .iter()
+        .map(|(_, ring)| (ring.clone(), level))
+        .collect();
+    let output = settle(&mut mixer, &rings);
+
+    // Four equal mics each get -6 dB, so the sum rises by 6 dB instead of 12 dB.
+    let expected = level * 4.0 * 0.5;
+    let last = output[output.len() - 1];
+    assert!(
+        (last - expected).abs() < expected * 0.02,
+        "expected {expected}, got {last}"
+    );
+}
+
+#[test]
+fn dominant_mic_takes_most_of_the_gain() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sources = add_sources(&mut mixer, 3);
+    let handles: Vec<_> = sources.iter().map(|(h, _)| *h).collect();
+    mixer.set_automix_group(&handles).unwrap();
+
+    // Talker on the first mic, room tone 30 dB down on the others.
+    let rings = vec![
+        (sources[0].1.clone(), 0.3f32),
+        (sources[1].1.clone(), 0.0095),
+        (sources[2].1.clone(), 0.0095),
+    ];
+    let output = settle(&mut mixer, &rings);
+    let last = output[output.len() - 1];
+    assert!((last - 0.3).abs() < 0.3 * 0.05, "talker attenuated: {last}");
+
+    // Raising a quiet mic's weight lets it claim more of the group gain.
+    mixer.set_automix_weight(handles[1], 1_000.0).unwrap();
+    let weighted = settle(&mut mixer, &rings);
+    assert!(weighted[weighted.len() - 1] < last);
+}
+
+#[test]
+fn sources_outside_the_group_are_untouched() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sources = add_sources(&mut mixer, 3);
+    mixer
+        .set_automix_group(&[sources[0].0, sources[1].0])
+        .unwrap();
+    // Only the third source carries signal; it is not a member so stays at unity.
+    let rings = vec![
+        (sources[0].1.clone(), 0.0f32),
+        (sources[1].1.clone(), 0.0),
+        (sources[2].1.clone(), 0.25),
+    ];
+    let output = settle(&mut mixer, &rings);
+    assert!((output[output.len() - 1] - 0.25).abs() < 1e-4);
+
+    mixer.set_automix_group(&[]).unwrap();
+}
+
+#[test]
+fn all_zero_weights_share_gain_evenly() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sources = add_sources(&mut mixer, 4);
+    let handles: Vec<_> = sources.iter().map(|(h, _)| *h).collect();
+    mixer.set_automix_group(&handles).unwrap();
+    for handle in &handles {
+        mixer.set_automix_weight(*handle, 0.0).unwrap();
+    }
+
+    let level = 0.1f32;
+    let rings: Vec<_> = sources
+        .iter()
+        .map(|(_, ring)| (ring.clone(), level))
+        .collect();
+    let output = settle(&mut mixer, &rings);
+
+    // Same result as four neutral weights: -6 dB each, not unity on every channel.
+    let expected = level * 4.0 * 0.5;
+    let last = output[output.len() - 1];
+    assert!(
+        (last - expected).abs() < expected * 0.02,
+        "expected {expected}, got {last}"
+    );
+
+    // A zero weight next to neutral ones is pushed almost all the way down.
+    for handle in &handles[1..] {
+        mixer.set_automix_weight(*handle, 1.0).unwrap();
+    }
+    let rings = vec![
+        (sources[0].1.clone(), level),
+        (sources[1].1.clone(), 0.0),
+        (sources[2].1.clone(), 0.0),
+        (sources[3].1.clone(), 0.0),
+    ];
+    let output = settle(&mut mixer, &rings);
+    assert!(output[output.len() - 1] < level * 0.05);
+}
+
+#[test]
+fn oversized_weights_are_clamped_and_infinite_ones_rejected() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sources = add_sources(&mut mixer, 4);
+    let handles: Vec<_> = sources.iter().map(|(h, _)| *h).collect();
+    mixer.set_automix_group(&handles).unwrap();
+
+    for weight in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+        assert!(matches!(
+            mixer.set_automix_weight(handles[0], weight),
+            Err(MixerError::InvalidAutomixWeight(_))
+        ));
+    }
+
+    // Full-scale input at the largest finite weight would overflow the group's summed power;
+    // clamped weights all match, so the mics share the gain evenly instead of going NaN.
+    for handle in &handles {
+        mixer.set_automix_weight(*handle, f32::MAX).unwrap();
+    }
+    let level = 1.0f32;
+    let rings: Vec<_> = sources
+        .iter()
+        .map(|(_, ring)| (ring.clone(), level))
+        .collect();
+    let output = settle(&mut mixer, &rings);
+    let expected = level * 4.0 * 0.5;
+    let last = output[output.len() - 1];
+    assert!(
+        (last - expected).abs() < expected * 0.02,
+        "expected {expected}, got {last}"
+    );
+}
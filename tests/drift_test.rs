@@ -0,0 +1,128 @@
+use device_kit::events::MixerEventKind;
+use device_kit::generator::GeneratorConfig;
+use device_kit::offline::{OfflineRenderer, ProducerClock};
+
+/// A low rate keeps minutes of simulated time quick in debug builds.
+const SAMPLE_RATE: u32 = 8_000;
+/// 10 ms blocks.
+const BLOCK_FRAMES: usize = 80;
+/// A producer period that never lines up with the device block.
+const PERIOD_FRAMES: usize = 73;
+/// Time the drift estimate is given to settle before the ring is watched.
+const WARM_UP_SECONDS: usize = 10;
+
+/// What a simulated run observed after the warm-up.
+struct Run {
+    min_fill: usize,
+    max_fill: usize,
+    underruns: usize,
+    /// Mean drift estimate over each simulated second.
+    drift_ppm: Vec<f32>,
+}
+
+fn producer(drift_ppm: f64, jitter_ns: u64, feedback: bool) -> ProducerClock {
+    ProducerClock {
+        period_frames: PERIOD_FRAMES,
+        jitter_ns,
+        feedback,
+        ..ProducerClock::new(drift_ppm, SAMPLE_RATE)
+    }
+}
+
+fn simulate(clock: ProducerClock, seconds: usize) -> Run {
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = renderer.add_producer(GeneratorConfig::reference(), clock);
+    let mut block = vec![0.0; BLOCK_FRAMES * 2];
+    let blocks_per_second = SAMPLE_RATE as usize / BLOCK_FRAMES;
+    let mut run = Run {
+        min_fill: usize::MAX,
+        max_fill: 0,
+        underruns: 0,
+        drift_ppm: Vec::with_capacity(seconds),
+    };
+    let mut second_ppm = 0.0f32;
+    for index in 0..seconds * blocks_per_second {
+        renderer.render_block(&mut block).unwrap();
+        let warm = index >= WARM_UP_SECONDS * blocks_per_second;
+        while let Some(event) = renderer.mixer().pop_event() {
+            if warm && matches!(event.kind, MixerEventKind::SourceUnderrun { .. }) {
+                run.underruns += 1;
+            }
+        }
+        if warm {
+            let fill = renderer.queued_frames(handle).unwrap();
+            run.min_fill = run.min_fill.min(fill);
+            run.max_fill = run.max_fill.max(fill);
+        }
+        second_ppm += renderer.mixer().source_drift_ppm(handle).unwrap();
+        if (index + 1) % blocks_per_second == 0 {
+            run.drift_ppm.push(second_ppm / blocks_per_second as f32);
+            second_ppm = 0.0;
+        }
+    }
+    run
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+#[test]
+fn fast_producers_are_followed_for_minutes() {
+    let run = simulate(producer(1_000.0, 0, true), 180);
+    assert!(
+        (run.drift_ppm[WARM_UP_SECONDS] - 1_000.0).abs() < 1.0,
+        "{}",
+        run.drift_ppm[WARM_UP_SECONDS]
+    );
+    assert!(
+        run.drift_ppm[WARM_UP_SECONDS..]
+            .iter()
+            .all(|ppm| (ppm - 1_000.0).abs() < 1.0)
+    );
+    // Without correction the ring would gain 1 440 frames over three minutes.
+    assert_eq!(run.underruns, 0);
+    assert!(run.min_fill > 0, "{}", run.min_fill);
+    assert!(
+        run.max_fill <= PERIOD_FRAMES * 3 + BLOCK_FRAMES,
+        "{}",
+        run.max_fill
+    );
+}
+
+#[test]
+fn jittery_slow_producers_are_followed_for_minutes() {
+    // 200 µs of wake-up jitter is 2% of the producer period.
+    let run = simulate(producer(-500.0, 200_000, true), 180);
+    // Single readings swing by hundreds of ppm, but the jitter averages out.
+    let settled = mean(&run.drift_ppm[60..]);
+    assert!((settled + 500.0).abs() < 5.0, "{settled}");
+    for window in run.drift_ppm[WARM_UP_SECONDS..].chunks(10) {
+        assert!((mean(window) + 500.0).abs() < 30.0, "{}", mean(window));
+    }
+    assert_eq!(run.underruns, 0);
+    assert!(run.min_fill > 0, "{}", run.min_fill);
+    assert!(
+        run.max_fill <= PERIOD_FRAMES * 3 + BLOCK_FRAMES,
+        "{}",
+        run.max_fill
+    );
+}
+
+#[test]
+fn uncorrected_drift_fills_or_starves_the_ring() {
+    let fast = simulate(producer(1_000.0, 0, false), 60);
+    assert!(fast.drift_ppm.iter().all(|&ppm| ppm == 0.0));
+    // 1 000 ppm of 8 kHz over the 50 watched seconds.
+    assert!(
+        fast.max_fill - fast.min_fill >= 380,
+        "{}..{}",
+        fast.min_fill,
+        fast.max_fill
+    );
+    assert_eq!(fast.underruns, 0);
+
+    let slow = simulate(producer(-1_000.0, 0, false), 60);
+    assert!(slow.underruns > 0);
+    assert_eq!(slow.min_fill, 0);
+}
@@ -0,0 +1,130 @@
+use device_kit::dynamics::DuckingParams;
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+const BED_LEVEL: f32 = 0.5;
+
+/// Push one block per source (key on the left channel, bed on the right) and render it.
+fn render_block(
+    mixer: &mut Mixer,
+    key: &SharedRingBuffer,
+    bed: &SharedRingBuffer,
+    key_level: f32,
+    phase: &mut f32,
+) -> Vec<f32> {
+    let mut key_input = vec![0.0f32; BLOCK_FRAMES * 2];
+    for frame in key_input.chunks_exact_mut(2) {
+        frame[0] = (*phase * std::f32::consts::TAU).sin() * key_level;
+        *phase = (*phase + 440.0 / SAMPLE_RATE as f32).fract();
+    }
+    let mut bed_input = vec![0.0f32; BLOCK_FRAMES * 2];
+    for frame in bed_input.chunks_exact_mut(2) {
+        frame[1] = BED_LEVEL;
+    }
+    key.push(&key_input, None);
+    bed.push(&bed_input, None);
+
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut buffer = AudioBuffer {
+        data: output.as_mut_ptr(),
+        frames: BLOCK_FRAMES as u32,
+        channels: 2,
+        timestamp_ns: 0,
+    };
+    mixer.process(&mut buffer).unwrap();
+    output
+}
+
+fn bed_gain_db(block: &[f32]) -> f32 {
+    let right = block[block.len() - 1];
+    20.0 * (right / BED_LEVEL).log10()
+}
+
+#[test]
+fn sidechain_ducks_targets_and_recovers() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (key, key_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let (bed, bed_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let params = DuckingParams {
+        threshold_db: -30.0,
+        depth_db: 12.0,
+        attack_ms: 5.0,
+        release_ms: 50.0,
+        hold_ms: 100.0,
+    };
+    mixer.set_ducking(key, &[bed], params).unwrap();
+
+    let mut phase = 0.0f32;
+    // Prime the rings so the resampler has history, then run with a silent key.
+    for _ in 0..20 {
+        render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    }
+    let idle = render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    assert!(
+        bed_gain_db(&idle).abs() < 0.1,
+        "bed ducked while key silent"
+    );
+
+    // 100 ms of speech-level key signal pulls the bed down by the full depth.
+    let mut ducked = Vec::new();
+    for _ in 0..19 {
+        ducked = render_block(&mut mixer, &key_ring, &bed_ring, 0.5, &mut phase);
+    }
+    let ducked_db = bed_gain_db(&ducked);
+    assert!(
+        (ducked_db + params.depth_db).abs() < 0.5,
+        "expected ~-{} dB, got {ducked_db}",
+        params.depth_db
+    );
+
+    // Within the hold window the bed stays down.
+    let held = render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    assert!(bed_gain_db(&held) < -params.depth_db + 1.0);
+
+    // After hold + several release constants the bed is back at unity.
+    let mut recovered = Vec::new();
+    for _ in 0..100 {
+        recovered = render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    }
+    assert!(bed_gain_db(&recovered).abs() < 0.1);
+
+    // Clearing the key leaves the bed untouched even while the key is loud.
+    mixer.clear_ducking(key).unwrap();
+    for _ in 0..10 {
+        recovered = render_block(&mut mixer, &key_ring, &bed_ring, 0.5, &mut phase);
+    }
+    assert!(bed_gain_db(&recovered).abs() < 0.1);
+
+    // Clearing resets the detector: a key re-armed straight after being cleared while holding
+    // does not start out ducked.
+    mixer.set_ducking(key, &[bed], params).unwrap();
+    for _ in 0..19 {
+        render_block(&mut mixer, &key_ring, &bed_ring, 0.5, &mut phase);
+    }
+    let held = render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    assert!(bed_gain_db(&held) < -params.depth_db + 1.0);
+    mixer.clear_ducking(key).unwrap();
+    mixer.set_ducking(key, &[bed], params).unwrap();
+    let rearmed = render_block(&mut mixer, &key_ring, &bed_ring, 0.0, &mut phase);
+    assert!(
+        bed_gain_db(&rearmed).abs() < 0.1,
+        "re-armed key started ducked: {} dB",
+        bed_gain_db(&rearmed)
+    );
+}
+
+#[test]
+fn sidechain_rejects_unknown_targets() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (key, _) = mixer.add_source(BLOCK_FRAMES * 4);
+    let mut other = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    other.add_source(BLOCK_FRAMES * 4);
+    let (foreign, _) = other.add_source(BLOCK_FRAMES * 4);
+    assert!(
+        mixer
+            .set_ducking(key, &[foreign], DuckingParams::default())
+            .is_err()
+    );
+}
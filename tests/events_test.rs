@@ -0,0 +1,143 @@
+use device_kit::events::{EVENT_LOG_CAPACITY, MixerEvent, MixerEventKind};
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer, MixerError};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+fn process(mixer: &mut Mixer, output: &mut [f32], channels: u32) -> Result<usize, MixerError> {
+    let mut buffer = AudioBuffer {
+        data: output.as_mut_ptr(),
+        frames: (output.len() / channels as usize) as u32,
+        channels,
+        timestamp_ns: 0,
+    };
+    mixer.process(&mut buffer)
+}
+
+fn render_blocks(mixer: &mut Mixer, ring: Option<&SharedRingBuffer>, input: &[f32], blocks: usize) {
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        if let Some(ring) = ring {
+            ring.push(input, None);
+        }
+        process(mixer, &mut output, 2).unwrap();
+        assert!(output.iter().all(|sample| sample.is_finite()));
+    }
+}
+
+fn drain(mixer: &Mixer) -> Vec<MixerEvent> {
+    std::iter::from_fn(|| mixer.pop_event()).collect()
+}
+
+#[test]
+fn underrun_is_reported_once_per_dropout() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let input = vec![0.25f32; BLOCK_FRAMES * 2];
+
+    // A source that has never received audio is idle, not starved.
+    render_blocks(&mut mixer, None, &input, 10);
+    assert!(drain(&mixer).is_empty());
+
+    render_blocks(&mut mixer, Some(&ring), &input, 20);
+    assert!(drain(&mixer).is_empty(), "steady feed must not underrun");
+
+    let before = device_kit::device_kit_monotonic_time_ns();
+    render_blocks(&mut mixer, None, &input, 10);
+    let events = drain(&mixer);
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert!(events[0].timestamp_ns >= before);
+    match events[0].kind {
+        MixerEventKind::SourceUnderrun {
+            source_id,
+            missing_frames,
+        } => {
+            assert_eq!(source_id, source.id());
+            assert!(missing_frames > 0 && missing_frames as usize <= BLOCK_FRAMES);
+        }
+        other => panic!("unexpected event {other:?}"),
+    }
+
+    render_blocks(&mut mixer, Some(&ring), &input, 5);
+    render_blocks(&mut mixer, None, &input, 5);
+    assert_eq!(drain(&mixer).len(), 1);
+    assert_eq!(mixer.event_counts().source_underruns, 2);
+}
+
+#[test]
+fn overflow_reports_dropped_frames() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, _ring) = mixer.add_source(BLOCK_FRAMES);
+    let input = vec![0.1f32; BLOCK_FRAMES * 2 * 3 / 2];
+    assert_eq!(
+        mixer.write_source(source, &input, None).unwrap(),
+        BLOCK_FRAMES
+    );
+    let events = drain(&mixer);
+    assert_eq!(
+        events.iter().map(|event| event.kind).collect::<Vec<_>>(),
+        vec![MixerEventKind::RingOverflow {
+            source_id: source.id(),
+            dropped_frames: (BLOCK_FRAMES / 2) as u32,
+        }]
+    );
+}
+
+#[test]
+fn non_finite_samples_are_silenced_and_reported() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let mut input = vec![0.5f32; BLOCK_FRAMES * 2];
+    input[17] = f32::NAN;
+    input[40] = f32::INFINITY;
+
+    render_blocks(&mut mixer, Some(&ring), &input, 10);
+    let events = drain(&mixer);
+    assert_eq!(
+        events.iter().map(|event| event.kind).collect::<Vec<_>>(),
+        vec![MixerEventKind::NonFinite {
+            source_id: Some(source.id()),
+        }]
+    );
+    assert!(mixer.master_levels().rms.is_finite());
+}
+
+#[test]
+fn format_errors_are_reported_once_per_run() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let _ = mixer.add_source(BLOCK_FRAMES * 8);
+    let mut mono = vec![0.0f32; BLOCK_FRAMES];
+    for _ in 0..3 {
+        assert!(process(&mut mixer, &mut mono, 1).is_err());
+    }
+    let mut stereo = vec![0.0f32; BLOCK_FRAMES * 2];
+    process(&mut mixer, &mut stereo, 2).unwrap();
+    assert!(process(&mut mixer, &mut mono, 1).is_err());
+
+    let events = drain(&mixer);
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert_eq!(
+        events[0].kind,
+        MixerEventKind::FormatError {
+            channels: 1,
+            frames: BLOCK_FRAMES as u32,
+        }
+    );
+    assert_eq!(mixer.event_counts().format_errors, 2);
+}
+
+#[test]
+fn full_log_counts_dropped_events() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, _ring) = mixer.add_source(BLOCK_FRAMES);
+    let input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let attempts = EVENT_LOG_CAPACITY + 10;
+    for _ in 0..attempts {
+        mixer.write_source(source, &input, None).unwrap();
+    }
+    let counts = mixer.event_counts();
+    assert_eq!(counts.ring_overflows as usize, attempts - 1);
+    assert_eq!(counts.dropped as usize, attempts - 1 - EVENT_LOG_CAPACITY);
+    assert_eq!(drain(&mixer).len(), EVENT_LOG_CAPACITY);
+}
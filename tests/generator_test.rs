@@ -0,0 +1,231 @@
+use device_kit::events::MixerEventKind;
+use device_kit::generator::{
+    GeneratorConfig, LOOPBACK_GENERATOR_PINK_NOISE, LOOPBACK_GENERATOR_SINE, LoopbackGenerator,
+    Waveform,
+};
+use device_kit::{
+    AudioBuffer, Mixer, MixerError, loopback_mixer_add_generator, loopback_mixer_create,
+    loopback_mixer_destroy, loopback_mixer_get_generator, loopback_mixer_register_node_source,
+    loopback_mixer_remove_generator, loopback_mixer_set_generator,
+};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+fn render(mixer: &mut Mixer, blocks: usize) -> Vec<f32> {
+    let mut rendered = Vec::with_capacity(blocks * BLOCK_FRAMES * 2);
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+        rendered.extend_from_slice(&output);
+    }
+    rendered
+}
+
+/// Left channel of one second of `config`, after checking both channels carry the same signal.
+fn render_second(config: GeneratorConfig) -> Vec<f32> {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    mixer.add_generator_source(config).unwrap();
+    let rendered = render(&mut mixer, SAMPLE_RATE as usize / BLOCK_FRAMES);
+    assert!(rendered.chunks_exact(2).all(|frame| frame[0] == frame[1]));
+    while let Some(event) = mixer.pop_event() {
+        assert!(
+            !matches!(event.kind, MixerEventKind::SourceUnderrun { .. }),
+            "unexpected {event:?}"
+        );
+    }
+    rendered.iter().step_by(2).copied().collect()
+}
+
+fn rms_dbfs(samples: &[f32]) -> f32 {
+    let power = samples.iter().map(|&s| f64::from(s * s)).sum::<f64>() / samples.len() as f64;
+    10.0 * power.log10() as f32
+}
+
+fn rising_zero_crossings(samples: &[f32]) -> usize {
+    samples
+        .windows(2)
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count()
+}
+
+#[test]
+fn reference_tone_is_1khz_at_the_alignment_level() {
+    let left = render_second(GeneratorConfig::reference());
+    // A -20 dBFS sine peaks at 0.1, so its RMS sits 3 dB lower.
+    assert!(
+        (rms_dbfs(&left) + 23.01).abs() < 0.05,
+        "{}",
+        rms_dbfs(&left)
+    );
+    let peak = left.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!((peak - 0.1).abs() < 1e-3, "{peak}");
+    assert!((999..=1_001).contains(&rising_zero_crossings(&left)));
+}
+
+#[test]
+fn sine_follows_frequency_and_level() {
+    let mut config = GeneratorConfig::new(Waveform::Sine {
+        frequency_hz: 440.0,
+    });
+    config.level_dbfs = -6.0;
+    let left = render_second(config);
+    assert!((rms_dbfs(&left) + 9.01).abs() < 0.05);
+    assert!((439..=441).contains(&rising_zero_crossings(&left)));
+}
+
+#[test]
+fn noise_matches_a_sine_at_the_same_level_and_repeats() {
+    for waveform in [Waveform::WhiteNoise, Waveform::PinkNoise] {
+        let config = GeneratorConfig::new(waveform);
+        let left = render_second(config);
+        let rms = rms_dbfs(&left);
+        assert!((rms + 23.01).abs() < 0.5, "{waveform:?}: {rms}");
+        assert_eq!(left, render_second(config), "{waveform:?}");
+    }
+
+    // Pink noise puts far more of its energy in the low end than white noise does.
+    let low_share = |left: &[f32]| {
+        let smoothed: Vec<f32> = left
+            .windows(32)
+            .map(|w| w.iter().sum::<f32>() / 32.0)
+            .collect();
+        10f32.powf((rms_dbfs(&smoothed) - rms_dbfs(left)) / 10.0)
+    };
+    let white = low_share(&render_second(GeneratorConfig::new(Waveform::WhiteNoise)));
+    let pink = low_share(&render_second(GeneratorConfig::new(Waveform::PinkNoise)));
+    assert!(pink > white * 5.0, "pink {pink} white {white}");
+}
+
+#[test]
+fn impulses_are_evenly_spaced_single_samples() {
+    let mut config = GeneratorConfig::new(Waveform::Impulse { rate_hz: 10.0 });
+    config.level_dbfs = 0.0;
+    let left = render_second(config);
+    let impulses: Vec<usize> = (0..left.len()).filter(|&i| left[i] != 0.0).collect();
+    let expected: Vec<usize> = (0..10).map(|i| i * 4_800).collect();
+    assert_eq!(impulses, expected);
+    assert!(impulses.iter().all(|&i| left[i] == 1.0));
+}
+
+#[test]
+fn sweeps_rise_and_restart() {
+    let config = GeneratorConfig::new(Waveform::Sweep {
+        start_hz: 100.0,
+        end_hz: 1_000.0,
+        duration_seconds: 0.5,
+    });
+    let left = render_second(config);
+    let half = left.len() / 2;
+    let early = rising_zero_crossings(&left[..half / 4]);
+    let late = rising_zero_crossings(&left[half * 3 / 4..half]);
+    assert!(late > early * 3, "early {early} late {late}");
+    assert_eq!(left[..half], left[half..]);
+}
+
+#[test]
+fn changes_apply_from_the_next_block() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = mixer
+        .add_generator_source(GeneratorConfig::reference())
+        .unwrap();
+    assert!(render(&mut mixer, 1).iter().any(|&s| s != 0.0));
+
+    let mut quiet = GeneratorConfig::new(Waveform::Impulse { rate_hz: 1.0 });
+    quiet.level_dbfs = -6.0;
+    mixer.set_generator(handle, quiet).unwrap();
+    assert_eq!(mixer.generator(handle).unwrap(), quiet);
+    let block = render(&mut mixer, 1);
+    assert!((block[0] - 0.501).abs() < 1e-3);
+    assert!(block[2..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn rejects_invalid_settings_and_other_sources() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let invalid = [
+        GeneratorConfig::new(Waveform::Sine { frequency_hz: 0.0 }),
+        GeneratorConfig::new(Waveform::Impulse { rate_hz: f32::NAN }),
+        GeneratorConfig {
+            waveform: Waveform::Reference,
+            level_dbfs: 3.0,
+        },
+    ];
+    for config in invalid {
+        assert!(matches!(
+            mixer.add_generator_source(config),
+            Err(MixerError::InvalidGenerator(rejected)) if rejected.waveform.name() == config.waveform.name()
+        ));
+    }
+
+    let handle = mixer
+        .add_generator_source(GeneratorConfig::reference())
+        .unwrap();
+    assert!(matches!(
+        mixer.set_generator(handle, invalid[0]),
+        Err(MixerError::InvalidGenerator(_))
+    ));
+    assert_eq!(
+        mixer.generator(handle).unwrap(),
+        GeneratorConfig::reference()
+    );
+
+    let (pushed, _ring) = mixer.add_source(BLOCK_FRAMES * 4);
+    assert!(matches!(
+        mixer.generator(pushed),
+        Err(MixerError::NotAGenerator(id)) if id == pushed.id()
+    ));
+    assert!(matches!(
+        mixer.set_generator(pushed, GeneratorConfig::reference()),
+        Err(MixerError::NotAGenerator(_))
+    ));
+}
+
+#[test]
+fn ffi_round_trips_settings_and_guards_indices() {
+    let handle = loopback_mixer_create(SAMPLE_RATE as f64, BLOCK_FRAMES as u32);
+    let sine = LoopbackGenerator {
+        kind: LOOPBACK_GENERATOR_SINE,
+        frequency_hz: 250.0,
+        end_frequency_hz: 0.0,
+        duration_seconds: 0.0,
+        level_dbfs: -12.0,
+    };
+    let mut out = LoopbackGenerator::default();
+    unsafe {
+        assert!(loopback_mixer_add_generator(handle, 7, sine));
+        assert!(!loopback_mixer_add_generator(handle, 7, sine));
+        assert!(loopback_mixer_register_node_source(handle, 8, 1_024));
+        assert!(!loopback_mixer_add_generator(handle, 8, sine));
+        assert!(!loopback_mixer_get_generator(handle, 8, &mut out));
+
+        assert!(loopback_mixer_get_generator(handle, 7, &mut out));
+        assert_eq!(
+            GeneratorConfig::from_ffi(&out),
+            GeneratorConfig::from_ffi(&sine)
+        );
+        let pink = LoopbackGenerator {
+            kind: LOOPBACK_GENERATOR_PINK_NOISE,
+            ..sine
+        };
+        assert!(loopback_mixer_set_generator(handle, 7, pink));
+        assert!(loopback_mixer_get_generator(handle, 7, &mut out));
+        assert_eq!(out.kind, LOOPBACK_GENERATOR_PINK_NOISE);
+        assert!(!loopback_mixer_set_generator(
+            handle,
+            7,
+            LoopbackGenerator { kind: 99, ..sine }
+        ));
+
+        assert!(!loopback_mixer_remove_generator(handle, 8));
+        assert!(loopback_mixer_remove_generator(handle, 7));
+        assert!(!loopback_mixer_get_generator(handle, 7, &mut out));
+        loopback_mixer_destroy(handle);
+    }
+}
@@ -0,0 +1,175 @@
+//! Renders fixed mixer scenarios offline and compares them against the reference renders in
+//! `tests/golden/`. After an intended change to the output, regenerate the references with
+//!
+//! ```sh
+//! DEVICE_KIT_UPDATE_GOLDENS=1 cargo test --test golden_test
+//! ```
+//!
+//! and listen to the new files before committing them.
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use device_kit::generator::{GeneratorConfig, Waveform};
+use device_kit::offline::{Comparison, OfflineInput, OfflineRenderer, Tolerance};
+use device_kit::wav::{SampleFormat, WavSpec, WavWriter, read_wav};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+const FRAMES: usize = SAMPLE_RATE as usize / 5;
+const UPDATE_VAR: &str = "DEVICE_KIT_UPDATE_GOLDENS";
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.wav"))
+}
+
+/// Compare `rendered` with the golden file `name`, or replace the file when regenerating. Goldens
+/// are float WAV so that overs past full scale survive.
+fn check(name: &str, rendered: &[f32], tolerance: Tolerance) {
+    let path = golden_path(name);
+    if env::var_os(UPDATE_VAR).is_some() {
+        let spec = WavSpec {
+            sample_rate: SAMPLE_RATE,
+            channels: 2,
+            format: SampleFormat::Float32,
+        };
+        let mut writer = WavWriter::new(File::create(&path).unwrap(), spec).unwrap();
+        writer.write_samples(rendered).unwrap();
+        writer.finish().unwrap();
+        return;
+    }
+
+    let file = File::open(&path).unwrap_or_else(|err| {
+        panic!(
+            "{}: {err}; run `{UPDATE_VAR}=1 cargo test --test golden_test` to create it",
+            path.display()
+        )
+    });
+    let (spec, golden) = read_wav(BufReader::new(file)).unwrap();
+    assert_eq!(spec.sample_rate, SAMPLE_RATE, "{name}");
+    assert_eq!(spec.channels, 2, "{name}");
+    assert_eq!(golden.len(), rendered.len(), "{name}: length changed");
+    let comparison = Comparison::between(&golden, rendered);
+    assert!(
+        comparison.is_within(&tolerance),
+        "{name} drifted from its golden render: {comparison} (allowed {tolerance:?}); if the \
+         change is intended, regenerate with `{UPDATE_VAR}=1 cargo test --test golden_test`"
+    );
+}
+
+fn tone(waveform: Waveform, level_dbfs: f32, seconds: f64) -> OfflineInput {
+    let mut config = GeneratorConfig::new(waveform);
+    config.level_dbfs = level_dbfs;
+    OfflineInput::from_generator(config, SAMPLE_RATE, seconds)
+}
+
+#[test]
+fn gain_changes_match_golden() {
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = renderer.add_input(tone(
+        Waveform::Sine {
+            frequency_hz: 440.0,
+        },
+        -6.0,
+        0.2,
+    ));
+    renderer.mixer_mut().set_gain(handle, 0.5).unwrap();
+    let mut rendered = renderer.render(FRAMES / 2).unwrap();
+    renderer.mixer_mut().set_gain(handle, 1.5).unwrap();
+    rendered.extend(renderer.render(FRAMES / 4).unwrap());
+    renderer.mixer_mut().set_mute(handle, true).unwrap();
+    rendered.extend(renderer.render(FRAMES / 4).unwrap());
+    check("gain", &rendered, Tolerance::strict());
+}
+
+#[test]
+fn latency_compensation_matches_golden() {
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let delayed = renderer.add_input(tone(Waveform::Impulse { rate_hz: 100.0 }, 0.0, 0.2));
+    let advanced = renderer.add_input(
+        tone(
+            Waveform::Sine {
+                frequency_hz: 1_000.0,
+            },
+            -20.0,
+            0.2,
+        )
+        .starting_at_frame(1_234),
+    );
+    let mixer = renderer.mixer_mut();
+    mixer.set_latency(delayed, 37).unwrap();
+    mixer.set_latency(advanced, -16).unwrap();
+    let mut rendered = renderer.render(FRAMES / 2).unwrap();
+    // Growing the delay mid-stream inserts silence; shrinking it drops frames.
+    renderer.mixer_mut().set_latency(delayed, 300).unwrap();
+    renderer.mixer_mut().set_latency(advanced, 0).unwrap();
+    rendered.extend(renderer.render(FRAMES / 2).unwrap());
+    check("latency", &rendered, Tolerance::strict());
+}
+
+#[test]
+fn drift_correction_matches_golden() {
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    // The producer runs 1% fast; pushing its whole signal up front keeps the ring from starving
+    // while the mixer speeds up to follow it.
+    let handle = renderer.add_input(
+        tone(
+            Waveform::Sine {
+                frequency_hz: 997.0,
+            },
+            -12.0,
+            0.3,
+        )
+        .in_chunks_of(SAMPLE_RATE as usize),
+    );
+    let mut rendered = vec![0.0; FRAMES * 2];
+    for block in rendered.chunks_mut(BLOCK_FRAMES * 2) {
+        let device_ns = renderer.clock().now_ns();
+        let elapsed = renderer.clock().frames() as f64 / SAMPLE_RATE as f64;
+        let source_ns = device_ns + (elapsed * 0.01 * 1e9) as u64;
+        renderer
+            .mixer_mut()
+            .submit_clock_feedback(handle, device_ns, source_ns)
+            .unwrap();
+        renderer.render_block(block).unwrap();
+    }
+    check("drift", &rendered, Tolerance::strict());
+}
+
+#[test]
+fn multi_source_mix_matches_golden() {
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let sweep = renderer.add_input(tone(
+        Waveform::Sweep {
+            start_hz: 100.0,
+            end_hz: 8_000.0,
+            duration_seconds: 0.2,
+        },
+        -12.0,
+        0.2,
+    ));
+    let noise = renderer.add_input(tone(Waveform::PinkNoise, -30.0, 0.15).starting_at_frame(2_000));
+    let ramp: Vec<f32> = (0..4_000)
+        .flat_map(|frame| {
+            let value = frame as f32 / 4_000.0 * 0.25;
+            [value, -value]
+        })
+        .collect();
+    renderer.add_input(
+        OfflineInput::from_samples(ramp)
+            .starting_at_frame(5_000)
+            .in_chunks_of(441),
+    );
+    let muted = renderer.add_input(tone(Waveform::Reference, -20.0, 0.2));
+    let mixer = renderer.mixer_mut();
+    mixer.set_gain(sweep, 0.7).unwrap();
+    mixer.set_gain(noise, 2.0).unwrap();
+    mixer.set_mute(muted, true).unwrap();
+    let rendered = renderer.render(FRAMES).unwrap();
+    check("multi_source", &rendered, Tolerance::strict());
+}
@@ -0,0 +1,73 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+use device_kit::log::{self, LogCode, LogLevel, LogRecord};
+use device_kit::{
+    device_kit_pop_log_into, device_kit_pop_log_record, device_kit_start_driver,
+    device_kit_start_engine, device_kit_stop_driver, device_kit_stop_engine,
+};
+
+// The log is process-wide, so the scenarios share one test to keep them ordered.
+#[test]
+fn structured_log_round_trip() {
+    while log::pop().is_some() {}
+
+    let before = device_kit::device_kit_monotonic_time_ns();
+    assert!(device_kit_start_driver());
+    device_kit_stop_driver();
+
+    // Structured records carry level, code and a host timestamp.
+    let mut record = LogRecord {
+        timestamp_ns: 0,
+        level: LogLevel::Trace,
+        code: LogCode::RenderOk,
+        args: [0.0; log::MAX_LOG_ARGS],
+    };
+    assert!(unsafe { device_kit_pop_log_record(&mut record) });
+    assert_eq!(record.level, LogLevel::Info);
+    assert_eq!(record.code, LogCode::DriverStarted);
+    assert!(record.timestamp_ns >= before);
+
+    // The formatted pop copies into the caller's buffer.
+    let mut buffer = [0 as c_char; 64];
+    let length = unsafe { device_kit_pop_log_into(buffer.as_mut_ptr(), buffer.len()) };
+    let text = unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_str().unwrap();
+    assert_eq!(text, "INFO driver stopped");
+    assert_eq!(length, text.len());
+    assert_eq!(
+        unsafe { device_kit_pop_log_into(buffer.as_mut_ptr(), buffer.len()) },
+        0
+    );
+    assert!(!unsafe { device_kit_pop_log_record(&mut record) });
+
+    // A short buffer truncates, reports the full length and still consumes the entry.
+    assert!(device_kit_start_engine());
+    let mut short = [0x55 as c_char; 8];
+    let length = unsafe { device_kit_pop_log_into(short.as_mut_ptr(), short.len()) };
+    assert_eq!(length, "INFO engine started".len());
+    let text = unsafe { CStr::from_ptr(short.as_ptr()) }.to_str().unwrap();
+    assert_eq!(text, "INFO en");
+    assert!(log::pop().is_none());
+
+    // Records above the level threshold never reach the queue.
+    log::set_level(LogLevel::Warn);
+    device_kit_stop_engine();
+    assert!(log::pop().is_none());
+    log::set_level(LogLevel::Info);
+
+    // A full queue drops new records and counts them.
+    for _ in 0..log::LOG_CAPACITY + 5 {
+        assert!(device_kit_start_engine());
+        device_kit_stop_engine();
+    }
+    let mut kept = 0;
+    while let Some(record) = log::pop() {
+        assert!(matches!(
+            record.code,
+            LogCode::EngineStarted | LogCode::EngineStopped
+        ));
+        kept += 1;
+    }
+    assert_eq!(kept, log::LOG_CAPACITY);
+    assert_eq!(log::dropped() as usize, log::LOG_CAPACITY + 10);
+}
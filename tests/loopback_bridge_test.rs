@@ -1,11 +1,18 @@
 use std::f32::consts::TAU;
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use coreaudio_sys::{AudioBuffer, AudioBufferList, AudioTimeStamp, kAudioTimeStampHostTimeValid};
 
+use device_kit::wav::{SampleFormat, WavSpec, WavWriter};
 use device_kit::{
-    LoopbackRenderArgs, device_kit_monotonic_time_ns, loopback_mixer_create,
-    loopback_mixer_destroy, loopback_mixer_process, loopback_mixer_push_node_frames,
-    loopback_mixer_register_node_source, loopback_mixer_set_node_gain, loopback_mixer_submit_input,
+    LoopbackMixerFfi, LoopbackRenderArgs, device_kit_monotonic_time_ns, loopback_mixer_create,
+    loopback_mixer_destroy, loopback_mixer_load_file, loopback_mixer_process,
+    loopback_mixer_push_node_frames, loopback_mixer_register_node_source,
+    loopback_mixer_set_file_playing, loopback_mixer_set_node_gain, loopback_mixer_submit_input,
+    loopback_mixer_unload_file,
 };
 
 const SAMPLE_RATE: f64 = 48_000.0;
@@ -125,3 +132,84 @@ fn loopback_node_source_push() {
     unsafe { loopback_mixer_destroy(handle) };
     assert!(produced, "expected non-silent output from node source");
 }
+
+#[test]
+fn file_sources_unload_while_rendering() {
+    let path = std::env::temp_dir().join(format!(
+        "device_kit_bridge_{}_unload.wav",
+        std::process::id()
+    ));
+    let spec = WavSpec {
+        sample_rate: SAMPLE_RATE as u32,
+        channels: 2,
+        format: SampleFormat::Float32,
+    };
+    let mut writer = WavWriter::new(File::create(&path).unwrap(), spec).unwrap();
+    writer.write_samples(&vec![0.25f32; 96_000]).unwrap();
+    writer.finish().unwrap();
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+    let handle = loopback_mixer_create(SAMPLE_RATE, BLOCK_FRAMES);
+    assert!(!handle.is_null());
+    for index in 1..=8 {
+        assert!(unsafe { loopback_mixer_load_file(handle, index, c_path.as_ptr()) });
+        assert!(unsafe { loopback_mixer_set_file_playing(handle, index, true) });
+    }
+
+    // Raw pointers are not Send, so the render thread gets the address.
+    let address = handle as usize;
+    let running = Arc::new(AtomicBool::new(true));
+    let blocks = Arc::new(AtomicUsize::new(0));
+    let render = {
+        let running = running.clone();
+        let blocks = blocks.clone();
+        std::thread::spawn(move || {
+            let handle = address as *mut LoopbackMixerFfi;
+            let mut output = vec![0.0f32; (BLOCK_FRAMES as usize) * 2];
+            let timestamp = default_timestamp();
+            let audio_buffer = AudioBuffer {
+                mNumberChannels: 2,
+                mDataByteSize: (output.len() * std::mem::size_of::<f32>()) as u32,
+                mData: output.as_mut_ptr() as *mut _,
+            };
+            let mut buffer_list = AudioBufferList {
+                mNumberBuffers: 1,
+                mBuffers: [audio_buffer],
+            };
+            let args = LoopbackRenderArgs {
+                buffer_list: &mut buffer_list as *mut _,
+                frame_count: BLOCK_FRAMES,
+                timestamp: &timestamp as *const _,
+            };
+            while running.load(Ordering::Acquire) {
+                let status = unsafe { loopback_mixer_process(handle, &args) };
+                assert_eq!(status, 0);
+                blocks.fetch_add(1, Ordering::Release);
+            }
+            output
+        })
+    };
+    let wait_for_blocks = |count: usize| {
+        let target = blocks.load(Ordering::Acquire) + count;
+        while blocks.load(Ordering::Acquire) < target {
+            std::thread::yield_now();
+        }
+    };
+
+    for index in 1..=8 {
+        wait_for_blocks(2);
+        assert!(unsafe { loopback_mixer_unload_file(handle, index) });
+        // The source is gone for the control side before the render thread detaches it.
+        assert!(!unsafe { loopback_mixer_unload_file(handle, index) });
+    }
+    wait_for_blocks(2);
+    running.store(false, Ordering::Release);
+    let output = render.join().unwrap();
+
+    unsafe { loopback_mixer_destroy(handle) };
+    fs::remove_file(&path).unwrap();
+    assert!(
+        output.iter().all(|&sample| sample == 0.0),
+        "expected silence once every file source is unloaded"
+    );
+}
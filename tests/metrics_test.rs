@@ -0,0 +1,101 @@
+#![cfg(feature = "metrics")]
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use device_kit::metrics::{CONTENT_TYPE, MetricsServer, render};
+use device_kit::{
+    get_mixer_status, loopback_mixer_create, loopback_mixer_destroy,
+    loopback_mixer_register_node_source,
+};
+
+fn get(addr: SocketAddr, request: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.to_string(), body.to_string())
+}
+
+/// Check the parts of the OpenMetrics grammar the exporter relies on: every sample belongs to
+/// the most recently declared family, families are declared once, and the text ends in `# EOF`.
+fn assert_well_formed(text: &str) {
+    assert!(text.ends_with("# EOF\n"), "missing EOF marker");
+    let mut declared = HashSet::new();
+    let mut current: Option<(String, String)> = None;
+    for line in text.lines() {
+        if line == "# EOF" {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').unwrap();
+            assert!(declared.insert(name.to_string()), "{name} declared twice");
+            current = Some((name.to_string(), kind.to_string()));
+            continue;
+        }
+        if line.starts_with("# HELP ") {
+            continue;
+        }
+        let (family, kind) = current.as_ref().expect("sample before any family");
+        let name = line.split(['{', ' ']).next().unwrap();
+        let suffixes: &[&str] = match kind.as_str() {
+            "counter" => &["_total"],
+            "histogram" => &["_bucket", "_count", "_sum"],
+            _ => &[""],
+        };
+        assert!(
+            suffixes
+                .iter()
+                .any(|suffix| name == format!("{family}{suffix}")),
+            "sample {name} outside family {family}"
+        );
+        let value = line.rsplit(' ').next().unwrap();
+        assert!(
+            value.parse::<f64>().is_ok() || ["+Inf", "-Inf", "NaN"].contains(&value),
+            "bad value in {line}"
+        );
+    }
+}
+
+#[test]
+fn serves_openmetrics_over_http() {
+    let handle = loopback_mixer_create(48_000.0, 256);
+    assert!(unsafe { loopback_mixer_register_node_source(handle, 7, 1_024) });
+
+    let status = get_mixer_status().unwrap();
+    let text = render(&status);
+    assert_well_formed(&text);
+    assert!(text.contains("loopback_sample_rate_hertz 48000\n"));
+    assert!(text.contains("loopback_source_underruns_total 0\n"));
+    assert!(text.contains("loopback_callback_load_bucket{le=\"+Inf\"} 0\n"));
+    assert!(text.contains("loopback_master_loudness_lufs{window=\"integrated\"} -Inf\n"));
+    assert!(text.contains("loopback_source_gain_db{source=\"1\",name=\"Microphone\"} 0\n"));
+    assert!(text.contains("loopback_source_muted{source=\"2\",name=\"Source #2\"} 0\n"));
+
+    let server = MetricsServer::start("127.0.0.1:0").unwrap();
+    let (head, body) = get(
+        server.local_addr(),
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+    assert!(head.contains(&format!("Content-Type: {CONTENT_TYPE}")));
+    assert!(head.contains(&format!("Content-Length: {}", body.len())));
+    assert_well_formed(&body);
+    assert!(
+        body.contains("loopback_source_level{source=\"2\",name=\"Source #2\",kind=\"rms\"} 0\n")
+    );
+
+    let (head, _) = get(server.local_addr(), "GET / HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 404"), "{head}");
+    let (head, _) = get(server.local_addr(), "POST /metrics HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 405"), "{head}");
+    drop(server);
+
+    let idle = MetricsServer::with_source("127.0.0.1:0", || None).unwrap();
+    let (head, _) = get(idle.local_addr(), "GET /metrics HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 503"), "{head}");
+
+    unsafe { loopback_mixer_destroy(handle) };
+}
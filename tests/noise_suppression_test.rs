@@ -0,0 +1,174 @@
+use device_kit::denoise::latency_frames;
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 256;
+
+/// Deterministic uniform noise in [-1, 1).
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+}
+
+/// Fan/HVAC-like noise: low-passed rumble with a quieter broadband hiss.
+fn hvac_noise(frames: usize, level: f32) -> Vec<f32> {
+    let mut noise = Noise(0x1234_5678);
+    let coefficient = (-std::f32::consts::TAU * 400.0 / SAMPLE_RATE as f32).exp();
+    let mut rumble = 0.0f32;
+    (0..frames)
+        .map(|_| {
+            rumble = coefficient * rumble + (1.0 - coefficient) * noise.next() * 6.0;
+            level * (rumble + 0.15 * noise.next())
+        })
+        .collect()
+}
+
+/// Speech-like signal: harmonic "vowels" with a gliding pitch, gated into syllables.
+fn speech_like(frames: usize, level: f32) -> Vec<f32> {
+    let mut phase = 0.0f32;
+    (0..frames)
+        .map(|n| {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            let f0 = 140.0 + 25.0 * (std::f32::consts::TAU * 0.7 * t).sin();
+            phase = (phase + f0 / SAMPLE_RATE as f32).fract();
+            let voiced: f32 = (1..=20)
+                .map(|h| (std::f32::consts::TAU * phase * h as f32).sin() / h as f32)
+                .sum();
+            let syllable = (t * 2.5).fract();
+            let envelope = if syllable < 0.6 {
+                (std::f32::consts::PI * syllable / 0.6).sin()
+            } else {
+                0.0
+            };
+            level * voiced * envelope
+        })
+        .collect()
+}
+
+/// Stream a mono signal through a single-source mixer and return the rendered left channel.
+fn render_through(mixer: &mut Mixer, ring: &SharedRingBuffer, signal: &[f32]) -> Vec<f32> {
+    let mut rendered = Vec::with_capacity(signal.len());
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for chunk in signal.chunks_exact(BLOCK_FRAMES) {
+        for (frame, sample) in input.chunks_exact_mut(2).zip(chunk) {
+            frame.fill(*sample);
+        }
+        ring.push(&input, None);
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+        rendered.extend(output.chunks_exact(2).map(|frame| frame[0]));
+    }
+    rendered
+}
+
+/// SNR of `output` against `clean` (delayed by `delay` frames) over frames from `start`.
+fn snr_db(output: &[f32], clean: &[f32], delay: usize, start: usize) -> f32 {
+    let (mut signal, mut error) = (0.0f64, 0.0f64);
+    for n in start..output.len() {
+        let reference = clean[n - delay] as f64;
+        signal += reference * reference;
+        error += (output[n] as f64 - reference).powi(2);
+    }
+    (10.0 * (signal / error).log10()) as f32
+}
+
+fn energy(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s * s).sum()
+}
+
+#[test]
+fn suppressor_improves_snr_of_noisy_speech() {
+    let frames = 5 * SAMPLE_RATE as usize;
+    let clean = speech_like(frames, 0.05);
+    let noise = hvac_noise(frames, 0.05);
+    let noisy: Vec<f32> = clean.iter().zip(&noise).map(|(s, n)| s + n).collect();
+    // The mixer's interpolator delays every source by one frame.
+    let mixer_delay = 1;
+    let start = SAMPLE_RATE as usize;
+
+    let mut bypass = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (_, ring) = bypass.add_source(BLOCK_FRAMES * 8);
+    let unprocessed = render_through(&mut bypass, &ring, &noisy);
+    let input_snr = snr_db(&unprocessed, &clean, mixer_delay, start);
+
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (mic, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.enable_noise_suppression(mic, 1.0).unwrap();
+    let processed = render_through(&mut mixer, &ring, &noisy);
+    let delay = mixer_delay + latency_frames(SAMPLE_RATE);
+    let output_snr = snr_db(&processed, &clean, delay, start);
+
+    assert!(
+        output_snr - input_snr > 4.0,
+        "SNR only improved from {input_snr:.1} dB to {output_snr:.1} dB"
+    );
+}
+
+#[test]
+fn strength_controls_noise_attenuation() {
+    let frames = 3 * SAMPLE_RATE as usize;
+    let noise = hvac_noise(frames, 0.05);
+    let start = SAMPLE_RATE as usize;
+    let delay = 1 + latency_frames(SAMPLE_RATE);
+    let input_energy = energy(&noise[start - delay..frames - delay]);
+
+    let mut attenuation = Vec::new();
+    for strength in [0.0f32, 0.5, 1.0] {
+        let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+        let (mic, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+        mixer.enable_noise_suppression(mic, strength).unwrap();
+        let processed = render_through(&mut mixer, &ring, &noise);
+        attenuation.push(10.0 * (input_energy / energy(&processed[start..])).log10());
+    }
+
+    assert!(
+        attenuation[0].abs() < 0.1,
+        "zero strength changed the level by {:.2} dB",
+        attenuation[0]
+    );
+    assert!(
+        attenuation[1] > 6.0 && attenuation[1] < attenuation[2],
+        "half strength attenuated by {:.1} dB",
+        attenuation[1]
+    );
+    assert!(
+        attenuation[2] > 15.0,
+        "full strength only attenuated noise by {:.1} dB",
+        attenuation[2]
+    );
+}
+
+#[test]
+fn strength_changes_reach_the_running_suppressor() {
+    let frames = 3 * SAMPLE_RATE as usize;
+    let noise = hvac_noise(frames, 0.05);
+    let start = SAMPLE_RATE as usize;
+    let delay = 1 + latency_frames(SAMPLE_RATE);
+    let input_energy = energy(&noise[start - delay..frames - delay]);
+
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (mic, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.enable_noise_suppression(mic, 0.0).unwrap();
+    // Only the first call hands a suppressor to the render thread; later ones just retune it,
+    // so they never back up however often they come before a block is rendered.
+    for _ in 0..1_000 {
+        mixer.enable_noise_suppression(mic, 1.0).unwrap();
+    }
+    let processed = render_through(&mut mixer, &ring, &noise);
+    let attenuation = 10.0 * (input_energy / energy(&processed[start..])).log10();
+    assert!(
+        attenuation > 15.0,
+        "retuned suppressor only attenuated noise by {attenuation:.1} dB"
+    );
+}
@@ -0,0 +1,101 @@
+use device_kit::loudness::NormalizationParams;
+use device_kit::ring::SharedRingBuffer;
+use device_kit::{AudioBuffer, Mixer};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+/// 1 kHz stereo tone; at `level_db` dBFS per channel it measures `level_db` LUFS.
+struct Tone {
+    amplitude: f32,
+    phase: f32,
+}
+
+impl Tone {
+    fn new(level_db: f32) -> Self {
+        Self {
+            amplitude: 10f32.powf(level_db / 20.0),
+            phase: 0.0,
+        }
+    }
+}
+
+fn render_seconds(mixer: &mut Mixer, ring: &SharedRingBuffer, tone: &mut Tone, seconds: f32) {
+    let blocks = (seconds * SAMPLE_RATE as f32) as usize / BLOCK_FRAMES;
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        for frame in input.chunks_exact_mut(2) {
+            frame.fill(tone.amplitude * (std::f32::consts::TAU * tone.phase).sin());
+            tone.phase = (tone.phase + 1_000.0 / SAMPLE_RATE as f32).fract();
+        }
+        ring.push(&input, None);
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+    }
+}
+
+#[test]
+fn quiet_programme_is_raised_to_target() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (_, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.set_loudness_normalization(Some(NormalizationParams {
+        target_lufs: -16.0,
+        max_gain_db: 20.0,
+        max_cut_db: 20.0,
+    }));
+    let mut tone = Tone::new(-28.0);
+
+    // The gain moves slowly: after one second it has only just started.
+    render_seconds(&mut mixer, &ring, &mut tone, 1.0);
+    assert!(mixer.normalization_gain_db() < 2.0);
+
+    render_seconds(&mut mixer, &ring, &mut tone, 20.0);
+    let gain = mixer.normalization_gain_db();
+    assert!((gain - 12.0).abs() < 0.3, "correction gain {gain:.2} dB");
+    let short_term = mixer.master_loudness().short_term;
+    assert!(
+        (short_term + 16.0).abs() < 0.3,
+        "output short-term loudness {short_term:.2} LUFS"
+    );
+}
+
+#[test]
+fn loud_programme_is_cut_and_boost_is_limited() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (_, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.set_loudness_normalization(Some(NormalizationParams::default()));
+    let mut loud = Tone::new(-8.0);
+    render_seconds(&mut mixer, &ring, &mut loud, 8.0);
+    let gain = mixer.normalization_gain_db();
+    assert!((gain + 8.0).abs() < 0.3, "correction gain {gain:.2} dB");
+
+    // Default max gain is 12 dB; a -40 LUFS programme would need 24 dB.
+    let mut quiet = Tone::new(-40.0);
+    render_seconds(&mut mixer, &ring, &mut quiet, 30.0);
+    let gain = mixer.normalization_gain_db();
+    assert!((gain - 12.0).abs() < 0.05, "correction gain {gain:.2} dB");
+}
+
+#[test]
+fn silence_does_not_pump_the_gain_and_disable_returns_to_unity() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (_, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.set_loudness_normalization(Some(NormalizationParams::default()));
+    let mut silence = Tone::new(-120.0);
+    render_seconds(&mut mixer, &ring, &mut silence, 10.0);
+    assert_eq!(mixer.normalization_gain_db(), 0.0);
+
+    let mut tone = Tone::new(-22.0);
+    render_seconds(&mut mixer, &ring, &mut tone, 10.0);
+    assert!(mixer.normalization_gain_db() > 5.0);
+
+    mixer.set_loudness_normalization(None);
+    render_seconds(&mut mixer, &ring, &mut tone, 5.0);
+    assert_eq!(mixer.normalization_gain_db(), 0.0);
+}
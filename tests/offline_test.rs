@@ -0,0 +1,334 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use device_kit::generator::{GeneratorConfig, Waveform};
+use device_kit::offline::{
+    Comparison, OfflineError, OfflineInput, OfflineRenderer, RenderSession, SessionInput,
+    SessionSource, Tolerance, VIRTUAL_CLOCK_START_NS, VirtualClock,
+};
+use device_kit::wav::{SampleFormat, WavSpec, WavWriter, read_wav};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("device_kit_offline_{}_{name}", std::process::id()))
+}
+
+/// A stereo ramp climbing by `step` per frame, the same on both channels.
+fn ramp(frames: usize, step: f32) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|frame| [frame as f32 * step, frame as f32 * step])
+        .collect()
+}
+
+fn session_error(text: &str) -> String {
+    match RenderSession::parse(text, Path::new("")) {
+        Err(OfflineError::Session(message)) => message,
+        other => panic!("expected a session error, got {other:?}"),
+    }
+}
+
+#[test]
+fn virtual_clock_counts_frames() {
+    let mut clock = VirtualClock::new(SAMPLE_RATE);
+    assert_eq!(clock.now_ns(), VIRTUAL_CLOCK_START_NS);
+    clock.advance(SAMPLE_RATE as usize / 2);
+    assert_eq!(clock.frames(), 24_000);
+    assert_eq!(clock.now_ns(), VIRTUAL_CLOCK_START_NS + 500_000_000);
+    assert_eq!(clock.ns_at(1), VIRTUAL_CLOCK_START_NS + 20_833);
+
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    renderer.render(1_000).unwrap();
+    assert_eq!(renderer.clock().frames(), 1_000);
+}
+
+#[test]
+fn renders_are_repeatable() {
+    let render = || {
+        let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+        let pink = GeneratorConfig::new(Waveform::PinkNoise);
+        renderer.add_input(OfflineInput::from_generator(pink, SAMPLE_RATE, 0.5).in_chunks_of(441));
+        renderer.add_input(OfflineInput::from_samples(ramp(9_600, 1e-5)).starting_at_frame(700));
+        renderer.render(SAMPLE_RATE as usize).unwrap()
+    };
+    let first = render();
+    assert!(first.iter().any(|&sample| sample != 0.0));
+    assert_eq!(first, render());
+}
+
+#[test]
+fn inputs_start_at_their_scheduled_frame() {
+    let start = 1_000;
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let input = OfflineInput::from_samples(vec![0.5; 2_000 * 2]).starting_at_frame(start);
+    assert_eq!(input.end_frame(), 3_000);
+    renderer.add_input(input);
+    assert_eq!(renderer.inputs_end_frame(), 3_000);
+
+    let left: Vec<f32> = renderer
+        .render(4_000)
+        .unwrap()
+        .into_iter()
+        .step_by(2)
+        .collect();
+    // The interpolator holds each source one frame behind, and keeps the final frame back until
+    // a successor arrives.
+    assert!(left[..=start as usize].iter().all(|&sample| sample == 0.0));
+    assert!(
+        left[start as usize + 1..3_000]
+            .iter()
+            .all(|&sample| sample == 0.5)
+    );
+    assert!(left[3_000..].iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn chunked_producers_match_block_fed_ones() {
+    let input = ramp(10_000, 1e-5);
+    let render = |chunk_frames: Option<usize>| {
+        let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+        let mut input = OfflineInput::from_samples(input.clone()).starting_at_frame(123);
+        if let Some(frames) = chunk_frames {
+            input = input.in_chunks_of(frames);
+        }
+        renderer.add_input(input);
+        renderer.render(12_000).unwrap()
+    };
+    let block_fed = render(None);
+    for chunk_frames in [1, 64, 441, 2_048] {
+        assert_eq!(
+            render(Some(chunk_frames)),
+            block_fed,
+            "chunks of {chunk_frames}"
+        );
+    }
+}
+
+#[test]
+fn writes_the_render_to_wav() {
+    let render = |renderer: &mut OfflineRenderer| {
+        let config = GeneratorConfig::reference();
+        renderer.add_input(OfflineInput::from_generator(config, SAMPLE_RATE, 0.2));
+    };
+    let mut expected = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    render(&mut expected);
+    let expected = expected.render(12_345).unwrap();
+
+    let path = temp_path("render.wav");
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    render(&mut renderer);
+    renderer
+        .render_to_wav(&path, 12_345, SampleFormat::Float32)
+        .unwrap();
+    let (spec, samples) = read_wav(File::open(&path).unwrap()).unwrap();
+    assert_eq!(spec.sample_rate, SAMPLE_RATE);
+    assert_eq!(spec.channels, 2);
+    assert_eq!(samples, expected);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn parses_sessions() {
+    let text = r#"
+        # Reproduces a clipped sweep.
+        [session]
+        sample_rate = 44_100
+        block_frames = 256
+        duration = 1.5
+
+        [[source]]
+        file = "take #1.wav"   # comments may follow values
+        start = 0.25
+        gain_db = -6
+        latency_frames = -32
+        chunk_frames = 441
+
+        [[source]]
+        generator = "sweep"
+        frequency = 100
+        end_frequency = 1e3
+        period = 0.5
+        level = -12.0
+        muted = true
+    "#;
+    let session = RenderSession::parse(text, Path::new("bugs")).unwrap();
+    assert_eq!(session.sample_rate, 44_100);
+    assert_eq!(session.block_frames, 256);
+    assert_eq!(session.duration_seconds, Some(1.5));
+    assert_eq!(
+        session.sources[0],
+        SessionSource {
+            input: SessionInput::File(Path::new("bugs").join("take #1.wav")),
+            start_seconds: 0.25,
+            chunk_frames: Some(441),
+            gain_db: -6.0,
+            muted: false,
+            latency_frames: -32,
+        }
+    );
+    let mut sweep = GeneratorConfig::new(Waveform::Sweep {
+        start_hz: 100.0,
+        end_hz: 1_000.0,
+        duration_seconds: 0.5,
+    });
+    sweep.level_dbfs = -12.0;
+    assert_eq!(
+        session.sources[1].input,
+        SessionInput::Generator {
+            config: sweep,
+            length_seconds: None,
+        }
+    );
+    assert!(session.sources[1].muted);
+}
+
+#[test]
+fn session_errors_name_the_line() {
+    let cases = [
+        ("sample_rate = 48000", "line 1: unknown field `sample_rate`"),
+        (
+            "[session]\nsample_rate = 0",
+            "line 2: sample_rate must be an integer",
+        ),
+        (
+            "[session]\nduration = \"long\"",
+            "line 2: invalid type: string \"long\"",
+        ),
+        (
+            "[session]\nduration = 1e300",
+            "line 2: duration must be at most 3600 seconds",
+        ),
+        (
+            "[[source]]\ngenerator = \"sine\"\nlength = 7200",
+            "line 3: length must be at most 3600 seconds",
+        ),
+        (
+            "[[source]]\nfile = \"a.wav\"\nstart = 1e20",
+            "line 3: start must be at most 3600 seconds",
+        ),
+        ("[session]\nbogus = 1", "line 2: unknown field `bogus`"),
+        ("[output]", "line 1: unknown field `output`"),
+        (
+            "[session]\n[session]",
+            "line 2: invalid table header; duplicate key `session`",
+        ),
+        (
+            "[[source]]\nfile = \"a.wav\"\nfile = \"b.wav\"",
+            "line 3: duplicate key `file`",
+        ),
+        (
+            "[[source]]\ngain_db = -3",
+            "line 1: a source needs exactly one of file or generator",
+        ),
+        (
+            "[[source]]\nfile = \"a.wav\"\ngenerator = \"sine\"",
+            "line 1: a source needs exactly one of file or generator",
+        ),
+        (
+            "[[source]]\ngenerator = \"square\"\nlength = 1",
+            "line 2: unknown generator \"square\"",
+        ),
+        (
+            "[[source]]\ngenerator = \"sine\"\nlevel = 3\nlength = 1",
+            "line 2: invalid generator",
+        ),
+        (
+            "[[source]]\ngenerator = \"sine\"",
+            "line 1: a generator needs a length",
+        ),
+        (
+            "[[source]]\nfile = \"a.wav\nstart = 1",
+            "line 2: invalid basic string",
+        ),
+        ("[[source]]\nfile = a.wav", "line 2: invalid string"),
+        (
+            "[[source]]\nstart = inf",
+            "line 2: start must be a finite number",
+        ),
+        (
+            "[[source]\nfile = \"a.wav\"",
+            "line 1: invalid table header",
+        ),
+    ];
+    for (text, expected) in cases {
+        let message = session_error(text);
+        assert!(message.starts_with(expected), "{text:?}: {message}");
+    }
+}
+
+#[test]
+fn sessions_render_files_and_generators() {
+    let dir = temp_path("session");
+    fs::create_dir_all(&dir).unwrap();
+    let clip = ramp(4_800, 1e-4);
+    let spec = WavSpec {
+        sample_rate: SAMPLE_RATE,
+        channels: 2,
+        format: SampleFormat::Float32,
+    };
+    let mut writer = WavWriter::new(File::create(dir.join("clip.wav")).unwrap(), spec).unwrap();
+    writer.write_samples(&clip).unwrap();
+    writer.finish().unwrap();
+
+    let session_path = dir.join("repro.toml");
+    fs::write(
+        &session_path,
+        "[session]\nblock_frames = 480\n\n[[source]]\nfile = \"clip.wav\"\nstart = 0.1\ngain_db = -6\nlatency_frames = 16\n\n[[source]]\ngenerator = \"reference\"\nlength = 0.05\nmuted = true\n",
+    )
+    .unwrap();
+    let session = RenderSession::load(&session_path).unwrap();
+
+    let mut renderer = OfflineRenderer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = renderer.add_input(OfflineInput::from_samples(clip).starting_at_frame(4_800));
+    renderer
+        .mixer_mut()
+        .set_gain(handle, 10f32.powf(-6.0 / 20.0))
+        .unwrap();
+    renderer.mixer_mut().set_latency(handle, 16).unwrap();
+    // The clip ends at frame 9 600; the latency and one spare block follow it.
+    let expected = renderer.render(9_600 + 16 + BLOCK_FRAMES).unwrap();
+    assert_eq!(session.render().unwrap(), expected);
+
+    let output = dir.join("out.wav");
+    session.render_to_wav(&output, SampleFormat::Pcm24).unwrap();
+    let (spec, samples) = read_wav(File::open(&output).unwrap()).unwrap();
+    assert_eq!(spec.format, SampleFormat::Pcm24);
+    assert_eq!(samples.len(), expected.len());
+
+    fs::remove_file(dir.join("clip.wav")).unwrap();
+    fs::write(&session_path, "[[source]]\nfile = \"clip.wav\"\n").unwrap();
+    let missing = RenderSession::load(&session_path).unwrap().render();
+    assert!(
+        matches!(&missing, Err(OfflineError::Io(err)) if err.to_string().contains("clip.wav")),
+        "{missing:?}"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn comparisons_measure_error_snr_and_correlation() {
+    let reference: Vec<f32> = (0..4_800).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let identical = Comparison::between(&reference, &reference);
+    assert_eq!(identical.max_abs_error, 0.0);
+    assert_eq!(identical.snr_db, f32::INFINITY);
+    assert!((identical.correlation - 1.0).abs() < 1e-6);
+    assert!(identical.is_within(&Tolerance::strict()));
+
+    // A 1% gain error leaves the correlation alone but sits 40 dB down.
+    let louder: Vec<f32> = reference.iter().map(|s| s * 1.01).collect();
+    let scaled = Comparison::between(&reference, &louder);
+    assert!((scaled.snr_db - 40.0).abs() < 0.01, "{scaled}");
+    assert!((scaled.max_abs_error - 0.005).abs() < 1e-4);
+    assert!((scaled.correlation - 1.0).abs() < 1e-6);
+    assert!(!scaled.is_within(&Tolerance::strict()));
+
+    let inverted: Vec<f32> = reference.iter().map(|s| -s).collect();
+    assert!((Comparison::between(&reference, &inverted).correlation + 1.0).abs() < 1e-6);
+
+    // Missing samples count as silence.
+    let truncated = Comparison::between(&reference, &reference[..2_400]);
+    assert!(truncated.max_abs_error > 0.4);
+    assert_eq!(Comparison::between(&[], &[]).correlation, 1.0);
+    assert_eq!(Comparison::between(&[0.0; 4], &[0.1; 4]).correlation, 0.0);
+}
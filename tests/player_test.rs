@@ -0,0 +1,260 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use device_kit::events::MixerEventKind;
+use device_kit::player::{PlayerError, PlayerStatus};
+use device_kit::wav::{SampleFormat, WavSpec, WavWriter};
+use device_kit::{AudioBuffer, Mixer, MixerError, SourceHandle};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+/// Write `samples` to a float WAV file in the temp directory.
+fn write_file(name: &str, sample_rate: u32, channels: u16, samples: &[f32]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "device_kit_player_{}_{name}.wav",
+        std::process::id()
+    ));
+    let spec = WavSpec {
+        sample_rate,
+        channels,
+        format: SampleFormat::Float32,
+    };
+    let mut writer = WavWriter::new(File::create(&path).unwrap(), spec).unwrap();
+    writer.write_samples(samples).unwrap();
+    writer.finish().unwrap();
+    path
+}
+
+/// A stereo ramp that never repeats a value, so misplaced frames are easy to spot.
+fn stereo_ramp(frames: usize) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|i| {
+            let value = i as f32 / frames as f32;
+            [value, -value]
+        })
+        .collect()
+}
+
+fn render(mixer: &mut Mixer, blocks: usize) -> Vec<f32> {
+    let mut rendered = Vec::with_capacity(blocks * BLOCK_FRAMES * 2);
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+        rendered.extend_from_slice(&output);
+    }
+    rendered
+}
+
+/// Wait for the decoder thread to queue at least `seconds` of audio.
+fn wait_for_buffer(mixer: &Mixer, handle: SourceHandle, seconds: f64) -> PlayerStatus {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let status = mixer.file_status(handle).unwrap();
+        if status.buffered_seconds >= seconds - 1e-9 {
+            return status;
+        }
+        assert!(Instant::now() < deadline, "decoder stalled: {status:?}");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn assert_no_underruns(mixer: &Mixer) {
+    while let Some(event) = mixer.pop_event() {
+        assert!(
+            !matches!(event.kind, MixerEventKind::SourceUnderrun { .. }),
+            "unexpected {event:?}"
+        );
+    }
+}
+
+#[test]
+fn plays_a_file_to_the_end_without_underruns() {
+    let samples = stereo_ramp(4_800);
+    let path = write_file("to_end", SAMPLE_RATE, 2, &samples);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = mixer.add_file_source(&path).unwrap();
+
+    let status = wait_for_buffer(&mixer, handle, 0.1);
+    assert_eq!(status.path, path);
+    assert_eq!(status.file_sample_rate, SAMPLE_RATE);
+    assert_eq!(status.file_channels, 2);
+    assert_eq!(status.duration_seconds, 0.1);
+    assert_eq!(status.position_seconds, 0.0);
+    assert!(!status.playing && !status.finished);
+    // Loaded files start paused and keep their queue.
+    assert!(render(&mut mixer, 2).iter().all(|&sample| sample == 0.0));
+
+    mixer.set_file_playing(handle, true).unwrap();
+    let rendered = render(&mut mixer, 12);
+    // The interpolator starts from the previous (silent) frame, so the file lags by one frame.
+    assert_eq!(rendered[..2], [0.0, 0.0]);
+    assert_eq!(rendered[2..samples.len()], samples[..samples.len() - 2]);
+    assert!(
+        rendered[samples.len() + 2..]
+            .iter()
+            .all(|&sample| sample == 0.0)
+    );
+
+    let status = mixer.file_status(handle).unwrap();
+    assert!(status.finished && !status.playing, "{status:?}");
+    assert_no_underruns(&mixer);
+
+    mixer.remove_source(handle).unwrap();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn pause_holds_the_position_and_seek_moves_it() {
+    let samples = stereo_ramp(48_000);
+    let path = write_file("seek", SAMPLE_RATE, 2, &samples);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = mixer.add_file_source(&path).unwrap();
+    wait_for_buffer(&mixer, handle, 0.1);
+
+    mixer.set_file_playing(handle, true).unwrap();
+    render(&mut mixer, 5);
+    mixer.set_file_playing(handle, false).unwrap();
+    assert!(render(&mut mixer, 3).iter().all(|&sample| sample == 0.0));
+    let status = mixer.file_status(handle).unwrap();
+    assert_eq!(status.position_seconds, 0.05);
+    assert!(!status.playing);
+
+    mixer.set_file_playing(handle, true).unwrap();
+    let resumed = render(&mut mixer, 1);
+    assert_eq!(resumed, samples[2_399 * 2..2_879 * 2]);
+
+    mixer.seek_file(handle, 0.5).unwrap();
+    assert_eq!(mixer.file_status(handle).unwrap().position_seconds, 0.5);
+    // Blocks are silent until the render thread has dropped the stale queue and the decoder
+    // starts refilling from the new position.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while mixer.file_status(handle).unwrap().buffered_seconds == 0.0 {
+        assert!(Instant::now() < deadline);
+        assert!(render(&mut mixer, 1).iter().all(|&sample| sample == 0.0));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    wait_for_buffer(&mixer, handle, 0.1);
+    let after_seek = render(&mut mixer, 1);
+    assert_eq!(after_seek[2..], samples[24_000 * 2..24_479 * 2]);
+    assert_eq!(mixer.file_status(handle).unwrap().position_seconds, 0.51);
+
+    // Seeking past the end clamps to it and finishes the file.
+    mixer.seek_file(handle, 10.0).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !mixer.file_status(handle).unwrap().finished {
+        assert!(Instant::now() < deadline);
+        render(&mut mixer, 1);
+    }
+    assert_eq!(mixer.file_status(handle).unwrap().position_seconds, 1.0);
+    assert_no_underruns(&mixer);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn loops_until_looping_is_cleared_and_restarts_when_finished() {
+    let samples = stereo_ramp(1_000);
+    let path = write_file("loop", SAMPLE_RATE, 2, &samples);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = mixer.add_file_source(&path).unwrap();
+    mixer.set_file_looping(handle, true).unwrap();
+    wait_for_buffer(&mixer, handle, 0.1);
+
+    mixer.set_file_playing(handle, true).unwrap();
+    let rendered = render(&mut mixer, 10);
+    for (frame, pair) in rendered.chunks_exact(2).enumerate().skip(1) {
+        let expected = (frame - 1) % 1_000;
+        assert_eq!(
+            pair,
+            &samples[expected * 2..expected * 2 + 2],
+            "frame {frame}"
+        );
+    }
+    assert!(mixer.file_status(handle).unwrap().looping);
+
+    mixer.set_file_looping(handle, false).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !mixer.file_status(handle).unwrap().finished {
+        assert!(Instant::now() < deadline);
+        render(&mut mixer, 1);
+    }
+    assert_no_underruns(&mixer);
+
+    // Playing a finished file starts it over.
+    mixer.set_file_playing(handle, true).unwrap();
+    let status = mixer.file_status(handle).unwrap();
+    assert!(status.playing && !status.finished);
+    assert_eq!(status.position_seconds, 0.0);
+    wait_for_buffer(&mixer, handle, 0.02);
+    let restarted = render(&mut mixer, 1);
+    assert_eq!(restarted[2..], samples[..(BLOCK_FRAMES - 1) * 2]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resamples_and_upmixes_mono_files() {
+    let frames = 2_400;
+    let samples: Vec<f32> = (0..frames).map(|i| i as f32 / frames as f32).collect();
+    let path = write_file("mono", SAMPLE_RATE / 2, 1, &samples);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let handle = mixer.add_file_source(&path).unwrap();
+
+    let status = wait_for_buffer(&mixer, handle, 0.09);
+    assert_eq!(status.file_sample_rate, SAMPLE_RATE / 2);
+    assert_eq!(status.file_channels, 1);
+    assert_eq!(status.duration_seconds, 0.1);
+
+    mixer.set_file_playing(handle, true).unwrap();
+    let rendered = render(&mut mixer, 9);
+    for (frame, pair) in rendered.chunks_exact(2).enumerate().skip(2) {
+        assert_eq!(pair[0], pair[1]);
+        // Halfway points between file frames are interpolated.
+        let expected = (frame - 1) as f32 / 2.0 / frames as f32;
+        assert!(
+            (pair[0] - expected).abs() < 1e-3,
+            "frame {frame}: {} vs {expected}",
+            pair[0]
+        );
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_unreadable_files_and_transport_on_other_sources() {
+    let path =
+        std::env::temp_dir().join(format!("device_kit_player_{}_text.wav", std::process::id()));
+    fs::write(&path, b"definitely not audio").unwrap();
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    assert!(matches!(
+        mixer.add_file_source(&path),
+        Err(PlayerError::Io(_))
+    ));
+    assert!(matches!(
+        mixer.add_file_source(path.with_extension("missing")),
+        Err(PlayerError::Io(_))
+    ));
+    fs::remove_file(&path).unwrap();
+
+    let (source, _ring) = mixer.add_source(BLOCK_FRAMES * 4);
+    assert!(matches!(
+        mixer.set_file_playing(source, true),
+        Err(MixerError::NotAFilePlayer(id)) if id == source.id()
+    ));
+    assert!(matches!(
+        mixer.file_status(source),
+        Err(MixerError::NotAFilePlayer(_))
+    ));
+
+    mixer.remove_source(source).unwrap();
+    assert!(matches!(
+        mixer.remove_source(source),
+        Err(MixerError::UnknownSource(_))
+    ));
+}
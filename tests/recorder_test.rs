@@ -0,0 +1,294 @@
+use std::f32::consts::TAU;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use device_kit::aec::EchoReference;
+use device_kit::recorder::{
+    MANIFEST_FILE_NAME, MASTER_FILE_NAME, MultitrackStatus, RecorderError, RecordingStatus,
+    StemStatus, stem_file_name,
+};
+use device_kit::wav::{SampleFormat, WavSpec, read_wav};
+use device_kit::{AudioBuffer, Mixer, MixerError};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("device_kit_{}_{name}.wav", std::process::id()))
+}
+
+/// Render `blocks` blocks of a 440 Hz sine on the left and a quieter 660 Hz sine on the right,
+/// returning everything the mixer output.
+fn render(mixer: &mut Mixer, blocks: usize) -> Vec<f32> {
+    let (_, ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let mut rendered = Vec::with_capacity(blocks * BLOCK_FRAMES * 2);
+    let mut input = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut n = 0usize;
+    for _ in 0..blocks {
+        for frame in input.chunks_exact_mut(2) {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            frame[0] = 0.5 * (TAU * 440.0 * t).sin();
+            frame[1] = 0.25 * (TAU * 660.0 * t).sin();
+            n += 1;
+        }
+        ring.push(&input, None);
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+        rendered.extend_from_slice(&output);
+    }
+    rendered
+}
+
+fn record_and_read(format: SampleFormat, name: &str) -> (Vec<f32>, WavSpec, Vec<f32>) {
+    let path = temp_path(name);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    mixer.start_recording(&path, format).unwrap();
+    let rendered = render(&mut mixer, 100);
+    let status = mixer.stop_recording().unwrap();
+    assert_eq!(status.path, path);
+    assert_eq!(status.format, format);
+    assert_eq!(status.frames, 100 * BLOCK_FRAMES as u64);
+    assert_eq!(status.dropped_frames, 0);
+    assert!(status.start_timestamp_ns > 0);
+
+    let (spec, samples) = read_wav(File::open(&path).unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    (rendered, spec, samples)
+}
+
+#[test]
+fn records_master_as_float() {
+    let (rendered, spec, samples) = record_and_read(SampleFormat::Float32, "f32");
+    assert_eq!(
+        spec,
+        WavSpec {
+            sample_rate: SAMPLE_RATE,
+            channels: 2,
+            format: SampleFormat::Float32,
+        }
+    );
+    assert_eq!(samples, rendered);
+}
+
+#[test]
+fn records_master_as_24_bit() {
+    let (rendered, spec, samples) = record_and_read(SampleFormat::Pcm24, "pcm24");
+    assert_eq!(spec.format, SampleFormat::Pcm24);
+    assert_eq!(samples.len(), rendered.len());
+    for (recorded, expected) in samples.iter().zip(&rendered) {
+        assert!((recorded - expected).abs() < 2.0 / 8_388_608.0);
+    }
+}
+
+#[test]
+fn rejects_overlapping_and_missing_recordings() {
+    let path = temp_path("overlap");
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    assert!(mixer.recording_status().is_none());
+    assert!(matches!(
+        mixer.stop_recording(),
+        Err(RecorderError::NotRecording)
+    ));
+    mixer.start_recording(&path, SampleFormat::Float32).unwrap();
+    assert_eq!(mixer.recording_status().unwrap().frames, 0);
+    assert!(matches!(
+        mixer.start_recording(&path, SampleFormat::Float32),
+        Err(RecorderError::AlreadyRecording)
+    ));
+    assert!(matches!(
+        Mixer::new(SAMPLE_RATE, BLOCK_FRAMES).start_recording(&path, SampleFormat::Pcm16),
+        Err(RecorderError::UnsupportedFormat(SampleFormat::Pcm16))
+    ));
+
+    // An empty recording is still a valid file.
+    mixer.stop_recording().unwrap();
+    let (_, samples) = read_wav(File::open(&path).unwrap()).unwrap();
+    assert!(samples.is_empty());
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        mixer.start_recording(
+            std::env::temp_dir().join("missing/dir/out.wav"),
+            SampleFormat::Float32
+        ),
+        Err(RecorderError::Io(_))
+    ));
+}
+
+#[test]
+fn stopping_waits_for_room_to_hand_the_render_thread_its_change() {
+    let path = temp_path("backlog");
+    let directory = std::env::temp_dir().join(format!("device_kit_{}_backlog", std::process::id()));
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (source, _ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.start_recording(&path, SampleFormat::Float32).unwrap();
+    mixer
+        .start_multitrack(&directory, SampleFormat::Float32)
+        .unwrap();
+    while mixer
+        .enable_echo_cancellation(source, EchoReference::Master, 32.0)
+        .is_ok()
+    {}
+
+    // Recording keeps running until the render thread can be told to let go of its taps.
+    assert!(matches!(
+        mixer.stop_recording(),
+        Err(RecorderError::Mixer(MixerError::RenderBacklog))
+    ));
+    assert!(matches!(
+        mixer.stop_multitrack(),
+        Err(RecorderError::Mixer(MixerError::RenderBacklog))
+    ));
+    assert!(mixer.recording_status().is_some());
+    assert!(mixer.multitrack_status().is_some());
+    render(&mut mixer, 10);
+    let frames = 10 * BLOCK_FRAMES as u64;
+    assert_eq!(mixer.stop_recording().unwrap().frames, frames);
+    let status = mixer.stop_multitrack().unwrap();
+    assert_eq!(status.master.frames, frames);
+    assert_eq!(status.stems[0].recording.frames, frames);
+    fs::remove_file(&path).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn records_aligned_stems_and_a_manifest() {
+    let directory = std::env::temp_dir().join(format!("device_kit_{}_stems", std::process::id()));
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let (quiet, quiet_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    let (loud, loud_ring) = mixer.add_source(BLOCK_FRAMES * 8);
+    mixer.set_gain(quiet, 0.5).unwrap();
+    mixer
+        .start_multitrack(&directory, SampleFormat::Float32)
+        .unwrap();
+    assert!(matches!(
+        mixer.start_multitrack(&directory, SampleFormat::Float32),
+        Err(RecorderError::AlreadyRecording)
+    ));
+    // Added after the session started, so it has no stem.
+    let (_late, _) = mixer.add_source(BLOCK_FRAMES * 8);
+
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    let mut n = 0usize;
+    for block in 0..50 {
+        let mut quiet_input = vec![0.0f32; BLOCK_FRAMES * 2];
+        let mut loud_input = vec![0.0f32; BLOCK_FRAMES * 2];
+        for (q, l) in quiet_input
+            .chunks_exact_mut(2)
+            .zip(loud_input.chunks_exact_mut(2))
+        {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            q.fill(0.5 * (TAU * 440.0 * t).sin());
+            l.copy_from_slice(&[0.25 * (TAU * 550.0 * t).sin(), 0.0]);
+            n += 1;
+        }
+        quiet_ring.push(&quiet_input, None);
+        // The loud source starves for a while; its stem must still keep pace with the master.
+        if !(10..20).contains(&block) {
+            loud_ring.push(&loud_input, None);
+        }
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 1_000 + block as u64,
+        };
+        mixer.process(&mut buffer).unwrap();
+    }
+    let live = mixer.multitrack_status().unwrap();
+    assert_eq!(live.stems.len(), 2);
+
+    let status = mixer.stop_multitrack().unwrap();
+    assert!(mixer.multitrack_status().is_none());
+    assert!(matches!(
+        mixer.stop_multitrack(),
+        Err(RecorderError::NotRecording)
+    ));
+    let frames = 50 * BLOCK_FRAMES as u64;
+    assert_eq!(status.master.frames, frames);
+    assert_eq!(status.master.start_timestamp_ns, 1_000);
+    assert_eq!(status.stems[0].source_id, quiet.id());
+    assert_eq!(status.stems[0].gain_linear, 0.5);
+    assert_eq!(status.stems[1].source_id, loud.id());
+    for stem in &status.stems {
+        assert_eq!(stem.recording.frames, frames);
+        assert_eq!(stem.recording.start_timestamp_ns, 1_000);
+    }
+
+    let read = |name: String| {
+        read_wav(File::open(directory.join(name)).unwrap())
+            .unwrap()
+            .1
+    };
+    let master = read(MASTER_FILE_NAME.to_string());
+    let quiet_stem = read(stem_file_name(quiet.id()));
+    let loud_stem = read(stem_file_name(loud.id()));
+    assert_eq!(master.len(), quiet_stem.len());
+    assert_eq!(master.len(), loud_stem.len());
+    // Stems are the post-fader contributions, so they add up to the master exactly.
+    for ((m, q), l) in master.iter().zip(&quiet_stem).zip(&loud_stem) {
+        assert_eq!(*m, q + l);
+    }
+    assert!(quiet_stem.iter().any(|&s| s != 0.0));
+    assert!(quiet_stem.iter().all(|s| s.abs() <= 0.25));
+    assert!(loud_stem.chunks_exact(2).all(|frame| frame[1] == 0.0));
+
+    let manifest = fs::read_to_string(directory.join(MANIFEST_FILE_NAME)).unwrap();
+    assert_eq!(manifest, status.manifest_json());
+    assert!(manifest.contains("\"sample_rate\": 48000"));
+    assert!(manifest.contains("\"format\": \"f32\""));
+    assert!(manifest.contains(&format!(
+        "\"id\": {}, \"name\": \"Source #{}\", \"gain_linear\": 0.5",
+        quiet.id(),
+        quiet.id()
+    )));
+    assert!(manifest.contains(&format!("\"file\": \"{}\"", stem_file_name(loud.id()))));
+    assert!(manifest.contains("\"start_timestamp_ns\": 1000"));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn manifest_escapes_names_and_omits_infinite_gain() {
+    let track = RecordingStatus {
+        path: PathBuf::from("session/source-3.wav"),
+        format: SampleFormat::Pcm24,
+        frames: 10,
+        dropped_frames: 0,
+        start_timestamp_ns: 5,
+    };
+    let status = MultitrackStatus {
+        directory: PathBuf::from("session"),
+        sample_rate: 44_100,
+        format: SampleFormat::Pcm24,
+        master: RecordingStatus {
+            path: PathBuf::from("session").join(MASTER_FILE_NAME),
+            ..track.clone()
+        },
+        stems: vec![StemStatus {
+            source_id: 3,
+            name: "Desk \"A\"\\\n".to_string(),
+            gain_linear: 0.0,
+            muted: true,
+            recording: track,
+        }],
+    };
+    assert_eq!(
+        status.manifest_json(),
+        concat!(
+            "{\n",
+            "  \"sample_rate\": 44100,\n",
+            "  \"format\": \"pcm24\",\n",
+            "  \"master\": {\"file\": \"master.wav\", \"frames\": 10, \"dropped_frames\": 0, \"start_timestamp_ns\": 5},\n",
+            "  \"sources\": [\n",
+            "    {\"id\": 3, \"name\": \"Desk \\\"A\\\"\\\\\\n\", \"gain_linear\": 0, \"gain_db\": null, \"muted\": true, \"file\": \"source-3.wav\", \"frames\": 10, \"dropped_frames\": 0, \"start_timestamp_ns\": 5}\n",
+            "  ]\n",
+            "}\n",
+        )
+    );
+}
@@ -0,0 +1,102 @@
+use std::io::ErrorKind;
+
+use device_kit::ring::SharedRingBuffer;
+use memmap2::{MmapMut, MmapOptions};
+
+/// Byte offsets of the `RingBufferHeader` fields as a C peer lays them out.
+const CAPACITY_OFFSET: usize = 0;
+const CHANNELS_OFFSET: usize = 4;
+const WRITE_INDEX_OFFSET: usize = 16;
+const READ_INDEX_OFFSET: usize = 24;
+const HEADER_BYTES: usize = 64;
+
+/// A mapping laid out the way a peer process would hand it over.
+fn peer_mapping(capacity_frames: u32, channels: u32, data_bytes: usize) -> MmapMut {
+    let mut mmap = MmapOptions::new()
+        .len(HEADER_BYTES + data_bytes)
+        .map_anon()
+        .unwrap();
+    mmap[CAPACITY_OFFSET..CAPACITY_OFFSET + 4].copy_from_slice(&capacity_frames.to_ne_bytes());
+    mmap[CHANNELS_OFFSET..CHANNELS_OFFSET + 4].copy_from_slice(&channels.to_ne_bytes());
+    mmap
+}
+
+fn set_indices(mmap: &mut MmapMut, write_index: u64, read_index: u64) {
+    mmap[WRITE_INDEX_OFFSET..WRITE_INDEX_OFFSET + 8].copy_from_slice(&write_index.to_ne_bytes());
+    mmap[READ_INDEX_OFFSET..READ_INDEX_OFFSET + 8].copy_from_slice(&read_index.to_ne_bytes());
+}
+
+fn open_error(mmap: MmapMut, channels: usize) -> ErrorKind {
+    match SharedRingBuffer::from_mmap(mmap, channels) {
+        Ok(_) => panic!("mapping should have been rejected"),
+        Err(err) => err.kind(),
+    }
+}
+
+#[test]
+fn peer_mappings_round_trip() {
+    let mut mmap = peer_mapping(8, 2, 8 * 2 * 4);
+    set_indices(&mut mmap, 6, 3);
+    let ring = SharedRingBuffer::from_mmap(mmap, 2).unwrap();
+    assert_eq!(ring.capacity_frames(), 8);
+    assert_eq!(ring.available_read(), 3);
+    assert_eq!(ring.push(&[1.0; 12], Some(5)), 5);
+    let mut out = [0.0; 16];
+    assert_eq!(ring.pop(&mut out), 8);
+    assert_eq!(&out[6..], &[1.0; 10]);
+}
+
+#[test]
+fn malformed_peer_headers_are_rejected() {
+    let short = MmapOptions::new().len(HEADER_BYTES - 1).map_anon().unwrap();
+    assert_eq!(open_error(short, 2), ErrorKind::InvalidData);
+    assert_eq!(
+        open_error(peer_mapping(8, 1, 64), 2),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(
+        open_error(peer_mapping(8, 0, 64), 0),
+        ErrorKind::InvalidData
+    );
+    // The header claims more frames than the mapping holds.
+    assert_eq!(
+        open_error(peer_mapping(9, 2, 64), 2),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(
+        open_error(peer_mapping(u32::MAX, 2, 64), 2),
+        ErrorKind::InvalidData
+    );
+
+    let mut behind = peer_mapping(8, 2, 64);
+    set_indices(&mut behind, 3, 4);
+    assert_eq!(open_error(behind, 2), ErrorKind::InvalidData);
+    let mut overfull = peer_mapping(8, 2, 64);
+    set_indices(&mut overfull, 9, 0);
+    assert_eq!(open_error(overfull, 2), ErrorKind::InvalidData);
+}
+
+#[test]
+fn zero_channel_rings_move_nothing() {
+    let ring = SharedRingBuffer::new_local(16, 0);
+    assert_eq!(ring.push(&[1.0; 8], None), 0);
+    assert_eq!(ring.pop(&mut [0.0; 8]), 0);
+    assert_eq!(ring.available_read(), 0);
+}
+
+#[test]
+fn indices_near_the_end_of_their_range_do_not_overflow() {
+    let mut mmap = peer_mapping(4, 1, 16);
+    set_indices(&mut mmap, u64::MAX - 1, u64::MAX - 2);
+    let ring = SharedRingBuffer::from_mmap(mmap, 1).unwrap();
+    assert_eq!(ring.available_read(), 1);
+    // The write index wraps past `u64::MAX` here.
+    assert_eq!(ring.push(&[1.0; 2], Some(1)), 2);
+    assert_eq!(ring.available_read(), 3);
+    assert_eq!(ring.discard(1), 1);
+    let mut out = [0.0; 4];
+    assert_eq!(ring.pop(&mut out), 2);
+    assert_eq!(out[..2], [1.0, 1.0]);
+    assert_eq!(ring.available_read(), 0);
+    assert_eq!(ring.discard(4), 0);
+}
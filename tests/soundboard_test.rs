@@ -0,0 +1,279 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use device_kit::soundboard::{
+    ClipConfig, MAX_CLIP_SECONDS, MAX_VOICES, RELEASE_MS, SoundboardError,
+};
+use device_kit::wav::{SampleFormat, WavSpec, WavWriter};
+use device_kit::{AudioBuffer, Mixer, MixerError, SourceHandle};
+
+const SAMPLE_RATE: u32 = 48_000;
+const BLOCK_FRAMES: usize = 480;
+const RELEASE_FRAMES: usize = (SAMPLE_RATE * RELEASE_MS / 1_000) as usize;
+
+/// Write `samples` to a float WAV file in the temp directory.
+fn write_clip(name: &str, sample_rate: u32, channels: u16, samples: &[f32]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "device_kit_soundboard_{}_{name}.wav",
+        std::process::id()
+    ));
+    let spec = WavSpec {
+        sample_rate,
+        channels,
+        format: SampleFormat::Float32,
+    };
+    let mut writer = WavWriter::new(File::create(&path).unwrap(), spec).unwrap();
+    writer.write_samples(samples).unwrap();
+    writer.finish().unwrap();
+    path
+}
+
+/// A stereo clip holding `value` on the left and `-value` on the right.
+fn constant_clip(name: &str, frames: usize, value: f32) -> PathBuf {
+    let samples: Vec<f32> = (0..frames).flat_map(|_| [value, -value]).collect();
+    write_clip(name, SAMPLE_RATE, 2, &samples)
+}
+
+fn render(mixer: &mut Mixer, blocks: usize) -> Vec<f32> {
+    let mut rendered = Vec::with_capacity(blocks * BLOCK_FRAMES * 2);
+    let mut output = vec![0.0f32; BLOCK_FRAMES * 2];
+    for _ in 0..blocks {
+        let mut buffer = AudioBuffer {
+            data: output.as_mut_ptr(),
+            frames: BLOCK_FRAMES as u32,
+            channels: 2,
+            timestamp_ns: 0,
+        };
+        mixer.process(&mut buffer).unwrap();
+        rendered.extend_from_slice(&output);
+    }
+    rendered
+}
+
+fn left(rendered: &[f32]) -> Vec<f32> {
+    rendered.iter().step_by(2).copied().collect()
+}
+
+fn voices(mixer: &Mixer, board: SourceHandle, clip_id: u32) -> u32 {
+    mixer
+        .clips(board)
+        .unwrap()
+        .into_iter()
+        .find(|clip| clip.id == clip_id)
+        .unwrap()
+        .voices
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} vs {expected}");
+}
+
+#[test]
+fn triggered_clips_play_once_from_the_next_block() {
+    let path = constant_clip("once", 1_000, 0.25);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    mixer
+        .load_clip(board, 1, &path, ClipConfig::default())
+        .unwrap();
+
+    let status = &mixer.clips(board).unwrap()[0];
+    assert_eq!(status.path, path);
+    assert_eq!(status.duration_seconds, 1_000.0 / SAMPLE_RATE as f64);
+    assert!(render(&mut mixer, 1).iter().all(|&sample| sample == 0.0));
+
+    mixer.trigger_clip(board, 1).unwrap();
+    let rendered = render(&mut mixer, 3);
+    assert!(
+        rendered[..2_000]
+            .chunks_exact(2)
+            .all(|frame| frame == [0.25, -0.25])
+    );
+    assert!(rendered[2_000..].iter().all(|&sample| sample == 0.0));
+    assert_eq!(voices(&mixer, board, 1), 0);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn polyphony_layers_voices_and_releases_the_oldest() {
+    let path = constant_clip("poly", 4_800, 0.1);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    let config = ClipConfig {
+        polyphony: 2,
+        ..ClipConfig::default()
+    };
+    mixer.load_clip(board, 1, &path, config).unwrap();
+
+    mixer.trigger_clip(board, 1).unwrap();
+    mixer.trigger_clip(board, 1).unwrap();
+    let layered = left(&render(&mut mixer, 1));
+    assert!(layered.iter().all(|&sample| (sample - 0.2).abs() < 1e-6));
+    assert_eq!(voices(&mixer, board, 1), 2);
+
+    // A third trigger fades the oldest voice out instead of cutting it.
+    mixer.trigger_clip(board, 1).unwrap();
+    let stolen = left(&render(&mut mixer, 1));
+    assert_eq!(voices(&mixer, board, 1), 2);
+    assert_close(stolen[0], 0.3);
+    assert!(stolen.windows(2).take(RELEASE_FRAMES).all(|w| w[1] <= w[0]));
+    assert_close(stolen[RELEASE_FRAMES], 0.2);
+
+    // With the default polyphony of 1 a retrigger restarts the clip.
+    mixer
+        .set_clip_config(board, 1, ClipConfig::default())
+        .unwrap();
+    mixer.stop_all_clips(board).unwrap();
+    render(&mut mixer, 1);
+    assert_eq!(voices(&mixer, board, 1), 0);
+    mixer.trigger_clip(board, 1).unwrap();
+    render(&mut mixer, 1);
+    mixer.trigger_clip(board, 1).unwrap();
+    let restarted = left(&render(&mut mixer, 1));
+    assert_close(restarted[0], 0.2);
+    assert_close(restarted[RELEASE_FRAMES], 0.1);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn choke_groups_silence_the_other_clips_in_the_group() {
+    let open = constant_clip("open", 9_600, 0.1);
+    let closed = constant_clip("closed", 9_600, 0.01);
+    let other = constant_clip("other", 9_600, 0.001);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    let hat = ClipConfig {
+        choke_group: Some(7),
+        ..ClipConfig::default()
+    };
+    mixer.load_clip(board, 1, &open, hat).unwrap();
+    mixer.load_clip(board, 2, &closed, hat).unwrap();
+    mixer
+        .load_clip(board, 3, &other, ClipConfig::default())
+        .unwrap();
+
+    mixer.trigger_clip(board, 1).unwrap();
+    mixer.trigger_clip(board, 3).unwrap();
+    render(&mut mixer, 1);
+    mixer.trigger_clip(board, 2).unwrap();
+    let choked = left(&render(&mut mixer, 2));
+    assert_close(choked[0], 0.111);
+    assert_close(choked[BLOCK_FRAMES], 0.011);
+    assert_eq!(voices(&mixer, board, 1), 0);
+    assert_eq!(voices(&mixer, board, 3), 1);
+
+    for path in [open, closed, other] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn gain_changes_apply_to_sounding_voices() {
+    let path = constant_clip("gain", 9_600, 0.5);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    let quiet = ClipConfig {
+        gain_db: -6.0,
+        ..ClipConfig::default()
+    };
+    mixer.load_clip(board, 4, &path, quiet).unwrap();
+    mixer.trigger_clip(board, 4).unwrap();
+    assert_close(render(&mut mixer, 1)[0], 0.5 * 10f32.powf(-6.0 / 20.0));
+
+    mixer
+        .set_clip_config(board, 4, ClipConfig::default())
+        .unwrap();
+    assert_close(render(&mut mixer, 1)[0], 0.5);
+
+    // Unloading fades the voice out.
+    mixer.unload_clip(board, 4).unwrap();
+    let faded = left(&render(&mut mixer, 1));
+    assert!(faded[RELEASE_FRAMES..].iter().all(|&sample| sample == 0.0));
+    assert!(mixer.clips(board).unwrap().is_empty());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resamples_and_upmixes_mono_clips() {
+    let samples: Vec<f32> = (0..2_400).map(|i| i as f32 / 2_400.0).collect();
+    let path = write_clip("mono", SAMPLE_RATE / 2, 1, &samples);
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    mixer
+        .load_clip(board, 1, &path, ClipConfig::default())
+        .unwrap();
+    assert_eq!(mixer.clips(board).unwrap()[0].duration_seconds, 0.1);
+
+    mixer.trigger_clip(board, 1).unwrap();
+    let rendered = render(&mut mixer, 10);
+    for (frame, pair) in rendered.chunks_exact(2).enumerate().take(4_798).skip(2) {
+        assert_eq!(pair[0], pair[1]);
+        let expected = (frame as f32 / 2.0 - 1.0) / 2_400.0;
+        assert!((pair[0] - expected).abs() < 1e-6, "frame {frame}");
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_bad_clips_settings_and_sources() {
+    let mut mixer = Mixer::new(SAMPLE_RATE, BLOCK_FRAMES);
+    let board = mixer.add_soundboard_source();
+    let path = constant_clip("reject", 100, 0.1);
+
+    for config in [
+        ClipConfig {
+            polyphony: 0,
+            ..ClipConfig::default()
+        },
+        ClipConfig {
+            polyphony: MAX_VOICES as u32 + 1,
+            ..ClipConfig::default()
+        },
+        ClipConfig {
+            gain_db: f32::INFINITY,
+            ..ClipConfig::default()
+        },
+    ] {
+        assert!(matches!(
+            mixer.load_clip(board, 1, &path, config),
+            Err(SoundboardError::InvalidClip(_))
+        ));
+    }
+    assert!(matches!(
+        mixer.trigger_clip(board, 1),
+        Err(SoundboardError::UnknownClip(1))
+    ));
+    assert!(matches!(
+        mixer.load_clip(
+            board,
+            1,
+            path.with_extension("missing"),
+            ClipConfig::default()
+        ),
+        Err(SoundboardError::Io(_))
+    ));
+
+    let long = write_clip(
+        "long",
+        SAMPLE_RATE / 100,
+        1,
+        &vec![0.0; (SAMPLE_RATE / 100 * (MAX_CLIP_SECONDS + 1)) as usize],
+    );
+    assert!(matches!(
+        mixer.load_clip(board, 1, &long, ClipConfig::default()),
+        Err(SoundboardError::Io(_))
+    ));
+
+    let (pushed, _ring) = mixer.add_source(BLOCK_FRAMES * 4);
+    assert!(matches!(
+        mixer.load_clip(pushed, 1, &path, ClipConfig::default()),
+        Err(SoundboardError::Mixer(MixerError::NotASoundboard(id))) if id == pushed.id()
+    ));
+    assert!(matches!(
+        mixer.clips(pushed),
+        Err(MixerError::NotASoundboard(_))
+    ));
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&long).unwrap();
+}